@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.17;
+
+// The verifier contract of RiscZero, which checks Groth16 proofs that wrap receipts.
+interface IRiscZeroVerifier {
+    function verify(
+        bytes calldata seal,
+        bytes32 imageId,
+        bytes32 postStateDigest,
+        bytes32 journalDigest
+    ) external view returns (bool);
+}
+
+// Records the result of games proven by the `finalize_game` guest, so that
+// other contracts can e.g. pay out a wager once a game is settled. Each game is
+// committed to the hash of its match metadata before it is played, so that
+// only the result of that match settles it, and not one that anybody proved
+// for a game with the same ID. Commitments and results are kept by the address
+// that committed them, so nobody can take a game ID over by committing it
+// first with a match hash of their own: others read the results committed by
+// an address they trust, such as their own or one of the players. The calldata
+// of `commit` is built by `verifier::evm::commit_calldata` and the one of
+// `settle` by `verifier::evm::Settlement`.
+contract TicTacToeSettlement {
+    // Matches `verifier::evm::Outcome`.
+    enum Outcome { Unsettled, Stalemate, WonByA, WonByB }
+
+    // Must match `JOURNAL_VERSION` of the game crate.
+    uint32 public constant JOURNAL_VERSION = 12;
+
+    // Words of the journal, see `verifier::evm`.
+    uint256 private constant HASHER_WORD = 1;
+    uint256 private constant GAME_ID_WORD = 2;
+    uint256 private constant MATCH_HASH_WORD = 34;
+    uint256 private constant STATE_WORD = 67;
+
+    IRiscZeroVerifier public immutable verifier;
+    // Of the `finalize_game` guest that the players agreed on.
+    bytes32 public immutable imageId;
+    // The `STATE_HASHER` of the game crate that the guest was built with, which
+    // journals record. Results proven with another state hasher are refused.
+    uint32 public immutable stateHasher;
+
+    // The `MatchMetadata::hash` of each committed game, by committer and game ID.
+    mapping(address => mapping(bytes32 => bytes32)) public matchHashes;
+    mapping(address => mapping(bytes32 => Outcome)) public outcomes;
+
+    event Committed(address indexed committer, bytes32 indexed gameId, bytes32 matchHash);
+    event Settled(address indexed committer, bytes32 indexed gameId, Outcome outcome);
+
+    constructor(IRiscZeroVerifier _verifier, bytes32 _imageId, uint32 _stateHasher) {
+        verifier = _verifier;
+        imageId = _imageId;
+        stateHasher = _stateHasher;
+    }
+
+    function commit(bytes32 gameId, bytes32 matchHash) external {
+        require(matchHash != bytes32(0), "invalid match hash");
+        require(matchHashes[msg.sender][gameId] == bytes32(0), "already committed");
+
+        matchHashes[msg.sender][gameId] = matchHash;
+
+        emit Committed(msg.sender, gameId, matchHash);
+    }
+
+    // Settles the game as committed by `committer`. Anybody can settle it,
+    // as only the result of the committed match does.
+    function settle(address committer, bytes calldata seal, bytes32 postStateDigest, bytes calldata journal) external {
+        require(
+            verifier.verify(seal, imageId, postStateDigest, sha256(journal)),
+            "invalid proof"
+        );
+        require(word(journal, 0) == JOURNAL_VERSION, "unsupported journal version");
+        require(word(journal, HASHER_WORD) == stateHasher, "unsupported state hasher");
+
+        bytes32 gameId = readGameId(journal);
+        require(matchHashes[committer][gameId] != bytes32(0), "not committed");
+        require(readMatchHash(journal) == matchHashes[committer][gameId], "result of another match");
+        require(outcomes[committer][gameId] == Outcome.Unsettled, "already settled");
+
+        Outcome outcome = readOutcome(journal);
+        outcomes[committer][gameId] = outcome;
+
+        emit Settled(committer, gameId, outcome);
+    }
+
+    // Each byte of the game ID is a word of its own.
+    function readGameId(bytes calldata journal) private pure returns (bytes32 gameId) {
+        for (uint256 i = 0; i < 32; i++) {
+            gameId |= bytes32(uint256(word(journal, GAME_ID_WORD + i) & 0xff) << (8 * (31 - i)));
+        }
+    }
+
+    // A digest is eight words, whose little endian bytes are the bytes of the
+    // digest, as returned by `Digest::as_bytes`.
+    function readMatchHash(bytes calldata journal) private pure returns (bytes32) {
+        require(journal.length >= (MATCH_HASH_WORD + 8) * 4, "journal too short");
+
+        return bytes32(journal[MATCH_HASH_WORD * 4:(MATCH_HASH_WORD + 8) * 4]);
+    }
+
+    // The variant of `State`, followed by the player for a winner.
+    function readOutcome(bytes calldata journal) private pure returns (Outcome) {
+        uint32 state = word(journal, STATE_WORD);
+
+        if (state == 1) {
+            return Outcome.Stalemate;
+        }
+
+        require(state == 2, "the game is not finished");
+
+        return word(journal, STATE_WORD + 1) == 0 ? Outcome.WonByA : Outcome.WonByB;
+    }
+
+    function word(bytes calldata journal, uint256 index) private pure returns (uint32 value) {
+        require(journal.length >= (index + 1) * 4, "journal too short");
+
+        for (uint256 i = 0; i < 4; i++) {
+            value |= uint32(uint8(journal[index * 4 + i])) << uint32(8 * i);
+        }
+    }
+}
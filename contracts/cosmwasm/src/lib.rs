@@ -0,0 +1,381 @@
+use std::fmt;
+
+use cosmwasm_std::{
+    entry_point, to_binary, Addr, BankMsg, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, Timestamp, Uint128
+};
+use cw_storage_plus::{Item, Map};
+use serde::{Serialize, Deserialize};
+use risc0_zkvm::sha::Digest;
+use game::{State, Player};
+use verifier::{
+    cosmwasm::{InstantiateMsg, ExecuteMsg, QueryMsg, GameResponse, Wager},
+    export::{ExportedReceipt, ReceiptKind, Proven}
+};
+
+// Of the `finalize_game` guest, see `InstantiateMsg`.
+const IMAGE_ID: Item<[u8; 32]> = Item::new("image_id");
+// By game ID.
+const GAMES: Map<&[u8], Game> = Map::new("games");
+
+// A committed game, which is settled once the receipt of its result is verified.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct Game {
+    committer: Addr,
+    match_hash: [u8; 32],
+    wager: Option<Stakes>,
+    result: Option<State>
+}
+
+// A validated `Wager`, with the stakes that were locked.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct Stakes {
+    players: [Addr; 2],
+    stake: Coin,
+    staked: [bool; 2],
+    deadline: Timestamp
+}
+
+#[derive(Debug)]
+pub enum ContractError {
+    Std(StdError),
+    // A game ID, match hash or image ID that is not 32 hex encoded bytes.
+    InvalidHex(&'static str),
+    InvalidAmount,
+    // The sender is not a player of the wager, or there is none.
+    NotAPlayer,
+    // The funds sent are not exactly the stake.
+    WrongStake(Coin),
+    AlreadyStaked,
+    // The sender has no stake locked to reclaim.
+    NotStaked,
+    // Stakes are locked before the deadline, and only reclaimed after it.
+    Expired,
+    NotExpired,
+    AlreadyCommitted,
+    NotCommitted,
+    // The receipt could not be decoded or verified, or is not of a result.
+    InvalidReceipt(String),
+    // The result is of another match than the committed one.
+    MatchMismatch,
+    Unfinished,
+    AlreadySettled
+}
+
+impl From<StdError> for ContractError {
+    fn from(error: StdError) -> Self {
+        Self::Std(error)
+    }
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Std(error) => write!(f, "{}", error),
+            Self::InvalidHex(what) => write!(f, "The {} must be 32 hex encoded bytes.", what),
+            Self::InvalidAmount => write!(f, "The stake must be a decimal amount."),
+            Self::NotAPlayer => write!(f, "Only the players of a wager can stake on it."),
+            Self::WrongStake(stake) => write!(f, "The stake is {}, exactly.", stake),
+            Self::AlreadyStaked => write!(f, "The stake is already locked."),
+            Self::NotStaked => write!(f, "No stake is locked."),
+            Self::Expired => write!(f, "The deadline of the wager has passed."),
+            Self::NotExpired => write!(f, "Stakes can only be reclaimed after the deadline of the wager."),
+            Self::AlreadyCommitted => write!(f, "The game is already committed."),
+            Self::NotCommitted => write!(f, "The game was not committed."),
+            Self::InvalidReceipt(reason) => write!(f, "Invalid receipt: {}", reason),
+            Self::MatchMismatch => write!(f, "The result is of another match than the committed one."),
+            Self::Unfinished => write!(f, "The game is not finished."),
+            Self::AlreadySettled => write!(f, "The game is already settled.")
+        }
+    }
+}
+
+impl std::error::Error for ContractError { }
+
+#[entry_point]
+pub fn instantiate(deps: DepsMut, _: Env, _: MessageInfo, msg: InstantiateMsg) -> Result<Response, ContractError> {
+    IMAGE_ID.save(deps.storage, &parse_hex(&msg.image_id, "image ID")?)?;
+
+    Ok(Response::new().add_attribute("image_id", msg.image_id))
+}
+
+#[entry_point]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Commit { game_id, match_hash, wager } => commit(deps, info, &game_id, &match_hash, wager),
+        ExecuteMsg::Stake { game_id } => stake(deps, env, info, &game_id),
+        ExecuteMsg::Settle { receipt } => settle(deps, &receipt),
+        ExecuteMsg::Refund { game_id } => refund(deps, env, info, &game_id)
+    }
+}
+
+#[entry_point]
+pub fn query(deps: Deps, _: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Game { game_id } => {
+            let game_id = parse_hex(&game_id, "game ID")
+                .map_err(|error| StdError::generic_err(error.to_string()))?;
+
+            let game = GAMES.load(deps.storage, &game_id)?;
+
+            to_binary(&GameResponse {
+                match_hash: hex::encode(game.match_hash),
+                wager: game.wager.as_ref().map(|stakes| Wager {
+                    player_a: stakes.players[0].to_string(),
+                    player_b: stakes.players[1].to_string(),
+                    denom: stakes.stake.denom.clone(),
+                    amount: stakes.stake.amount.to_string(),
+                    deadline: stakes.deadline.seconds()
+                }),
+                staked: game.wager.map_or([false; 2], |stakes| stakes.staked),
+                result: game.result
+            })
+        }
+    }
+}
+
+fn commit(
+    deps: DepsMut,
+    info: MessageInfo,
+    game_id: &str,
+    match_hash: &str,
+    wager: Option<Wager>
+) -> Result<Response, ContractError> {
+    let id = parse_hex(game_id, "game ID")?;
+
+    if GAMES.has(deps.storage, &id) {
+        return Err(ContractError::AlreadyCommitted);
+    }
+
+    let wager = match wager {
+        Some(wager) => Some(Stakes {
+            players: [
+                deps.api.addr_validate(&wager.player_a)?,
+                deps.api.addr_validate(&wager.player_b)?
+            ],
+            stake: Coin {
+                denom: wager.denom,
+                amount: wager.amount.parse::<u128>().map_err(|_| ContractError::InvalidAmount)?.into()
+            },
+            staked: [false; 2],
+            deadline: Timestamp::from_seconds(wager.deadline)
+        }),
+        None => None
+    };
+
+    let game = Game {
+        committer: info.sender,
+        match_hash: parse_hex(match_hash, "match hash")?,
+        wager,
+        result: None
+    };
+    GAMES.save(deps.storage, &id, &game)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "commit")
+        .add_attribute("game_id", game_id)
+        .add_attribute("committer", game.committer))
+}
+
+// Locks the stake of the player that sends it, until the game is settled or
+// the player reclaims it after the deadline.
+fn stake(deps: DepsMut, env: Env, info: MessageInfo, game_id: &str) -> Result<Response, ContractError> {
+    let id = parse_hex(game_id, "game ID")?;
+
+    let mut game = GAMES.may_load(deps.storage, &id)?.ok_or(ContractError::NotCommitted)?;
+
+    if game.result.is_some() {
+        return Err(ContractError::AlreadySettled);
+    }
+
+    let stakes = game.wager.as_mut().ok_or(ContractError::NotAPlayer)?;
+
+    let index = stakes.players.iter()
+        .position(|player| *player == info.sender)
+        .ok_or(ContractError::NotAPlayer)?;
+
+    if stakes.staked[index] {
+        return Err(ContractError::AlreadyStaked);
+    }
+
+    if env.block.time >= stakes.deadline {
+        return Err(ContractError::Expired);
+    }
+
+    if info.funds != [stakes.stake.clone()] {
+        return Err(ContractError::WrongStake(stakes.stake.clone()));
+    }
+
+    stakes.staked[index] = true;
+    GAMES.save(deps.storage, &id, &game)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "stake")
+        .add_attribute("game_id", game_id)
+        .add_attribute("player", info.sender))
+}
+
+// Anyone can settle a game, since the receipt proves its result.
+fn settle(deps: DepsMut, receipt: &str) -> Result<Response, ContractError> {
+    let invalid = |error: &dyn fmt::Display| ContractError::InvalidReceipt(error.to_string());
+
+    let exported = ExportedReceipt::decode(receipt).map_err(|error| invalid(&error))?;
+
+    if exported.kind != ReceiptKind::Result {
+        return Err(invalid(&"not the receipt of a result"));
+    }
+
+    let image_id = Digest::try_from(IMAGE_ID.load(deps.storage)?.as_slice())
+        .expect("image IDs are saved as 32 bytes");
+
+    let Proven::Result(journal) = exported.verify_against(image_id).map_err(|error| invalid(&error))? else {
+        unreachable!("the receipt of a result proves a result");
+    };
+
+    let mut game = GAMES.may_load(deps.storage, &journal.game_id)?
+        .ok_or(ContractError::NotCommitted)?;
+
+    if game.match_hash != journal.match_hash.as_bytes() {
+        return Err(ContractError::MatchMismatch);
+    }
+
+    if game.result.is_some() {
+        return Err(ContractError::AlreadySettled);
+    }
+
+    if journal.state == State::InProgress {
+        return Err(ContractError::Unfinished);
+    }
+
+    game.result = Some(journal.state);
+    GAMES.save(deps.storage, &journal.game_id, &game)?;
+
+    let result = match journal.state {
+        State::Winner(player) => format!("won by {:?}", player),
+        _ => "stalemate".to_string()
+    };
+
+    let payouts = match &game.wager {
+        Some(stakes) => payouts(stakes, journal.state),
+        None => vec![]
+    };
+
+    Ok(Response::new()
+        .add_messages(payouts)
+        .add_attribute("action", "settle")
+        .add_attribute("game_id", hex::encode(journal.game_id))
+        .add_attribute("result", result))
+}
+
+// Returns the stake of the player that sends it once the deadline has passed
+// without the game being settled. The stake of the opponent stays locked
+// until they reclaim it too, or the game is settled, which returns it then.
+fn refund(deps: DepsMut, env: Env, info: MessageInfo, game_id: &str) -> Result<Response, ContractError> {
+    let id = parse_hex(game_id, "game ID")?;
+
+    let mut game = GAMES.may_load(deps.storage, &id)?.ok_or(ContractError::NotCommitted)?;
+
+    if game.result.is_some() {
+        return Err(ContractError::AlreadySettled);
+    }
+
+    let stakes = game.wager.as_mut().ok_or(ContractError::NotAPlayer)?;
+
+    let index = stakes.players.iter()
+        .position(|player| *player == info.sender)
+        .ok_or(ContractError::NotAPlayer)?;
+
+    if !stakes.staked[index] {
+        return Err(ContractError::NotStaked);
+    }
+
+    if env.block.time < stakes.deadline {
+        return Err(ContractError::NotExpired);
+    }
+
+    stakes.staked[index] = false;
+
+    let refund = BankMsg::Send { to_address: info.sender.to_string(), amount: vec![stakes.stake.clone()] };
+    GAMES.save(deps.storage, &id, &game)?;
+
+    Ok(Response::new()
+        .add_message(refund)
+        .add_attribute("action", "refund")
+        .add_attribute("game_id", game_id)
+        .add_attribute("player", info.sender))
+}
+
+// The pot to the winner if both stakes were locked, or else the stakes back.
+fn payouts(stakes: &Stakes, state: State) -> Vec<BankMsg> {
+    let pay = |player: Player, times: u128| BankMsg::Send {
+        to_address: stakes.players[player as usize].to_string(),
+        amount: vec![Coin {
+            denom: stakes.stake.denom.clone(),
+            amount: stakes.stake.amount * Uint128::from(times)
+        }]
+    };
+
+    match state {
+        State::Winner(player) if stakes.staked == [true; 2] => vec![pay(player, 2)],
+        _ => [Player::A, Player::B].into_iter()
+            .filter(|player| stakes.staked[*player as usize])
+            .map(|player| pay(player, 1))
+            .collect()
+    }
+}
+
+fn parse_hex(text: &str, what: &'static str) -> Result<[u8; 32], ContractError> {
+    hex::decode(text).ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ContractError::InvalidHex(what))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{coins, testing::{mock_dependencies, mock_env, mock_info}};
+
+    use super::*;
+
+    #[test]
+    fn stakes_are_reclaimed_after_the_deadline() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let game_id = hex::encode([1; 32]);
+
+        instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), InstantiateMsg { image_id: hex::encode([2; 32]) }).unwrap();
+
+        let wager = Wager {
+            player_a: "alice".into(),
+            player_b: "bob".into(),
+            denom: "ustake".into(),
+            amount: "100".into(),
+            deadline: env.block.time.plus_seconds(60).seconds()
+        };
+        let commit = ExecuteMsg::Commit { game_id: game_id.clone(), match_hash: hex::encode([3; 32]), wager: Some(wager) };
+        execute(deps.as_mut(), env.clone(), mock_info("server", &[]), commit).unwrap();
+
+        // Only player A locks their stake, and player B never does.
+        let stake = ExecuteMsg::Stake { game_id: game_id.clone() };
+        execute(deps.as_mut(), env.clone(), mock_info("alice", &coins(100, "ustake")), stake.clone()).unwrap();
+
+        let refund = ExecuteMsg::Refund { game_id: game_id.clone() };
+        let error = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), refund.clone()).unwrap_err();
+        assert!(matches!(error, ContractError::NotExpired));
+
+        env.block.time = env.block.time.plus_seconds(60);
+
+        let error = execute(deps.as_mut(), env.clone(), mock_info("bob", &coins(100, "ustake")), stake).unwrap_err();
+        assert!(matches!(error, ContractError::Expired));
+
+        let error = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), refund.clone()).unwrap_err();
+        assert!(matches!(error, ContractError::NotStaked));
+
+        let response = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), refund.clone()).unwrap();
+        assert_eq!(response.messages.len(), 1);
+        assert_eq!(response.messages[0].msg, BankMsg::Send { to_address: "alice".into(), amount: coins(100, "ustake") }.into());
+
+        // Which can only be reclaimed once.
+        let error = execute(deps.as_mut(), env, mock_info("alice", &[]), refund).unwrap_err();
+        assert!(matches!(error, ContractError::NotStaked));
+    }
+}
@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use game::{
+    Journal, GameJournal, PrivateJournal, BatchJournal, DrawJournal,
+    ThreatJournal, InitJournal, LengthJournal
+};
+
+// Journals are decoded from receipts sent by the server, which clients
+// have to refuse rather than panic on when they are malformed.
+fuzz_target!(|bytes: &[u8]| {
+    let _ = Journal::decode(bytes);
+    let _ = GameJournal::decode(bytes);
+    let _ = PrivateJournal::decode(bytes);
+    let _ = BatchJournal::decode(bytes);
+    let _ = DrawJournal::decode(bytes);
+    let _ = ThreatJournal::decode(bytes);
+    let _ = InitJournal::decode(bytes);
+    let _ = LengthJournal::decode(bytes);
+});
@@ -0,0 +1,28 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use game::{TicTacToe, Point, State};
+
+// Plays each byte as a move, with the column in the low and the row in the
+// high four bits, so that moves out of bounds and on occupied cells are made
+// as well, on every game that they reach.
+fuzz_target!(|bytes: &[u8]| {
+    let mut game = TicTacToe::new();
+
+    for byte in bytes {
+        let point = Point::new((byte & 0x0f) as usize, (byte >> 4) as usize);
+        let before = game;
+
+        match game.make_move(point) {
+            Ok(()) => assert_eq!(game.get(point), Some(before.current_player())),
+            // A rejected move leaves the game as it was.
+            Err(_) => assert_eq!(game.as_bytes(), before.as_bytes())
+        }
+
+        assert_eq!(TicTacToe::from_bytes(&game.as_bytes()).map(|decoded| decoded.as_bytes()), Some(game.as_bytes()));
+
+        if before.state() != State::InProgress {
+            assert_eq!(game.as_bytes(), before.as_bytes());
+        }
+    }
+});
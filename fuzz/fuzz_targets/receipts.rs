@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use verifier::export::ExportedReceipt;
+
+// Exported receipts are read from files and text pasted by anyone. The bytes
+// are decoded as text, and as the receipt that they encode as hex, so that
+// the bincode decoding of the receipt is reached without a valid encoding.
+fuzz_target!(|bytes: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        let _ = ExportedReceipt::decode(text);
+    }
+
+    let _ = ExportedReceipt::decode(&hex::encode(bytes));
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use game::{TicTacToe, STATE_SIZE};
+
+// Guests read the game as raw bytes. Any bytes that decode have to encode
+// back to the same bytes, or the state hash would not be the one committed.
+fuzz_target!(|bytes: &[u8]| {
+    let Ok(bytes) = <&[u8; STATE_SIZE]>::try_from(bytes) else {
+        return;
+    };
+
+    if let Some(game) = TicTacToe::from_bytes(bytes) {
+        assert_eq!(&game.as_bytes(), bytes);
+    }
+});
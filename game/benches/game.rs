@@ -0,0 +1,55 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, black_box};
+use game::{TicTacToe, Point, metadata::MatchMetadata};
+
+// A game that the next move, (2, 2), wins for player A along the diagonal.
+const WINNING: &str = "|X|O| |\n| |X| |\n|O| | |";
+
+// A game that the next move, (2, 2), fills, ending it in a stalemate.
+const FILLING: &str = "|X|O|X|\n|X|O|O|\n|O|X| |";
+
+// `update_state` is private, so it is measured through the moves that end a
+// game, where it is most of the work, next to the opening move of `make_move`.
+fn moves(c: &mut Criterion) {
+    let mut group = c.benchmark_group("make_move");
+
+    for (name, game) in [
+        ("opening", TicTacToe::new()),
+        ("win", TicTacToe::from_ascii(WINNING).unwrap()),
+        ("stalemate", TicTacToe::from_ascii(FILLING).unwrap())
+    ] {
+        let point = if name == "opening" { Point::new(1, 1) } else { Point::new(2, 2) };
+
+        group.bench_function(name, |b| b.iter_batched(
+            || game,
+            |mut game| game.make_move(black_box(point)),
+            BatchSize::SmallInput
+        ));
+    }
+
+    group.finish();
+}
+
+fn encoding(c: &mut Criterion) {
+    let game = TicTacToe::from_ascii(WINNING).unwrap();
+    let bytes = game.as_bytes();
+
+    c.bench_function("as_bytes", |b| b.iter(|| black_box(&game).as_bytes()));
+    c.bench_function("from_bytes", |b| b.iter(|| TicTacToe::from_bytes(black_box(&bytes))));
+}
+
+// The hashes that clients recompute for every receipt, with the state hasher of this build.
+fn hashing(c: &mut Criterion) {
+    let game = TicTacToe::from_ascii(WINNING).unwrap();
+    let metadata = MatchMetadata::default();
+    let state_hash = game.state_hash();
+
+    c.bench_function("state_hash", |b| b.iter(|| black_box(&game).state_hash()));
+    c.bench_function("salted_hash", |b| b.iter(|| black_box(&game).salted_hash(&[7; 32])));
+    c.bench_function("chain_hash", |b| b.iter(|| {
+        game::chain_hash(black_box(&state_hash), 3, Point::new(2, 2), &state_hash)
+    }));
+    c.bench_function("match_hash", |b| b.iter(|| black_box(&metadata).hash()));
+}
+
+criterion_group!(benches, moves, encoding, hashing);
+criterion_main!(benches);
@@ -0,0 +1,94 @@
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Serialize, Deserialize};
+
+use crate::Point;
+
+// A move together with the acting player's signature over `move_message`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SignedMove {
+    pub point: Point,
+    // An ed25519 signature, stored as a vector since serde
+    // doesn't support arrays longer than 32 elements.
+    pub signature: Vec<u8>
+}
+
+impl SignedMove {
+    pub fn new(point: Point, signature: Vec<u8>) -> Self {
+        Self { point, signature }
+    }
+
+    pub fn verify(&self, public_key: &[u8; 32], game_id: &[u8; 32], move_index: u32) -> bool {
+        let Ok(public_key) = VerifyingKey::from_bytes(public_key) else {
+            return false;
+        };
+
+        let Ok(signature) = <[u8; 64]>::try_from(self.signature.as_slice()) else {
+            return false;
+        };
+
+        let message = move_message(game_id, move_index, self.point);
+
+        public_key.verify_strict(&message, &Signature::from_bytes(&signature)).is_ok()
+    }
+}
+
+// game_id || move_index || x || y
+// Including the game ID and move index prevents a signature from
+// being replayed in another game or at a later point in the same game.
+pub fn move_message(game_id: &[u8; 32], move_index: u32, point: Point) -> [u8; 44] {
+    let mut message = [0u8; 32 + 4 + 4 + 4];
+
+    message[0..32].copy_from_slice(game_id);
+    message[32..36].copy_from_slice(&move_index.to_le_bytes());
+    message[36..40].copy_from_slice(&(point.x() as u32).to_le_bytes());
+    message[40..44].copy_from_slice(&(point.y() as u32).to_le_bytes());
+
+    message
+}
+
+// What a player can agree to that ends the game without its result being
+// proven, which they sign so that the server cannot claim it for them.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Concession {
+    // Gives the game to the opponent.
+    Resign,
+    // Offers or accepts a draw, which is signed by both players.
+    Draw
+}
+
+// "resign" or "draw" || game_id || move_index
+// The prefix keeps the signature from being taken for a move or a chat message.
+pub fn concession_message(concession: Concession, game_id: &[u8; 32], move_index: u32) -> Vec<u8> {
+    let prefix: &[u8] = match concession {
+        Concession::Resign => b"resign",
+        Concession::Draw => b"draw"
+    };
+
+    [prefix, game_id, &move_index.to_le_bytes()].concat()
+}
+
+// Whether the player with `public_key` agreed to `concession` at `move_index`
+// of the game. Players seated without a key sign nothing, so the server is
+// taken at its word for them, as it is for their moves.
+pub fn is_conceded(
+    public_key: Option<[u8; 32]>,
+    signature: Option<&[u8]>,
+    concession: Concession,
+    game_id: &[u8; 32],
+    move_index: u32
+) -> bool {
+    let Some(public_key) = public_key else {
+        return true;
+    };
+
+    let (Ok(public_key), Some(Ok(signature))) = (
+        VerifyingKey::from_bytes(&public_key),
+        signature.map(<[u8; 64]>::try_from)
+    ) else {
+        return false;
+    };
+
+    let message = concession_message(concession, game_id, move_index);
+
+    public_key.verify_strict(&message, &Signature::from_bytes(&signature)).is_ok()
+}
@@ -0,0 +1,92 @@
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Serialize, Deserialize};
+
+use crate::{Player, metadata::GameId};
+
+// Longer chat messages are refused, so that they cannot crowd out the moves.
+pub const MAX_CHAT_LENGTH: usize = 280;
+
+// A chat message of `player`, signed with the key that the player was seated
+// with, so that it can be attributed to them. It is only relayed and logged,
+// never proven, and has no effect on the game.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ChatMessage {
+    pub game_id: GameId,
+    // The number of moves made when the message was sent.
+    pub move_index: u32,
+    pub player: Player,
+    pub text: String,
+    // An ed25519 signature over `chat_message`, stored as a vector
+    // since serde doesn't support arrays longer than 32 elements.
+    pub signature: Vec<u8>
+}
+
+impl ChatMessage {
+    pub fn verify(&self, public_key: &[u8; 32]) -> bool {
+        if self.text.len() > MAX_CHAT_LENGTH {
+            return false;
+        }
+
+        let Ok(public_key) = VerifyingKey::from_bytes(public_key) else {
+            return false;
+        };
+
+        let Ok(signature) = <[u8; 64]>::try_from(self.signature.as_slice()) else {
+            return false;
+        };
+
+        let message = chat_message(&self.game_id, self.move_index, self.player, &self.text);
+
+        public_key.verify_strict(&message, &Signature::from_bytes(&signature)).is_ok()
+    }
+}
+
+// "chat" || game_id || move_index || player || text
+// The prefix keeps the signature from being passed off as one over a move.
+pub fn chat_message(game_id: &GameId, move_index: u32, player: Player, text: &str) -> Vec<u8> {
+    let mut message = Vec::with_capacity(4 + 32 + 4 + 1 + text.len());
+
+    message.extend_from_slice(b"chat");
+    message.extend_from_slice(game_id);
+    message.extend_from_slice(&move_index.to_le_bytes());
+    message.push(player as u8);
+    message.extend_from_slice(text.as_bytes());
+
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{SigningKey, Signer};
+
+    use super::*;
+
+    #[test]
+    fn chat_messages_are_signed_by_the_player() {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let public_key = key.verifying_key().to_bytes();
+
+        let sign = |text: &str| {
+            let message = chat_message(&[1; 32], 2, Player::A, text);
+
+            ChatMessage {
+                game_id: [1; 32],
+                move_index: 2,
+                player: Player::A,
+                text: text.into(),
+                signature: key.sign(&message).to_bytes().to_vec()
+            }
+        };
+
+        let chat = sign("Good luck!");
+        assert!(chat.verify(&public_key));
+        assert!(!chat.verify(&SigningKey::from_bytes(&[2; 32]).verifying_key().to_bytes()));
+
+        // Attributed to the other player, or moved to another point of the game.
+        assert!(!ChatMessage { player: Player::B, ..chat.clone() }.verify(&public_key));
+        assert!(!ChatMessage { move_index: 3, ..chat.clone() }.verify(&public_key));
+        assert!(!ChatMessage { text: "Good game!".into(), ..chat }.verify(&public_key));
+
+        assert!(!sign(&"a".repeat(MAX_CHAT_LENGTH + 1)).verify(&public_key));
+    }
+}
@@ -0,0 +1,73 @@
+use ed25519_dalek::{Signature, VerifyingKey};
+use risc0_zkvm::sha::Digest;
+use serde::{Serialize, Deserialize};
+
+use crate::{Player, metadata::GameId};
+
+// The time that each player has to make a move once they are asked for it,
+// after which they forfeit the game.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Clock {
+    pub move_limit_secs: u32
+}
+
+// The statement that `player` ran out of time for the move with the given
+// index, signed by the server that enforces the clock. The guest has no clock
+// to prove it with, so it is the signature that the opponent verifies, along
+// with the chain hash, which pins the state of the game that it was made in.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Forfeit {
+    pub game_id: GameId,
+    pub move_index: u32,
+    pub chain_hash: Digest,
+    pub player: Player,
+    pub clock: Clock,
+    // An ed25519 signature over `forfeit_message`, stored as a vector
+    // since serde doesn't support arrays longer than 32 elements.
+    pub signature: Vec<u8>
+}
+
+impl Forfeit {
+    pub fn verify(&self, referee: &[u8; 32]) -> bool {
+        let Ok(referee) = VerifyingKey::from_bytes(referee) else {
+            return false;
+        };
+
+        let Ok(signature) = <[u8; 64]>::try_from(self.signature.as_slice()) else {
+            return false;
+        };
+
+        let message = forfeit_message(&self.game_id, self.move_index, &self.chain_hash, self.player, self.clock);
+
+        referee.verify_strict(&message, &Signature::from_bytes(&signature)).is_ok()
+    }
+
+    // The opponent of the player that ran out of time.
+    pub fn winner(&self) -> Player {
+        match self.player {
+            Player::A => Player::B,
+            Player::B => Player::A
+        }
+    }
+}
+
+// "forfeit" || game_id || move_index || chain_hash || player || move_limit_secs
+// The prefix keeps the signature from being passed off as one over a move.
+pub fn forfeit_message(
+    game_id: &GameId,
+    move_index: u32,
+    chain_hash: &Digest,
+    player: Player,
+    clock: Clock
+) -> [u8; 7 + 32 + 4 + 32 + 1 + 4] {
+    let mut message = [0u8; 7 + 32 + 4 + 32 + 1 + 4];
+
+    message[0..7].copy_from_slice(b"forfeit");
+    message[7..39].copy_from_slice(game_id);
+    message[39..43].copy_from_slice(&move_index.to_le_bytes());
+    message[43..75].copy_from_slice(chain_hash.as_bytes());
+    message[75] = player as u8;
+    message[76..80].copy_from_slice(&clock.move_limit_secs.to_le_bytes());
+
+    message
+}
@@ -0,0 +1,73 @@
+use risc0_zkvm::sha::{Sha256, Digest, Impl};
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    Player,
+    journal::{self, JournalError, JOURNAL_VERSION}, hasher::STATE_HASHER
+};
+
+// Picks the first player of a match so that neither player can bias the
+// outcome. Both players commit to a random value, and once both commitments
+// have been exchanged the values are revealed and combined.
+
+// H(game_id || player || value). The values are random, so unlike move
+// commitments they need no salt. The game and the player are bound in so that
+// a player cannot copy the commitment of their opponent and then reveal their
+// value, which would always cancel it out and leave the first player to them.
+pub fn commitment(game_id: &[u8; 32], player: Player, value: &[u8; 32]) -> Digest {
+    let mut bytes = [0u8; 32 + 1 + 32];
+
+    bytes[..32].copy_from_slice(game_id);
+    bytes[32] = player as u8;
+    bytes[33..].copy_from_slice(value);
+
+    *Impl::hash_bytes(&bytes)
+}
+
+// Player A moves first if the lowest bit of the XOR of the values is zero.
+pub fn first_player(value_a: &[u8; 32], value_b: &[u8; 32]) -> Player {
+    if (value_a[0] ^ value_b[0]) & 1 == 0 {
+        Player::A
+    } else {
+        Player::B
+    }
+}
+
+// What the coin flip guest commits.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FlipJournal {
+    pub version: u32,
+    pub hasher: u32,
+    pub game_id: [u8; 32],
+    // The commitments of player A and player B that were opened.
+    pub commitments: [Digest; 2],
+    pub first_player: Player
+}
+
+impl FlipJournal {
+    // Panics if either value doesn't match the commitment of its player, or
+    // the players committed the same way.
+    pub fn new(game_id: [u8; 32], commitments: [Digest; 2], values: [[u8; 32]; 2]) -> Self {
+        assert_ne!(commitments[0], commitments[1], "Both players made the same commitment.");
+
+        for ((player, value), commitment) in [Player::A, Player::B].into_iter().zip(&values).zip(&commitments) {
+            assert_eq!(
+                self::commitment(&game_id, player, value),
+                *commitment,
+                "Revealed value does not match the commitment."
+            );
+        }
+
+        Self {
+            version: JOURNAL_VERSION,
+            hasher: STATE_HASHER,
+            game_id,
+            commitments,
+            first_player: first_player(&values[0], &values[1])
+        }
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        journal::decode_versioned(bytes)
+    }
+}
@@ -0,0 +1,36 @@
+use risc0_zkvm::sha::{Sha256, Digest, Impl};
+use serde::{Serialize, Deserialize};
+
+use crate::{Player, Point};
+
+// A move that was hidden behind a commitment until it is revealed.
+// The salt prevents the opponent from brute forcing the commitment,
+// since there are only a handful of possible moves.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Reveal {
+    pub point: Point,
+    pub salt: [u8; 32]
+}
+
+impl Reveal {
+    pub fn new(point: Point, salt: [u8; 32]) -> Self {
+        Self { point, salt }
+    }
+
+    // H(game_id || player || move_index || x || y || salt). The game, the player
+    // and the index of the move are bound in so that a commitment only opens for
+    // the move it was made for. It cannot be replayed in another game or turn,
+    // nor copied by the opponent, who could otherwise reveal the same move.
+    pub fn commitment(&self, game_id: &[u8; 32], player: Player, move_index: u32) -> Digest {
+        let mut bytes = [0u8; 32 + 1 + 4 + 4 + 4 + 32];
+
+        bytes[..32].copy_from_slice(game_id);
+        bytes[32] = player as u8;
+        bytes[33..37].copy_from_slice(&move_index.to_le_bytes());
+        bytes[37..41].copy_from_slice(&(self.point.x() as u32).to_le_bytes());
+        bytes[41..45].copy_from_slice(&(self.point.y() as u32).to_le_bytes());
+        bytes[45..].copy_from_slice(&self.salt);
+
+        *Impl::hash_bytes(&bytes)
+    }
+}
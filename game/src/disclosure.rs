@@ -0,0 +1,72 @@
+use risc0_zkvm::sha::Digest;
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    TicTacToe, Player, Point, MoveError, CELL_COUNT,
+    journal::{self, JournalError, JOURNAL_VERSION}, hasher::STATE_HASHER
+};
+
+// A part of the board that can be disclosed on its own.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Region {
+    Row(usize),
+    Column(usize),
+    Cell(Point)
+}
+
+// What the disclosure guest commits. It reveals the cells of `region` of the
+// board with the salted hash `state_hash` and nothing about the other cells,
+// e.g. as a hint or to spectators of a private game.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DisclosureJournal {
+    pub version: u32,
+    pub hasher: u32,
+    pub state_hash: Digest,
+    pub region: Region,
+    pub cells: Vec<(Point, Option<Player>)>
+}
+
+impl Region {
+    pub fn points(&self) -> Vec<Point> {
+        match *self {
+            Self::Row(y) => (0..CELL_COUNT).map(|x| Point::new(x, y)).collect(),
+            Self::Column(x) => (0..CELL_COUNT).map(|y| Point::new(x, y)).collect(),
+            Self::Cell(point) => vec![point]
+        }
+    }
+}
+
+impl DisclosureJournal {
+    pub fn new(game: &TicTacToe, salt: &[u8; 32], region: Region) -> Result<Self, MoveError> {
+        let points = region.points();
+
+        if points.iter().any(|point| point.x() >= CELL_COUNT || point.y() >= CELL_COUNT) {
+            return Err(MoveError::PointOutOfBounds);
+        }
+
+        Ok(Self {
+            version: JOURNAL_VERSION,
+            hasher: STATE_HASHER,
+            state_hash: game.salted_hash(salt),
+            region,
+            cells: points
+                .into_iter()
+                .map(|point| (point, game.get(point)))
+                .collect()
+        })
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        journal::decode_versioned(bytes)
+    }
+}
+
+impl std::fmt::Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Row(y) => write!(f, "row {}", y),
+            Self::Column(x) => write!(f, "column {}", x),
+            Self::Cell(point) => write!(f, "cell {}", point)
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{TicTacToe, Point, State, CELL_COUNT};
+
+// Minimax search over all continuations of a game. Scores are from the
+// perspective of the player whose turn it is: 1 for a win, 0 for a
+// stalemate and -1 for a loss, assuming perfect play from both sides.
+
+pub fn evaluate(game: &TicTacToe) -> i8 {
+    negamax(game, -2, 2)
+}
+
+// The first of the moves with the best score, if the game is in progress.
+pub fn best_move(game: &TicTacToe) -> Option<Point> {
+    let mut best: Option<(Point, i8)> = None;
+
+    for (point, next) in moves(game) {
+        let score = -negamax(&next, -2, 2);
+
+        if !matches!(best, Some((_, best)) if best >= score) {
+            best = Some((point, score));
+        }
+    }
+
+    best.map(|(point, _)| point)
+}
+
+// Whether playing `point` achieves the best possible outcome for the current player.
+pub fn is_optimal(game: &TicTacToe, point: Point) -> bool {
+    let mut next = *game;
+
+    if next.make_move(point).is_err() {
+        return false;
+    }
+
+    -negamax(&next, -2, 2) == evaluate(game)
+}
+
+// The moves that the current player can make, if the game is in progress.
+pub fn legal_moves(game: &TicTacToe) -> impl Iterator<Item = Point> + '_ {
+    moves(game).map(|(point, _)| point)
+}
+
+fn negamax(game: &TicTacToe, mut alpha: i8, beta: i8) -> i8 {
+    match game.state() {
+        // The winner is always the player that made the last move.
+        State::Winner(_) => return -1,
+        State::Stalemate => return 0,
+        State::InProgress => { }
+    }
+
+    let mut best = -2;
+
+    for (_, next) in moves(game) {
+        let score = -negamax(&next, -beta, -alpha);
+
+        best = best.max(score);
+        alpha = alpha.max(score);
+
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    best
+}
+
+fn moves(game: &TicTacToe) -> impl Iterator<Item = (Point, TicTacToe)> + '_ {
+    (0..CELL_COUNT)
+        .flat_map(|y| (0..CELL_COUNT).map(move |x| Point::new(x, y)))
+        .filter_map(|point| {
+            let mut next = *game;
+
+            next.make_move(point).ok().map(|_| (point, next))
+        })
+}
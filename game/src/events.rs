@@ -0,0 +1,14 @@
+use crate::{TicTacToe, Player, Point, State};
+
+// Implemented by integrations (GUIs, bots, loggers) that want to react
+// to game transitions as they happen instead of polling the game state.
+// All methods have empty default implementations so that observers only
+// need to implement the events they are interested in.
+pub trait GameObserver {
+    // Called after `player` has successfully played `point`.
+    fn on_move(&mut self, _player: Player, _point: Point, _game: &TicTacToe) { }
+
+    // Called after a move has changed the state of the game, i.e.
+    // when the game has been won or has ended in a stalemate.
+    fn on_state_change(&mut self, _previous: State, _current: State) { }
+}
@@ -0,0 +1,96 @@
+use risc0_zkvm::sha::Digest;
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    TicTacToe, Point, MoveError, State,
+    journal::{self, JournalError, JOURNAL_VERSION}, hasher::STATE_HASHER
+};
+
+// A move as asserted by a party without a proof, e.g. in an optimistic
+// protocol where proofs are only produced when a move is disputed.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct MoveClaim {
+    pub move_index: u32,
+    pub prev_state_hash: Digest,
+    pub point: Point,
+    pub new_state_hash: Digest,
+    pub state: State
+}
+
+// Why a claim is invalid.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Fraud {
+    // The claim doesn't start from the state it is disputed against. Clients
+    // refuse it, as the game that the fraud is proven on is up to the prover.
+    StateMismatch,
+    IllegalMove(MoveError),
+    // The move is legal but doesn't lead to the claimed state.
+    WrongResult
+}
+
+// What the fraud proof guest commits. It attests that `claim` is invalid
+// when played on the game with the hash `state_hash`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FraudJournal {
+    pub version: u32,
+    pub hasher: u32,
+    pub state_hash: Digest,
+    pub claim: MoveClaim,
+    pub fraud: Fraud
+}
+
+impl MoveClaim {
+    // The claim of an honest party playing `point` on `game`.
+    pub fn new(game: &TicTacToe, move_index: u32, point: Point) -> Result<Self, MoveError> {
+        let mut next = *game;
+        next.make_move(point)?;
+
+        Ok(Self {
+            move_index,
+            prev_state_hash: game.state_hash(),
+            point,
+            new_state_hash: next.state_hash(),
+            state: next.state()
+        })
+    }
+}
+
+impl FraudJournal {
+    // Returns `None` if the claim is valid for `game`.
+    pub fn new(game: &TicTacToe, claim: MoveClaim) -> Option<Self> {
+        let state_hash = game.state_hash();
+        let mut next = *game;
+
+        let fraud = if claim.prev_state_hash != state_hash {
+            Fraud::StateMismatch
+        } else if let Err(error) = next.make_move(claim.point) {
+            Fraud::IllegalMove(error)
+        } else if claim.new_state_hash != next.state_hash() || claim.state != next.state() {
+            Fraud::WrongResult
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            version: JOURNAL_VERSION,
+            hasher: STATE_HASHER,
+            state_hash,
+            claim,
+            fraud
+        })
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        journal::decode_versioned(bytes)
+    }
+}
+
+impl std::fmt::Display for Fraud {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StateMismatch => write!(f, "The move was claimed against a different state."),
+            Self::IllegalMove(error) => write!(f, "The move was illegal: {}", error),
+            Self::WrongResult => write!(f, "The move doesn't lead to the claimed state.")
+        }
+    }
+}
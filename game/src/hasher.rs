@@ -0,0 +1,81 @@
+use risc0_zkvm::sha::{Sha256, Digest, Impl};
+
+// Hashes game states. The implementation is selected with a feature flag
+// and journals record the `STATE_HASHER` that the guest was built with,
+// so that clients can refuse state hashes they would compute differently.
+pub trait StateHasher {
+    // Identifies the hash function in journals.
+    const ID: u32;
+
+    fn hash(bytes: &[u8]) -> Digest;
+}
+
+pub struct Sha256Hasher;
+
+// Blake2b with a 256 bit output.
+#[cfg(feature = "blake2b")]
+pub struct Blake2bHasher;
+
+// Poseidon over BN254 with the circom parameters, which is cheap to
+// recompute in SNARK circuits and on-chain verifiers. The input is read as
+// a single big endian field element, which is enough for a game state.
+#[cfg(feature = "poseidon")]
+pub struct PoseidonHasher;
+
+// Poseidon takes precedence over Blake2b if both features are enabled.
+#[cfg(feature = "poseidon")]
+pub type SelectedHasher = PoseidonHasher;
+
+#[cfg(all(feature = "blake2b", not(feature = "poseidon")))]
+pub type SelectedHasher = Blake2bHasher;
+
+#[cfg(not(any(feature = "blake2b", feature = "poseidon")))]
+pub type SelectedHasher = Sha256Hasher;
+
+pub const STATE_HASHER: u32 = <SelectedHasher as StateHasher>::ID;
+
+impl StateHasher for Sha256Hasher {
+    const ID: u32 = 0;
+
+    fn hash(bytes: &[u8]) -> Digest {
+        *Impl::hash_bytes(bytes)
+    }
+}
+
+#[cfg(feature = "blake2b")]
+impl StateHasher for Blake2bHasher {
+    const ID: u32 = 1;
+
+    fn hash(bytes: &[u8]) -> Digest {
+        use blake2::{Blake2b, Digest as _, digest::consts::U32};
+
+        digest_from_bytes(&Blake2b::<U32>::digest(bytes))
+    }
+}
+
+#[cfg(feature = "poseidon")]
+impl StateHasher for PoseidonHasher {
+    const ID: u32 = 2;
+
+    fn hash(bytes: &[u8]) -> Digest {
+        use light_poseidon::{Poseidon, PoseidonBytesHasher};
+
+        let hash = Poseidon::<ark_bn254::Fr>::new_circom(1)
+            .and_then(|mut poseidon| poseidon.hash_bytes_be(&[bytes]))
+            .expect("input fits in a single field element");
+
+        digest_from_bytes(&hash)
+    }
+}
+
+// The bytes of the resulting digest are the same as the given ones.
+#[cfg(any(feature = "blake2b", feature = "poseidon"))]
+fn digest_from_bytes(bytes: &[u8]) -> Digest {
+    let mut words = [0u32; 8];
+
+    for (word, bytes) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+
+    Digest::from(words)
+}
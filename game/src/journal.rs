@@ -0,0 +1,516 @@
+use risc0_zkvm::{
+    serde::from_slice,
+    sha::{Sha256, Digest, Impl}
+};
+use serde::{Serialize, Deserialize};
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    TicTacToe, Player, Point, MoveError, State,
+    hasher::STATE_HASHER, metadata::{MatchMetadata, Ruleset}
+};
+
+// Must be incremented whenever the layout of `Journal` changes so that
+// clients refuse to interpret receipts produced by a different guest.
+pub const JOURNAL_VERSION: u32 = 12;
+
+// What the guest commits. The version is always the first field so that
+// it can be decoded regardless of what the rest of the journal looks like.
+// It is followed by the `STATE_HASHER` that the guest was built with.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Journal {
+    pub version: u32,
+    pub hasher: u32,
+    // The number of moves accepted before this one.
+    pub move_index: u32,
+    pub prev_chain_hash: Digest,
+    // The head of the hash chain after this move. Clients track it so that
+    // receipts cannot be replayed, dropped or reordered without detection.
+    pub chain_hash: Digest,
+    // Set when the move was hidden behind a commitment made beforehand.
+    // Clients must check that it matches the commitment they received.
+    pub commitment: Option<Digest>,
+    // Set when the move was authorized by a signature of the player. It is the
+    // hash of the match metadata that holds the public keys of the players.
+    pub match_hash: Option<Digest>,
+    pub response: VmResponse
+}
+
+// The outcome of a move. An illegal move does not abort the guest
+// but produces a rejection instead, so that it can be verified as well.
+#[derive(Serialize, Deserialize, Debug)]
+pub enum VmResponse {
+    Accepted {
+        // The match that the move was made in, see `MatchMetadata::game_id`.
+        game_id: [u8; 32],
+        game: TicTacToe,
+        prev_state_hash: Digest,
+        new_state_hash: Digest,
+        player: Player,
+        point: Point
+    },
+    Rejected {
+        game_id: [u8; 32],
+        error: MoveError,
+        prev_state_hash: Digest,
+        player: Player,
+        point: Point
+    }
+}
+
+// What the finalization guest commits after replaying an entire match
+// from its initial state, attesting to its result in a single receipt.
+// Since it names the match, it can be stored or published on its own as
+// proof that e.g. player A won game G after a legal sequence of moves.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GameJournal {
+    pub version: u32,
+    pub hasher: u32,
+    pub game_id: [u8; 32],
+    pub match_hash: Digest,
+    pub move_count: u32,
+    pub initial_state_hash: Digest,
+    pub final_state_hash: Digest,
+    // Equal to the head that clients end up with after
+    // verifying the per-move receipts of the same game.
+    pub chain_hash: Digest,
+    pub state: State
+}
+
+// What the length guest commits. It attests that the match finished with
+// the given result in at most `max_moves` moves, without revealing the
+// moves, the exact length of the game or the final board.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LengthJournal {
+    pub version: u32,
+    pub hasher: u32,
+    pub game_id: [u8; 32],
+    pub match_hash: Digest,
+    pub max_moves: u32,
+    // Lets the players check that it is the game they have played.
+    pub chain_hash: Digest,
+    pub state: State
+}
+
+// What the private move guest commits. It reveals neither the board nor
+// the move, only that a legal move (or an illegal one) was made between
+// two salted state hashes and what the state of the game is afterwards.
+// There is no hash chain since it would reveal the move by brute force.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PrivateJournal {
+    pub version: u32,
+    pub hasher: u32,
+    pub move_index: u32,
+    pub prev_state_hash: Digest,
+    pub new_state_hash: Digest,
+    pub accepted: bool,
+    pub state: State
+}
+
+// What the batch guest commits after playing several moves in one session.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchJournal {
+    pub version: u32,
+    pub hasher: u32,
+    pub game_id: [u8; 32],
+    pub move_index: u32,
+    pub prev_chain_hash: Digest,
+    pub chain_hash: Digest,
+    pub prev_state_hash: Digest,
+    // The accepted moves and the hash of the state after each of them.
+    pub points: Vec<Point>,
+    pub state_hashes: Vec<Digest>,
+    pub game: TicTacToe,
+    // The first illegal move of the batch, if any.
+    // The moves following it are not played.
+    pub rejected: Option<(Point, MoveError)>
+}
+
+// What the draw guest commits. It attests that the game
+// with the given state can only end in a stalemate.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DrawJournal {
+    pub version: u32,
+    pub hasher: u32,
+    pub state_hash: Digest
+}
+
+// What the threat guest commits. It attests that `player` can win the game
+// with the given state on their next move without revealing the move.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ThreatJournal {
+    pub version: u32,
+    pub hasher: u32,
+    pub state_hash: Digest,
+    pub player: Player
+}
+
+// What the initialization guest commits. It attests that a game with the
+// given initial state was set up from the match metadata with `match_hash`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InitJournal {
+    pub version: u32,
+    pub hasher: u32,
+    pub match_hash: Digest,
+    pub game_id: [u8; 32],
+    pub first_player: Player,
+    pub ruleset: Ruleset,
+    // Also the start of the hash chain.
+    pub state_hash: Digest
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum JournalError {
+    UnsupportedVersion(u32),
+    UnsupportedHasher(u32),
+    Malformed
+}
+
+impl Journal {
+    // A rejected move doesn't change the game state and so doesn't advance the chain.
+    pub fn new(move_index: u32, prev_chain_hash: Digest, response: VmResponse) -> Self {
+        let chain_hash = match &response {
+            VmResponse::Accepted { point, new_state_hash, .. } => chain_hash(
+                &prev_chain_hash,
+                move_index,
+                *point,
+                new_state_hash
+            ),
+            VmResponse::Rejected { .. } => prev_chain_hash
+        };
+
+        Self {
+            version: JOURNAL_VERSION,
+            hasher: STATE_HASHER,
+            move_index,
+            prev_chain_hash,
+            chain_hash,
+            commitment: None,
+            match_hash: None,
+            response
+        }
+    }
+
+    pub fn with_commitment(mut self, commitment: Digest) -> Self {
+        self.commitment = Some(commitment);
+
+        self
+    }
+
+    pub fn with_match_hash(mut self, match_hash: Digest) -> Self {
+        self.match_hash = Some(match_hash);
+
+        self
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        decode_versioned(bytes)
+    }
+}
+
+impl GameJournal {
+    pub fn play(metadata: &MatchMetadata, moves: &[Point]) -> Result<Self, MoveError> {
+        let initial = metadata.initial_game();
+        let mut game = initial;
+        let mut chain_hash = initial.state_hash();
+
+        for (index, point) in moves.iter().enumerate() {
+            game.make_move(*point)?;
+
+            chain_hash = self::chain_hash(
+                &chain_hash,
+                index as u32,
+                *point,
+                &game.state_hash()
+            );
+        }
+
+        Ok(Self {
+            version: JOURNAL_VERSION,
+            hasher: STATE_HASHER,
+            game_id: metadata.game_id,
+            match_hash: metadata.hash(),
+            move_count: moves.len() as u32,
+            initial_state_hash: initial.state_hash(),
+            final_state_hash: game.state_hash(),
+            chain_hash,
+            state: game.state()
+        })
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        decode_versioned(bytes)
+    }
+}
+
+impl LengthJournal {
+    pub fn new(game: &GameJournal, max_moves: u32) -> Self {
+        Self {
+            version: JOURNAL_VERSION,
+            hasher: STATE_HASHER,
+            game_id: game.game_id,
+            match_hash: game.match_hash,
+            max_moves,
+            chain_hash: game.chain_hash,
+            state: game.state
+        }
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        decode_versioned(bytes)
+    }
+}
+
+impl PrivateJournal {
+    pub fn play(game: &mut TicTacToe, point: Point, salt: &[u8; 32], move_index: u32) -> Self {
+        let prev_state_hash = game.salted_hash(salt);
+        let accepted = game.make_move(point).is_ok();
+
+        Self {
+            version: JOURNAL_VERSION,
+            hasher: STATE_HASHER,
+            move_index,
+            prev_state_hash,
+            new_state_hash: game.salted_hash(salt),
+            accepted,
+            state: game.state()
+        }
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        decode_versioned(bytes)
+    }
+}
+
+impl BatchJournal {
+    pub fn play(
+        game: &mut TicTacToe,
+        game_id: [u8; 32],
+        moves: &[Point],
+        move_index: u32,
+        prev_chain_hash: Digest
+    ) -> Self {
+        let prev_state_hash = game.state_hash();
+        let mut chain_hash = prev_chain_hash;
+
+        let mut points = Vec::with_capacity(moves.len());
+        let mut state_hashes = Vec::with_capacity(moves.len());
+        let mut rejected = None;
+
+        for point in moves {
+            if let Err(error) = game.make_move(*point) {
+                rejected = Some((*point, error));
+
+                break;
+            }
+
+            let state_hash = game.state_hash();
+
+            chain_hash = self::chain_hash(
+                &chain_hash,
+                move_index + points.len() as u32,
+                *point,
+                &state_hash
+            );
+
+            points.push(*point);
+            state_hashes.push(state_hash);
+        }
+
+        Self {
+            version: JOURNAL_VERSION,
+            hasher: STATE_HASHER,
+            game_id,
+            move_index,
+            prev_chain_hash,
+            chain_hash,
+            prev_state_hash,
+            points,
+            state_hashes,
+            game: *game,
+            rejected
+        }
+    }
+
+    pub fn new_state_hash(&self) -> Digest {
+        self.state_hashes.last().copied().unwrap_or(self.prev_state_hash)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        decode_versioned(bytes)
+    }
+}
+
+impl InitJournal {
+    pub fn new(metadata: &MatchMetadata) -> Self {
+        Self {
+            version: JOURNAL_VERSION,
+            hasher: STATE_HASHER,
+            match_hash: metadata.hash(),
+            game_id: metadata.game_id,
+            first_player: metadata.first_player,
+            ruleset: metadata.ruleset,
+            state_hash: metadata.initial_game().state_hash()
+        }
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        decode_versioned(bytes)
+    }
+}
+
+impl DrawJournal {
+    pub fn new(state_hash: Digest) -> Self {
+        Self {
+            version: JOURNAL_VERSION,
+            hasher: STATE_HASHER,
+            state_hash
+        }
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        decode_versioned(bytes)
+    }
+}
+
+impl ThreatJournal {
+    pub fn new(state_hash: Digest, player: Player) -> Self {
+        Self {
+            version: JOURNAL_VERSION,
+            hasher: STATE_HASHER,
+            state_hash,
+            player
+        }
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        decode_versioned(bytes)
+    }
+}
+
+// All journals start with their version and the state hasher.
+pub(crate) fn decode_versioned<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, JournalError> {
+    let (version, hasher): (u32, u32) = from_slice(bytes).map_err(|_| JournalError::Malformed)?;
+
+    if version != JOURNAL_VERSION {
+        return Err(JournalError::UnsupportedVersion(version));
+    }
+
+    if hasher != STATE_HASHER {
+        return Err(JournalError::UnsupportedHasher(hasher));
+    }
+
+    from_slice(bytes).map_err(|_| JournalError::Malformed)
+}
+
+impl VmResponse {
+    // Plays `point` on `game` of the match `game_id` and describes the outcome.
+    pub fn play(game: &mut TicTacToe, point: Point, game_id: [u8; 32]) -> Self {
+        let prev_state_hash = game.state_hash();
+        let player = game.current_player();
+
+        match game.make_move(point) {
+            Ok(()) => Self::Accepted {
+                game_id,
+                game: *game,
+                prev_state_hash,
+                new_state_hash: game.state_hash(),
+                player,
+                point
+            },
+            Err(error) => Self::Rejected {
+                game_id,
+                error,
+                prev_state_hash,
+                player,
+                point
+            }
+        }
+    }
+
+    pub fn game_id(&self) -> [u8; 32] {
+        match self {
+            Self::Accepted { game_id, .. } |
+            Self::Rejected { game_id, .. } => *game_id
+        }
+    }
+
+    pub fn prev_state_hash(&self) -> Digest {
+        match self {
+            Self::Accepted { prev_state_hash, .. } |
+            Self::Rejected { prev_state_hash, .. } => *prev_state_hash
+        }
+    }
+
+    // The hash of the state after the move. Since a rejected move leaves
+    // the game untouched, this is the same as the previous hash in that case.
+    pub fn new_state_hash(&self) -> Digest {
+        match self {
+            Self::Accepted { new_state_hash, .. } => *new_state_hash,
+            Self::Rejected { prev_state_hash, .. } => *prev_state_hash
+        }
+    }
+
+    // The player who made the move.
+    pub fn player(&self) -> Player {
+        match self {
+            Self::Accepted { player, .. } |
+            Self::Rejected { player, .. } => *player
+        }
+    }
+
+    // The cell that the player attempted to fill.
+    pub fn point(&self) -> Point {
+        match self {
+            Self::Accepted { point, .. } |
+            Self::Rejected { point, .. } => *point
+        }
+    }
+}
+
+// The hash chain starts at the hash of the initial game state, which is this
+// one for a game where player A moves first without a nonce, see `Client::new`.
+// Matches start from `MatchMetadata::initial_game` instead, which depends on
+// their `first_player` and nonce, so their chain starts at its state hash.
+pub fn initial_chain_hash() -> Digest {
+    TicTacToe::initial_hash()
+}
+
+// H(prev_chain_hash || move_index || x || y || new_state_hash)
+pub fn chain_hash(
+    prev_chain_hash: &Digest,
+    move_index: u32,
+    point: Point,
+    new_state_hash: &Digest
+) -> Digest {
+    let mut bytes = Vec::with_capacity(32 + 4 + 4 + 4 + 32);
+
+    bytes.extend_from_slice(prev_chain_hash.as_bytes());
+    bytes.extend_from_slice(&move_index.to_le_bytes());
+    bytes.extend_from_slice(&(point.x() as u32).to_le_bytes());
+    bytes.extend_from_slice(&(point.y() as u32).to_le_bytes());
+    bytes.extend_from_slice(new_state_hash.as_bytes());
+
+    *Impl::hash_bytes(&bytes)
+}
+
+impl std::fmt::Display for JournalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JournalError::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported journal version {}. Expected version {}.",
+                version,
+                JOURNAL_VERSION
+            ),
+            JournalError::UnsupportedHasher(hasher) => write!(
+                f,
+                "Journal states were hashed with state hasher {}. Expected state hasher {}.",
+                hasher,
+                STATE_HASHER
+            ),
+            JournalError::Malformed => write!(f, "Journal could not be decoded.")
+        }
+    }
+}
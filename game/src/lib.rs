@@ -2,9 +2,45 @@ use std::mem;
 
 use risc0_zkvm::sha::{Sha256, Digest, Impl};
 use serde::{Serialize, Deserialize};
+use static_assertions::{assert_eq_align, assert_eq_size, const_assert_eq};
+use memoffset::offset_of;
+
+use hasher::{StateHasher, SelectedHasher};
+
+pub mod render;
+pub mod events;
+pub mod metadata;
+pub mod journal;
+pub mod commitment;
+pub mod simultaneous;
+pub mod auth;
+pub mod engine;
+pub mod hasher;
+pub mod coin_flip;
+pub mod disclosure;
+pub mod fraud;
+pub mod clock;
+pub mod chat;
+pub mod vectors;
+
+pub use journal::{
+    VmResponse, Journal, GameJournal, PrivateJournal, BatchJournal, DrawJournal,
+    ThreatJournal, InitJournal, LengthJournal,
+    JournalError, JOURNAL_VERSION,
+    chain_hash, initial_chain_hash
+};
 
 const CELL_COUNT: usize = 3;
 
+pub const NONCE_SIZE: usize = 16;
+
+// The size of the byte encoding of `TicTacToe`, see `as_bytes`.
+pub const STATE_SIZE: usize =
+    (CELL_COUNT * CELL_COUNT) +
+    mem::size_of::<Player>() +
+    mem::size_of::<State>() +
+    NONCE_SIZE;
+
 // repr(C) allows us to interpret the struct as raw bytes
 // in the order that fields are defined in it.
 #[repr(C)]
@@ -12,14 +48,29 @@ const CELL_COUNT: usize = 3;
 pub struct TicTacToe {
     board: [[Cell; CELL_COUNT]; CELL_COUNT],
     previous: Player,
-    state: State
+    state: State,
+    // Chosen at random for each match. Since it is part of every state hash,
+    // receipts from one match cannot be replayed into another one, even
+    // if the board is the same.
+    nonce: [u8; NONCE_SIZE]
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct VmResponse {
-    pub game: TicTacToe,
-    pub prev_state_hash: Digest
-}
+// `as_bytes` and `from_bytes` rely on the fields being laid out
+// in the order that they are defined in, without any padding.
+assert_eq_align!(TicTacToe, u8);
+assert_eq_size!(TicTacToe, [u8; STATE_SIZE]);
+assert_eq_size!(Cell, u8);
+assert_eq_size!(Player, u8);
+const_assert_eq!(offset_of!(TicTacToe, board), 0);
+const_assert_eq!(offset_of!(TicTacToe, previous), CELL_COUNT * CELL_COUNT);
+const_assert_eq!(
+    offset_of!(TicTacToe, state),
+    offset_of!(TicTacToe, previous) + mem::size_of::<Player>()
+);
+const_assert_eq!(
+    offset_of!(TicTacToe, nonce),
+    offset_of!(TicTacToe, state) + mem::size_of::<State>()
+);
 
 #[repr(u8)]
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
@@ -41,6 +92,14 @@ pub enum MoveError {
     GameFinished
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ParseBoardError {
+    InvalidRowCount,
+    InvalidRow(usize),
+    InvalidCell(char),
+    InvalidMoveCount
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
 pub enum State {
     InProgress,
@@ -69,12 +128,116 @@ impl TicTacToe {
         Self {
             board,
             previous: Player::B,
-            state: State::InProgress
+            state: State::InProgress,
+            nonce: [0; NONCE_SIZE]
+        }
+    }
+
+    pub fn with_nonce(mut self, nonce: [u8; NONCE_SIZE]) -> Self {
+        self.nonce = nonce;
+
+        self
+    }
+
+    pub fn nonce(&self) -> [u8; NONCE_SIZE] {
+        self.nonce
+    }
+
+    // Parses a board in the same layout that `print_board` emits, i.e.
+    // one "|X|O| |" line per row. Leading and trailing blank lines are ignored.
+    // Whose turn it is gets derived from the number of marks of each player
+    // on the board, taking player A (X) to have moved first as in `new`. Boards
+    // of matches whose `first_player` is B are parsed with the wrong player on
+    // turn, so those are built with `starting_with` instead.
+    pub fn from_ascii(ascii: &str) -> Result<Self, ParseBoardError> {
+        let mut game = Self::new();
+        let mut rows = ascii.trim_matches(|c| c == '\n' || c == '\r').lines();
+
+        let mut player_1_count = 0;
+        let mut player_2_count = 0;
+
+        for y in 0..CELL_COUNT {
+            let row = rows.next().ok_or(ParseBoardError::InvalidRowCount)?;
+            let chars: Vec<char> = row.trim_end_matches('\r').chars().collect();
+
+            if chars.len() != CELL_COUNT * 2 + 1 {
+                return Err(ParseBoardError::InvalidRow(y));
+            }
+
+            for x in 0..CELL_COUNT {
+                if chars[x * 2] != '|' {
+                    return Err(ParseBoardError::InvalidRow(y));
+                }
+
+                game.board[y][x] = match chars[x * 2 + 1] {
+                    'X' => {
+                        player_1_count += 1;
+
+                        Cell::Player1
+                    },
+                    'O' => {
+                        player_2_count += 1;
+
+                        Cell::Player2
+                    },
+                    ' ' => Cell::Vacant,
+                    other => return Err(ParseBoardError::InvalidCell(other))
+                };
+            }
+
+            if chars[CELL_COUNT * 2] != '|' {
+                return Err(ParseBoardError::InvalidRow(y));
+            }
+        }
+
+        if rows.next().is_some() {
+            return Err(ParseBoardError::InvalidRowCount);
+        }
+
+        game.previous = if player_1_count == player_2_count {
+            Player::B
+        } else if player_1_count == player_2_count + 1 {
+            Player::A
+        } else {
+            return Err(ParseBoardError::InvalidMoveCount);
+        };
+
+        game.update_state();
+
+        // The winner must be the one that made the last move.
+        if let State::Winner(winner) = game.state {
+            if winner != game.previous {
+                return Err(ParseBoardError::InvalidMoveCount);
+            }
+        }
+
+        Ok(game)
+    }
+
+    // A new game where `player` makes the first move.
+    pub fn starting_with(player: Player) -> Self {
+        Self {
+            previous: player.flip(),
+            ..Self::new()
         }
     }
 
     pub fn initial_hash() -> Digest {
-        let bytes = Self::new().as_bytes();
+        Self::new().state_hash()
+    }
+
+    pub fn state_hash(&self) -> Digest {
+        SelectedHasher::hash(&self.as_bytes())
+    }
+
+    // H(salt || state). Since there are only so many possible boards, the
+    // plain state hash can be reversed by brute force. A secret salt
+    // prevents that for parties that are not supposed to see the board.
+    // Always SHA-256, since the salted state doesn't fit in a single
+    // field element for Poseidon.
+    pub fn salted_hash(&self, salt: &[u8; 32]) -> Digest {
+        let mut bytes = salt.to_vec();
+        bytes.extend_from_slice(&self.as_bytes());
 
         *Impl::hash_bytes(&bytes)
     }
@@ -107,10 +270,51 @@ impl TicTacToe {
         self.state
     }
 
+    // Whether the game can only end in a stalemate, no matter how it is played.
+    // Searches all continuations so it is only cheap late in the game.
+    pub fn is_draw(&self) -> bool {
+        !self.can_be_won()
+    }
+
+    // A move that immediately wins the game for the current player, if any.
+    pub fn winning_move(&self) -> Option<Point> {
+        let player = self.current_player();
+
+        (0..CELL_COUNT)
+            .flat_map(|y| (0..CELL_COUNT).map(move |x| Point::new(x, y)))
+            .find(|point| {
+                let mut game = *self;
+
+                game.make_move(*point).is_ok() && game.state == State::Winner(player)
+            })
+    }
+
+    fn can_be_won(&self) -> bool {
+        match self.state {
+            State::Winner(_) => true,
+            State::Stalemate => false,
+            State::InProgress => (0..CELL_COUNT).any(|y| (0..CELL_COUNT).any(|x| {
+                let mut game = *self;
+
+                game.make_move(Point::new(x, y)).is_ok() && game.can_be_won()
+            }))
+        }
+    }
+
     pub fn current_player(&self) -> Player {
         self.previous.flip()
     }
 
+    // The player that took the cell, if any. Points off the board, such
+    // as those of deserialized moves, are taken by no one.
+    pub fn get(&self, point: Point) -> Option<Player> {
+        match self.board.get(point.y)?.get(point.x)? {
+            Cell::Player1 => Some(Player::A),
+            Cell::Player2 => Some(Player::B),
+            Cell::Vacant => None
+        }
+    }
+
     fn update_state(&mut self) {
         let mut has_vacant = false;
 
@@ -185,40 +389,56 @@ impl TicTacToe {
     }
 
     pub fn print_board(&self) {
-        let mut row = [0u8; CELL_COUNT * 2];
-
-        for y in 0..CELL_COUNT {
-            let mut i = 0;
-            
-            for x in 0..CELL_COUNT {
-                let cell = match self.board[y][x] {
-                    Cell::Player1 => 'X',
-                    Cell::Player2 => 'O',
-                    Cell::Vacant => ' '
-                } as u8;
-
-                row[i] = cell;
-                row[i + 1] = '|' as u8;
-                i += 2;
-            }
+        println!("{}", self);
+    }
 
-            println!(
-                "|{}",
-                unsafe { std::str::from_utf8_unchecked(&row) }
-            );
-        }
+    pub fn as_bytes(&self) -> [u8; STATE_SIZE] {
+        unsafe { mem::transmute(*self) }
     }
 
-    pub fn as_bytes(&self) -> [
-        u8;
-        (CELL_COUNT * CELL_COUNT) +
-        mem::size_of::<Player>() +
-        mem::size_of::<State>()
-    ] {
-        // Assert that the struct contains no padding.
-        assert_eq!(mem::align_of::<TicTacToe>(), 1);
+    // The inverse of `as_bytes`, which lets guests read the game as raw bytes
+    // instead of decoding it with serde. Returns `None` if any of the fields
+    // holds a value that isn't valid for its type.
+    pub fn from_bytes(bytes: &[u8; STATE_SIZE]) -> Option<Self> {
+        let (board, rest) = bytes.split_at(CELL_COUNT * CELL_COUNT);
+        let (previous, rest) = rest.split_at(mem::size_of::<Player>());
+        let (state, nonce) = rest.split_at(mem::size_of::<State>());
+
+        let mut game = Self::new();
+
+        for (index, byte) in board.iter().enumerate() {
+            game.board[index / CELL_COUNT][index % CELL_COUNT] = match byte {
+                0 => Cell::Player1,
+                1 => Cell::Player2,
+                2 => Cell::Vacant,
+                _ => return None
+            };
+        }
 
-        unsafe { mem::transmute(*self) }
+        game.previous = match previous[0] {
+            0 => Player::A,
+            1 => Player::B,
+            _ => return None
+        };
+
+        // The layout of `State` is up to the compiler,
+        // so compare against the encoding of each value.
+        game.state = [
+            State::InProgress,
+            State::Stalemate,
+            State::Winner(Player::A),
+            State::Winner(Player::B)
+        ]
+        .into_iter()
+        .find(|candidate| {
+            let encoded: [u8; mem::size_of::<State>()] = unsafe { mem::transmute(*candidate) };
+
+            encoded == state
+        })?;
+
+        game.nonce.copy_from_slice(nonce);
+
+        Some(game)
     }
 }
 
@@ -226,6 +446,20 @@ impl Point {
     pub fn new(x: usize, y: usize) -> Self {
         Self { x, y }
     }
+
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    pub fn y(&self) -> usize {
+        self.y
+    }
+}
+
+impl std::fmt::Display for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.x, self.y)
+    }
 }
 
 impl Into<Cell> for Player {
@@ -260,3 +494,271 @@ impl std::fmt::Display for MoveError {
         }
     }
 }
+
+impl std::fmt::Display for ParseBoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseBoardError::InvalidRowCount => write!(
+                f,
+                "Board must consist of exactly {} rows.",
+                CELL_COUNT
+            ),
+            ParseBoardError::InvalidRow(row) => write!(
+                f,
+                "Row {} is malformed. Expected a row such as \"|X|O| |\".",
+                row
+            ),
+            ParseBoardError::InvalidCell(cell) => write!(
+                f,
+                "Invalid cell '{}'. Must be one of 'X', 'O' or ' '.",
+                cell
+            ),
+            ParseBoardError::InvalidMoveCount => write!(
+                f,
+                "The number of moves made by each player is not reachable in a real game."
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_ascii_derives_turn_and_state() {
+        let game = TicTacToe::from_ascii("
+|X|O| |
+| |X| |
+|O| | |
+").unwrap();
+
+        assert_eq!(game.state(), State::InProgress);
+        assert_eq!(game.current_player(), Player::A);
+
+        let game = TicTacToe::from_ascii("
+|X|O| |
+|O|X| |
+| | |X|
+").unwrap();
+
+        assert_eq!(game.state(), State::Winner(Player::A));
+    }
+
+    #[test]
+    fn from_ascii_matches_played_game() {
+        let mut game = TicTacToe::new();
+        game.make_move(Point::new(1, 1)).unwrap();
+        game.make_move(Point::new(0, 0)).unwrap();
+
+        let parsed = TicTacToe::from_ascii("
+|O| | |
+| |X| |
+| | | |
+").unwrap();
+
+        assert_eq!(game.as_bytes(), parsed.as_bytes());
+    }
+
+    #[test]
+    fn render_round_trips_through_from_ascii() {
+        let ascii = "|X|O| |\n| |X| |\n|O| | |";
+        let game = TicTacToe::from_ascii(ascii).unwrap();
+
+        assert_eq!(game.to_string(), ascii);
+        assert_eq!(
+            format!("{:#}", game),
+            "┌────┬────┬────┐
+│ ❌ │ ⭕ │    │
+├────┼────┼────┤
+│    │ ❌ │    │
+├────┼────┼────┤
+│ ⭕ │    │    │
+└────┴────┴────┘"
+        );
+    }
+
+    #[test]
+    fn simultaneous_moves_resolve_deterministically() {
+        use simultaneous::RoundOutcome;
+
+        let mut game = TicTacToe::new();
+
+        assert_eq!(
+            game.make_simultaneous_moves(Point::new(1, 1), Point::new(1, 1)),
+            Ok(RoundOutcome::Collision)
+        );
+        assert_eq!(game.get(Point::new(1, 1)), None);
+
+        let mut game = TicTacToe::from_ascii("
+|X|O| |
+|X|O| |
+| | | |
+").unwrap();
+
+        assert_eq!(
+            game.make_simultaneous_moves(Point::new(0, 2), Point::new(1, 2)),
+            Ok(RoundOutcome::Placed)
+        );
+        assert_eq!(game.state(), State::Stalemate);
+
+        let mut game = TicTacToe::from_ascii("
+|X|O|X|
+|O|X|O|
+|O|X| |
+").unwrap();
+
+        assert_eq!(
+            game.make_simultaneous_moves(Point::new(2, 2), Point::new(2, 2)),
+            Ok(RoundOutcome::Collision)
+        );
+        assert_eq!(game.state(), State::Stalemate);
+    }
+
+    #[test]
+    fn detects_positions_that_cannot_be_won() {
+        let game = TicTacToe::from_ascii("
+|X|O|X|
+|X|O|O|
+|O|X| |
+").unwrap();
+
+        assert_eq!(game.state(), State::InProgress);
+        assert!(game.is_draw());
+
+        let game = TicTacToe::from_ascii("
+|X|O|X|
+| |O| |
+| | | |
+").unwrap();
+
+        assert!(!game.is_draw());
+        assert_eq!(game.winning_move(), None);
+
+        let game = TicTacToe::from_ascii("
+|X|O|X|
+| |O| |
+|X| | |
+").unwrap();
+
+        assert_eq!(game.current_player(), Player::B);
+        assert_eq!(game.winning_move(), Some(Point::new(1, 2)));
+    }
+
+    #[test]
+    fn from_bytes_inverts_as_bytes() {
+        let mut game = TicTacToe::starting_with(Player::B).with_nonce([7; NONCE_SIZE]);
+
+        for (x, y) in [(0, 0), (1, 1), (0, 1), (2, 2), (0, 2)] {
+            let decoded = TicTacToe::from_bytes(&game.as_bytes()).unwrap();
+            assert_eq!(decoded.as_bytes(), game.as_bytes());
+
+            game.make_move(Point::new(x, y)).unwrap();
+        }
+
+        assert_eq!(game.state(), State::Winner(Player::B));
+        assert_eq!(TicTacToe::from_bytes(&game.as_bytes()).unwrap().state(), game.state());
+
+        let mut bytes = game.as_bytes();
+        bytes[0] = 3;
+
+        assert!(TicTacToe::from_bytes(&bytes).is_none());
+    }
+
+    #[test]
+    fn player_b_can_move_first() {
+        let mut game = TicTacToe::starting_with(Player::B);
+        assert_eq!(game.current_player(), Player::B);
+        assert_ne!(game.as_bytes(), TicTacToe::new().as_bytes());
+
+        game.make_move(Point::new(1, 1)).unwrap();
+        assert_eq!(game.get(Point::new(1, 1)), Some(Player::B));
+        assert_eq!(game.current_player(), Player::A);
+    }
+
+    #[test]
+    fn points_off_the_board_are_taken_by_no_one() {
+        let game = TicTacToe::new();
+
+        assert_eq!(game.get(Point::new(CELL_COUNT, 1)), None);
+        assert_eq!(game.get(Point::new(1, CELL_COUNT)), None);
+    }
+
+    #[test]
+    fn fraud_is_detected_in_invalid_claims() {
+        use fraud::{MoveClaim, FraudJournal, Fraud};
+
+        let mut game = TicTacToe::new();
+        let claim = MoveClaim::new(&game, 0, Point::new(1, 1)).unwrap();
+        assert!(FraudJournal::new(&game, claim).is_none());
+
+        game.make_move(Point::new(1, 1)).unwrap();
+
+        let replayed = FraudJournal::new(&game, claim).unwrap();
+        assert_eq!(replayed.fraud, Fraud::StateMismatch);
+
+        let illegal = MoveClaim {
+            move_index: 1,
+            prev_state_hash: game.state_hash(),
+            point: Point::new(1, 1),
+            new_state_hash: game.state_hash(),
+            state: game.state()
+        };
+        let journal = FraudJournal::new(&game, illegal).unwrap();
+        assert_eq!(journal.fraud, Fraud::IllegalMove(MoveError::CellOccupied));
+    }
+
+    #[test]
+    fn engine_finds_optimal_moves() {
+        let game = TicTacToe::new();
+        assert_eq!(engine::evaluate(&game), 0);
+
+        let game = TicTacToe::from_ascii("
+|X|O|X|
+| |O| |
+|X| | |
+").unwrap();
+
+        assert_eq!(engine::evaluate(&game), 1);
+        assert!(engine::is_optimal(&game, engine::best_move(&game).unwrap()));
+        assert!(engine::is_optimal(&game, Point::new(1, 2)));
+        assert!(!engine::is_optimal(&game, Point::new(2, 2)));
+    }
+
+    #[test]
+    fn from_ascii_rejects_bad_input() {
+        assert_eq!(
+            TicTacToe::from_ascii("| | | |\n| | | |").unwrap_err(),
+            ParseBoardError::InvalidRowCount
+        );
+        assert_eq!(
+            TicTacToe::from_ascii("| | | |\n| |Z| |\n| | | |").unwrap_err(),
+            ParseBoardError::InvalidCell('Z')
+        );
+        assert_eq!(
+            TicTacToe::from_ascii("| | | |\n| | |\n| | | |").unwrap_err(),
+            ParseBoardError::InvalidRow(1)
+        );
+        assert_eq!(
+            TicTacToe::from_ascii("|X| | |\n|X| | |\n| | | |").unwrap_err(),
+            ParseBoardError::InvalidMoveCount
+        );
+    }
+
+    #[test]
+    fn rematches_swap_the_first_player() {
+        use metadata::MatchMetadata;
+
+        let metadata = MatchMetadata { game_id: [1; 32], ..MatchMetadata::default() };
+        let rematch = metadata.rematch([2; NONCE_SIZE]);
+
+        assert_eq!(rematch.first_player, Player::B);
+        assert_eq!(rematch.nonce, [2; NONCE_SIZE]);
+        assert_eq!((&rematch.player_a, &rematch.player_b), (&metadata.player_a, &metadata.player_b));
+        assert_ne!(rematch.game_id, metadata.game_id);
+
+        // Derived, so that either player can check it.
+        assert_eq!(rematch, metadata.rematch([2; NONCE_SIZE]));
+        assert_ne!(rematch.game_id, metadata.rematch([3; NONCE_SIZE]).game_id);
+    }
+}
@@ -0,0 +1,167 @@
+use risc0_zkvm::{
+    serde::to_vec,
+    sha::{Sha256, Digest, Impl}
+};
+use serde::{Serialize, Deserialize};
+
+use crate::{TicTacToe, Player, NONCE_SIZE, render::{self, Glyphs}};
+
+// Identifies a match, and so every receipt proven for it.
+pub type GameId = [u8; 32];
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct PlayerInfo {
+    pub name: String,
+    pub symbol: String,
+    // The ed25519 key that the player signs their moves with, if any.
+    pub public_key: Option<[u8; 32]>
+}
+
+// Information about a match that is agreed upon by both players
+// before it starts but is not part of the game state itself.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct MatchMetadata {
+    pub game_id: GameId,
+    pub player_a: PlayerInfo,
+    pub player_b: PlayerInfo,
+    pub first_player: Player,
+    pub ruleset: Ruleset,
+    // The guest build that both players agreed to prove moves with, if any.
+    pub image_id: Option<Digest>,
+    // Folded into every state hash of the match, see `TicTacToe::with_nonce`.
+    pub nonce: [u8; NONCE_SIZE]
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Ruleset {
+    // The players take turns.
+    Standard,
+    // Both players move at the same time, see `simultaneous`.
+    Simultaneous
+}
+
+impl PlayerInfo {
+    pub fn new(name: impl Into<String>, symbol: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            symbol: symbol.into(),
+            public_key: None
+        }
+    }
+
+    pub fn with_public_key(mut self, public_key: [u8; 32]) -> Self {
+        self.public_key = Some(public_key);
+
+        self
+    }
+}
+
+impl MatchMetadata {
+    pub fn new(game_id: GameId, player_a: PlayerInfo, player_b: PlayerInfo) -> Self {
+        Self {
+            game_id,
+            player_a,
+            player_b,
+            first_player: Player::A,
+            ruleset: Ruleset::Standard,
+            image_id: None,
+            nonce: [0; NONCE_SIZE]
+        }
+    }
+
+    pub fn with_first_player(mut self, player: Player) -> Self {
+        self.first_player = player;
+
+        self
+    }
+
+    pub fn with_ruleset(mut self, ruleset: Ruleset) -> Self {
+        self.ruleset = ruleset;
+
+        self
+    }
+
+    pub fn with_image_id(mut self, image_id: Digest) -> Self {
+        self.image_id = Some(image_id);
+
+        self
+    }
+
+    pub fn with_nonce(mut self, nonce: [u8; NONCE_SIZE]) -> Self {
+        self.nonce = nonce;
+
+        self
+    }
+
+    // The terms of a rematch between the same players, with the other player
+    // moving first and a fresh `nonce`. Its game ID is derived from that of
+    // this match and the nonce, so that the players can check that they were
+    // offered a rematch of the game they played rather than another match.
+    pub fn rematch(&self, nonce: [u8; NONCE_SIZE]) -> Self {
+        let mut bytes = [0; 7 + 32 + NONCE_SIZE];
+        bytes[0..7].copy_from_slice(b"rematch");
+        bytes[7..39].copy_from_slice(&self.game_id);
+        bytes[39..].copy_from_slice(&nonce);
+
+        let mut game_id = [0; 32];
+        game_id.copy_from_slice(Impl::hash_bytes(&bytes).as_bytes());
+
+        Self { game_id, first_player: self.first_player.flip(), nonce, ..self.clone() }
+    }
+
+    // The game that the match starts with.
+    pub fn initial_game(&self) -> TicTacToe {
+        TicTacToe::starting_with(self.first_player).with_nonce(self.nonce)
+    }
+
+    // Committed by guests that depend on the metadata, so that
+    // clients can check that it is the one they agreed upon.
+    pub fn hash(&self) -> Digest {
+        let words = to_vec(self).expect("metadata is always serializable");
+        let bytes: Vec<u8> = words.iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+
+        *Impl::hash_bytes(&bytes)
+    }
+
+    pub fn player(&self, player: Player) -> &PlayerInfo {
+        match player {
+            Player::A => &self.player_a,
+            Player::B => &self.player_b
+        }
+    }
+
+    // Glyphs that draw each player using their symbol. The shorter
+    // symbol is padded so that every cell has the same width.
+    pub fn glyphs(&self) -> Glyphs {
+        let width_a = render::display_width(&self.player_a.symbol);
+        let width_b = render::display_width(&self.player_b.symbol);
+        let width = width_a.max(width_b);
+
+        Glyphs {
+            player_a: pad(&self.player_a.symbol, width - width_a),
+            player_b: pad(&self.player_b.symbol, width - width_b),
+            vacant: " ".repeat(width),
+            width
+        }
+    }
+}
+
+impl Default for MatchMetadata {
+    fn default() -> Self {
+        Self {
+            game_id: [0; 32],
+            player_a: PlayerInfo::new("Player 1", "X"),
+            player_b: PlayerInfo::new("Player 2", "O"),
+            first_player: Player::A,
+            ruleset: Ruleset::Standard,
+            image_id: None,
+            nonce: [0; NONCE_SIZE]
+        }
+    }
+}
+
+fn pad(symbol: &str, padding: usize) -> String {
+    format!("{}{}", symbol, " ".repeat(padding))
+}
@@ -0,0 +1,189 @@
+use std::fmt;
+
+use crate::{TicTacToe, Player, Point, CELL_COUNT};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Frame {
+    // "|X|O| |" rows, the same layout that `TicTacToe::from_ascii` accepts.
+    Ascii,
+    // A grid drawn using Unicode box-drawing characters.
+    BoxDrawing
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Glyphs {
+    pub player_a: String,
+    pub player_b: String,
+    pub vacant: String,
+    // The number of terminal columns that a single glyph occupies.
+    pub width: usize
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Style {
+    pub frame: Frame,
+    pub glyphs: Glyphs
+}
+
+impl Glyphs {
+    pub fn ascii() -> Self {
+        Self {
+            player_a: "X".into(),
+            player_b: "O".into(),
+            vacant: " ".into(),
+            width: 1
+        }
+    }
+
+    pub fn emoji() -> Self {
+        Self {
+            player_a: "❌".into(),
+            player_b: "⭕".into(),
+            vacant: "  ".into(),
+            width: 2
+        }
+    }
+
+    pub fn get(&self, player: Option<Player>) -> &str {
+        match player {
+            Some(Player::A) => &self.player_a,
+            Some(Player::B) => &self.player_b,
+            None => &self.vacant
+        }
+    }
+}
+
+impl Style {
+    pub fn ascii() -> Self {
+        Self {
+            frame: Frame::Ascii,
+            glyphs: Glyphs::ascii()
+        }
+    }
+
+    pub fn unicode() -> Self {
+        Self {
+            frame: Frame::BoxDrawing,
+            glyphs: Glyphs::emoji()
+        }
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self::ascii()
+    }
+}
+
+impl TicTacToe {
+    pub fn render(&self, style: &Style) -> String {
+        self.render_with(
+            style.frame,
+            style.glyphs.width,
+            |_, player| style.glyphs.get(player).to_string()
+        )
+    }
+
+    // Calls `glyph` for every cell to get what should be drawn in it.
+    // Whatever it returns must occupy exactly `width` terminal columns,
+    // which allows for wrapping glyphs in terminal escape sequences.
+    pub fn render_with<F>(&self, frame: Frame, width: usize, mut glyph: F) -> String
+        where F: FnMut(Point, Option<Player>) -> String
+    {
+        let mut out = String::new();
+
+        let line = |left: char, middle: char, right: char| {
+            let segment = "─".repeat(width + 2);
+            let mut line = String::new();
+
+            line.push(left);
+
+            for x in 0..CELL_COUNT {
+                line.push_str(&segment);
+                line.push(if x == CELL_COUNT - 1 { right } else { middle });
+            }
+
+            line
+        };
+
+        if frame == Frame::BoxDrawing {
+            out.push_str(&line('┌', '┬', '┐'));
+            out.push('\n');
+        }
+
+        for y in 0..CELL_COUNT {
+            let separator = match frame {
+                Frame::Ascii => "|",
+                Frame::BoxDrawing => "│"
+            };
+
+            out.push_str(separator);
+
+            for x in 0..CELL_COUNT {
+                let point = Point::new(x, y);
+                let glyph = glyph(point, self.get(point));
+
+                match frame {
+                    Frame::Ascii => out.push_str(&glyph),
+                    Frame::BoxDrawing => {
+                        out.push(' ');
+                        out.push_str(&glyph);
+                        out.push(' ');
+                    }
+                }
+
+                out.push_str(separator);
+            }
+
+            if y < CELL_COUNT - 1 {
+                out.push('\n');
+
+                if frame == Frame::BoxDrawing {
+                    out.push_str(&line('├', '┼', '┤'));
+                    out.push('\n');
+                }
+            }
+        }
+
+        if frame == Frame::BoxDrawing {
+            out.push('\n');
+            out.push_str(&line('└', '┴', '┘'));
+        }
+
+        out
+    }
+}
+
+// An approximation of the number of terminal columns that `text` occupies,
+// accounting for East Asian wide characters and the common emoji ranges.
+pub fn display_width(text: &str) -> usize {
+    text.chars().map(|c| match c as u32 {
+        0x200B..=0x200D | 0xFE00..=0xFE0F => 0,
+        0x1100..=0x115F |
+        0x231A..=0x231B |
+        0x2705 | 0x274C | 0x2B1B..=0x2B1C | 0x2B50 | 0x2B55 |
+        0x2E80..=0xA4CF |
+        0xAC00..=0xD7A3 |
+        0xF900..=0xFAFF |
+        0xFE30..=0xFE4F |
+        0xFF00..=0xFF60 |
+        0xFFE0..=0xFFE6 |
+        0x1F300..=0x1F64F |
+        0x1F680..=0x1F6FF |
+        0x1F900..=0x1F9FF => 2,
+        _ => 1
+    }).sum()
+}
+
+// The alternate flag ("{:#}") renders using `Style::unicode()`.
+impl fmt::Display for TicTacToe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let style = if f.alternate() {
+            Style::unicode()
+        } else {
+            Style::ascii()
+        };
+
+        f.write_str(&self.render(&style))
+    }
+}
@@ -0,0 +1,210 @@
+use risc0_zkvm::sha::Digest;
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    TicTacToe, Player, Point, MoveError, State, Cell, CELL_COUNT,
+    journal::{self, JournalError, JOURNAL_VERSION}, hasher::STATE_HASHER
+};
+
+// In the simultaneous variant both players pick a cell at the same time,
+// hidden behind commitments, and both moves are applied in a single round.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum RoundOutcome {
+    // Both marks were placed.
+    Placed,
+    // Both players picked the same cell, so it stays vacant. A collision on
+    // the last vacant cell ends the game in a stalemate since the players
+    // can no longer pick different cells.
+    Collision
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum RoundResponse {
+    Resolved {
+        game: TicTacToe,
+        prev_state_hash: Digest,
+        new_state_hash: Digest,
+        // The moves of player A and player B, in that order.
+        points: [Point; 2],
+        outcome: RoundOutcome
+    },
+    Rejected {
+        // The player whose move was illegal.
+        player: Player,
+        error: MoveError,
+        prev_state_hash: Digest,
+        points: [Point; 2]
+    }
+}
+
+// What the simultaneous-move guest commits.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RoundJournal {
+    pub version: u32,
+    pub hasher: u32,
+    pub game_id: [u8; 32],
+    // Every round consists of two moves, one by each player.
+    pub move_index: u32,
+    pub prev_chain_hash: Digest,
+    pub chain_hash: Digest,
+    // The commitments of player A and player B that were opened.
+    pub commitments: [Digest; 2],
+    pub response: RoundResponse
+}
+
+impl TicTacToe {
+    // Plays a round of the simultaneous variant. Unlike `make_move`
+    // this doesn't change whose turn it is, since both players move.
+    pub fn make_simultaneous_moves(
+        &mut self,
+        a: Point,
+        b: Point
+    ) -> Result<RoundOutcome, (Player, MoveError)> {
+        self.validate(a).map_err(|error| (Player::A, error))?;
+        self.validate(b).map_err(|error| (Player::B, error))?;
+
+        if a == b {
+            if self.vacant_count() == 1 {
+                self.state = State::Stalemate;
+            }
+
+            return Ok(RoundOutcome::Collision);
+        }
+
+        self.board[a.y][a.x] = Cell::Player1;
+        self.board[b.y][b.x] = Cell::Player2;
+
+        self.state = match (self.has_line(Cell::Player1), self.has_line(Cell::Player2)) {
+            (true, true) => State::Stalemate,
+            (true, false) => State::Winner(Player::A),
+            (false, true) => State::Winner(Player::B),
+            (false, false) if self.vacant_count() == 0 => State::Stalemate,
+            (false, false) => State::InProgress
+        };
+
+        Ok(RoundOutcome::Placed)
+    }
+
+    fn validate(&self, point: Point) -> Result<(), MoveError> {
+        if self.state != State::InProgress {
+            return Err(MoveError::GameFinished);
+        }
+
+        if point.x >= CELL_COUNT || point.y >= CELL_COUNT {
+            return Err(MoveError::PointOutOfBounds);
+        }
+
+        if self.board[point.y][point.x] != Cell::Vacant {
+            return Err(MoveError::CellOccupied);
+        }
+
+        Ok(())
+    }
+
+    fn vacant_count(&self) -> usize {
+        self.board.iter()
+            .flatten()
+            .filter(|cell| **cell == Cell::Vacant)
+            .count()
+    }
+
+    fn has_line(&self, cell: Cell) -> bool {
+        let mut left_diag = true;
+        let mut right_diag = true;
+
+        for i in 0..CELL_COUNT {
+            let horizontal = (0..CELL_COUNT).all(|x| self.board[i][x] == cell);
+            let vertical = (0..CELL_COUNT).all(|y| self.board[y][i] == cell);
+
+            if horizontal || vertical {
+                return true;
+            }
+
+            left_diag &= self.board[i][i] == cell;
+            right_diag &= self.board[i][CELL_COUNT - 1 - i] == cell;
+        }
+
+        left_diag || right_diag
+    }
+}
+
+impl RoundResponse {
+    pub fn play(game: &mut TicTacToe, points: [Point; 2]) -> Self {
+        let prev_state_hash = game.state_hash();
+
+        match game.make_simultaneous_moves(points[0], points[1]) {
+            Ok(outcome) => Self::Resolved {
+                game: *game,
+                prev_state_hash,
+                new_state_hash: game.state_hash(),
+                points,
+                outcome
+            },
+            Err((player, error)) => Self::Rejected {
+                player,
+                error,
+                prev_state_hash,
+                points
+            }
+        }
+    }
+
+    pub fn prev_state_hash(&self) -> Digest {
+        match self {
+            Self::Resolved { prev_state_hash, .. } |
+            Self::Rejected { prev_state_hash, .. } => *prev_state_hash
+        }
+    }
+}
+
+impl RoundJournal {
+    // A resolved round adds a link to the hash chain for each of the two moves.
+    pub fn new(
+        game_id: [u8; 32],
+        move_index: u32,
+        prev_chain_hash: Digest,
+        commitments: [Digest; 2],
+        response: RoundResponse
+    ) -> Self {
+        let chain_hash = match &response {
+            RoundResponse::Resolved { points, new_state_hash, .. } => round_chain_hash(
+                &prev_chain_hash,
+                move_index,
+                *points,
+                new_state_hash
+            ),
+            RoundResponse::Rejected { .. } => prev_chain_hash
+        };
+
+        Self {
+            version: JOURNAL_VERSION,
+            hasher: STATE_HASHER,
+            game_id,
+            move_index,
+            prev_chain_hash,
+            chain_hash,
+            commitments,
+            response
+        }
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        journal::decode_versioned(bytes)
+    }
+}
+
+pub fn round_chain_hash(
+    prev_chain_hash: &Digest,
+    move_index: u32,
+    points: [Point; 2],
+    new_state_hash: &Digest
+) -> Digest {
+    let chain_hash = journal::chain_hash(
+        prev_chain_hash,
+        move_index,
+        points[0],
+        new_state_hash
+    );
+
+    journal::chain_hash(&chain_hash, move_index + 1, points[1], new_state_hash)
+}
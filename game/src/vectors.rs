@@ -0,0 +1,131 @@
+use crate::{TicTacToe, Point, Player, State, NONCE_SIZE, STATE_SIZE};
+
+// A position with the encoding of `TicTacToe::as_bytes` and its SHA-256 hash,
+// which is the state hash of the default build. Receipts commit to these
+// hashes, so a change to either breaks every receipt proven before it. The
+// tests of this module check the game against them, and other
+// implementations of the game can check theirs, see `STATE_VECTORS`.
+#[derive(Clone, Copy, Debug)]
+pub struct StateVector {
+    pub name: &'static str,
+    // Played from a new game with `nonce`, with player A moving first.
+    pub moves: &'static [(usize, usize)],
+    pub nonce: [u8; NONCE_SIZE],
+    pub state: State,
+    // The nine cells row by row, 0 for player A, 1 for player B and 2 for an
+    // empty one, then the player that moved last, the state, with 2 for a game
+    // in progress, 3 for a stalemate and the player for a win, and the nonce.
+    pub bytes: [u8; STATE_SIZE],
+    pub sha256: [u8; 32]
+}
+
+pub const STATE_VECTORS: [StateVector; 5] = [
+    StateVector {
+        name: "new game",
+        moves: &[],
+        nonce: [0; NONCE_SIZE],
+        state: State::InProgress,
+        bytes: [
+            2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 2,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+        ],
+        sha256: [
+            0xec, 0x4d, 0x57, 0x0b, 0x48, 0xf8, 0xdd, 0x8d, 0x58, 0x4c, 0x34, 0x2e, 0x94, 0x1a, 0xb3, 0x18,
+            0x9b, 0xf3, 0x31, 0x90, 0x58, 0xd3, 0xda, 0x34, 0x37, 0x4b, 0x8a, 0x5f, 0xe3, 0xee, 0x7c, 0x04
+        ]
+    },
+    StateVector {
+        name: "center opening",
+        moves: &[(1, 1)],
+        nonce: [0; NONCE_SIZE],
+        state: State::InProgress,
+        bytes: [
+            2, 2, 2, 2, 0, 2, 2, 2, 2, 0, 2,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+        ],
+        sha256: [
+            0x7b, 0xcd, 0x26, 0x24, 0xad, 0x45, 0xe4, 0x50, 0x9f, 0x49, 0x24, 0x7d, 0xbe, 0x5d, 0xcb, 0xae,
+            0xbe, 0xe6, 0x6c, 0xfa, 0xf6, 0x40, 0x5d, 0xb9, 0x5e, 0x4b, 0xa4, 0x2d, 0x19, 0x62, 0x54, 0x9f
+        ]
+    },
+    StateVector {
+        name: "won by A",
+        moves: &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)],
+        nonce: [0; NONCE_SIZE],
+        state: State::Winner(Player::A),
+        bytes: [
+            0, 1, 2, 0, 1, 2, 0, 2, 2, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+        ],
+        sha256: [
+            0x5a, 0x91, 0xf6, 0xc5, 0xf4, 0x5a, 0xde, 0xe0, 0x18, 0x81, 0x99, 0x19, 0x3a, 0xdc, 0xd3, 0x14,
+            0xdc, 0x25, 0x66, 0x60, 0xb2, 0xb5, 0x67, 0x99, 0x35, 0x75, 0x7f, 0xde, 0x70, 0xb7, 0x32, 0x75
+        ]
+    },
+    StateVector {
+        name: "won by B",
+        moves: &[(0, 0), (1, 1), (2, 0), (1, 0), (2, 2), (1, 2)],
+        nonce: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+        state: State::Winner(Player::B),
+        bytes: [
+            0, 1, 0, 2, 1, 2, 2, 1, 0, 1, 1,
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15
+        ],
+        sha256: [
+            0xd0, 0xac, 0xb2, 0x16, 0x34, 0xc4, 0xd6, 0x20, 0x39, 0x7b, 0xf1, 0x1f, 0x9f, 0xb7, 0xf0, 0xce,
+            0x70, 0xff, 0xb9, 0x56, 0x54, 0x90, 0x27, 0x89, 0x33, 0xed, 0x02, 0xc0, 0x8b, 0x8c, 0x50, 0xb6
+        ]
+    },
+    StateVector {
+        name: "stalemate",
+        moves: &[(1, 1), (0, 0), (2, 2), (0, 2), (0, 1), (2, 1), (1, 0), (1, 2), (2, 0)],
+        nonce: [0xff; NONCE_SIZE],
+        state: State::Stalemate,
+        bytes: [
+            1, 0, 0, 0, 0, 1, 1, 1, 0, 0, 3,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff
+        ],
+        sha256: [
+            0xd8, 0x2d, 0xe6, 0xfd, 0x13, 0xc5, 0x31, 0xa1, 0xee, 0xcd, 0x52, 0x18, 0xd9, 0xfd, 0xc8, 0x3c,
+            0xfe, 0xa0, 0x6a, 0x11, 0x58, 0xa8, 0xcc, 0xa0, 0xce, 0x48, 0xc0, 0x16, 0x38, 0xb2, 0xbf, 0x0e
+        ]
+    }
+];
+
+impl StateVector {
+    // The game that the moves of the vector lead to.
+    pub fn game(&self) -> TicTacToe {
+        let mut game = TicTacToe::new().with_nonce(self.nonce);
+
+        for &(x, y) in self.moves {
+            game.make_move(Point::new(x, y)).expect("the moves of test vectors are legal");
+        }
+
+        game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm::sha::{Sha256, Impl};
+
+    use super::*;
+    use crate::hasher::STATE_HASHER;
+
+    #[test]
+    fn games_match_their_test_vectors() {
+        for vector in STATE_VECTORS {
+            let game = vector.game();
+
+            assert_eq!(game.state(), vector.state, "{}", vector.name);
+            assert_eq!(game.as_bytes(), vector.bytes, "{}", vector.name);
+            assert_eq!(Impl::hash_bytes(&vector.bytes).as_bytes(), vector.sha256, "{}", vector.name);
+            assert_eq!(TicTacToe::from_bytes(&vector.bytes).map(|game| game.as_bytes()), Some(vector.bytes));
+
+            // Only the default build hashes states with SHA-256.
+            if STATE_HASHER == 0 {
+                assert_eq!(game.state_hash().as_bytes(), vector.sha256, "{}", vector.name);
+            }
+        }
+    }
+}
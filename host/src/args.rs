@@ -0,0 +1,32 @@
+use risc0_zkvm::sha::Digest;
+use game::metadata::{GameId, PlayerInfo};
+
+// Parsers of the arguments that the executables take, most of them hex
+// encoded, for the `value_parser` of their `clap` arguments.
+
+pub fn parse_digest(arg: &str) -> Result<Digest, String> {
+    hex::decode(arg).ok()
+        .and_then(|bytes| Digest::try_from(bytes.as_slice()).ok())
+        .ok_or_else(|| "hashes and image IDs must be 32 hex encoded bytes".into())
+}
+
+pub fn parse_game_id(arg: &str) -> Result<GameId, String> {
+    hex::decode(arg).ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| "game IDs must be 32 hex encoded bytes".into())
+}
+
+// An EVM address, with or without the 0x prefix.
+pub fn parse_address(arg: &str) -> Result<[u8; 20], String> {
+    hex::decode(arg.trim_start_matches("0x")).ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| "addresses must be 20 hex encoded bytes".into())
+}
+
+// A player given as "name" or "name:symbol", with the default symbol if it is missing.
+pub fn player_info(arg: &str, default_symbol: &str) -> PlayerInfo {
+    match arg.split_once(':') {
+        Some((name, symbol)) if !symbol.is_empty() => PlayerInfo::new(name, symbol),
+        _ => PlayerInfo::new(arg.trim_end_matches(':'), default_symbol)
+    }
+}
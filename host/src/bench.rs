@@ -0,0 +1,149 @@
+use std::{path::{Path, PathBuf}, time::Instant};
+
+use clap::ValueEnum;
+use ed25519_dalek::{SigningKey, Signer};
+use rand::rngs::OsRng;
+use game::{Player, Point, metadata::MatchMetadata, commitment::Reveal, auth::{self, SignedMove}, engine};
+
+use crate::{
+    server::Server, client::Client, config::Config, stats::{self, GameStats},
+    error::PlayError, local::Variant
+};
+
+// The moves proven by `run`, of a game that only ends in a stalemate after the last one.
+pub const MOVES: [(usize, usize); 9] = [
+    (0, 0), (1, 1), (2, 2), (0, 1), (2, 1), (2, 0), (0, 2), (1, 2), (1, 0)
+];
+
+// Proves the initial state and the moves of a game in each of `variants`, showing
+// what it takes to prove and verify each of them, and compares the variants with
+// each other and with the runs of other builds in `compare`.
+pub fn run(
+    moves: usize,
+    variants: &[Variant],
+    compare: &[PathBuf],
+    json: Option<&Path>,
+    config: &Config
+) -> Result<(), PlayError> {
+    let moves = moves.min(MOVES.len());
+    let mut runs = vec![];
+
+    for &variant in variants {
+        let name = variant.to_possible_value().expect("no variant is skipped").get_name().to_string();
+
+        // Their receipts prove more than one move, or none.
+        if matches!(variant, Variant::Simultaneous | Variant::Batch | Variant::Optimistic) {
+            println!("The {name} variant cannot be benchmarked move by move.\n");
+
+            continue;
+        }
+
+        let mut stats = GameStats::new(name, config.prover.backend);
+        bench_variant(variant, moves, &mut stats)?;
+
+        if moves > 0 {
+            println!("Proved {} moves in {:.2?} on average.\n", moves, stats.total().proving / moves as u32);
+            println!("{stats}\n");
+        }
+
+        runs.push(stats);
+    }
+
+    let mut summaries: Vec<_> = runs.iter().map(GameStats::summary).collect();
+
+    for path in compare {
+        let compared = stats::read_summaries(path)
+            .unwrap_or_else(|error| panic!("failed to read {}: {error}", path.display()));
+
+        summaries.extend(compared);
+    }
+
+    if summaries.len() > 1 {
+        println!("{}", stats::summary_table(&summaries));
+    }
+
+    if let Some(path) = json {
+        stats::write_runs(&runs, path).map_err(PlayError::Output)?;
+    }
+
+    Ok(())
+}
+
+// Proves the moves of `MOVES` in a variant that proves one move per
+// receipt, or the best moves in the optimal one, as it only proves those.
+fn bench_variant(variant: Variant, moves: usize, stats: &mut GameStats) -> Result<(), PlayError> {
+    let keys = [SigningKey::generate(&mut OsRng), SigningKey::generate(&mut OsRng)];
+    let salt: [u8; 32] = rand::random();
+
+    let mut metadata = MatchMetadata::default();
+
+    if variant == Variant::Signed {
+        metadata.player_a.public_key = Some(keys[0].verifying_key().to_bytes());
+        metadata.player_b.public_key = Some(keys[1].verifying_key().to_bytes());
+    }
+
+    let mut server = Server::new(metadata);
+    let match_hash = server.metadata.hash();
+
+    println!("Benchmarking the {} variant.", stats.variant);
+
+    let mut client = match variant {
+        Variant::Private => Client::with_initial_hash(server.game.salted_hash(&salt)),
+        _ => {
+            let start = Instant::now();
+            let receipt = server.prove_init()?;
+            println!("Proved the initial state in {:.2?}.", start.elapsed());
+
+            Client::from_init_receipt(&receipt, &match_hash)
+        }
+    };
+
+    client.pin(server.pin());
+
+    for (x, y) in MOVES.into_iter().take(moves) {
+        let point = match variant {
+            Variant::Optimal => engine::best_move(&server.game).expect("the benchmarked game has not ended"),
+            _ => Point::new(x, y)
+        };
+
+        let reveal = Reveal::new(point, rand::random());
+        let commitment = reveal.commitment(&server.metadata.game_id, server.game.current_player(), server.move_index);
+
+        let (receipt, proof) = match variant {
+            Variant::Blind => server.execute_blind_move(commitment, reveal)?,
+            Variant::Signed => {
+                let key = match server.game.current_player() {
+                    Player::A => &keys[0],
+                    Player::B => &keys[1]
+                };
+
+                let message = auth::move_message(&server.metadata.game_id, server.move_index, point);
+                server.execute_signed_move(SignedMove::new(point, key.sign(&message).to_bytes().to_vec()))?
+            },
+            Variant::Private => server.execute_private_move(point, &salt)?,
+            Variant::Optimal => server.execute_optimal_move(point)?,
+            _ => server.execute_move(point)?
+        };
+
+        println!("{point}: {proof}");
+
+        let start = Instant::now();
+
+        match variant {
+            Variant::Blind => { client.verify_blind_receipt(&receipt, &commitment); },
+            Variant::Signed => { client.verify_signed_receipt(&receipt, &match_hash); },
+            Variant::Private => { client.verify_private_receipt(&receipt); },
+            Variant::Optimal => { client.verify_optimal_receipt(&receipt); },
+            _ => { client.verify_receipt(&receipt); }
+        }
+
+        stats.record(server.move_index, proof, start.elapsed());
+
+        let mut game = server.game;
+        game.make_move(point).expect("benchmarked moves are legal");
+
+        server.apply_move(point, game);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use std::{io, process};
+
+use clap::Parser;
+use game::metadata::GameId;
+use host::{remote, args::{parse_game_id, player_info}, config::Config, identity::Identity};
+
+#[derive(Parser)]
+#[command(name = "client", about = "Plays or watches a game on a lobby server.")]
+struct Args {
+    #[arg(help = "The address of the server [default: the configured one]")]
+    address: Option<String>,
+    #[arg(default_value = "Player:X", help = "The name and symbol of the player, as \"name:symbol\"")]
+    player: String,
+    #[arg(long, value_parser = parse_game_id, help = "Watches the game with this ID instead of playing")]
+    watch: Option<GameId>
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    let config = Config::load(None).unwrap_or_else(|error| panic!("{error}"));
+    let address = args.address.unwrap_or(config.client.server);
+
+    if let Some(game_id) = args.watch {
+        return exit_on_error(remote::watch(&address, game_id).await);
+    }
+
+    let identity = Identity::load_or_generate(&config.client.identity)
+        .unwrap_or_else(|error| panic!("failed to read the identity key: {error}"));
+
+    exit_on_error(remote::play(&address, player_info(&args.player, "X"), &identity).await);
+}
+
+fn exit_on_error(result: io::Result<()>) {
+    if let Err(error) = result {
+        eprintln!("{error}");
+        process::exit(1);
+    }
+}
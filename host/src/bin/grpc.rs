@@ -0,0 +1,28 @@
+use std::net::SocketAddr;
+
+use clap::Parser;
+use tracing::info;
+use host::{games::Games, grpc::{GameService, GameServer}, logging::{self, LogFormat}};
+
+#[derive(Parser)]
+#[command(name = "grpc", about = "Serves games over gRPC.")]
+struct Args {
+    #[arg(default_value = "0.0.0.0:50051", help = "The address to listen on")]
+    address: SocketAddr,
+    #[arg(long, value_enum, default_value_t = LogFormat::Text, help = "How the server logs, text or one JSON object per line")]
+    log_format: LogFormat
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    logging::init(args.log_format);
+    info!("Serving games on {}...", args.address);
+
+    tonic::transport::Server::builder()
+        .add_service(GameServer::new(GameService::new(Games::spawn())))
+        .serve(args.address)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,183 @@
+use std::{ffi::CString, num::NonZeroU32, sync::Arc, time::Instant};
+
+use egui_glow::{EguiGlow, glow::{self, HasContext}};
+use glutin::{
+    config::ConfigTemplateBuilder,
+    context::{ContextApi, ContextAttributesBuilder, NotCurrentGlContextSurfaceAccessor, PossiblyCurrentContext},
+    display::{GetGlDisplay, GlDisplay},
+    surface::{GlSurface, Surface, SurfaceAttributesBuilder, SwapInterval, WindowSurface}
+};
+use glutin_winit::{DisplayBuilder, ApiPrefence};
+use raw_window_handle::HasRawWindowHandle;
+use winit::{
+    dpi::{LogicalSize, PhysicalSize},
+    event::{Event, WindowEvent, StartCause},
+    event_loop::{EventLoop, ControlFlow},
+    window::{Window, WindowBuilder}
+};
+use clap::Parser;
+use game::metadata::MatchMetadata;
+use host::{gui::App, args};
+
+// The window and the OpenGL context and surface that egui paints on.
+struct GlWindow {
+    window: Window,
+    context: PossiblyCurrentContext,
+    surface: Surface<WindowSurface>
+}
+
+#[derive(Parser)]
+#[command(name = "gui", about = "Plays a local game in a window.")]
+struct Args {
+    #[arg(help = "The name and symbol of the first player, as \"name:symbol\"")]
+    player_a: Option<String>,
+    #[arg(help = "The name and symbol of the second player, as \"name:symbol\"")]
+    player_b: Option<String>
+}
+
+// Plays a local game in a window, taking the "name" or "name:symbol" of both players.
+fn main() {
+    let args = Args::parse();
+
+    let mut metadata = MatchMetadata {
+        game_id: rand::random(),
+        nonce: rand::random(),
+        ..MatchMetadata::default()
+    };
+
+    for (info, arg) in [(&mut metadata.player_a, args.player_a), (&mut metadata.player_b, args.player_b)] {
+        if let Some(arg) = arg {
+            *info = args::player_info(&arg, &info.symbol);
+        }
+    }
+
+    println!("Proving the initial state of game {}...", hex::encode(metadata.game_id));
+
+    let mut app = App::new(metadata).expect("failed to prove the initial state");
+
+    let event_loop = EventLoop::new();
+    let (gl_window, gl) = create_window(&event_loop);
+    let gl = Arc::new(gl);
+
+    let mut egui_glow = EguiGlow::new(&event_loop, gl.clone(), None);
+
+    event_loop.run(move |event, _, control_flow| {
+        let mut redraw = || {
+            let repaint_after = egui_glow.run(&gl_window.window, |ctx| app.ui(ctx));
+
+            *control_flow = if repaint_after.is_zero() {
+                gl_window.window.request_redraw();
+                ControlFlow::Poll
+            } else {
+                match Instant::now().checked_add(repaint_after) {
+                    Some(instant) => ControlFlow::WaitUntil(instant),
+                    None => ControlFlow::Wait
+                }
+            };
+
+            unsafe {
+                gl.clear_color(0.1, 0.1, 0.1, 1.0);
+                gl.clear(glow::COLOR_BUFFER_BIT);
+            }
+
+            egui_glow.paint(&gl_window.window);
+
+            gl_window.surface.swap_buffers(&gl_window.context).expect("failed to swap buffers");
+            gl_window.window.set_visible(true);
+        };
+
+        match event {
+            // Windows only delivers redraw requests reliably once the other events are handled,
+            // see https://github.com/rust-windowing/winit/issues/987.
+            Event::RedrawEventsCleared if cfg!(windows) => redraw(),
+            Event::RedrawRequested(_) if !cfg!(windows) => redraw(),
+            Event::WindowEvent { event, .. } => {
+                match &event {
+                    WindowEvent::CloseRequested | WindowEvent::Destroyed => *control_flow = ControlFlow::Exit,
+                    WindowEvent::Resized(size) => gl_window.resize(*size),
+                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => gl_window.resize(**new_inner_size),
+                    _ => { }
+                }
+
+                if egui_glow.on_event(&event).repaint {
+                    gl_window.window.request_redraw();
+                }
+            },
+            Event::NewEvents(StartCause::ResumeTimeReached { .. }) => gl_window.window.request_redraw(),
+            Event::LoopDestroyed => egui_glow.destroy(),
+            _ => { }
+        }
+    });
+}
+
+// Creates the window along with an OpenGL context, falling back to OpenGL ES.
+fn create_window(event_loop: &EventLoop<()>) -> (GlWindow, glow::Context) {
+    // Kept hidden until the first frame is painted.
+    let builder = WindowBuilder::new()
+        .with_title("Tic-Tac-Toe")
+        .with_inner_size(LogicalSize::new(720.0, 480.0))
+        .with_visible(false);
+
+    let template = ConfigTemplateBuilder::new()
+        .prefer_hardware_accelerated(None)
+        .with_depth_size(0)
+        .with_stencil_size(0)
+        .with_transparency(false);
+
+    let (window, config) = DisplayBuilder::new()
+        .with_preference(ApiPrefence::FallbackEgl)
+        .with_window_builder(Some(builder.clone()))
+        .build(event_loop, template, |mut configs| configs.next().expect("no OpenGL configuration"))
+        .expect("failed to create the OpenGL display");
+
+    let display = config.display();
+    let window_handle = window.as_ref().map(|window| window.raw_window_handle());
+
+    let attributes = ContextAttributesBuilder::new().build(window_handle);
+    let fallback = ContextAttributesBuilder::new()
+        .with_context_api(ContextApi::Gles(None))
+        .build(window_handle);
+
+    let context = unsafe {
+        display.create_context(&config, &attributes)
+            .or_else(|_| display.create_context(&config, &fallback))
+            .expect("failed to create the OpenGL context")
+    };
+
+    let window = window.unwrap_or_else(|| {
+        glutin_winit::finalize_window(event_loop, builder, &config).expect("failed to create the window")
+    });
+
+    let (width, height): (u32, u32) = window.inner_size().into();
+
+    let attributes = SurfaceAttributesBuilder::<WindowSurface>::new().build(
+        window.raw_window_handle(),
+        NonZeroU32::new(width.max(1)).unwrap(),
+        NonZeroU32::new(height.max(1)).unwrap()
+    );
+
+    let surface = unsafe {
+        display.create_window_surface(&config, &attributes).expect("failed to create the window surface")
+    };
+
+    let context = context.make_current(&surface).expect("failed to make the OpenGL context current");
+
+    surface.set_swap_interval(&context, SwapInterval::Wait(NonZeroU32::new(1).unwrap()))
+        .expect("failed to enable vsync");
+
+    let gl = unsafe {
+        glow::Context::from_loader_function(|name| {
+            display.get_proc_address(&CString::new(name).expect("invalid OpenGL function name"))
+        })
+    };
+
+    (GlWindow { window, context, surface }, gl)
+}
+
+impl GlWindow {
+    fn resize(&self, size: PhysicalSize<u32>) {
+        if let (Some(width), Some(height)) = (NonZeroU32::new(size.width), NonZeroU32::new(size.height)) {
+            self.surface.resize(&self.context, width, height);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use game::{State, VmResponse, metadata::{MatchMetadata, GameId}};
+use host::{client::Client, store::{Store, GameRecord}};
+
+const LEADERBOARD_SIZE: usize = 20;
+
+#[derive(Parser)]
+#[command(name = "history", about = "Lists and audits the games recorded in a database.")]
+struct Args {
+    #[arg(help = "The SQLite database that the games were recorded in")]
+    database: PathBuf,
+    #[arg(required_unless_present = "leaderboard", help = "The name of the player to list the games of, or the ID of the game to audit")]
+    query: Option<String>,
+    #[arg(long, conflicts_with = "query", help = "Lists the highest rated players instead")]
+    leaderboard: bool
+}
+
+// Lists the games of a player, or audits a game by verifying its receipts
+// again, when given its ID instead. Given `--leaderboard`, lists the highest
+// rated players.
+fn main() {
+    let args = Args::parse();
+
+    let store = Store::open(args.database).expect("failed to open the database");
+
+    if args.leaderboard {
+        for (rank, rating) in store.leaderboard(LEADERBOARD_SIZE).expect("failed to query the ratings").iter().enumerate() {
+            println!(
+                "{:>3}. {} {:.0} ({} won, {} drawn, {} lost)",
+                rank + 1,
+                rating.player,
+                rating.rating,
+                rating.wins,
+                rating.draws,
+                rating.losses
+            );
+        }
+
+        return;
+    }
+
+    let query = args.query.expect("the query is required without --leaderboard");
+
+    let game_id: Option<GameId> = hex::decode(&query).ok()
+        .and_then(|bytes| bytes.try_into().ok());
+
+    match game_id {
+        Some(game_id) => audit(&store, &game_id),
+        None => {
+            for record in store.games_of(&query).expect("failed to query the games") {
+                println!(
+                    "{} {} vs {}: {}",
+                    hex::encode(record.metadata.game_id),
+                    record.metadata.player_a.name,
+                    record.metadata.player_b.name,
+                    result(&record.metadata, record.state)
+                );
+            }
+        }
+    }
+}
+
+fn audit(store: &Store, game_id: &GameId) {
+    let GameRecord { metadata, state, init, .. } = store.game(game_id)
+        .expect("failed to query the game")
+        .expect("no such game");
+
+    let mut client = Client::from_init_receipt(&init, &metadata.hash());
+
+    for record in store.moves(game_id).expect("failed to query the moves") {
+        let player = metadata.player(record.player);
+
+        match client.verify_receipt(&record.receipt) {
+            VmResponse::Accepted { point, .. } => {
+                println!("{}: {} ({}) played {}.", record.move_index, player.name, player.symbol, point);
+            },
+            VmResponse::Rejected { error, .. } => {
+                println!("{}: {} attempted {}: {}", record.move_index, player.name, record.point, error);
+            }
+        }
+    }
+
+    assert_eq!(client.game_state, state, "Recorded result mismatch!");
+
+    println!("{}", result(&metadata, state));
+}
+
+fn result(metadata: &MatchMetadata, state: State) -> String {
+    match state {
+        State::InProgress => "in progress".into(),
+        State::Winner(player) => format!("{} won", metadata.player(player).name),
+        State::Stalemate => "stalemate".into()
+    }
+}
@@ -0,0 +1,77 @@
+use std::{thread, path::PathBuf};
+
+use clap::Parser;
+use tiny_http::{Server, Method, Response, Header};
+use tracing::info;
+use host::{games::Games, jsonrpc, config::Config, prover, logging::{self, LogFormat}};
+#[cfg(feature = "sqlite")]
+use host::store::Store;
+
+#[derive(Parser)]
+#[command(name = "jsonrpc", about = "Serves games over JSON-RPC.")]
+struct Args {
+    #[arg(default_value = "0.0.0.0:8545", help = "The address to listen on")]
+    address: String,
+    #[arg(long, value_enum, help = "How the server logs, text or one JSON object per line [default: the configured one]")]
+    log_format: Option<LogFormat>,
+    #[arg(long, help = "The SQLite database to record the games in, with the sqlite feature [default: the configured one]")]
+    db: Option<PathBuf>
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let server = Server::http(&args.address).expect("failed to bind the server address");
+    let config = Config::load(None).unwrap_or_else(|error| panic!("{error}"));
+    prover::configure(&config.prover);
+    logging::init(args.log_format.unwrap_or(config.server.log_format));
+    let games = spawn_games(&config, args.db);
+
+    info!("Serving JSON-RPC requests on http://{}...", args.address);
+
+    for mut request in server.incoming_requests() {
+        let games = games.clone();
+
+        // Moves take long to prove, so every request is answered on its own thread.
+        thread::spawn(move || {
+            if request.method() != &Method::Post {
+                let _ = request.respond(Response::empty(405));
+
+                return;
+            }
+
+            let mut body = String::new();
+
+            if request.as_reader().read_to_string(&mut body).is_err() {
+                let _ = request.respond(Response::empty(400));
+
+                return;
+            }
+
+            let _ = match jsonrpc::handle(&games, &body) {
+                Some(response) => {
+                    let content_type = Header::from_bytes("Content-Type", "application/json")
+                        .expect("header is valid");
+
+                    request.respond(Response::from_string(response).with_header(content_type))
+                },
+                None => request.respond(Response::empty(204))
+            };
+        });
+    }
+}
+
+// Games are recorded in the database given with `--db`, or in the configured one, if any.
+#[cfg_attr(not(feature = "sqlite"), allow(unused_variables))]
+fn spawn_games(config: &Config, db: Option<PathBuf>) -> Games {
+    #[cfg(feature = "sqlite")]
+    {
+        let path = db.or_else(|| config.storage.database.clone());
+
+        if let Some(path) = path {
+            return Games::spawn_with_store(Store::open(path).expect("failed to open the database"));
+        }
+    }
+
+    Games::spawn()
+}
@@ -0,0 +1,196 @@
+use std::{io::{self, Write}, process};
+
+use clap::Parser;
+use futures::StreamExt;
+use libp2p::{Multiaddr, PeerId, swarm::SwarmEvent, request_response::{Event, Message}};
+use tokio::{sync::mpsc, task};
+use risc0_zkvm::SessionReceipt;
+
+use game::{Point, State, VmResponse, render::{Style, Frame}};
+use host::{
+    games::new_match, input::{self, Command}, render::Renderer,
+    peer::{Peer, PeerMessage}, p2p::{self, LISTEN_ADDRESS}
+};
+
+#[derive(Parser)]
+#[command(name = "peer", about = "Plays a game against another peer, without a server.")]
+struct Args {
+    #[arg(help = "The address of the peer to dial, such as /ip4/127.0.0.1/tcp/7879")]
+    address: Option<Multiaddr>
+}
+
+// Without an address, waits for the other peer to connect. The peer that
+// dials proposes the match and plays first.
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    let mut swarm = p2p::swarm();
+
+    let dialing = match args.address {
+        Some(address) => {
+            swarm.dial(address).expect("failed to dial the peer");
+
+            true
+        },
+        None => {
+            swarm.listen_on(LISTEN_ADDRESS.parse().unwrap()).unwrap();
+
+            false
+        }
+    };
+
+    let (points_tx, mut points) = mpsc::unbounded_channel::<Point>();
+    let (receipts_tx, mut receipts) = mpsc::unbounded_channel::<SessionReceipt>();
+
+    let mut peer: Option<Peer> = None;
+    let mut opponent: Option<PeerId> = None;
+    let mut renderer: Option<Renderer> = None;
+
+    loop {
+        tokio::select! {
+            event = swarm.select_next_some() => match event {
+                SwarmEvent::NewListenAddr { address, .. } => {
+                    println!("Waiting for a peer on {address}...");
+                },
+                SwarmEvent::ConnectionEstablished { peer_id, .. } if dialing && peer.is_none() => {
+                    // The initial state is proven before anything else can happen.
+                    let (ours, proposal) = task::block_in_place(|| Peer::propose(new_match(None, None)))
+                        .expect("failed to prove the initial state");
+
+                    swarm.behaviour_mut().send_request(&peer_id, proposal);
+
+                    renderer = Some(started(&ours));
+                    prompt(&ours, &points_tx);
+
+                    peer = Some(ours);
+                    opponent = Some(peer_id);
+                },
+                SwarmEvent::ConnectionClosed { peer_id, .. } if Some(peer_id) == opponent => {
+                    panic!("Lost the connection to the other peer!");
+                },
+                SwarmEvent::Behaviour(Event::Message { peer: peer_id, message }) => match message {
+                    Message::Request { request, channel, .. } => {
+                        let _ = swarm.behaviour_mut().send_response(channel, PeerMessage::Ack);
+
+                        match request {
+                            PeerMessage::Propose { metadata, pin, receipt } if peer.is_none() => {
+                                let ours = Peer::accept(*metadata, pin, &receipt);
+
+                                renderer = Some(started(&ours));
+
+                                peer = Some(ours);
+                                opponent = Some(peer_id);
+                            },
+                            PeerMessage::Move(receipt) if opponent == Some(peer_id) => {
+                                let ours = peer.as_mut().unwrap();
+
+                                let response = ours.apply(&receipt, ours.player.flip());
+                                played(ours, renderer.as_ref().unwrap(), response);
+
+                                prompt(ours, &points_tx);
+                            },
+                            _ => panic!("Unexpected message from {peer_id}!")
+                        }
+                    },
+                    Message::Response { .. } if is_finished(&peer) => break,
+                    Message::Response { .. } => {}
+                },
+                SwarmEvent::Behaviour(Event::ResponseSent { .. }) if is_finished(&peer) => break,
+                SwarmEvent::Behaviour(Event::OutboundFailure { error, .. }) => {
+                    panic!("Failed to reach the other peer: {error}");
+                },
+                _ => {}
+            },
+            Some(point) = points.recv() => {
+                // Proving blocks for a long time, the swarm is kept running meanwhile.
+                let proof = peer.as_ref().unwrap().server.move_proof(point);
+                let receipts_tx = receipts_tx.clone();
+
+                task::spawn_blocking(move || {
+                    let (receipt, stats) = proof.prove().expect("failed to prove the move");
+                    println!("{stats}");
+
+                    let _ = receipts_tx.send(receipt);
+                });
+            },
+            Some(receipt) = receipts.recv() => {
+                let ours = peer.as_mut().unwrap();
+
+                // Our own receipts are verified too, to keep the hash chain in sync.
+                let response = ours.apply(&receipt, ours.player);
+                let accepted = matches!(response, VmResponse::Accepted { .. });
+
+                played(ours, renderer.as_ref().unwrap(), response);
+
+                if accepted {
+                    swarm.behaviour_mut().send_request(&opponent.unwrap(), PeerMessage::Move(receipt));
+                } else {
+                    prompt(ours, &points_tx);
+                }
+            }
+        }
+    }
+
+    let peer = peer.unwrap();
+
+    match peer.server.game.state() {
+        State::Winner(player) => println!("{} won!", peer.server.metadata.player(player).name),
+        _ => println!("The game ended in a stalemate.")
+    }
+}
+
+fn started(peer: &Peer) -> Renderer {
+    let metadata = &peer.server.metadata;
+    let info = metadata.player(peer.player);
+    println!("Joined game {} as {} ({}).", hex::encode(metadata.game_id), info.name, info.symbol);
+
+    let renderer = Renderer::new(Style {
+        frame: Frame::Ascii,
+        glyphs: metadata.glyphs()
+    });
+    println!("{}", renderer.render(&peer.server.game, None));
+
+    renderer
+}
+
+fn played(peer: &Peer, renderer: &Renderer, response: VmResponse) {
+    match response {
+        VmResponse::Accepted { player, point, .. } => {
+            let player = peer.server.metadata.player(player);
+            println!("{} ({}) played {}.", player.name, player.symbol, point);
+
+            println!("{}", renderer.render(&peer.server.game, Some(point)));
+        },
+        VmResponse::Rejected { error, .. } => println!("{error}")
+    }
+}
+
+// Reads our next move on a blocking thread if it's our turn.
+fn prompt(peer: &Peer, points: &mpsc::UnboundedSender<Point>) {
+    if peer.server.game.state() != State::InProgress || !peer.is_our_turn() {
+        return;
+    }
+
+    let points = points.clone();
+
+    task::spawn_blocking(move || {
+        print!("Your turn: ");
+        io::stdout().flush().unwrap();
+
+        match input::wait_for_input() {
+            Ok(Command::Move(point)) => {
+                let _ = points.send(point);
+            },
+            // There is no message to resign with, so the opponent is left instead.
+            Ok(_) => {
+                println!("Left the game.");
+                process::exit(0);
+            },
+            Err(error) => println!("Failed to read the next move: {error}")
+        }
+    });
+}
+
+fn is_finished(peer: &Option<Peer>) -> bool {
+    matches!(peer, Some(peer) if peer.server.game.state() != State::InProgress)
+}
@@ -0,0 +1,180 @@
+use std::{path::PathBuf, process};
+
+use anyhow::{Context, Result, bail};
+use clap::{Parser, Subcommand};
+use host::{
+    server::Server, export::{ExportedReceipt, ReceiptKind, JournalFormat, Proven}, evm::{self, Settlement}, solana, near,
+    config::Config, settlement::Submitter, args::{parse_digest, parse_address}
+};
+use risc0_zkvm::sha::Digest;
+
+#[derive(Parser)]
+#[command(name = "receipt", about = "Exports, verifies and settles the receipts of saved games.")]
+struct Args {
+    #[command(subcommand)]
+    command: Command
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Exports a move receipt of a saved game to a file of its own")]
+    Export {
+        game: PathBuf,
+        #[arg(help = "The index of the receipt in the game")]
+        index: usize,
+        file: PathBuf
+    },
+    #[command(about = "Verifies an exported receipt and shows what it proves")]
+    Verify {
+        file: PathBuf,
+        #[arg(long, help = "Prints the decoded journal as JSON")]
+        json: bool
+    },
+    #[command(about = "Prints an exported receipt as base64, to share it as text")]
+    Encode {
+        file: PathBuf,
+        #[arg(long, help = "Prints it as hex instead")]
+        hex: bool
+    },
+    #[command(about = "Writes a receipt shared as text to a file")]
+    Decode {
+        text: String,
+        file: PathBuf
+    },
+    #[command(about = "Prints the call that commits an EVM contract to a saved game")]
+    Commit {
+        game: PathBuf
+    },
+    #[command(about = "Prints the call that settles the receipt of a result in an EVM contract")]
+    Settle {
+        file: PathBuf,
+        #[arg(help = "The hex encoded seal of the receipt")]
+        seal: String,
+        #[arg(value_parser = parse_post_state_digest, help = "The hex encoded post state digest of the receipt")]
+        post_state_digest: Digest,
+        #[arg(
+            long,
+            value_parser = parse_address,
+            required_unless_present_any = ["solana", "near"],
+            help = "The address that committed the game to the contract, whose commitment is settled"
+        )]
+        committer: Option<[u8; 20]>,
+        #[arg(long, conflicts_with = "near", help = "Prints the input of the verifier on Solana instead")]
+        solana: bool,
+        #[arg(long, help = "Prints the input of the verifier on NEAR instead")]
+        near: bool
+    },
+    #[command(subcommand, about = "Commits games to and settles them in the CosmWasm contract")]
+    Cosmwasm(Cosmwasm)
+}
+
+#[derive(Subcommand)]
+enum Cosmwasm {
+    #[command(about = "Commits the contract to a saved game")]
+    Commit {
+        game: PathBuf
+    },
+    #[command(about = "Submits the receipt of a result to the contract")]
+    Settle {
+        file: PathBuf
+    }
+}
+
+// Exports a move receipt of a game saved with `Server::save` to a file of its
+// own, or verifies an exported receipt and shows what it proves. Exported
+// receipts can also be encoded as base64 or hex to share them as text, and the
+// receipt of a result turned into the call that settles it in an EVM contract,
+// which the game has to be committed to first, or the input of the verifiers on Solana and NEAR,
+// or submitted to the CosmWasm contract that a saved game was committed to.
+fn main() {
+    if let Err(error) = run(Args::parse().command) {
+        eprintln!("{error:#}");
+        process::exit(1);
+    }
+}
+
+fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Export { game, index, file } => {
+            let server = Server::load(game).context("failed to load the game")?;
+
+            let receipt = server.receipts.get(index).context("no such receipt")?.clone();
+
+            ExportedReceipt::new(ReceiptKind::Move, receipt).write(&file)
+                .context("failed to write the receipt")?;
+
+            println!("Exported receipt {index} of game {} to {}.", hex::encode(server.metadata.game_id), file.display());
+        },
+        Command::Verify { file, json } => {
+            let exported = ExportedReceipt::read(file).context("failed to read the receipt")?;
+
+            let proven = exported.verify().context("receipt verification failed")?;
+
+            let format = if json { JournalFormat::Json } else { JournalFormat::Text };
+            println!("{}", proven.format(format));
+        },
+        Command::Encode { file, hex } => {
+            let exported = ExportedReceipt::read(file).context("failed to read the receipt")?;
+
+            let text = if hex { exported.to_hex() } else { exported.to_base64() };
+            println!("{}", text.context("failed to encode the receipt")?);
+        },
+        Command::Decode { text, file } => {
+            let exported = ExportedReceipt::decode(&text).context("failed to decode the receipt")?;
+            exported.write(&file).context("failed to write the receipt")?;
+
+            println!("Decoded a receipt to {}, which can be verified now.", file.display());
+        },
+        Command::Commit { game } => {
+            let server = Server::load(game).context("failed to load the game")?;
+
+            println!("{:#}", evm::commit_calldata(&server.metadata));
+        },
+        Command::Settle { file, seal, post_state_digest, committer, solana, near } => {
+            let exported = ExportedReceipt::read(file).context("failed to read the receipt")?;
+
+            let Proven::Result(journal) = exported.verify().context("receipt verification failed")? else {
+                bail!("only the receipt of the result of a game can be settled");
+            };
+
+            let seal = hex::decode(seal.trim_start_matches("0x")).context("the seal must be hex encoded")?;
+
+            let settlement = Settlement::new(&exported, seal, post_state_digest)
+                .context("failed to settle the receipt")?;
+
+            let image_id = exported.kind.image_id();
+            let json = if solana {
+                solana::to_json(&settlement, image_id).context("the seal is not a Groth16 seal")?
+            } else if near {
+                near::to_json(&settlement, image_id)
+            } else {
+                settlement.to_json(image_id, &committer.context("the address that committed the game is needed")?)
+            };
+
+            eprintln!("Settling game {} with {:?}.", hex::encode(journal.game_id), journal.state);
+            println!("{json:#}");
+        },
+        Command::Cosmwasm(Cosmwasm::Commit { game }) => {
+            let server = Server::load(game).context("failed to load the game")?;
+
+            println!("{}", submitter()?.commit(&server.metadata, None).context("failed to commit the game")?);
+        },
+        Command::Cosmwasm(Cosmwasm::Settle { file }) => {
+            let exported = ExportedReceipt::read(file).context("failed to read the receipt")?;
+
+            println!("{}", submitter()?.settle(&exported).context("failed to settle the game")?);
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_post_state_digest(arg: &str) -> Result<Digest, String> {
+    parse_digest(arg.trim_start_matches("0x"))
+}
+
+fn submitter() -> Result<Submitter> {
+    let config = Config::load(None)?;
+
+    Ok(Submitter::new(&config.settlement)?)
+}
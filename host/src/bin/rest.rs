@@ -0,0 +1,27 @@
+use std::net::SocketAddr;
+
+use clap::Parser;
+use tracing::info;
+use host::{games::Games, rest, logging::{self, LogFormat}};
+
+#[derive(Parser)]
+#[command(name = "rest", about = "Serves games over a REST API.")]
+struct Args {
+    #[arg(default_value = "0.0.0.0:8080", help = "The address to listen on")]
+    address: SocketAddr,
+    #[arg(long, value_enum, default_value_t = LogFormat::Text, help = "How the server logs, text or one JSON object per line")]
+    log_format: LogFormat
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    logging::init(args.log_format);
+    info!("Serving the REST API on http://{}...", args.address);
+
+    axum::Server::bind(&args.address)
+        .serve(rest::router(Games::spawn()).into_make_service())
+        .await
+        .unwrap();
+}
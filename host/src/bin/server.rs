@@ -0,0 +1,42 @@
+use clap::Parser;
+use game::clock::Clock;
+use host::{
+    hall, protocol::Transport, config::Config, prover, logging::{self, LogFormat},
+    recovery::{Recovery, DEFAULT_CHECKPOINT_INTERVAL}
+};
+
+#[derive(Parser)]
+#[command(name = "server", about = "Runs the lobby server that remote players connect to.")]
+struct Args {
+    #[arg(help = "The address to listen on [default: the configured one]")]
+    address: Option<String>,
+    #[arg(long, help = "Accepts WebSocket connections instead, for browsers")]
+    websocket: bool,
+    #[arg(long, value_enum, help = "How the server logs, text or one JSON object per line [default: the configured one]")]
+    log_format: Option<LogFormat>
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    let config = Config::load(None).unwrap_or_else(|error| panic!("{error}"));
+    prover::configure(&config.prover);
+    logging::init(args.log_format.unwrap_or(config.server.log_format));
+
+    let address = args.address.unwrap_or(config.server.address);
+
+    let transport = if args.websocket {
+        Transport::WebSocket
+    } else {
+        config.server.transport
+    };
+
+    let clock = config.server.move_limit.map(|move_limit_secs| Clock { move_limit_secs });
+
+    let interval = config.storage.checkpoint_interval.unwrap_or(DEFAULT_CHECKPOINT_INTERVAL);
+    let recovery = config.storage.recovery
+        .map(|dir| Recovery::open(dir, interval).expect("failed to open the recovery directory"));
+
+    hall::run(&address, transport, clock, recovery).await;
+}
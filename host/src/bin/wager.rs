@@ -0,0 +1,88 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+use game::{Player, metadata::MatchMetadata};
+use host::{
+    config::Config, prover, selfplay, settlement::Submitter, stats::GameStats,
+    bot::{Bot, Strategy}, export::{ExportedReceipt, ReceiptKind}
+};
+use verifier::cosmwasm::Wager;
+
+// How long the players have to stake and settle the game before they can
+// reclaim their stakes.
+const DEADLINE_SECS: u64 = 60 * 60;
+
+#[derive(Parser)]
+#[command(name = "wager", about = "Plays a wagered game against the CosmWasm settlement contract.")]
+struct Args {
+    #[arg(help = "The key of player A in the CLI of the chain")]
+    key_a: String,
+    #[arg(help = "The key of player B in the CLI of the chain")]
+    key_b: String,
+    #[arg(value_parser = parse_stake, help = "The stake of each player, such as 100ustake")]
+    stake: (String, String)
+}
+
+// Plays a wagered game end to end against the CosmWasm settlement contract:
+// the game is committed with the accounts of both players, each of them locks
+// the stake, two bots play the game, and the receipt of its result is
+// submitted to pay the pot to the winner. The keys are those of the CLI of
+// the chain, with the contract and the key of the host in `[settlement]`.
+fn main() {
+    let Args { key_a, key_b, stake: (amount, denom) } = Args::parse();
+
+    let config = Config::load(None).unwrap_or_else(|error| panic!("{error}"));
+    prover::configure(&config.prover);
+
+    let submitter = Submitter::new(&config.settlement).unwrap_or_else(|error| panic!("{error}"));
+
+    let metadata = MatchMetadata {
+        game_id: rand::random(),
+        nonce: rand::random(),
+        ..MatchMetadata::default()
+    };
+
+    let address = |key: &str| submitter.address(key).unwrap_or_else(|error| panic!("{error}"));
+
+    let wager = Wager {
+        player_a: address(&key_a),
+        player_b: address(&key_b),
+        denom: denom.clone(),
+        amount: amount.clone(),
+        deadline: SystemTime::now().duration_since(UNIX_EPOCH).expect("the clock is after 1970").as_secs() + DEADLINE_SECS
+    };
+
+    submitter.commit(&metadata, Some(wager.clone())).unwrap_or_else(|error| panic!("{error}"));
+    println!("Committed game {} with a stake of {amount}{denom} each.", hex::encode(metadata.game_id));
+
+    for (player, key) in [(Player::A, &key_a), (Player::B, &key_b)] {
+        submitter.stake(&metadata, key, &wager).unwrap_or_else(|error| panic!("{error}"));
+        println!("Player {player:?} locked their stake.");
+    }
+
+    let bots = [
+        Bot { player: Player::A, strategy: Strategy::Random },
+        Bot { player: Player::B, strategy: Strategy::Random }
+    ];
+
+    let mut stats = GameStats::new("standard", config.prover.backend);
+    let played = selfplay::play_game(metadata.clone(), bots, &mut stats).expect("failed to prove the game");
+    println!("The game ended with {:?}, submitting the receipt of its result...", played.server.game.state());
+
+    let exported = ExportedReceipt::new(ReceiptKind::Result, played.result);
+    submitter.settle(&exported).unwrap_or_else(|error| panic!("{error}"));
+
+    let game = submitter.game(&metadata).unwrap_or_else(|error| panic!("{error}"));
+    println!("Settled with {:?}, the pot is released.", game.result.expect("the game was settled"));
+}
+
+// Splits a stake such as "100ustake" into its amount and denomination.
+fn parse_stake(arg: &str) -> Result<(String, String), String> {
+    let split = arg.find(|c: char| !c.is_ascii_digit())
+        .filter(|&split| split > 0)
+        .ok_or_else(|| format!("stakes must be an amount followed by a denomination, such as 100ustake, not \"{arg}\""))?;
+
+    let (amount, denom) = arg.split_at(split);
+
+    Ok((amount.into(), denom.into()))
+}
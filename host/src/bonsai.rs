@@ -0,0 +1,239 @@
+use std::{fmt, io::Read, thread, time::{Duration, Instant}};
+
+use serde::{Serialize, Deserialize};
+use methods::GuestMethod;
+use risc0_zkvm::{sha::Digest, Session as VmSession, SessionReceipt, Result};
+
+use crate::prover::{Backend, Progress};
+
+// Sent along with every request, as Bonsai proves with the version of the client.
+const RISC0_VERSION: &str = "0.15.1";
+
+// How often the status of a session is checked while it is being proven.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// How long a session can take, queued and proven, before it is given up on,
+// so that a move is not waited on forever if Bonsai never finishes it.
+const PROVING_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+// A client of the Bonsai proving service, which proves sessions remotely so
+// that players are not kept waiting by a slow machine, see `prover::Backend`.
+#[derive(Clone)]
+pub struct Client {
+    agent: ureq::Agent,
+    url: String,
+    api_key: String
+}
+
+#[derive(Debug)]
+pub enum BonsaiError {
+    // The session ended without a receipt, with the status and reason given by Bonsai.
+    Failed(String, Option<String>),
+    // A response without something that it should have had, such as the URL of the receipt.
+    Missing(&'static str),
+    // The session was still queued or running after `PROVING_TIMEOUT`.
+    TimedOut(Duration)
+}
+
+// Where an image or input is uploaded to.
+#[derive(Deserialize)]
+struct Upload {
+    url: String,
+    uuid: Option<String>
+}
+
+#[derive(Serialize)]
+struct SessionRequest<'a> {
+    img: &'a str,
+    input: &'a str
+}
+
+#[derive(Deserialize)]
+struct Session {
+    uuid: String
+}
+
+#[derive(Deserialize)]
+struct SessionStatus {
+    status: String,
+    receipt_url: Option<String>,
+    error_msg: Option<String>
+}
+
+impl Client {
+    pub fn new(url: &str, api_key: &str) -> Self {
+        Self {
+            agent: ureq::Agent::new(),
+            url: url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string()
+        }
+    }
+
+    // Uploads the guest, unless Bonsai already has it, and the input, and then
+    // waits for the session to be proven before downloading its receipt.
+    pub fn prove(&self, method: GuestMethod, input: &[u8]) -> Result<SessionReceipt> {
+        let image_id = hex::encode(Digest::from(method.image_id()).as_bytes());
+
+        self.upload_image(&image_id, method.elf())?;
+
+        let input_id = self.upload_input(input)?;
+
+        let session: Session = self.request("POST", "sessions/create")
+            .send_json(SessionRequest { img: &image_id, input: &input_id })?
+            .into_json()?;
+
+        let url = wait_for_receipt(
+            || Ok(self.request("GET", &format!("sessions/status/{}", session.uuid)).call()?.into_json()?),
+            POLL_INTERVAL,
+            PROVING_TIMEOUT
+        )?;
+
+        self.download_receipt(&url)
+    }
+
+    fn upload_image(&self, image_id: &str, elf: &[u8]) -> Result<()> {
+        let response = self.request("GET", &format!("images/upload/{image_id}")).call()?;
+
+        // Answered with no content when the image has been uploaded before.
+        if response.status() == 204 {
+            return Ok(());
+        }
+
+        let upload: Upload = response.into_json()?;
+        self.agent.put(&upload.url).send_bytes(elf)?;
+
+        Ok(())
+    }
+
+    // Returns the ID that the session refers to the input by.
+    fn upload_input(&self, input: &[u8]) -> Result<String> {
+        let upload: Upload = self.request("GET", "inputs/upload").call()?.into_json()?;
+        self.agent.put(&upload.url).send_bytes(input)?;
+
+        Ok(upload.uuid.ok_or(BonsaiError::Missing("an input ID"))?)
+    }
+
+    // Receipts are bincode encoded, like the ones of saved games.
+    fn download_receipt(&self, url: &str) -> Result<SessionReceipt> {
+        let mut bytes = vec![];
+        self.agent.get(url).call()?.into_reader().read_to_end(&mut bytes)?;
+
+        Ok(bincode::deserialize(&bytes)?)
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        self.agent.request(method, &format!("{}/{}", self.url, path))
+            .set("x-api-key", &self.api_key)
+            .set("x-risc0-version", RISC0_VERSION)
+    }
+}
+
+// Only uploads `method` and `input`, Bonsai runs the session again itself.
+impl Backend for Client {
+    fn prove(
+        &self,
+        method: GuestMethod,
+        input: &[u8],
+        session: &VmSession,
+        progress: &mut dyn FnMut(Progress)
+    ) -> Result<SessionReceipt> {
+        let receipt = Client::prove(self, method, input)?;
+
+        let segments = session.segments.len();
+        progress(Progress { proven: segments, segments });
+
+        Ok(receipt)
+    }
+}
+
+// Polls the status of a session every `interval` until it has succeeded, returning
+// the URL of its receipt, or until it has failed or `timeout` has passed.
+fn wait_for_receipt(
+    mut poll: impl FnMut() -> Result<SessionStatus>,
+    interval: Duration,
+    timeout: Duration
+) -> Result<String> {
+    let start = Instant::now();
+
+    loop {
+        let status = poll()?;
+
+        match status.status.as_str() {
+            "QUEUED" | "PENDING" | "RUNNING" if start.elapsed() < timeout => thread::sleep(interval),
+            "QUEUED" | "PENDING" | "RUNNING" => return Err(BonsaiError::TimedOut(timeout).into()),
+            "SUCCEEDED" => return Ok(status.receipt_url.ok_or(BonsaiError::Missing("a receipt"))?),
+            _ => return Err(BonsaiError::Failed(status.status, status.error_msg).into())
+        }
+    }
+}
+
+impl fmt::Display for BonsaiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Failed(status, Some(reason)) => write!(f, "Bonsai session {}: {}", status, reason),
+            Self::Failed(status, None) => write!(f, "Bonsai session {}.", status),
+            Self::Missing(what) => write!(f, "Bonsai did not return {}.", what),
+            Self::TimedOut(timeout) => write!(f, "Bonsai did not prove the session within {:?}.", timeout)
+        }
+    }
+}
+
+impl std::error::Error for BonsaiError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(status: &str, receipt_url: Option<&str>, error_msg: Option<&str>) -> SessionStatus {
+        SessionStatus {
+            status: status.into(),
+            receipt_url: receipt_url.map(Into::into),
+            error_msg: error_msg.map(Into::into)
+        }
+    }
+
+    // Polls the statuses in order, without waiting in between.
+    fn wait(statuses: Vec<SessionStatus>, timeout: Duration) -> (Result<String>, usize) {
+        let mut statuses = statuses.into_iter();
+        let mut polls = 0;
+
+        let result = wait_for_receipt(|| {
+            polls += 1;
+
+            Ok(statuses.next().expect("polled after the last status"))
+        }, Duration::ZERO, timeout);
+
+        (result, polls)
+    }
+
+    #[test]
+    fn sessions_are_waited_on_until_they_end() {
+        let (url, polls) = wait(vec![
+            status("QUEUED", None, None),
+            status("PENDING", None, None),
+            status("RUNNING", None, None),
+            status("SUCCEEDED", Some("https://receipts/1"), None)
+        ], PROVING_TIMEOUT);
+
+        assert_eq!(url.unwrap(), "https://receipts/1");
+        assert_eq!(polls, 4);
+
+        let (error, _) = wait(vec![
+            status("RUNNING", None, None),
+            status("FAILED", None, Some("guest panicked"))
+        ], PROVING_TIMEOUT);
+
+        assert_eq!(error.unwrap_err().to_string(), "Bonsai session FAILED: guest panicked");
+
+        let (error, _) = wait(vec![status("SUCCEEDED", None, None)], PROVING_TIMEOUT);
+        assert_eq!(error.unwrap_err().to_string(), "Bonsai did not return a receipt.");
+    }
+
+    #[test]
+    fn sessions_are_given_up_on_after_the_timeout() {
+        let (error, polls) = wait(vec![status("QUEUED", None, None), status("RUNNING", None, None)], Duration::ZERO);
+
+        assert_eq!(error.unwrap_err().to_string(), format!("Bonsai did not prove the session within {:?}.", Duration::ZERO));
+        assert_eq!(polls, 1);
+    }
+}
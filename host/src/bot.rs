@@ -0,0 +1,70 @@
+use rand::seq::IteratorRandom;
+use risc0_zkvm::Result;
+use game::{TicTacToe, Player, Point, State, engine};
+
+use crate::hosting::Players;
+
+// How the computer picks its moves.
+#[derive(Clone, Copy, PartialEq, Debug, clap::ValueEnum)]
+pub enum Strategy {
+    // Any legal move.
+    Random,
+    // The best move according to a minimax search, so it never loses.
+    Minimax
+}
+
+// The computer playing one side of a local game. Its moves are proven by the
+// server and verified by the clients just like those of a remote opponent.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Bot {
+    pub player: Player,
+    pub strategy: Strategy
+}
+
+impl Bot {
+    // The move of the bot, if it is its turn.
+    pub fn next_move(&self, game: &TicTacToe) -> Option<Point> {
+        if game.state() != State::InProgress || game.current_player() != self.player {
+            return None;
+        }
+
+        match self.strategy {
+            Strategy::Random => engine::legal_moves(game).choose(&mut rand::thread_rng()),
+            Strategy::Minimax => engine::best_move(game)
+        }
+    }
+}
+
+// Both sides of a game played by the computer, see `selfplay`.
+impl Players for [Bot; 2] {
+    fn next_move(&mut self, game: &TicTacToe) -> Result<Point> {
+        self.iter()
+            .find_map(|bot| bot.next_move(game))
+            .ok_or_else(|| anyhow::anyhow!("neither bot is on turn"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bot_only_moves_on_its_turn() {
+        let mut game = TicTacToe::new();
+        let bot = Bot { player: Player::B, strategy: Strategy::Minimax };
+
+        assert_eq!(bot.next_move(&game), None);
+
+        for point in [Point::new(0, 0), Point::new(1, 1), Point::new(0, 1)] {
+            game.make_move(point).unwrap();
+        }
+
+        // Blocks the column that player A is about to complete.
+        assert_eq!(bot.next_move(&game), Some(Point::new(0, 2)));
+
+        let random = Bot { strategy: Strategy::Random, ..bot };
+        let point = random.next_move(&game).unwrap();
+
+        assert!(game.make_move(point).is_ok());
+    }
+}
@@ -0,0 +1,357 @@
+use std::{env, fmt, fs, io, path::{Path, PathBuf}};
+
+use serde::{Serialize, Deserialize, Deserializer, de::Error};
+use game::metadata::Ruleset;
+
+use crate::{
+    logging::LogFormat, identity::IDENTITY_FILE,
+    protocol::{Transport, DEFAULT_ADDRESS, LISTEN_ADDRESS}
+};
+
+// Read from the working directory when no other file is given.
+pub const CONFIG_FILE: &str = "tic-tac-toe.toml";
+
+// Environment variables with this prefix override the settings of the file,
+// e.g. `TIC_TAC_TOE_SERVER_ADDRESS`, see `Config::apply_env`.
+pub const ENV_PREFIX: &str = "TIC_TAC_TOE_";
+
+// The settings of the executables, from a TOML file such as:
+//
+//     [server]
+//     address = "0.0.0.0:7878"
+//     transport = "websocket"
+//
+//     [storage]
+//     database = "games.db"
+//
+// Every setting is optional and command line arguments take precedence over them.
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub client: ClientConfig,
+    pub storage: StorageConfig,
+    pub prover: ProverConfig,
+    pub game: GameConfig,
+    pub settlement: SettlementConfig
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    // The address that the lobby server listens on.
+    pub address: String,
+    pub transport: Transport,
+    pub log_format: LogFormat,
+    // How many seconds players have for each move before they forfeit the game, if limited.
+    pub move_limit: Option<u32>
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    // The address of the lobby server that clients connect to.
+    pub server: String,
+    // The secret key of the player, generated on first use, see `identity::Identity`.
+    pub identity: PathBuf
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    // The SQLite database that games are recorded in, see `store::Store`.
+    pub database: Option<PathBuf>,
+    // Where games are saved to and replayed from, when given a relative path.
+    pub saves: Option<PathBuf>,
+    // Where the lobby server keeps the games being played, to resume them after a restart.
+    pub recovery: Option<PathBuf>,
+    // How many moves and take-backs apart the games kept there are checkpointed
+    // whole, see `recovery::Recovery`. What happens in between is logged.
+    pub checkpoint_interval: Option<u32>
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProverConfig {
+    pub backend: ProverBackend,
+    // The API of the Bonsai backend and the key to it.
+    pub bonsai_url: Option<String>,
+    pub bonsai_api_key: Option<String>,
+    // Skips proving, which needs a newer version of Risc0 and so is refused for now.
+    pub dev_mode: bool
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    // Of the games played locally when no variant is given.
+    #[serde(deserialize_with = "deserialize_ruleset")]
+    pub ruleset: Ruleset
+}
+
+// Where results are settled on a Cosmos chain, see `settlement::Submitter`.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct SettlementConfig {
+    // The command line interface of the chain, which signs and broadcasts the transactions.
+    pub cli: String,
+    // The address of the settlement contract and the name of the key that pays for the transactions.
+    pub contract: Option<String>,
+    pub from: Option<String>,
+    // Of the CLI when not given, such as its own configuration.
+    pub node: Option<String>,
+    pub chain_id: Option<String>
+}
+
+// Where moves are proven.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ProverBackend {
+    // On this machine.
+    #[default]
+    Local,
+    // Remotely by Bonsai, which needs the `bonsai` feature, see `bonsai::Client`.
+    Bonsai
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(io::Error),
+    Parse(toml::de::Error),
+    // An environment variable with an invalid value, or an unknown one.
+    Env(String),
+    DevMode,
+    // A setting that the Bonsai backend needs, or the feature that it needs.
+    Bonsai(&'static str),
+    // A setting that settling results needs.
+    Settlement(&'static str)
+}
+
+impl Config {
+    // Reads the settings from `path`, or from `CONFIG_FILE` if it exists,
+    // and then applies the overrides of the environment.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let text = match path {
+            Some(path) => Some(fs::read_to_string(path).map_err(ConfigError::Read)?),
+            None => match fs::read_to_string(CONFIG_FILE) {
+                Ok(text) => Some(text),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+                Err(error) => return Err(ConfigError::Read(error))
+            }
+        };
+
+        let mut config = match text {
+            Some(text) => Self::from_toml(&text)?,
+            None => Self::default()
+        };
+
+        config.apply_env(env::vars())?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, ConfigError> {
+        toml::from_str(text).map_err(ConfigError::Parse)
+    }
+
+    // Overrides the settings with the variables that start with `ENV_PREFIX`,
+    // named after the section and the setting, e.g. `TIC_TAC_TOE_PROVER_DEV_MODE`.
+    pub fn apply_env(&mut self, vars: impl IntoIterator<Item = (String, String)>) -> Result<(), ConfigError> {
+        for (name, value) in vars {
+            let Some(setting) = name.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+
+            let invalid = || ConfigError::Env(name.clone());
+
+            match setting {
+                "SERVER_ADDRESS" => self.server.address = value,
+                "SERVER_TRANSPORT" => {
+                    self.server.transport = clap::ValueEnum::from_str(&value, true).map_err(|_| invalid())?;
+                },
+                "SERVER_LOG_FORMAT" => {
+                    self.server.log_format = clap::ValueEnum::from_str(&value, true).map_err(|_| invalid())?;
+                },
+                "SERVER_MOVE_LIMIT" => self.server.move_limit = Some(value.parse().map_err(|_| invalid())?),
+                "CLIENT_SERVER" => self.client.server = value,
+                "CLIENT_IDENTITY" => self.client.identity = value.into(),
+                "STORAGE_DATABASE" => self.storage.database = Some(value.into()),
+                "STORAGE_SAVES" => self.storage.saves = Some(value.into()),
+                "STORAGE_RECOVERY" => self.storage.recovery = Some(value.into()),
+                "STORAGE_CHECKPOINT_INTERVAL" => {
+                    self.storage.checkpoint_interval = Some(value.parse().map_err(|_| invalid())?);
+                },
+                "PROVER_BACKEND" => {
+                    self.prover.backend = match value.as_str() {
+                        "local" => ProverBackend::Local,
+                        "bonsai" => ProverBackend::Bonsai,
+                        _ => return Err(invalid())
+                    };
+                },
+                "PROVER_BONSAI_URL" => self.prover.bonsai_url = Some(value),
+                "PROVER_BONSAI_API_KEY" => self.prover.bonsai_api_key = Some(value),
+                "PROVER_DEV_MODE" => self.prover.dev_mode = value.parse().map_err(|_| invalid())?,
+                "SETTLEMENT_CLI" => self.settlement.cli = value,
+                "SETTLEMENT_CONTRACT" => self.settlement.contract = Some(value),
+                "SETTLEMENT_FROM" => self.settlement.from = Some(value),
+                "SETTLEMENT_NODE" => self.settlement.node = Some(value),
+                "SETTLEMENT_CHAIN_ID" => self.settlement.chain_id = Some(value),
+                "GAME_RULESET" => self.game.ruleset = parse_ruleset(&value).ok_or_else(invalid)?,
+                _ => return Err(invalid())
+            }
+        }
+
+        Ok(())
+    }
+
+    // Where a game is saved to or replayed from, relative to the saves directory.
+    pub fn save_path(&self, path: &Path) -> PathBuf {
+        match &self.storage.saves {
+            Some(saves) => saves.join(path),
+            None => path.to_path_buf()
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.prover.dev_mode {
+            return Err(ConfigError::DevMode);
+        }
+
+        if self.prover.backend == ProverBackend::Bonsai {
+            if !cfg!(feature = "bonsai") {
+                return Err(ConfigError::Bonsai("to be built with the bonsai feature"));
+            }
+
+            if self.prover.bonsai_url.is_none() {
+                return Err(ConfigError::Bonsai("a URL"));
+            }
+
+            if self.prover.bonsai_api_key.is_none() {
+                return Err(ConfigError::Bonsai("an API key"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            address: LISTEN_ADDRESS.into(),
+            transport: Transport::Tcp,
+            log_format: LogFormat::Text,
+            move_limit: None
+        }
+    }
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            server: DEFAULT_ADDRESS.into(),
+            identity: IDENTITY_FILE.into()
+        }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self { ruleset: Ruleset::Standard }
+    }
+}
+
+impl Default for SettlementConfig {
+    fn default() -> Self {
+        Self {
+            cli: "wasmd".into(),
+            contract: None,
+            from: None,
+            node: None,
+            chain_id: None
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read(error) => write!(f, "Failed to read the configuration: {}", error),
+            Self::Parse(error) => write!(f, "Invalid configuration: {}", error),
+            Self::Env(name) => write!(f, "Invalid or unknown environment variable {}.", name),
+            Self::DevMode => write!(f, "Dev mode needs a newer version of Risc0 than this project is pinned to."),
+            Self::Bonsai(what) => write!(f, "The Bonsai backend needs {}.", what),
+            Self::Settlement(what) => write!(f, "Settling results needs {}.", what)
+        }
+    }
+}
+
+impl std::error::Error for ConfigError { }
+
+fn parse_ruleset(name: &str) -> Option<Ruleset> {
+    match name {
+        "standard" => Some(Ruleset::Standard),
+        "simultaneous" => Some(Ruleset::Simultaneous),
+        _ => None
+    }
+}
+
+fn deserialize_ruleset<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Ruleset, D::Error> {
+    let name = String::deserialize(deserializer)?;
+
+    parse_ruleset(&name).ok_or_else(|| D::Error::custom(format!("unknown ruleset \"{name}\"")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn environment_overrides_the_file() {
+        let mut config = Config::from_toml(r#"
+            [server]
+            address = "0.0.0.0:9000"
+            transport = "websocket"
+            move_limit = 30
+
+            [game]
+            ruleset = "simultaneous"
+        "#).unwrap();
+
+        assert_eq!(config.server.transport, Transport::WebSocket);
+        assert_eq!(config.server.move_limit, Some(30));
+        assert_eq!(config.game.ruleset, Ruleset::Simultaneous);
+        assert_eq!(config.client, ClientConfig::default());
+
+        config.apply_env([
+            ("TIC_TAC_TOE_SERVER_ADDRESS".to_string(), "127.0.0.1:9001".to_string()),
+            ("TIC_TAC_TOE_STORAGE_DATABASE".to_string(), "games.db".to_string()),
+            ("TIC_TAC_TOE_SERVER_LOG_FORMAT".to_string(), "json".to_string()),
+            ("PATH".to_string(), "/bin".to_string())
+        ]).unwrap();
+
+        assert_eq!(config.server.address, "127.0.0.1:9001");
+        assert_eq!(config.server.log_format, LogFormat::Json);
+        assert_eq!(config.storage.database, Some(PathBuf::from("games.db")));
+
+        let unknown = config.apply_env([("TIC_TAC_TOE_COLOR".to_string(), "red".to_string())]);
+        assert!(matches!(unknown, Err(ConfigError::Env(_))));
+
+        assert!(matches!(Config::from_toml("[prover]\nbackend = \"remote\""), Err(ConfigError::Parse(_))));
+    }
+
+    #[test]
+    fn bonsai_needs_its_settings() {
+        let mut config = Config::from_toml("[prover]\nbackend = \"bonsai\"").unwrap();
+
+        assert!(matches!(config.validate(), Err(ConfigError::Bonsai(_))));
+
+        config.apply_env([
+            ("TIC_TAC_TOE_PROVER_BONSAI_URL".to_string(), "https://api.bonsai.xyz".to_string()),
+            ("TIC_TAC_TOE_PROVER_BONSAI_API_KEY".to_string(), "key".to_string())
+        ]).unwrap();
+
+        assert_eq!(config.validate().is_ok(), cfg!(feature = "bonsai"));
+    }
+}
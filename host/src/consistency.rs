@@ -0,0 +1,146 @@
+use std::{
+    collections::HashSet, fmt, thread,
+    sync::{Mutex, atomic::{AtomicUsize, Ordering}}
+};
+
+use risc0_zkvm::{serde::to_vec, Result};
+use game::{TicTacToe, Point, Journal, VmResponse, STATE_SIZE, metadata::MatchMetadata};
+
+use crate::server::Server;
+
+// The points that every game is checked with: all cells, whether they are
+// empty or not, and one out of bounds.
+const POINTS: [(usize, usize); 10] = [
+    (0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1), (0, 2), (1, 2), (2, 2), (3, 3)
+];
+
+// A move that the `make_move` guest committed another journal for than the
+// game played natively, which a client would then fail to verify or, worse,
+// verify a result that the game does not agree with.
+#[derive(Clone, Debug)]
+pub struct Mismatch {
+    pub game: TicTacToe,
+    pub point: Point,
+    pub expected: Vec<u8>,
+    pub journal: Vec<u8>
+}
+
+// Every game reachable from a new one with player A moving first, each once,
+// including the ones that have ended. There are 5478 of them.
+pub fn reachable_games() -> Vec<TicTacToe> {
+    let mut seen = HashSet::<[u8; STATE_SIZE]>::new();
+    let mut games = vec![];
+    let mut stack = vec![TicTacToe::new()];
+
+    while let Some(game) = stack.pop() {
+        if !seen.insert(game.as_bytes()) {
+            continue;
+        }
+
+        games.push(game);
+
+        for (x, y) in POINTS {
+            let mut next = game;
+
+            if next.make_move(Point::new(x, y)).is_ok() {
+                stack.push(next);
+            }
+        }
+    }
+
+    games
+}
+
+// The journal that the guest has to commit for `point` on `game`, as bytes,
+// with the move index and the head of the chain that the game would have.
+pub fn expected_journal(game: &TicTacToe, point: Point, game_id: [u8; 32]) -> Vec<u8> {
+    let mut next = *game;
+    let response = VmResponse::play(&mut next, point, game_id);
+
+    let journal = Journal::new(move_index(game), game.state_hash(), response);
+
+    to_vec(&journal).expect("journals are always serializable")
+        .into_iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect()
+}
+
+// Runs every move of `POINTS` on every game through the guest, without
+// proving it, on `threads` threads and compares the journals with the ones of
+// the native game. Calls `progress` with the number of moves checked so far.
+pub fn check(games: &[TicTacToe], threads: usize, progress: impl Fn(usize) + Sync) -> Result<Vec<Mismatch>> {
+    let metadata = MatchMetadata::default();
+    let moves: Vec<(TicTacToe, Point)> = games.iter()
+        .flat_map(|game| POINTS.map(|(x, y)| (*game, Point::new(x, y))))
+        .collect();
+
+    let next = AtomicUsize::new(0);
+    let mismatches = Mutex::new(vec![]);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.max(1))
+            .map(|_| scope.spawn(|| -> Result<()> {
+                while let Some(&(game, point)) = moves.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let mut server = Server::new(metadata.clone());
+                    server.game = game;
+                    server.move_index = move_index(&game);
+                    server.chain_hash = game.state_hash();
+
+                    let journal = server.move_proof(point).execute()?;
+                    let expected = expected_journal(&game, point, metadata.game_id);
+
+                    if journal != expected {
+                        mismatches.lock().expect("the mismatches are never poisoned")
+                            .push(Mismatch { game, point, expected, journal });
+                    }
+
+                    progress(next.load(Ordering::Relaxed).min(moves.len()));
+                }
+
+                Ok(())
+            }))
+            .collect();
+
+        workers.into_iter()
+            .try_for_each(|worker| worker.join().expect("the consistency checker panicked"))
+    })?;
+
+    Ok(mismatches.into_inner().expect("the mismatches are never poisoned"))
+}
+
+// The number of moves that led to `game`, which is the number of marks on its board.
+fn move_index(game: &TicTacToe) -> u32 {
+    POINTS.iter()
+        .filter(|(x, y)| *x < 3 && *y < 3 && game.get(Point::new(*x, *y)).is_some())
+        .count() as u32
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let journal = Journal::decode(&self.journal)
+            .map(|journal| format!("{:?}", journal.response))
+            .unwrap_or_else(|error| format!("an undecodable journal: {error}"));
+
+        write!(f, "{} on\n{}\nwas proven as {journal}", self.point, self.game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_reachable_game_is_found_once() {
+        let games = reachable_games();
+
+        assert_eq!(games.len(), 5478);
+        assert_eq!(games.iter().map(move_index).max(), Some(9));
+
+        let journal = expected_journal(&games[0], Point::new(1, 1), [0; 32]);
+        let Journal { move_index, response: VmResponse::Accepted { .. }, .. } = Journal::decode(&journal).unwrap() else {
+            panic!("the opening move was rejected");
+        };
+
+        assert_eq!(move_index, 0);
+    }
+}
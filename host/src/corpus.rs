@@ -0,0 +1,250 @@
+use std::{fs, io, path::Path, panic::{self, AssertUnwindSafe}, time::Instant};
+
+use serde::Serialize;
+use risc0_zkvm::Result;
+use game::{State, Player, Point, MoveError, VmResponse, metadata::MatchMetadata};
+
+use crate::{
+    server::Server, client::Client, input, protocol::invalid_data,
+    selfplay::panic_message, stats::{GameStats, MoveStats}
+};
+
+// A game of a corpus, see `parse`, to be proven and verified move by move by
+// `run`, which is how changes to the guests are checked against known games.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScriptedGame {
+    // Of the file that the game was read from, counted from 1.
+    pub line: usize,
+    pub moves: Vec<Point>,
+    pub expected: Option<State>
+}
+
+// What came of proving a game of a corpus.
+#[derive(Serialize, Clone, Debug)]
+pub struct GameReport {
+    pub line: usize,
+    pub moves: usize,
+    // The moves that the guest rejected, which do not count towards `moves`.
+    pub rejected: Vec<Rejection>,
+    // As proven by the `finalize_game` receipt, unless the game failed.
+    pub state: Option<State>,
+    pub expected: Option<State>,
+    pub final_state_hash: Option<String>,
+    pub chain_hash: Option<String>,
+    pub total: MoveStats,
+    pub error: Option<String>
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+pub struct Rejection {
+    // The position of the move in its line, counted from 0.
+    pub index: usize,
+    pub error: MoveError
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    #[serde(flatten)]
+    stats: &'a GameStats,
+    total: MoveStats,
+    passed: usize,
+    failed: usize,
+    games: &'a [GameReport]
+}
+
+// Reads a corpus of one game per line, each a list of moves separated by
+// whitespace, in any form that `input::parse_point` accepts without a space,
+// such as "a1 b2 1,0". A game can end with "=>" and the result that it has to
+// have, "A", "B", "draw" or "-" for a game that is not over. Blank lines and
+// the ones starting with "#" are skipped.
+pub fn parse(text: &str) -> io::Result<Vec<ScriptedGame>> {
+    let mut games = vec![];
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (moves, expected) = match line.split_once("=>") {
+            Some((moves, expected)) => (moves, Some(parse_state(expected.trim()).ok_or_else(|| {
+                invalid_data(format!("line {line_number}: unknown result {:?}", expected.trim()))
+            })?)),
+            None => (line, None)
+        };
+
+        let moves = moves.split_whitespace()
+            .map(|word| input::parse_point(word).ok_or_else(|| {
+                invalid_data(format!("line {line_number}: {word:?} is not a move"))
+            }))
+            .collect::<io::Result<_>>()?;
+
+        games.push(ScriptedGame { line: line_number, moves, expected });
+    }
+
+    Ok(games)
+}
+
+pub fn read(path: impl AsRef<Path>) -> io::Result<Vec<ScriptedGame>> {
+    parse(&fs::read_to_string(path)?)
+}
+
+// Proves and verifies every game of a corpus, as `selfplay::play` does the
+// games of bots, with what it took recorded in `stats`. A game that fails is
+// reported with its error and the next one started.
+pub fn run(games: &[ScriptedGame], stats: &mut GameStats) -> Vec<GameReport> {
+    games.iter()
+        .map(|game| {
+            let start = Instant::now();
+            let mut game_stats = GameStats::new(stats.variant.clone(), stats.prover);
+
+            let report = match panic::catch_unwind(AssertUnwindSafe(|| play_game(game, &mut game_stats))) {
+                Ok(Ok(report)) => report,
+                Ok(Err(error)) => GameReport::failed(game, &game_stats, error.to_string()),
+                Err(panic) => GameReport::failed(game, &game_stats, panic_message(panic))
+            };
+
+            match (&report.error, report.passed()) {
+                (Some(error), _) => eprintln!("Line {}: failed: {error}", game.line),
+                (None, true) => eprintln!("Line {}: {:?} in {:.2?}.", game.line, report.state.unwrap(), start.elapsed()),
+                (None, false) => eprintln!(
+                    "Line {}: {:?}, expected {:?}.",
+                    game.line,
+                    report.state.unwrap(),
+                    report.expected.unwrap()
+                )
+            }
+
+            stats.moves.extend(game_stats.moves);
+
+            report
+        })
+        .collect()
+}
+
+// The reports of the games, with what it took to prove and verify all of their moves.
+pub fn to_json(reports: &[GameReport], stats: &GameStats) -> String {
+    let passed = reports.iter().filter(|report| report.passed()).count();
+
+    let report = Report {
+        stats,
+        total: stats.total(),
+        passed,
+        failed: reports.len() - passed,
+        games: reports
+    };
+
+    serde_json::to_string_pretty(&report).expect("reports are always serializable")
+}
+
+// Proves the initial state, every move and the result of a game, verified by
+// the client of a player. Moves that the guest rejects are reported and
+// skipped, as a server would. Panics if a receipt fails verification.
+fn play_game(scripted: &ScriptedGame, stats: &mut GameStats) -> Result<GameReport> {
+    let mut server = Server::new(MatchMetadata {
+        game_id: rand::random(),
+        nonce: rand::random(),
+        ..MatchMetadata::default()
+    });
+
+    let match_hash = server.metadata.hash();
+    let init = server.prove_init()?;
+
+    let mut client = Client::from_init_receipt(&init, &match_hash);
+    client.pin(server.pin());
+
+    let mut rejected = vec![];
+
+    for (index, &point) in scripted.moves.iter().enumerate() {
+        if server.game.state() != State::InProgress {
+            anyhow::bail!("move {index} was made after the game had ended");
+        }
+
+        let (receipt, proof) = server.execute_move(point)?;
+
+        let start = Instant::now();
+        let response = client.verify_receipt(&receipt);
+        stats.record(server.move_index, proof, start.elapsed());
+
+        match response {
+            VmResponse::Accepted { game, .. } => {
+                server.receipts.push(receipt);
+                server.apply_move(point, game);
+            },
+            VmResponse::Rejected { error, .. } => rejected.push(Rejection { index, error })
+        }
+    }
+
+    let result = server.finalize()?;
+    let journal = client.verify_final_receipt(&result, &match_hash);
+
+    assert_eq!(journal.state, server.game.state(), "The result does not match the moves!");
+
+    Ok(GameReport {
+        line: scripted.line,
+        moves: server.moves.len(),
+        rejected,
+        state: Some(journal.state),
+        expected: scripted.expected,
+        final_state_hash: Some(hex::encode(journal.final_state_hash.as_bytes())),
+        chain_hash: Some(hex::encode(journal.chain_hash.as_bytes())),
+        total: stats.total(),
+        error: None
+    })
+}
+
+impl GameReport {
+    fn failed(game: &ScriptedGame, stats: &GameStats, error: String) -> Self {
+        Self {
+            line: game.line,
+            moves: 0,
+            rejected: vec![],
+            state: None,
+            expected: game.expected,
+            final_state_hash: None,
+            chain_hash: None,
+            total: stats.total(),
+            error: Some(error)
+        }
+    }
+
+    // Whether the game was proven and verified, with the expected result if it has one.
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.expected.map_or(true, |expected| self.state == Some(expected))
+    }
+}
+
+fn parse_state(result: &str) -> Option<State> {
+    match result.to_ascii_lowercase().as_str() {
+        "a" => Some(State::Winner(Player::A)),
+        "b" => Some(State::Winner(Player::B)),
+        "draw" | "stalemate" => Some(State::Stalemate),
+        "-" => Some(State::InProgress),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corpus_lines_are_parsed_with_their_results() {
+        let games = parse("# wins of A\na1 b1 a2 b2 a3 => A\n\n  1,1 0,0 =>draw\nc3\n").unwrap();
+
+        assert_eq!(games, [
+            ScriptedGame {
+                line: 2,
+                moves: vec![Point::new(0, 0), Point::new(1, 0), Point::new(0, 1), Point::new(1, 1), Point::new(0, 2)],
+                expected: Some(State::Winner(Player::A))
+            },
+            ScriptedGame { line: 4, moves: vec![Point::new(1, 1), Point::new(0, 0)], expected: Some(State::Stalemate) },
+            ScriptedGame { line: 5, moves: vec![Point::new(2, 2)], expected: None }
+        ]);
+
+        assert!(parse("a1\nb2 x\n").unwrap_err().to_string().starts_with("line 2"));
+        assert!(parse("a1 => C").is_err());
+    }
+}
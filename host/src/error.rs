@@ -0,0 +1,46 @@
+use std::{fmt, io};
+
+use game::Player;
+
+// Why a game on this machine could not go on.
+#[derive(Debug)]
+pub enum PlayError {
+    // The input of the players could not be read, or it ended.
+    Input(io::Error),
+    // A receipt failed to be proven, even after retrying, see `prover::PROVING_ATTEMPTS`.
+    Prover(anyhow::Error),
+    // A receipt, the statistics or the game could not be written.
+    Output(io::Error),
+    // The player gave the game to the opponent, see `input::Command`.
+    Resigned(Player),
+    // The players left the game unfinished.
+    Quit
+}
+
+impl From<anyhow::Error> for PlayError {
+    fn from(error: anyhow::Error) -> Self {
+        Self::Prover(error)
+    }
+}
+
+impl fmt::Display for PlayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Input(error) => write!(f, "Failed to read the next move: {}", error),
+            Self::Prover(error) => write!(f, "Failed to prove the game: {}", error),
+            Self::Output(error) => write!(f, "Failed to write the game: {}", error),
+            Self::Resigned(player) => write!(f, "Player {:?} resigned.", player),
+            Self::Quit => write!(f, "The game was abandoned.")
+        }
+    }
+}
+
+impl std::error::Error for PlayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Input(error) | Self::Output(error) => Some(error),
+            Self::Prover(error) => Some(error.as_ref()),
+            Self::Resigned(_) | Self::Quit => None
+        }
+    }
+}
@@ -0,0 +1,419 @@
+use std::{collections::HashMap, fmt, sync::mpsc, thread};
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde::{Serialize, Deserialize};
+use risc0_zkvm::{sha::Digest, SessionReceipt};
+use game::{
+    State, Player, Point, MoveError,
+    metadata::{MatchMetadata, PlayerInfo, GameId}
+};
+
+use crate::server::Server;
+#[cfg(feature = "sqlite")]
+use crate::store::{Store, Rating, VerifiedResult};
+
+// A handle to the games played by a service. They are owned by a single
+// thread, which is also the one that proves the moves, so that the handle
+// can be shared by the threads serving requests.
+#[derive(Clone)]
+pub struct Games {
+    commands: mpsc::Sender<Command>
+}
+
+// Many simultaneous games, each with its own server and so its own state and
+// hash chain, along with the log of the receipts proven for it.
+#[derive(Default)]
+pub struct Matches {
+    games: HashMap<GameId, Match>,
+    // Where every game and move is recorded as well, if anywhere.
+    #[cfg(feature = "sqlite")]
+    store: Option<Store>
+}
+
+// The receipt of the move with the given index, or of the initial state of
+// the game if it is `None`.
+#[derive(Clone)]
+pub struct MoveReceipt {
+    pub move_index: Option<u32>,
+    pub receipt: SessionReceipt,
+    // Why the move was rejected, if it was. The receipt proves it either way.
+    pub error: Option<MoveError>
+}
+
+// A receipt as sent to JSON clients, with its bytes in base64.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EncodedReceipt {
+    // Missing for the receipt of the initial state of a game.
+    pub move_index: Option<u32>,
+    // Why the move was rejected, if it was.
+    pub error: Option<String>,
+    pub journal: String,
+    // The segment receipts, which prove the journal along with it, see `verifier::receipt`.
+    pub seal: String,
+    // The bincode encoded `SessionReceipt`.
+    pub receipt: String
+}
+
+// The names of the players of a new game, see `new_match`.
+#[derive(Deserialize, Default)]
+pub struct NewGame {
+    pub player_a: Option<String>,
+    pub player_b: Option<String>
+}
+
+// A summary of a game, which the moves can be replayed from.
+#[derive(Clone, Debug)]
+pub struct GameSummary {
+    pub metadata: MatchMetadata,
+    pub state: State,
+    pub next_player: Player,
+    pub state_hash: Digest,
+    pub moves: Vec<Point>
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum GameError {
+    NotFound,
+    Ended,
+    NoSuchReceipt(u32),
+    Prover(String),
+    Storage(String),
+    Stopped
+}
+
+// The receipts of a game so far and the receiver of new ones, unless it has ended.
+pub type Subscription = (Vec<MoveReceipt>, Option<mpsc::Receiver<MoveReceipt>>);
+
+type Reply<T> = mpsc::Sender<Result<T, GameError>>;
+
+enum Command {
+    Create(Box<MatchMetadata>, Reply<MoveReceipt>),
+    Move(GameId, Point, Reply<MoveReceipt>),
+    Receipt(GameId, u32, Reply<MoveReceipt>),
+    Summary(GameId, Reply<GameSummary>),
+    Subscribe(GameId, Reply<Subscription>),
+    #[cfg(feature = "sqlite")]
+    Leaderboard(usize, Reply<Vec<Rating>>)
+}
+
+struct Match {
+    server: Server,
+    init: MoveReceipt,
+    receipts: Vec<MoveReceipt>,
+    // Dropped once the game has ended, which ends the subscriptions.
+    subscribers: Vec<mpsc::Sender<MoveReceipt>>
+}
+
+impl Games {
+    // Starts the thread that plays the games.
+    pub fn spawn() -> Self {
+        Self::spawn_with(Matches::new)
+    }
+
+    // Starts the thread that plays the games, recording them in `store`.
+    #[cfg(feature = "sqlite")]
+    pub fn spawn_with_store(store: Store) -> Self {
+        Self::spawn_with(move || Matches::new().with_store(store))
+    }
+
+    // Servers aren't `Send`, so the games are created on their own thread.
+    fn spawn_with(matches: impl FnOnce() -> Matches + Send + 'static) -> Self {
+        let (commands, receiver) = mpsc::channel();
+        thread::spawn(move || run(matches(), receiver));
+
+        Self { commands }
+    }
+
+    // Starts the game of `metadata`, returning the receipt of its initial state.
+    pub fn create(&self, metadata: MatchMetadata) -> Result<MoveReceipt, GameError> {
+        self.request(|reply| Command::Create(Box::new(metadata), reply))
+    }
+
+    // Plays `point` for the player whose turn it is.
+    pub fn play(&self, game_id: GameId, point: Point) -> Result<MoveReceipt, GameError> {
+        self.request(|reply| Command::Move(game_id, point, reply))
+    }
+
+    pub fn receipt(&self, game_id: GameId, move_index: u32) -> Result<MoveReceipt, GameError> {
+        self.request(|reply| Command::Receipt(game_id, move_index, reply))
+    }
+
+    pub fn summary(&self, game_id: GameId) -> Result<GameSummary, GameError> {
+        self.request(|reply| Command::Summary(game_id, reply))
+    }
+
+    pub fn subscribe(&self, game_id: GameId) -> Result<Subscription, GameError> {
+        self.request(|reply| Command::Subscribe(game_id, reply))
+    }
+
+    // The highest rated players, see `Store::leaderboard`.
+    #[cfg(feature = "sqlite")]
+    pub fn leaderboard(&self, limit: usize) -> Result<Vec<Rating>, GameError> {
+        self.request(|reply| Command::Leaderboard(limit, reply))
+    }
+
+    fn request<T>(&self, command: impl FnOnce(Reply<T>) -> Command) -> Result<T, GameError> {
+        let (reply, response) = mpsc::channel();
+
+        self.commands.send(command(reply)).map_err(|_| GameError::Stopped)?;
+
+        response.recv().map_err(|_| GameError::Stopped)?
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "No such game."),
+            Self::Ended => write!(f, "The game has already ended."),
+            Self::NoSuchReceipt(index) => write!(f, "No receipt for move {}.", index),
+            Self::Prover(error) => write!(f, "Proving failed: {}", error),
+            Self::Storage(error) => write!(f, "Recording the game failed: {}", error),
+            Self::Stopped => write!(f, "The game thread has stopped.")
+        }
+    }
+}
+
+impl std::error::Error for GameError { }
+
+impl From<&MoveReceipt> for EncodedReceipt {
+    fn from(receipt: &MoveReceipt) -> Self {
+        let bytes = bincode::serialize(&receipt.receipt).expect("receipts are always serializable");
+
+        Self {
+            move_index: receipt.move_index,
+            error: receipt.error.map(|error| error.to_string()),
+            journal: BASE64.encode(&receipt.receipt.journal),
+            seal: BASE64.encode(verifier::receipt::encode_seal(&receipt.receipt)),
+            receipt: BASE64.encode(bytes)
+        }
+    }
+}
+
+// The metadata of a new game with a random ID, between players
+// with the given names or the default ones if they are not given.
+pub fn new_match(player_a: Option<String>, player_b: Option<String>) -> MatchMetadata {
+    let defaults = MatchMetadata::default();
+
+    let player_info = |name: Option<String>, default: PlayerInfo| match name {
+        Some(name) => PlayerInfo::new(name, default.symbol),
+        None => default
+    };
+
+    MatchMetadata::new(
+        rand::random(),
+        player_info(player_a, defaults.player_a),
+        player_info(player_b, defaults.player_b)
+    )
+}
+
+impl Matches {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub fn with_store(self, store: Store) -> Self {
+        Self { store: Some(store), ..self }
+    }
+
+    // Starts the game of `metadata`, returning the receipt of its initial state.
+    pub fn create(&mut self, metadata: MatchMetadata) -> Result<MoveReceipt, GameError> {
+        let game_id = metadata.game_id;
+        let server = Server::new(metadata);
+
+        let receipt = server.prove_init().map_err(|error| GameError::Prover(error.to_string()))?;
+        let init = MoveReceipt { move_index: None, receipt, error: None };
+
+        #[cfg(feature = "sqlite")]
+        if let Some(store) = &self.store {
+            store.record_game(&server.metadata, &init.receipt)
+                .map_err(|error| GameError::Storage(error.to_string()))?;
+        }
+
+        self.games.insert(game_id, Match {
+            server,
+            init: init.clone(),
+            receipts: vec![],
+            subscribers: vec![]
+        });
+
+        Ok(init)
+    }
+
+    // Plays `point` for the player whose turn it is. A move that fails to be
+    // recorded in the store is played nonetheless.
+    pub fn play(&mut self, game_id: GameId, point: Point) -> Result<MoveReceipt, GameError> {
+        let game = self.games.get_mut(&game_id).ok_or(GameError::NotFound)?;
+        let server = &mut game.server;
+
+        if server.game.state() != State::InProgress {
+            return Err(GameError::Ended);
+        }
+
+        #[cfg(feature = "sqlite")]
+        let player = server.game.current_player();
+
+        let (receipt, _) = server.execute_move(point)
+            .map_err(|error| GameError::Prover(error.to_string()))?;
+
+        let move_index = server.move_index;
+        let mut next = server.game;
+
+        let error = match next.make_move(point) {
+            Ok(()) => {
+                server.apply_move(point, next);
+
+                None
+            },
+            Err(error) => Some(error)
+        };
+
+        let receipt = MoveReceipt { move_index: Some(move_index), receipt, error };
+
+        game.receipts.push(receipt.clone());
+        game.subscribers.retain(|subscriber| subscriber.send(receipt.clone()).is_ok());
+
+        if server.game.state() != State::InProgress {
+            game.subscribers.clear();
+        }
+
+        #[cfg(feature = "sqlite")]
+        if let Some(store) = &self.store {
+            store.record_move(&game_id, player, point, &receipt, server.game.state())
+                .map_err(|error| GameError::Storage(error.to_string()))?;
+
+            if server.game.state() != State::InProgress {
+                rate(store, server)?;
+            }
+        }
+
+        Ok(receipt)
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub fn leaderboard(&self, limit: usize) -> Result<Vec<Rating>, GameError> {
+        let store = self.store.as_ref().ok_or_else(|| GameError::Storage("games are not recorded".into()))?;
+
+        store.leaderboard(limit).map_err(|error| GameError::Storage(error.to_string()))
+    }
+
+    // The latest receipt of the move with the given index, which is
+    // the accepted one if the move was rejected before.
+    pub fn receipt(&self, game_id: GameId, move_index: u32) -> Result<MoveReceipt, GameError> {
+        let game = self.games.get(&game_id).ok_or(GameError::NotFound)?;
+
+        game.receipts.iter()
+            .rev()
+            .find(|receipt| receipt.move_index == Some(move_index))
+            .cloned()
+            .ok_or(GameError::NoSuchReceipt(move_index))
+    }
+
+    pub fn summary(&self, game_id: GameId) -> Result<GameSummary, GameError> {
+        let server = &self.games.get(&game_id).ok_or(GameError::NotFound)?.server;
+
+        Ok(GameSummary {
+            metadata: server.metadata.clone(),
+            state: server.game.state(),
+            next_player: server.game.current_player(),
+            state_hash: server.game.state_hash(),
+            moves: server.moves.clone()
+        })
+    }
+
+    pub fn subscribe(&mut self, game_id: GameId) -> Result<Subscription, GameError> {
+        let game = self.games.get_mut(&game_id).ok_or(GameError::NotFound)?;
+
+        let receiver = match game.server.game.state() {
+            State::InProgress => {
+                let (sender, receiver) = mpsc::channel();
+                game.subscribers.push(sender);
+
+                Some(receiver)
+            },
+            _ => None
+        };
+
+        let mut receipts = vec![game.init.clone()];
+        receipts.extend(game.receipts.iter().cloned());
+
+        Ok((receipts, receiver))
+    }
+
+    // The server of a game, which holds its state and hash chain.
+    pub fn server(&self, game_id: GameId) -> Option<&Server> {
+        self.games.get(&game_id).map(|game| &game.server)
+    }
+}
+
+fn run(mut matches: Matches, commands: mpsc::Receiver<Command>) {
+    for command in commands {
+        match command {
+            Command::Create(metadata, reply) => {
+                let _ = reply.send(matches.create(*metadata));
+            },
+            Command::Move(game_id, point, reply) => {
+                let _ = reply.send(matches.play(game_id, point));
+            },
+            Command::Receipt(game_id, move_index, reply) => {
+                let _ = reply.send(matches.receipt(game_id, move_index));
+            },
+            Command::Summary(game_id, reply) => {
+                let _ = reply.send(matches.summary(game_id));
+            },
+            Command::Subscribe(game_id, reply) => {
+                let _ = reply.send(matches.subscribe(game_id));
+            },
+            #[cfg(feature = "sqlite")]
+            Command::Leaderboard(limit, reply) => {
+                let _ = reply.send(matches.leaderboard(limit));
+            }
+        }
+    }
+}
+
+// Proves the result of a game that has ended and rates its players from it
+// once the receipt of the result has been verified.
+#[cfg(feature = "sqlite")]
+fn rate(store: &Store, server: &Server) -> Result<(), GameError> {
+    let receipt = server.finalize().map_err(|error| GameError::Prover(error.to_string()))?;
+
+    let result = VerifiedResult::verify(&server.metadata, receipt)
+        .map_err(|error| GameError::Prover(error.to_string()))?;
+
+    store.record_result(&result).map_err(|error| GameError::Storage(error.to_string()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Client;
+
+    #[test]
+    fn simultaneous_games_are_kept_apart() {
+        let mut matches = Matches::new();
+
+        let first = MatchMetadata { game_id: [1; 32], ..MatchMetadata::default() };
+        let second = MatchMetadata { game_id: [2; 32], ..MatchMetadata::default() };
+
+        matches.create(first).unwrap();
+        matches.create(second).unwrap();
+
+        let receipt = matches.play([1; 32], Point::new(1, 1)).unwrap();
+        matches.play([2; 32], Point::new(0, 0)).unwrap();
+        matches.play([2; 32], Point::new(0, 1)).unwrap();
+
+        let mut player = Client::new();
+        player.game_id = [1; 32];
+        player.verify_receipt(&receipt.receipt);
+
+        assert_eq!(matches.summary([1; 32]).unwrap().moves, vec![Point::new(1, 1)]);
+        assert_eq!(matches.summary([2; 32]).unwrap().moves.len(), 2);
+        assert_eq!(matches.server([1; 32]).unwrap().chain_hash, player.chain_hash);
+        assert_eq!(matches.receipt([1; 32], 1).err(), Some(GameError::NoSuchReceipt(1)));
+        assert_eq!(matches.play([3; 32], Point::new(0, 0)).err(), Some(GameError::NotFound));
+    }
+}
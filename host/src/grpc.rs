@@ -0,0 +1,130 @@
+// Every request fails with a `Status`, which is large but what tonic expects.
+#![allow(clippy::result_large_err)]
+
+use std::pin::Pin;
+
+use tokio::{sync::mpsc, task};
+use tokio_stream::{Stream, wrappers::ReceiverStream};
+use tonic::{Request, Response, Status};
+use game::{Point, metadata::GameId};
+
+use crate::games::{self, Games, GameError, MoveReceipt};
+
+pub mod proto {
+    tonic::include_proto!("tictactoe");
+}
+
+use proto::{
+    game_server::Game,
+    CreateGameRequest, CreateGameResponse, MakeMoveRequest, MakeMoveResponse,
+    StreamReceiptsRequest, Receipt
+};
+
+pub use proto::game_server::GameServer;
+
+pub struct GameService {
+    games: Games
+}
+
+impl GameService {
+    pub fn new(games: Games) -> Self {
+        Self { games }
+    }
+
+    // Runs a request against the games on the blocking pool, since it may have to wait for a proof.
+    async fn request<T: Send + 'static>(
+        &self,
+        request: impl FnOnce(&Games) -> Result<T, GameError> + Send + 'static
+    ) -> Result<T, Status> {
+        let games = self.games.clone();
+
+        task::spawn_blocking(move || request(&games))
+            .await
+            .map_err(|error| Status::internal(error.to_string()))?
+            .map_err(status)
+    }
+}
+
+#[tonic::async_trait]
+impl Game for GameService {
+    type StreamReceiptsStream = Pin<Box<dyn Stream<Item = Result<Receipt, Status>> + Send>>;
+
+    async fn create_game(
+        &self,
+        request: Request<CreateGameRequest>
+    ) -> Result<Response<CreateGameResponse>, Status> {
+        let request = request.into_inner();
+        let name = |name: String| Some(name).filter(|name| !name.is_empty());
+
+        let metadata = games::new_match(name(request.player_a), name(request.player_b));
+        let game_id = metadata.game_id.to_vec();
+
+        let init = self.request(move |games| games.create(metadata)).await?;
+
+        Ok(Response::new(CreateGameResponse { game_id, init: Some(encode(&init)) }))
+    }
+
+    async fn make_move(
+        &self,
+        request: Request<MakeMoveRequest>
+    ) -> Result<Response<MakeMoveResponse>, Status> {
+        let request = request.into_inner();
+        let game_id = game_id(&request.game_id)?;
+        let point = Point::new(request.x as usize, request.y as usize);
+
+        let receipt = self.request(move |games| games.play(game_id, point)).await?;
+
+        Ok(Response::new(MakeMoveResponse {
+            receipt: Some(encode(&receipt)),
+            accepted: receipt.error.is_none(),
+            error: receipt.error.map(|error| error.to_string()).unwrap_or_default()
+        }))
+    }
+
+    async fn stream_receipts(
+        &self,
+        request: Request<StreamReceiptsRequest>
+    ) -> Result<Response<Self::StreamReceiptsStream>, Status> {
+        let game_id = game_id(&request.into_inner().game_id)?;
+        let (receipts, receiver) = self.request(move |games| games.subscribe(game_id)).await?;
+
+        let (sender, stream) = mpsc::channel(16);
+
+        // Forwards the receipts until the game ends or the client goes away.
+        task::spawn_blocking(move || {
+            let moves = receipts.into_iter()
+                .chain(receiver.into_iter().flatten())
+                .filter(|receipt| receipt.move_index.is_some());
+
+            for receipt in moves {
+                if sender.blocking_send(Ok(encode(&receipt))).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(stream))))
+    }
+}
+
+fn encode(receipt: &MoveReceipt) -> Receipt {
+    Receipt {
+        move_index: receipt.move_index.unwrap_or_default(),
+        journal: receipt.receipt.journal.clone(),
+        receipt: bincode::serialize(&receipt.receipt).expect("receipts are always serializable")
+    }
+}
+
+fn status(error: GameError) -> Status {
+    match error {
+        GameError::NotFound | GameError::NoSuchReceipt(_) => Status::not_found(error.to_string()),
+        GameError::Ended => Status::failed_precondition(error.to_string()),
+        GameError::Prover(_) | GameError::Storage(_) | GameError::Stopped => {
+            Status::internal(error.to_string())
+        }
+    }
+}
+
+fn game_id(bytes: &[u8]) -> Result<GameId, Status> {
+    bytes.try_into().map_err(|_| Status::invalid_argument("game ID must be 32 bytes"))
+}
@@ -0,0 +1,210 @@
+use std::time::Duration;
+
+use egui::{
+    Context, Color32, RichText, FontId, Vec2, Button, ProgressBar,
+    ScrollArea, CentralPanel, SidePanel, TopBottomPanel
+};
+use risc0_zkvm::{Result, sha::Digest};
+use game::{State, Point, VmResponse, metadata::MatchMetadata};
+
+use crate::{server::{Server, ProofStats}, client::Client, prover::ProofQueue};
+
+// How often the window checks whether the move being proven is done.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+const CELL_SIZE: f32 = 96.0;
+
+// A local game of the standard variant in a window, in which the players
+// take turns clicking on the board. Each move is proven on another thread
+// and verified by the clients of both players before it is shown.
+pub struct App {
+    server: Server,
+    player_a: Client,
+    player_b: Client,
+    // The move being proven, which is the only one in the queue.
+    proving: Option<Point>,
+    queue: ProofQueue,
+    outcome: Option<Outcome>,
+    // The hashes after each verified move, starting with those of the initial state.
+    chain: Vec<Link>
+}
+
+// What became of the last move.
+enum Outcome {
+    Verified(ProofStats),
+    Rejected(String),
+    Failed(String)
+}
+
+struct Link {
+    label: String,
+    state_hash: Digest,
+    chain_hash: Digest
+}
+
+impl App {
+    // Proves the initial state of the match, which the clients of both players verify.
+    pub fn new(metadata: MatchMetadata) -> Result<Self> {
+        let server = Server::new(metadata);
+
+        let receipt = server.prove_init()?;
+        let match_hash = server.metadata.hash();
+
+        let mut player_a = Client::from_init_receipt(&receipt, &match_hash);
+        let mut player_b = Client::from_init_receipt(&receipt, &match_hash);
+
+        player_a.pin(server.pin());
+        player_b.pin(server.pin());
+
+        let chain = vec![Link {
+            label: "Initial state".into(),
+            state_hash: player_b.state_hash,
+            chain_hash: player_b.chain_hash
+        }];
+
+        Ok(Self { server, player_a, player_b, proving: None, queue: ProofQueue::new(), outcome: None, chain })
+    }
+
+    pub fn ui(&mut self, ctx: &Context) {
+        self.poll_prover();
+
+        if self.proving.is_some() {
+            ctx.request_repaint_after(POLL_INTERVAL);
+        }
+
+        SidePanel::right("chain").min_width(280.0).show(ctx, |ui| {
+            ui.heading("State hash chain");
+
+            ScrollArea::vertical().stick_to_bottom(true).show(ui, |ui| {
+                for link in &self.chain {
+                    ui.separator();
+                    ui.label(&link.label);
+                    ui.monospace(format!("state {}", short_hash(&link.state_hash)));
+                    ui.monospace(format!("chain {}", short_hash(&link.chain_hash)));
+                }
+            });
+        });
+
+        TopBottomPanel::bottom("status").show(ctx, |ui| {
+            ui.horizontal(|ui| match (&self.proving, &self.outcome) {
+                (Some(point), _) => {
+                    ui.spinner();
+                    ui.label(format!("Proving {point}…"));
+
+                    if let Some(progress) = self.queue.progress() {
+                        let text = format!("{}/{} segments", progress.proven, progress.segments);
+
+                        ui.add(ProgressBar::new(progress.fraction()).text(text));
+                    }
+                },
+                (None, Some(Outcome::Verified(stats))) => {
+                    ui.colored_label(Color32::GREEN, "Receipt verified ✓");
+                    ui.label(stats.to_string());
+                },
+                (None, Some(Outcome::Rejected(error))) => {
+                    ui.colored_label(Color32::RED, format!("Receipt verified, move rejected: {error}"));
+                },
+                (None, Some(Outcome::Failed(error))) => {
+                    ui.colored_label(Color32::RED, format!("Proving failed: {error}"));
+                },
+                (None, None) => {
+                    ui.label("Initial state verified ✓");
+                }
+            });
+        });
+
+        CentralPanel::default().show(ctx, |ui| {
+            ui.heading(self.heading());
+            ui.add_space(8.0);
+
+            let glyphs = self.server.metadata.glyphs();
+            let playing = self.proving.is_none() && self.server.game.state() == State::InProgress;
+            let mut chosen = None;
+
+            for y in 0..3 {
+                ui.horizontal(|ui| {
+                    for x in 0..3 {
+                        let point = Point::new(x, y);
+                        let player = self.server.game.get(point);
+
+                        let mut text = RichText::new(glyphs.get(player)).font(FontId::proportional(48.0));
+
+                        if self.server.last_move == Some(point) {
+                            text = text.strong();
+                        }
+
+                        let cell = Button::new(text).min_size(Vec2::splat(CELL_SIZE));
+
+                        if ui.add_enabled(playing && player.is_none(), cell).clicked() {
+                            chosen = Some(point);
+                        }
+                    }
+                });
+            }
+
+            if let Some(point) = chosen {
+                self.queue.push(self.server.move_proof(point));
+                self.proving = Some(point);
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    fn heading(&self) -> String {
+        let metadata = &self.server.metadata;
+
+        match self.server.game.state() {
+            State::Winner(player) => format!("{} wins!", metadata.player(player).name),
+            State::Stalemate => "Stalemate!".into(),
+            State::InProgress => {
+                let player = metadata.player(self.server.game.current_player());
+
+                format!("{} ({}) to move", player.name, player.symbol)
+            }
+        }
+    }
+
+    // Verifies the receipt of the move being proven once it is done.
+    fn poll_prover(&mut self) {
+        let Some((_, result)) = self.queue.try_next() else {
+            return;
+        };
+
+        let Some(point) = self.proving.take() else {
+            return;
+        };
+
+        let (receipt, stats) = match result {
+            Ok(result) => result,
+            Err(error) => {
+                self.outcome = Some(Outcome::Failed(error.to_string()));
+
+                return;
+            }
+        };
+
+        self.player_a.verify_receipt(&receipt);
+        self.server.receipts.push(receipt.clone());
+
+        self.outcome = Some(match self.player_b.verify_receipt(&receipt) {
+            VmResponse::Accepted { game, player, .. } => {
+                let player = self.server.metadata.player(player);
+
+                self.chain.push(Link {
+                    label: format!("{}. {} ({}) played {}", self.server.move_index + 1, player.name, player.symbol, point),
+                    state_hash: self.player_b.state_hash,
+                    chain_hash: self.player_b.chain_hash
+                });
+
+                self.server.apply_move(point, game);
+
+                Outcome::Verified(stats)
+            },
+            VmResponse::Rejected { error, .. } => Outcome::Rejected(error.to_string())
+        });
+    }
+}
+
+fn short_hash(digest: &Digest) -> String {
+    hex::encode(&digest.as_bytes()[..12])
+}
@@ -0,0 +1,1789 @@
+use std::{io, fmt, future::{self, Future}, rc::Rc, cell::RefCell, collections::HashMap, time::Duration};
+
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use tokio::{net::{TcpListener, TcpStream}, sync::{mpsc, broadcast, oneshot}, task, time::{self, Instant}};
+use tracing::{info, warn};
+use risc0_zkvm::{Result, SessionReceipt, sha::Digest};
+use game::{
+    State, Player, Point,
+    metadata::{MatchMetadata, Ruleset, GameId}, clock::Clock, chat::{ChatMessage, MAX_CHAT_LENGTH},
+    auth::{self, SignedMove, Concession}
+};
+
+use crate::{
+    server::Server, lobby::{Lobby, LobbyError, PlayerId}, identity,
+    protocol::{self, Channel, Connection, InProcess, Transport, ServerMessage, ClientMessage, SessionToken, invalid_data},
+    recovery::{Recovery, RecoveredMatch, SavedSeat, Event},
+    websocket::WebSocketConnection
+};
+
+// How long a game waits for a player to come back after losing their connection.
+const RECONNECT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+// How long to wait before accepting connections again after failing to.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+// How many messages a spectator can fall behind by before it is disconnected.
+const SPECTATOR_BACKLOG: usize = 64;
+
+// A player joining an open game, with the metadata of the match, which is sent to
+// the task of the player that opened the game to be played there.
+type Joined<C> = (MatchMetadata, C);
+
+// A connection resuming a seat, along with the number of `Move` and `TakenBack`
+// messages that its client received and the head of the hash chain it verified.
+type Resumed<C> = (C, u32, Digest);
+
+// The lobby along with where to send the players joining the games that others
+// opened, where to send the connections resuming a seat in a game,
+// and the games being played, which spectators can watch. Players that
+// run out of time on the `clock` forfeit, as signed with the `referee` key.
+// The games are kept in `recovery`, if given, to be resumed after a restart.
+struct Hall<C> {
+    lobby: Lobby,
+    hosts: HashMap<u32, oneshot::Sender<Joined<C>>>,
+    seats: HashMap<SessionToken, mpsc::UnboundedSender<Resumed<C>>>,
+    matches: HashMap<GameId, Spectated>,
+    clock: Option<Clock>,
+    referee: SigningKey,
+    recovery: Option<Recovery>
+}
+
+// What spectators of a game are sent: its start, the receipts of the moves
+// made so far, and then every message that the players are sent as well.
+struct Spectated {
+    start: ServerMessage,
+    receipts: Vec<SessionReceipt>,
+    updates: broadcast::Sender<ServerMessage>
+}
+
+// A player seated in a game, whose connection is replaced when they resume the seat.
+struct Seat<C> {
+    connection: C,
+    token: SessionToken,
+    resumed: mpsc::UnboundedReceiver<Resumed<C>>,
+    // The `Move` and `TakenBack` messages sent to the player in the current
+    // game, which are sent again from where the player says they lost track.
+    sent: Vec<ServerMessage>,
+    // The head of the hash chain that the client has verified before each of
+    // the messages and after the last one, which it has to resume from.
+    chain_hashes: Vec<Digest>
+}
+
+// What the player on turn answered when asked for a move.
+enum Answer {
+    Move(Point),
+    // See `ClientMessage::RequestTakeBack`.
+    TakeBack,
+    // With the signature of the player, if they were seated with a key, as
+    // are the draws offered.
+    Resign(Option<Vec<u8>>),
+    OfferDraw(Option<Vec<u8>>),
+    // The player ran out of time on the clock.
+    OutOfTime
+}
+
+// Seats the players connecting to `address` in the lobby and plays their games,
+// with each move to be made within the limit of the `clock`, if given. The
+// games are kept in `recovery`, if given, and the ones found there on
+// starting are resumed once their players are back.
+pub async fn run(address: &str, transport: Transport, clock: Option<Clock>, recovery: Option<Recovery>) {
+    let listener = TcpListener::bind(address).await.expect("failed to bind the server address");
+
+    // Servers notify observers that aren't `Send`, so all games run on this
+    // thread, with their moves being proven on the blocking pool.
+    let games = task::LocalSet::new();
+
+    match transport {
+        Transport::Tcp => {
+            info!("Waiting for players on {address}...");
+
+            games.run_until(serve(listener, clock, recovery, |stream| async { Connection::new(stream) })).await;
+        },
+        Transport::WebSocket => {
+            info!("Waiting for players on ws://{address}...");
+
+            games.run_until(serve(listener, clock, recovery, WebSocketConnection::accept)).await;
+        }
+    }
+}
+
+// The players of a hall that connect within the same process, over
+// `protocol::in_process` channels, such as in tests or applications hosting the
+// games themselves. The games are played exactly as they are for the players
+// connecting over the network. Must be created on a `task::LocalSet`.
+pub struct LocalHall {
+    hall: Rc<RefCell<Hall<InProcess>>>
+}
+
+impl LocalHall {
+    pub fn new(clock: Option<Clock>, recovery: Option<Recovery>) -> Self {
+        Self { hall: open_hall(clock, recovery) }
+    }
+
+    // The end of the player of a new connection to the hall, as if they had
+    // connected to the server.
+    pub fn connect(&self) -> InProcess {
+        let (player, connection) = protocol::in_process();
+        admit(&self.hall, connection, "in-process");
+
+        player
+    }
+}
+
+async fn serve<C, F>(
+    listener: TcpListener,
+    clock: Option<Clock>,
+    recovery: Option<Recovery>,
+    connect: impl Fn(TcpStream) -> F
+)
+    where C: Channel + 'static, F: Future<Output = io::Result<C>>
+{
+    let hall = open_hall(clock, recovery);
+
+    loop {
+        // Such as when the process runs out of file descriptors, which the
+        // players that are already connected give back once they leave.
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(error) => {
+                warn!(%error, "Failed to accept a connection.");
+                time::sleep(ACCEPT_RETRY_DELAY).await;
+                continue;
+            }
+        };
+
+        let connection = match connect(stream).await {
+            Ok(connection) => connection,
+            Err(error) => {
+                warn!(%peer, %error, "Failed to connect a player.");
+                continue;
+            }
+        };
+
+        admit(&hall, connection, peer);
+    }
+}
+
+// Resumes the games kept in `recovery`, if given, once their players are back.
+fn open_hall<C: Channel + 'static>(clock: Option<Clock>, recovery: Option<Recovery>) -> Rc<RefCell<Hall<C>>> {
+    let referee = match &recovery {
+        Some(recovery) => recovery.referee().expect("failed to read the referee key"),
+        None => SigningKey::generate(&mut OsRng)
+    };
+
+    let hall = Rc::new(RefCell::new(Hall {
+        lobby: Lobby::new(),
+        hosts: HashMap::new(),
+        seats: HashMap::new(),
+        matches: HashMap::new(),
+        clock,
+        referee,
+        recovery
+    }));
+
+    recover(&hall);
+
+    hall
+}
+
+fn admit<C: Channel + 'static>(hall: &Rc<RefCell<Hall<C>>>, connection: C, peer: impl fmt::Display + 'static) {
+    info!(%peer, "A player connected.");
+
+    let hall = hall.clone();
+
+    task::spawn_local(async move {
+        if let Err(error) = enter(hall, connection).await {
+            info!(%peer, %error, "A player left.");
+        }
+    });
+}
+
+// Keeps the client in the lobby until it opens a game or joins one. Joining
+// starts the game, with the player that opened it as player A, whose task
+// plays it, see `host`. Clients of
+// builds that cannot verify the receipts of this one are turned away first,
+// as are their attempts to resume a seat or watch a game.
+async fn enter<C: Channel>(hall: Rc<RefCell<Hall<C>>>, mut connection: C) -> Result<()> {
+    protocol::greet(&mut connection).await?;
+
+    let info = match connection.recv().await? {
+        ClientMessage::Register(info) => info,
+        ClientMessage::Resume { token, received, chain_hash } => {
+            let seat = hall.borrow().seats.get(&token).cloned();
+
+            return match seat {
+                Some(seat) => {
+                    let _ = seat.send((connection, received, chain_hash));
+
+                    Ok(())
+                },
+                None => {
+                    connection.send(&ServerMessage::ResumeRefused("No such seat.".into())).await?;
+
+                    Err(invalid_data("resumed an unknown seat").into())
+                }
+            };
+        },
+        ClientMessage::Watch(game_id) => return watch(&hall, game_id, connection).await,
+        _ => return Err(invalid_data("expected to register in the lobby").into())
+    };
+
+    if let Some(public_key) = info.public_key {
+        authenticate(&mut connection, &public_key).await?;
+    }
+
+    let mut player = hall.borrow_mut().lobby.register(info.clone());
+
+    loop {
+        let games = hall.borrow().lobby.open_games().cloned().collect();
+        connection.send(&ServerMessage::Lobby(games)).await?;
+
+        let message = match connection.recv().await {
+            Ok(message) => message,
+            Err(error) => {
+                let mut hall = hall.borrow_mut();
+
+                for game_id in hall.lobby.unregister(player) {
+                    hall.hosts.remove(&game_id);
+                }
+
+                return Err(error.into());
+            }
+        };
+
+        match message {
+            ClientMessage::ListGames => {},
+            // Only sequential games are played by this server.
+            ClientMessage::Open(terms) if terms.ruleset != Ruleset::Standard => {
+                connection.send(&ServerMessage::Refused("Only standard games can be played.".into())).await?;
+            },
+            ClientMessage::Open(terms) => {
+                let opened = hall.borrow_mut().lobby.open(player, terms);
+
+                match opened {
+                    Ok(game_id) => return host(&hall, player, game_id, connection).await,
+                    Err(error) => connection.send(&ServerMessage::Refused(error.to_string())).await?
+                }
+            },
+            ClientMessage::Join(game_id) => {
+                let joined = hall.borrow_mut().lobby.join(player, game_id);
+                let joined = joined.map(|metadata| (metadata, hall.borrow_mut().hosts.remove(&game_id)));
+
+                match joined {
+                    Ok((metadata, Some(host))) => match host.send((metadata, connection)) {
+                        Ok(()) => return Ok(()),
+                        Err((_, returned)) => connection = returned
+                    },
+                    Ok((_, None)) => { },
+                    Err(error) => {
+                        connection.send(&ServerMessage::Refused(error.to_string())).await?;
+                        continue;
+                    }
+                }
+
+                // The player that opened the game left as it was joined, and
+                // joining it took this player out of the lobby as well.
+                player = hall.borrow_mut().lobby.register(info.clone());
+                connection.send(&ServerMessage::Refused(LobbyError::NotFound.to_string())).await?;
+            },
+            _ => return Err(invalid_data("expected a lobby request").into())
+        }
+    }
+}
+
+// Waits for another player to join the game that the player opened, and then
+// plays it. The game is closed if the player leaves before, or sends anything
+// but waits, so that nobody joins a game whose host is gone.
+async fn host<C: Channel>(hall: &RefCell<Hall<C>>, player: PlayerId, game_id: u32, mut connection: C) -> Result<()> {
+    let (sender, joined) = oneshot::channel();
+    hall.borrow_mut().hosts.insert(game_id, sender);
+
+    let error = tokio::select! {
+        joined = joined => match joined {
+            Ok((metadata, guest)) => return play(hall, metadata, [connection, guest]).await,
+            Err(_) => invalid_data("the game was closed")
+        },
+        message = connection.recv::<ClientMessage>() => match message {
+            Ok(_) => invalid_data("expected to wait for another player"),
+            Err(error) => error
+        }
+    };
+
+    let mut hall = hall.borrow_mut();
+
+    for game_id in hall.lobby.unregister(player) {
+        hall.hosts.remove(&game_id);
+    }
+
+    Err(error.into())
+}
+
+// Challenges the client to sign with the secret key of the public key that it
+// registered with, so that nobody can register with the key of another player.
+async fn authenticate(connection: &mut impl Channel, public_key: &[u8; 32]) -> Result<()> {
+    let challenge: identity::Challenge = rand::random();
+    connection.send(&ServerMessage::Challenge(challenge)).await?;
+
+    let ClientMessage::Authenticate(signature) = connection.recv().await? else {
+        return Err(invalid_data("expected to authenticate").into());
+    };
+
+    if !identity::verify_login(public_key, &challenge, &signature) {
+        connection.send(&ServerMessage::Refused("Failed to authenticate.".into())).await?;
+
+        return Err(invalid_data("failed to authenticate").into());
+    }
+
+    Ok(())
+}
+
+// Plays the game between the players, and the rematches that they agree to.
+async fn play<C: Channel>(
+    hall: &RefCell<Hall<C>>,
+    mut metadata: MatchMetadata,
+    connections: [C; 2]
+) -> Result<()> {
+    let mut seats = vec![];
+
+    for connection in connections {
+        let token: SessionToken = rand::random();
+        let resumed = take_seat(hall, token);
+
+        seats.push(Seat { connection, token, resumed, sent: vec![], chain_hashes: vec![] });
+    }
+
+    let result = match play_match(hall, metadata.clone(), &mut seats).await {
+        Ok(()) => play_rematches(hall, metadata, &mut seats).await,
+        result => result
+    };
+
+    leave_seats(hall, seats.iter().map(|seat| seat.token));
+
+    result
+}
+
+// Plays the rematches that the players agree to after a game with the given terms.
+async fn play_rematches<C: Channel>(
+    hall: &RefCell<Hall<C>>,
+    mut metadata: MatchMetadata,
+    seats: &mut [Seat<C>]
+) -> Result<()> {
+    while rematch(seats).await {
+        metadata = metadata.rematch(rand::random());
+        play_match(hall, metadata.clone(), seats).await?;
+    }
+
+    Ok(())
+}
+
+// Where the connections resuming the seat of `token` are sent.
+fn take_seat<C>(hall: &RefCell<Hall<C>>, token: SessionToken) -> mpsc::UnboundedReceiver<Resumed<C>> {
+    let (sender, resumed) = mpsc::unbounded_channel();
+    hall.borrow_mut().seats.insert(token, sender);
+
+    resumed
+}
+
+fn leave_seats<C>(hall: &RefCell<Hall<C>>, tokens: impl IntoIterator<Item = SessionToken>) {
+    let mut hall = hall.borrow_mut();
+
+    for token in tokens {
+        hall.seats.remove(&token);
+    }
+}
+
+// Resumes the games that were kept in the recovery directory when the server
+// stopped, each once both of its players are back, which they have up to
+// `RECONNECT_TIMEOUT` for. The moves that were received but not proven yet
+// are proven first, and the players are then asked for moves as before.
+fn recover<C: Channel + 'static>(hall: &Rc<RefCell<Hall<C>>>) {
+    let saved = match hall.borrow().recovery.as_ref().map(Recovery::saved) {
+        Some(Ok(saved)) => saved,
+        Some(Err(error)) => {
+            warn!(%error, "Failed to read the recovery directory.");
+            return;
+        },
+        None => return
+    };
+
+    for path in saved {
+        let recovered = hall.borrow().recovery.as_ref().expect("games are only recovered from a directory").load(&path);
+
+        let recovered = match recovered {
+            Ok(recovered) => recovered,
+            Err(error) => {
+                warn!(path = %path.display(), %error, "Failed to recover a game.");
+                continue;
+            }
+        };
+
+        let game_id = hex::encode(recovered.server.metadata.game_id);
+        info!(%game_id, move_index = recovered.server.move_index, "Recovered a game, waiting for its players.");
+
+        let hall = hall.clone();
+
+        task::spawn_local(async move {
+            if let Err(error) = play_recovered(&hall, recovered).await {
+                info!(%game_id, %error, "A recovered game was aborted.");
+            }
+        });
+    }
+}
+
+async fn play_recovered<C: Channel>(hall: &RefCell<Hall<C>>, recovered: RecoveredMatch) -> Result<()> {
+    let RecoveredMatch { mut server, init, seats: saved } = recovered;
+    let tokens: Vec<_> = saved.iter().map(|seat| seat.token).collect();
+    let receivers: Vec<_> = tokens.iter().map(|token| take_seat(hall, *token)).collect();
+
+    open_to_spectators(hall, &server, init);
+
+    let mut seats = vec![];
+
+    for (SavedSeat { token, sent, chain_hashes }, mut resumed) in saved.into_iter().zip(receivers) {
+        match come_back(&mut resumed, &sent, &chain_hashes).await {
+            Ok(connection) => seats.push(Seat { connection, token, resumed, sent, chain_hashes }),
+            Err(error) => {
+                end_match(hall, &server.metadata.game_id);
+                leave_seats(hall, tokens);
+
+                return Err(error);
+            }
+        }
+    }
+
+    let metadata = server.metadata.clone();
+
+    let result = match continue_match(hall, &mut server, &mut seats).await {
+        Ok(()) => play_rematches(hall, metadata, &mut seats).await,
+        result => result
+    };
+
+    leave_seats(hall, tokens);
+
+    result
+}
+
+async fn play_match<C: Channel>(
+    hall: &RefCell<Hall<C>>,
+    metadata: MatchMetadata,
+    seats: &mut [Seat<C>]
+) -> Result<()> {
+    let mut server = Server::new(metadata);
+    let receipt = server.prove_init_async().await?;
+
+    let game_id = server.metadata.game_id;
+    info!(game_id = %hex::encode(game_id), "Starting a game.");
+
+    let clock = hall.borrow().clock;
+    let referee = hall.borrow().referee.verifying_key().to_bytes();
+
+    if let Some(recovery) = &hall.borrow().recovery {
+        if let Err(error) = recovery.start(&game_id, &receipt) {
+            warn!(game_id = %hex::encode(game_id), %error, "Failed to keep the game for recovery.");
+        }
+    }
+
+    open_to_spectators(hall, &server, receipt.clone());
+
+    let mut result = Ok(());
+
+    for (player, seat) in [Player::A, Player::B].into_iter().zip(seats.iter_mut()) {
+        seat.sent.clear();
+        seat.chain_hashes = vec![server.chain_hash];
+
+        result = seat.connection.send(&ServerMessage::Welcome {
+            player,
+            metadata: Box::new(server.metadata.clone()),
+            pin: server.pin(),
+            receipt: receipt.clone(),
+            token: seat.token,
+            clock,
+            referee
+        }).await;
+
+        if result.is_err() {
+            break;
+        }
+    }
+
+    match result {
+        Ok(()) => continue_match(hall, &mut server, seats).await,
+        Err(error) => {
+            end_match(hall, &game_id);
+
+            Err(error.into())
+        }
+    }
+}
+
+// Plays the game of `server` from where it is to its end.
+async fn continue_match<C: Channel>(hall: &RefCell<Hall<C>>, server: &mut Server, seats: &mut [Seat<C>]) -> Result<()> {
+    checkpoint(hall, server, seats);
+
+    let result = play_moves(hall, server, seats).await;
+    end_match(hall, &server.metadata.game_id);
+
+    result
+}
+
+fn open_to_spectators<C>(hall: &RefCell<Hall<C>>, server: &Server, receipt: SessionReceipt) {
+    let clock = hall.borrow().clock;
+    let referee = hall.borrow().referee.verifying_key().to_bytes();
+
+    hall.borrow_mut().matches.insert(server.metadata.game_id, Spectated {
+        start: ServerMessage::Spectating {
+            metadata: Box::new(server.metadata.clone()),
+            pin: server.pin(),
+            receipt,
+            clock,
+            referee
+        },
+        receipts: server.receipts.clone(),
+        updates: broadcast::channel(SPECTATOR_BACKLOG).0
+    });
+}
+
+// Disconnects the spectators of a game that has ended or was aborted, and forgets it.
+fn end_match<C>(hall: &RefCell<Hall<C>>, game_id: &GameId) {
+    let mut hall = hall.borrow_mut();
+    hall.matches.remove(game_id);
+
+    if let Some(recovery) = &hall.recovery {
+        if let Err(error) = recovery.remove(game_id) {
+            warn!(game_id = %hex::encode(game_id), %error, "Failed to forget a recovered game.");
+        }
+    }
+}
+
+// Saves the whole game along with what its players were sent, to be recovered
+// after a restart, if the hall keeps its games. A game that fails to be
+// saved goes on, as it is only lost if the server stops as well.
+fn checkpoint<C>(hall: &RefCell<Hall<C>>, server: &Server, seats: &[Seat<C>]) {
+    let hall = hall.borrow();
+
+    let Some(recovery) = &hall.recovery else {
+        return;
+    };
+
+    let seats = seats.iter()
+        .map(|seat| SavedSeat { token: seat.token, sent: seat.sent.clone(), chain_hashes: seat.chain_hashes.clone() })
+        .collect();
+
+    if let Err(error) = recovery.checkpoint(server, seats) {
+        warn!(game_id = %hex::encode(server.metadata.game_id), %error, "Failed to checkpoint the game.");
+    }
+}
+
+// Appends what happened to the game to its log, between checkpoints.
+fn record<C>(hall: &RefCell<Hall<C>>, server: &Server, seats: &[Seat<C>], event: Event) {
+    let hall = hall.borrow();
+
+    let Some(recovery) = &hall.recovery else {
+        return;
+    };
+
+    if let Err(error) = recovery.append(&server.metadata.game_id, seats[0].sent.len(), event) {
+        warn!(game_id = %hex::encode(server.metadata.game_id), %error, "Failed to log the game.");
+    }
+}
+
+// Logs the message for both players, with the head of the hash chain that
+// their clients have verified after it, and records the event that led to it
+// before sending it, so that players that miss it are sent it again even after
+// a restart. The whole game is checkpointed every `Recovery::is_due` messages.
+async fn send_logged<C: Channel>(
+    hall: &RefCell<Hall<C>>,
+    server: &Server,
+    seats: &mut [Seat<C>],
+    message: ServerMessage,
+    event: Event
+) -> Result<()> {
+    record(hall, server, seats, event);
+
+    for seat in seats.iter_mut() {
+        seat.sent.push(message.clone());
+        seat.chain_hashes.push(server.chain_hash);
+    }
+
+    let due = matches!(&hall.borrow().recovery, Some(recovery) if recovery.is_due(seats[0].sent.len()));
+
+    if due {
+        checkpoint(hall, server, seats);
+    }
+
+    for seat in seats.iter_mut() {
+        seat.send_last().await?;
+    }
+
+    Ok(())
+}
+
+// Offers the players a rematch, telling them if it was declined. Players that
+// are gone decline it, as do those that answer with anything else.
+async fn rematch<C: Channel>(seats: &mut [Seat<C>]) -> bool {
+    let mut accepted = true;
+
+    for seat in seats.iter_mut() {
+        if seat.connection.send(&ServerMessage::RematchOffered).await.is_err() {
+            accepted = false;
+        } else {
+            accepted = matches!(seat.connection.recv().await, Ok(ClientMessage::AcceptRematch(true)));
+        }
+
+        if !accepted {
+            break;
+        }
+    }
+
+    if !accepted {
+        for seat in seats.iter_mut() {
+            let _ = seat.connection.send(&ServerMessage::Refused("The rematch was declined.".into())).await;
+        }
+    }
+
+    accepted
+}
+
+async fn play_moves<C: Channel>(
+    hall: &RefCell<Hall<C>>,
+    server: &mut Server,
+    seats: &mut [Seat<C>]
+) -> Result<()> {
+    let game_id = hex::encode(server.metadata.game_id);
+    let clock = hall.borrow().clock;
+
+    // When the move on turn is due, kept until it is made so that players
+    // cannot get more time by dropping their connection and resuming the seat.
+    let mut deadline = None;
+
+    while let State::InProgress = server.game.state() {
+        let seat = match server.game.current_player() {
+            Player::A => 0,
+            Player::B => 1
+        };
+
+        // Moves received before the server restarted are proven without asking for them again.
+        let point = match server.pending.first().copied() {
+            Some(point) => point,
+            None => {
+                let due = *deadline.get_or_insert_with(|| {
+                    clock.map(|clock| Instant::now() + Duration::from_secs(clock.move_limit_secs.into()))
+                });
+
+                let point = match ask_for_move(hall, server, seats, due).await {
+                    Ok(Answer::Move(point)) => point,
+                    Ok(Answer::TakeBack) => {
+                        // The player on turn gets the full time for the move again once it is taken back.
+                        if take_back(hall, server, seats).await? {
+                            deadline = None;
+                        }
+
+                        continue;
+                    },
+                    Ok(Answer::OutOfTime) => return forfeit(hall, server, seats).await,
+                    Ok(Answer::Resign(signature)) => {
+                        let resigned = ServerMessage::Resigned { player: server.game.current_player(), signature };
+
+                        return end_early(hall, server, seats, resigned).await;
+                    },
+                    Ok(Answer::OfferDraw(signature)) => {
+                        if let Some(signatures) = offer_draw(hall, server, seats, signature).await? {
+                            return end_early(hall, server, seats, ServerMessage::Drawn { signatures }).await;
+                        }
+
+                        continue;
+                    },
+                    Err(error) if error.kind() == io::ErrorKind::InvalidData => return Err(error.into()),
+                    Err(_) => {
+                        seats[seat].resume().await?;
+                        continue;
+                    }
+                };
+
+                server.pending.push(point);
+                record(hall, server, seats, Event::Received(point));
+                deadline = None;
+
+                point
+            }
+        };
+
+        let (receipt, stats) = server.execute_move_async(point).await?;
+        info!(
+            %game_id,
+            move_index = server.move_index,
+            cycles = stats.cycles,
+            segments = stats.segments,
+            receipt_size = stats.receipt_size,
+            "Proved a move in {:.2?}.",
+            stats.duration
+        );
+
+        server.pending.clear();
+
+        let mut game = server.game;
+
+        if game.make_move(point).is_ok() {
+            server.apply_move(point, game);
+        }
+
+        // Kept to be sent again to spectators that start watching later on.
+        server.receipts.push(receipt.clone());
+        publish(hall, &server.metadata.game_id, ServerMessage::Move(receipt.clone()));
+
+        // Rejected moves are sent as well, since their receipt
+        // proves that the player attempted an illegal move.
+        let proved = Event::Proved { point, receipt: receipt.clone() };
+        send_logged(hall, server, seats, ServerMessage::Move(receipt), proved).await?;
+    }
+
+    info!(%game_id, "Proving the result of the game...");
+    let receipt = server.finalize_async().await?;
+    publish(hall, &server.metadata.game_id, ServerMessage::Finished(receipt.clone()));
+
+    for seat in seats.iter_mut() {
+        while seat.connection.send(&ServerMessage::Finished(receipt.clone())).await.is_err() {
+            seat.resume().await?;
+        }
+    }
+
+    Ok(())
+}
+
+// Sends the message to the spectators of the game, keeping move receipts
+// for those that start watching later on.
+fn publish<C>(hall: &RefCell<Hall<C>>, game_id: &GameId, message: ServerMessage) {
+    if let Some(spectated) = hall.borrow_mut().matches.get_mut(game_id) {
+        match &message {
+            ServerMessage::Move(receipt) => spectated.receipts.push(receipt.clone()),
+            ServerMessage::TakenBack { receipts, .. } => spectated.receipts.truncate(*receipts as usize),
+            _ => { }
+        }
+
+        // Fails only if nobody is watching.
+        let _ = spectated.updates.send(message);
+    }
+}
+
+// Sends the spectator the receipts of the game until it has ended.
+async fn watch<C: Channel>(hall: &RefCell<Hall<C>>, game_id: GameId, mut connection: C) -> Result<()> {
+    // Subscribing along with copying the receipts so far, so that none are missed or repeated.
+    let spectated = hall.borrow().matches.get(&game_id).map(|spectated| {
+        (spectated.start.clone(), spectated.receipts.clone(), spectated.updates.subscribe())
+    });
+
+    let Some((start, receipts, mut updates)) = spectated else {
+        connection.send(&ServerMessage::Refused("No such game.".into())).await?;
+
+        return Err(invalid_data("watched an unknown game").into());
+    };
+
+    connection.send(&start).await?;
+    send_missed(&mut connection, &receipts, 0).await?;
+
+    loop {
+        let message = updates.recv().await
+            .map_err(|_| invalid_data("spectator fell behind or the game was aborted"))?;
+
+        connection.send(&message).await?;
+
+        let ended = matches!(
+            message,
+            ServerMessage::Finished(_) | ServerMessage::Forfeit(_) | ServerMessage::Resigned { .. } | ServerMessage::Drawn { .. }
+        );
+
+        if ended {
+            return Ok(());
+        }
+    }
+}
+
+// Asks the player on turn for a move, to be made before the `deadline` if
+// given. Chat messages of either player are relayed meanwhile, see
+// `relay_chat`, and the clock keeps running while they chat, so that chatting
+// cannot stall the game. Players that registered with a key have to sign
+// their moves with it, and are asked again for moves that are not signed by
+// them, before anything is proven.
+async fn ask_for_move<C: Channel>(
+    hall: &RefCell<Hall<C>>,
+    server: &Server,
+    seats: &mut [Seat<C>],
+    deadline: Option<Instant>
+) -> io::Result<Answer> {
+    let player = server.game.current_player();
+    let (seat, opponent) = match player {
+        Player::A => (0, 1),
+        Player::B => (1, 0)
+    };
+
+    let public_key = server.metadata.player(player).public_key;
+
+    seats[seat].connection.send(&ServerMessage::YourTurn).await?;
+
+    // An opponent whose connection is lost is waited for once they are sent something.
+    let mut listening = true;
+
+    loop {
+        let (on_turn, waiting) = pair(seats, seat);
+
+        let message = tokio::select! {
+            message = on_turn.connection.recv::<ClientMessage>() => message?,
+            message = waiting.connection.recv::<ClientMessage>(), if listening => {
+                match message {
+                    Ok(ClientMessage::Chat(chat)) => relay_chat(hall, server, seats, opponent, chat).await,
+                    Ok(_) => {
+                        let refusal = ServerMessage::Refused("It is not your turn.".into());
+                        let _ = seats[opponent].connection.send(&refusal).await;
+                    },
+                    Err(_) => listening = false
+                }
+
+                continue;
+            },
+            _ = until(deadline) => return Ok(Answer::OutOfTime)
+        };
+
+        match message {
+            ClientMessage::Move(point) if public_key.is_none() => return Ok(Answer::Move(point)),
+            ClientMessage::SignedMove(signed) if is_move_of(server, &signed) => return Ok(Answer::Move(signed.point)),
+            ClientMessage::Move(_) | ClientMessage::SignedMove(_) => {
+                warn!(game_id = %hex::encode(server.metadata.game_id), ?player, "Refused a move that was not signed.");
+
+                let refusal = ServerMessage::Refused("The move was not signed by you.".into());
+                seats[seat].connection.send(&refusal).await?;
+                seats[seat].connection.send(&ServerMessage::YourTurn).await?;
+            },
+            ClientMessage::RequestTakeBack => return Ok(Answer::TakeBack),
+            ClientMessage::Resign if public_key.is_none() => return Ok(Answer::Resign(None)),
+            ClientMessage::OfferDraw if public_key.is_none() => return Ok(Answer::OfferDraw(None)),
+            ClientMessage::SignedResign(signature) if is_concession_of(server, player, Concession::Resign, &signature) => {
+                return Ok(Answer::Resign(Some(signature)));
+            },
+            ClientMessage::SignedOfferDraw(signature) if is_concession_of(server, player, Concession::Draw, &signature) => {
+                return Ok(Answer::OfferDraw(Some(signature)));
+            },
+            ClientMessage::Resign | ClientMessage::SignedResign(_) | ClientMessage::OfferDraw | ClientMessage::SignedOfferDraw(_) => {
+                let refusal = ServerMessage::Refused("The resignation or draw offer was not signed by you.".into());
+                seats[seat].connection.send(&refusal).await?;
+                seats[seat].connection.send(&ServerMessage::YourTurn).await?;
+            },
+            ClientMessage::Chat(chat) => relay_chat(hall, server, seats, seat, chat).await,
+            _ => return Err(invalid_data("expected a move"))
+        }
+    }
+}
+
+// The seat of index `seat` and the other one.
+fn pair<C>(seats: &mut [Seat<C>], seat: usize) -> (&mut Seat<C>, &mut Seat<C>) {
+    let (first, second) = seats.split_at_mut(1);
+
+    match seat {
+        0 => (&mut first[0], &mut second[0]),
+        _ => (&mut second[0], &mut first[0])
+    }
+}
+
+// Waits until the deadline, if there is one, or else forever.
+async fn until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => time::sleep_until(deadline).await,
+        None => future::pending().await
+    }
+}
+
+// Receives the next message of the player of `seat` other than a chat
+// message, relaying the chat messages that they send before it.
+async fn recv_answer<C: Channel>(
+    hall: &RefCell<Hall<C>>,
+    server: &Server,
+    seats: &mut [Seat<C>],
+    seat: usize
+) -> io::Result<ClientMessage> {
+    loop {
+        match seats[seat].connection.recv().await? {
+            ClientMessage::Chat(chat) => relay_chat(hall, server, seats, seat, chat).await,
+            message => return Ok(message)
+        }
+    }
+}
+
+// Relays the chat message of the player of `seat` to their opponent and the
+// spectators, and logs it along with the events of the game, if it is theirs,
+// see `is_chat_of`. Chat messages are not sent again to players resuming their seat.
+async fn relay_chat<C: Channel>(
+    hall: &RefCell<Hall<C>>,
+    server: &Server,
+    seats: &mut [Seat<C>],
+    seat: usize,
+    chat: ChatMessage
+) {
+    let player = if seat == 0 { Player::A } else { Player::B };
+
+    if !is_chat_of(server, player, &chat) {
+        let refusal = ServerMessage::Refused("The chat message was not sent by you in this game.".into());
+        let _ = seats[seat].connection.send(&refusal).await;
+
+        return;
+    }
+
+    info!(
+        game_id = %hex::encode(chat.game_id),
+        move_index = chat.move_index,
+        ?player,
+        text = %chat.text,
+        "Relaying a chat message."
+    );
+
+    record(hall, server, seats, Event::Chat(chat.clone()));
+    publish(hall, &server.metadata.game_id, ServerMessage::Chat(chat.clone()));
+
+    let _ = seats[1 - seat].connection.send(&ServerMessage::Chat(chat)).await;
+}
+
+// Whether the move was signed by the player on turn, for this point of the game.
+fn is_move_of(server: &Server, signed: &SignedMove) -> bool {
+    let player = server.game.current_player();
+
+    let Some(public_key) = server.metadata.player(player).public_key else {
+        return false;
+    };
+
+    signed.verify(&public_key, &server.metadata.game_id, server.move_index)
+}
+
+// Whether the player signed the concession, at this point of the game.
+fn is_concession_of(server: &Server, player: Player, concession: Concession, signature: &[u8]) -> bool {
+    let public_key = server.metadata.player(player).public_key;
+
+    public_key.is_some()
+        && auth::is_conceded(public_key, Some(signature), concession, &server.metadata.game_id, server.move_index)
+}
+
+// Whether the chat message is of the player, in this game up to this point,
+// signed by them if they were seated with a key.
+fn is_chat_of(server: &Server, player: Player, chat: &ChatMessage) -> bool {
+    let signed = match server.metadata.player(player).public_key {
+        Some(public_key) => chat.verify(&public_key),
+        None => chat.text.len() <= MAX_CHAT_LENGTH
+    };
+
+    signed
+        && chat.game_id == server.metadata.game_id
+        && chat.move_index <= server.move_index
+        && chat.player == player
+}
+
+// Takes back the last move of the player on turn along with the reply of their
+// opponent, if the opponent agrees to it, after which the player is asked for
+// a move again. Neither needs a receipt to return to the state before those
+// moves, since they have verified it already, see `Client::take_back`.
+// Returns whether the moves were taken back.
+async fn take_back<C: Channel>(hall: &RefCell<Hall<C>>, server: &mut Server, seats: &mut [Seat<C>]) -> Result<bool> {
+    let (requester, opponent) = match server.game.current_player() {
+        Player::A => (0, 1),
+        Player::B => (1, 0)
+    };
+
+    let Some(move_index) = server.move_index.checked_sub(2) else {
+        let refusal = ServerMessage::Refused("There is no move of yours to take back.".into());
+
+        // The player is asked for a move again either way.
+        let _ = seats[requester].connection.send(&refusal).await;
+
+        return Ok(false);
+    };
+
+    let accepted = match ask_to_take_back(hall, server, seats, opponent).await {
+        Ok(accepted) => accepted,
+        Err(error) if error.kind() == io::ErrorKind::InvalidData => return Err(error.into()),
+        // An opponent that is gone cannot agree to it.
+        Err(_) => {
+            seats[opponent].resume().await?;
+
+            false
+        }
+    };
+
+    if !accepted {
+        let _ = seats[requester].connection.send(&ServerMessage::Refused("The take-back was declined.".into())).await;
+
+        return Ok(false);
+    }
+
+    server.take_back(move_index);
+    info!(game_id = %hex::encode(server.metadata.game_id), move_index, "Took back the last two moves.");
+
+    let taken_back = ServerMessage::TakenBack { move_index, receipts: server.receipts.len() as u32 };
+    publish(hall, &server.metadata.game_id, taken_back.clone());
+
+    send_logged(hall, server, seats, taken_back, Event::TakenBack { move_index }).await?;
+
+    Ok(true)
+}
+
+async fn ask_to_take_back<C: Channel>(
+    hall: &RefCell<Hall<C>>,
+    server: &Server,
+    seats: &mut [Seat<C>],
+    opponent: usize
+) -> io::Result<bool> {
+    seats[opponent].connection.send(&ServerMessage::TakeBackRequested).await?;
+
+    match recv_answer(hall, server, seats, opponent).await? {
+        ClientMessage::AcceptTakeBack(accepted) => Ok(accepted),
+        _ => Err(invalid_data("expected an answer to the take-back"))
+    }
+}
+
+// Asks the opponent of the player on turn whether to agree to the draw that
+// the player offered, telling the player if they declined it, after which the
+// player is asked for a move again. Returns the signatures of players A and
+// B if the opponent agreed, see `ServerMessage::Drawn`.
+async fn offer_draw<C: Channel>(
+    hall: &RefCell<Hall<C>>,
+    server: &Server,
+    seats: &mut [Seat<C>],
+    offered: Option<Vec<u8>>
+) -> Result<Option<[Option<Vec<u8>>; 2]>> {
+    let (offerer, opponent) = match server.game.current_player() {
+        Player::A => (0, 1),
+        Player::B => (1, 0)
+    };
+
+    let accepted = match ask_to_draw(hall, server, seats, opponent).await {
+        Ok(accepted) => accepted,
+        Err(error) if error.kind() == io::ErrorKind::InvalidData => return Err(error.into()),
+        // An opponent that is gone cannot agree to it.
+        Err(_) => {
+            seats[opponent].resume().await?;
+
+            None
+        }
+    };
+
+    let Some(accepted) = accepted else {
+        let _ = seats[offerer].connection.send(&ServerMessage::Refused("The draw was declined.".into())).await;
+
+        return Ok(None);
+    };
+
+    let mut signatures = [None, None];
+    signatures[offerer] = offered;
+    signatures[opponent] = accepted;
+
+    Ok(Some(signatures))
+}
+
+// Returns the signature of the opponent if they agreed to the draw, or `None`
+// inside if they were seated without a key. An agreement that is not signed
+// by an opponent with a key counts as declining it.
+async fn ask_to_draw<C: Channel>(
+    hall: &RefCell<Hall<C>>,
+    server: &Server,
+    seats: &mut [Seat<C>],
+    seat: usize
+) -> io::Result<Option<Option<Vec<u8>>>> {
+    let opponent = server.game.current_player().flip();
+    let public_key = server.metadata.player(opponent).public_key;
+
+    seats[seat].connection.send(&ServerMessage::DrawOffered).await?;
+
+    match recv_answer(hall, server, seats, seat).await? {
+        ClientMessage::AcceptDraw(true) if public_key.is_none() => Ok(Some(None)),
+        ClientMessage::SignedAcceptDraw(signature) if is_concession_of(server, opponent, Concession::Draw, &signature) => {
+            Ok(Some(Some(signature)))
+        },
+        ClientMessage::AcceptDraw(_) | ClientMessage::SignedAcceptDraw(_) => Ok(None),
+        _ => Err(invalid_data("expected an answer to the draw offer"))
+    }
+}
+
+// Ends the game before it is over, as the player on turn resigned or both
+// players agreed to a draw, sending the players and spectators `message`.
+// Nothing is proven for it, the receipts so far prove the game up to there.
+async fn end_early<C: Channel>(
+    hall: &RefCell<Hall<C>>,
+    server: &Server,
+    seats: &mut [Seat<C>],
+    message: ServerMessage
+) -> Result<()> {
+    info!(game_id = %hex::encode(server.metadata.game_id), move_index = server.move_index, "The game ended early.");
+    publish(hall, &server.metadata.game_id, message.clone());
+
+    // The game is over either way, so players that are gone are not waited for.
+    for seat in seats.iter_mut() {
+        let _ = seat.connection.send(&message).await;
+    }
+
+    Ok(())
+}
+
+// Ends the game with a loss for the player on turn, who ran out of time,
+// sending the players and spectators the forfeit signed by the referee.
+async fn forfeit<C: Channel>(hall: &RefCell<Hall<C>>, server: &Server, seats: &mut [Seat<C>]) -> Result<()> {
+    let clock = hall.borrow().clock.expect("only games with a clock are forfeited");
+    let forfeit = server.forfeit(clock, &hall.borrow().referee);
+
+    info!(game_id = %hex::encode(server.metadata.game_id), player = ?forfeit.player, "A player ran out of time.");
+    publish(hall, &server.metadata.game_id, ServerMessage::Forfeit(forfeit.clone()));
+
+    // The game is over either way, so players that are gone are not waited for.
+    for seat in seats.iter_mut() {
+        let _ = seat.connection.send(&ServerMessage::Forfeit(forfeit.clone())).await;
+    }
+
+    Ok(())
+}
+
+impl<C: Channel> Seat<C> {
+    async fn resume(&mut self) -> Result<()> {
+        self.connection = come_back(&mut self.resumed, &self.sent, &self.chain_hashes).await?;
+
+        Ok(())
+    }
+
+    // Sends the message logged last, see `send_logged`, waiting for
+    // the player to come back if it fails to be sent.
+    async fn send_last(&mut self) -> Result<()> {
+        let message = self.sent.last().expect("a message was logged");
+
+        if self.connection.send(message).await.is_err() {
+            self.resume().await?;
+        }
+
+        Ok(())
+    }
+}
+
+// Waits for the player to come back, sending them the `Move` and `TakenBack`
+// messages they missed, which may have been lost along with a connection even
+// when sending them succeeded. Clients that did not verify the hash chain
+// that the server has them at are refused, as their receipts would not follow.
+async fn come_back<C: Channel>(
+    resumed: &mut mpsc::UnboundedReceiver<Resumed<C>>,
+    sent: &[ServerMessage],
+    chain_hashes: &[Digest]
+) -> Result<C> {
+    loop {
+        let resumed = time::timeout(RECONNECT_TIMEOUT, resumed.recv()).await;
+
+        let Ok(Some((mut connection, received, chain_hash))) = resumed else {
+            return Err(invalid_data("player did not come back").into());
+        };
+
+        if chain_hashes.get(received as usize) != Some(&chain_hash) {
+            let refusal = ServerMessage::ResumeRefused("The game cannot be resumed from where you are.".into());
+            let _ = connection.send(&refusal).await;
+
+            continue;
+        }
+
+        if send_all(&mut connection, sent.iter().skip(received as usize)).await.is_ok() {
+            return Ok(connection);
+        }
+    }
+}
+
+async fn send_all<'a>(
+    connection: &mut impl Channel,
+    messages: impl Iterator<Item = &'a ServerMessage>
+) -> io::Result<()> {
+    for message in messages {
+        connection.send(message).await?;
+    }
+
+    Ok(())
+}
+
+async fn send_missed(
+    connection: &mut impl Channel,
+    receipts: &[SessionReceipt],
+    received: u32
+) -> io::Result<()> {
+    for receipt in receipts.iter().skip(received as usize) {
+        connection.send(&ServerMessage::Move(receipt.clone())).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use ed25519_dalek::Signer;
+    use game::{VmResponse, metadata::PlayerInfo};
+
+    use super::*;
+    use crate::{client::Client, lobby::{Terms, OpenGame}, identity::Identity};
+
+    // A player seated by `seat_players`, with the client that verifies what they are sent.
+    struct Seated {
+        connection: InProcess,
+        client: Client,
+        metadata: MatchMetadata,
+        token: SessionToken,
+        referee: [u8; 32]
+    }
+
+    // Registers Alice and Bob, with the key of their identity if given, has
+    // Alice open a game and Bob join it.
+    async fn seat_players(hall: &LocalHall, identities: [Option<&Identity>; 2]) -> Vec<Seated> {
+        let mut players = [hall.connect(), hall.connect()];
+
+        for ((player, name), identity) in players.iter_mut().zip(["Alice", "Bob"]).zip(identities) {
+            protocol::greet(player).await.unwrap();
+
+            let public_key = identity.map(Identity::public_key);
+            let info = PlayerInfo { name: name.into(), symbol: name[..1].into(), public_key };
+            player.send(&ClientMessage::Register(info)).await.unwrap();
+
+            if let Some(identity) = identity {
+                let ServerMessage::Challenge(challenge) = player.recv().await.unwrap() else {
+                    panic!("expected to be challenged");
+                };
+                player.send(&ClientMessage::Authenticate(identity.sign_login(&challenge))).await.unwrap();
+            }
+
+            let ServerMessage::Lobby(_) = player.recv().await.unwrap() else {
+                panic!("expected the lobby");
+            };
+        }
+
+        players[0].send(&ClientMessage::Open(Terms::local())).await.unwrap();
+
+        // The game is opened by the task of the other player, which may not have run yet.
+        let game_id = loop {
+            players[1].send(&ClientMessage::ListGames).await.unwrap();
+
+            let ServerMessage::Lobby(games) = players[1].recv().await.unwrap() else {
+                panic!("expected the lobby");
+            };
+
+            if let Some(game) = games.first() {
+                assert_eq!(game.host.name, "Alice");
+                break game.id;
+            }
+
+            task::yield_now().await;
+        };
+
+        players[1].send(&ClientMessage::Join(game_id)).await.unwrap();
+
+        let mut seated = vec![];
+
+        for (player, mut connection) in [Player::A, Player::B].into_iter().zip(players) {
+            let ServerMessage::Welcome { player: seat, metadata, pin, receipt, token, referee, .. } = connection.recv().await.unwrap() else {
+                panic!("expected to be seated");
+            };
+            assert_eq!(seat, player);
+
+            let mut client = Client::from_init_receipt(&receipt, &metadata.hash());
+            client.pin(pin);
+            seated.push(Seated { connection, client, metadata: *metadata, token, referee });
+        }
+
+        seated
+    }
+
+    // Lists the open games until they are as expected, as they are opened and
+    // closed by the tasks of other players, which may not have run yet.
+    async fn list_games(player: &mut InProcess, expected: impl Fn(&[OpenGame]) -> bool) -> Vec<OpenGame> {
+        loop {
+            player.send(&ClientMessage::ListGames).await.unwrap();
+
+            let ServerMessage::Lobby(games) = player.recv().await.unwrap() else {
+                panic!("expected the lobby");
+            };
+
+            if expected(&games) {
+                return games;
+            }
+
+            task::yield_now().await;
+        }
+    }
+
+    // Has the player of `seat`, who is on turn, make the move, which both players verify.
+    async fn play_move(players: &mut [Seated], seat: usize, point: Point) {
+        your_turn(&mut players[seat]).await;
+        players[seat].connection.send(&ClientMessage::Move(point)).await.unwrap();
+
+        for player in players.iter_mut() {
+            received_move(player).await;
+        }
+    }
+
+    async fn your_turn(player: &mut Seated) {
+        let ServerMessage::YourTurn = player.connection.recv().await.unwrap() else {
+            panic!("expected to be on turn");
+        };
+    }
+
+    async fn refused(player: &mut Seated) -> String {
+        let ServerMessage::Refused(reason) = player.connection.recv().await.unwrap() else {
+            panic!("expected to be refused");
+        };
+
+        reason
+    }
+
+    async fn received_move(player: &mut Seated) {
+        let ServerMessage::Move(receipt) = player.connection.recv().await.unwrap() else {
+            panic!("expected the receipt of the move");
+        };
+        let VmResponse::Accepted { .. } = player.client.verify_receipt(&receipt) else {
+            panic!("the move was rejected");
+        };
+    }
+
+    // Connects the player again, dropping their connection, and resumes their
+    // seat after the first `received` messages, from where their client is.
+    async fn resume(hall: &LocalHall, player: &mut Seated, received: u32) {
+        player.connection = hall.connect();
+        protocol::greet(&mut player.connection).await.unwrap();
+
+        let resume = ClientMessage::Resume { token: player.token, received, chain_hash: player.client.chain_hash };
+        player.connection.send(&resume).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn games_are_played_over_in_process_connections() {
+        task::LocalSet::new().run_until(async {
+            let hall = LocalHall::new(None, None);
+            let mut players = seat_players(&hall, [None, None]).await;
+
+            // Player A wins on the first column.
+            for (index, (x, y)) in [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)].into_iter().enumerate() {
+                play_move(&mut players, index % 2, Point::new(x, y)).await;
+            }
+
+            for player in &mut players {
+                let ServerMessage::Finished(receipt) = player.connection.recv().await.unwrap() else {
+                    panic!("expected the result");
+                };
+                assert_eq!(player.client.verify_final_receipt(&receipt, &player.metadata.hash()).state, State::Winner(Player::A));
+            }
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn games_are_closed_when_their_host_leaves() {
+        task::LocalSet::new().run_until(async {
+            let hall = LocalHall::new(None, None);
+            let [mut alice, mut bob] = [hall.connect(), hall.connect()];
+
+            for (player, name) in [(&mut alice, "Alice"), (&mut bob, "Bob")] {
+                protocol::greet(player).await.unwrap();
+                player.send(&ClientMessage::Register(PlayerInfo::new(name, &name[..1]))).await.unwrap();
+
+                let ServerMessage::Lobby(_) = player.recv().await.unwrap() else {
+                    panic!("expected the lobby");
+                };
+            }
+
+            // Games proven by another guest than the one of the server are refused.
+            let terms = Terms { image_id: Digest::from([7; 8]), ..Terms::local() };
+            alice.send(&ClientMessage::Open(terms)).await.unwrap();
+
+            let ServerMessage::Refused(_) = alice.recv().await.unwrap() else {
+                panic!("expected the game to be refused");
+            };
+            let ServerMessage::Lobby(_) = alice.recv().await.unwrap() else {
+                panic!("expected the lobby");
+            };
+
+            alice.send(&ClientMessage::Open(Terms::local())).await.unwrap();
+            let open = list_games(&mut bob, |games| !games.is_empty()).await;
+
+            drop(alice);
+            list_games(&mut bob, <[OpenGame]>::is_empty).await;
+
+            bob.send(&ClientMessage::Join(open[0].id)).await.unwrap();
+
+            let ServerMessage::Refused(_) = bob.recv().await.unwrap() else {
+                panic!("expected the game to be gone");
+            };
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn players_resuming_their_seat_are_sent_what_they_missed() {
+        task::LocalSet::new().run_until(async {
+            let hall = LocalHall::new(None, None);
+            let mut players = seat_players(&hall, [None, None]).await;
+
+            play_move(&mut players, 0, Point::new(0, 0)).await;
+            play_move(&mut players, 1, Point::new(1, 0)).await;
+
+            // Bob loses his connection, and with it the next move of Alice.
+            players[1].connection = hall.connect();
+
+            your_turn(&mut players[0]).await;
+            players[0].connection.send(&ClientMessage::Move(Point::new(0, 1))).await.unwrap();
+            received_move(&mut players[0]).await;
+
+            // Resuming from a point of the game that the server never had Bob at is refused.
+            let mut stale = hall.connect();
+            protocol::greet(&mut stale).await.unwrap();
+            stale.send(&ClientMessage::Resume { token: players[1].token, received: 1, chain_hash: players[1].client.chain_hash }).await.unwrap();
+
+            let ServerMessage::ResumeRefused(_) = stale.recv().await.unwrap() else {
+                panic!("expected the resume to be refused");
+            };
+
+            resume(&hall, &mut players[1], 2).await;
+            received_move(&mut players[1]).await;
+
+            // Bob asks to take back the moves since his first one, and loses his connection again.
+            your_turn(&mut players[1]).await;
+            players[1].connection.send(&ClientMessage::RequestTakeBack).await.unwrap();
+            players[1].connection = hall.connect();
+
+            let ServerMessage::TakeBackRequested = players[0].connection.recv().await.unwrap() else {
+                panic!("expected to be asked to take back the moves");
+            };
+            players[0].connection.send(&ClientMessage::AcceptTakeBack(true)).await.unwrap();
+
+            resume(&hall, &mut players[1], 3).await;
+
+            for player in &mut players {
+                let ServerMessage::TakenBack { move_index, .. } = player.connection.recv().await.unwrap() else {
+                    panic!("expected the moves to be taken back");
+                };
+                assert_eq!(move_index, 1);
+
+                player.client.take_back(move_index);
+            }
+
+            assert_eq!(players[0].client.chain_hash, players[1].client.chain_hash);
+
+            your_turn(&mut players[1]).await;
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn recovered_games_are_played_on_once_their_players_are_back() {
+        let dir = env::temp_dir().join(format!("zk-tic-tac-toe-{}.hall", std::process::id()));
+        let recovery = Recovery::open(&dir, 1).unwrap();
+
+        // The game as it was when the server stopped: the first move was sent
+        // to both players, and the second one received but not proven yet.
+        let mut server = Server::new(MatchMetadata { game_id: [4; 32], ..MatchMetadata::default() });
+        let init = server.prove_init().unwrap();
+        let initial_hash = server.chain_hash;
+
+        let (receipt, _) = server.execute_move(Point::new(1, 1)).unwrap();
+        let mut game = server.game;
+        game.make_move(Point::new(1, 1)).unwrap();
+        server.apply_move(Point::new(1, 1), game);
+        server.receipts.push(receipt.clone());
+
+        let seats: Vec<_> = [[1; 32], [2; 32]].into_iter()
+            .map(|token| SavedSeat { token, sent: vec![ServerMessage::Move(receipt.clone())], chain_hashes: vec![initial_hash, server.chain_hash] })
+            .collect();
+
+        recovery.start(&server.metadata.game_id, &init).unwrap();
+        recovery.checkpoint(&server, seats.clone()).unwrap();
+        recovery.append(&server.metadata.game_id, 1, Event::Received(Point::new(0, 0))).unwrap();
+
+        let referee = recovery.referee().unwrap().verifying_key().to_bytes();
+
+        task::LocalSet::new().run_until(async {
+            let hall = LocalHall::new(None, Some(recovery));
+            let mut players = vec![];
+
+            for seat in &seats {
+                let mut client = Client::from_init_receipt(&init, &server.metadata.hash());
+                client.pin(server.pin());
+
+                let VmResponse::Accepted { .. } = client.verify_receipt(&receipt) else {
+                    panic!("the move was rejected");
+                };
+
+                let mut connection = hall.connect();
+                protocol::greet(&mut connection).await.unwrap();
+
+                let resume = ClientMessage::Resume { token: seat.token, received: 1, chain_hash: client.chain_hash };
+                connection.send(&resume).await.unwrap();
+
+                players.push(Seated { connection, client, metadata: server.metadata.clone(), token: seat.token, referee });
+            }
+
+            // The pending move is proven without asking for it again, after which the game goes on.
+            for player in &mut players {
+                received_move(player).await;
+            }
+
+            assert_eq!(players[0].client.chain_hash, players[1].client.chain_hash);
+
+            your_turn(&mut players[0]).await;
+        }).await;
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn moves_are_taken_back_once_the_opponent_agrees() {
+        task::LocalSet::new().run_until(async {
+            let hall = LocalHall::new(None, None);
+            let mut players = seat_players(&hall, [None, None]).await;
+
+            // There is nothing to take back before the first move of the player.
+            your_turn(&mut players[0]).await;
+            players[0].connection.send(&ClientMessage::RequestTakeBack).await.unwrap();
+            assert_eq!(refused(&mut players[0]).await, "There is no move of yours to take back.");
+
+            play_move(&mut players, 0, Point::new(0, 0)).await;
+            play_move(&mut players, 1, Point::new(1, 0)).await;
+
+            // The player is asked for a move again once the opponent declines it.
+            for accepted in [false, true] {
+                your_turn(&mut players[0]).await;
+                players[0].connection.send(&ClientMessage::RequestTakeBack).await.unwrap();
+
+                let ServerMessage::TakeBackRequested = players[1].connection.recv().await.unwrap() else {
+                    panic!("expected to be asked to take back the moves");
+                };
+                players[1].connection.send(&ClientMessage::AcceptTakeBack(accepted)).await.unwrap();
+
+                if !accepted {
+                    assert_eq!(refused(&mut players[0]).await, "The take-back was declined.");
+                }
+            }
+
+            for player in &mut players {
+                let ServerMessage::TakenBack { move_index, receipts } = player.connection.recv().await.unwrap() else {
+                    panic!("expected the moves to be taken back");
+                };
+                assert_eq!((move_index, receipts), (0, 0));
+
+                player.client.take_back(move_index);
+            }
+
+            assert_eq!(players[0].client.chain_hash, players[1].client.chain_hash);
+
+            // Player A is on turn again for the first move.
+            play_move(&mut players, 0, Point::new(1, 1)).await;
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn games_end_early_on_resigning_or_agreeing_to_a_draw() {
+        task::LocalSet::new().run_until(async {
+            let hall = LocalHall::new(None, None);
+            let mut players = seat_players(&hall, [None, None]).await;
+
+            your_turn(&mut players[0]).await;
+            players[0].connection.send(&ClientMessage::OfferDraw).await.unwrap();
+
+            let ServerMessage::DrawOffered = players[1].connection.recv().await.unwrap() else {
+                panic!("expected to be offered a draw");
+            };
+            players[1].connection.send(&ClientMessage::AcceptDraw(true)).await.unwrap();
+
+            for player in &mut players {
+                let ServerMessage::Drawn { signatures } = player.connection.recv().await.unwrap() else {
+                    panic!("expected the game to be drawn");
+                };
+                assert_eq!(signatures, [None, None]);
+            }
+
+            let hall = LocalHall::new(None, None);
+            let mut players = seat_players(&hall, [None, None]).await;
+
+            play_move(&mut players, 0, Point::new(0, 0)).await;
+
+            your_turn(&mut players[1]).await;
+            players[1].connection.send(&ClientMessage::Resign).await.unwrap();
+
+            for player in &mut players {
+                let ServerMessage::Resigned { player: resigned, signature } = player.connection.recv().await.unwrap() else {
+                    panic!("expected the game to be resigned");
+                };
+                assert_eq!((resigned, signature), (Player::B, None));
+            }
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn moves_not_signed_by_players_with_a_key_are_refused() {
+        task::LocalSet::new().run_until(async {
+            let hall = LocalHall::new(None, None);
+            let alice = Identity::generate();
+            let mut players = seat_players(&hall, [Some(&alice), None]).await;
+
+            let point = Point::new(0, 0);
+            let game_id = players[0].metadata.game_id;
+            let signed = |move_index| {
+                let signature = alice.key().sign(&auth::move_message(&game_id, move_index, point)).to_bytes().to_vec();
+
+                ClientMessage::SignedMove(SignedMove::new(point, signature))
+            };
+
+            // Neither a move without a signature nor one signed for another move index is proven.
+            for refused_move in [ClientMessage::Move(point), signed(1)] {
+                your_turn(&mut players[0]).await;
+                players[0].connection.send(&refused_move).await.unwrap();
+                assert_eq!(refused(&mut players[0]).await, "The move was not signed by you.");
+            }
+
+            your_turn(&mut players[0]).await;
+            players[0].connection.send(&signed(0)).await.unwrap();
+
+            for player in &mut players {
+                received_move(player).await;
+            }
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn chat_of_either_player_is_relayed_at_any_time_and_logged() {
+        let dir = env::temp_dir().join(format!("zk-tic-tac-toe-{}.chat", std::process::id()));
+        let recovery = Recovery::open(&dir, 5).unwrap();
+
+        task::LocalSet::new().run_until(async {
+            let hall = LocalHall::new(None, Some(recovery));
+            let alice = Identity::generate();
+            let mut players = seat_players(&hall, [Some(&alice), None]).await;
+            let game_id = players[0].metadata.game_id;
+
+            your_turn(&mut players[0]).await;
+
+            // Bob chats while Alice is on turn, unsigned as he has no key.
+            let unsigned = ChatMessage { game_id, move_index: 0, player: Player::B, text: "Good luck!".into(), signature: vec![] };
+            players[1].connection.send(&ClientMessage::Chat(unsigned.clone())).await.unwrap();
+
+            let ServerMessage::Chat(chat) = players[0].connection.recv().await.unwrap() else {
+                panic!("expected the chat message");
+            };
+            assert_eq!(chat, unsigned);
+
+            // He cannot move before his turn, nor chat in another game or as Alice.
+            players[1].connection.send(&ClientMessage::Move(Point::new(1, 1))).await.unwrap();
+            assert_eq!(refused(&mut players[1]).await, "It is not your turn.");
+
+            for chat in [ChatMessage { game_id: [9; 32], ..unsigned.clone() }, ChatMessage { player: Player::A, ..unsigned }] {
+                players[1].connection.send(&ClientMessage::Chat(chat)).await.unwrap();
+                assert_eq!(refused(&mut players[1]).await, "The chat message was not sent by you in this game.");
+            }
+
+            // Alice signs her chat messages, and is still asked for her move after them.
+            let signature = alice.key().sign(&game::chat::chat_message(&game_id, 0, Player::A, "Thanks!"));
+            let signed = ChatMessage {
+                game_id,
+                move_index: 0,
+                player: Player::A,
+                text: "Thanks!".into(),
+                signature: signature.to_bytes().to_vec()
+            };
+            players[0].connection.send(&ClientMessage::Chat(signed.clone())).await.unwrap();
+
+            let ServerMessage::Chat(chat) = players[1].connection.recv().await.unwrap() else {
+                panic!("expected the chat message");
+            };
+            assert_eq!(chat, signed);
+
+            let point = Point::new(0, 0);
+            let signature = alice.key().sign(&auth::move_message(&game_id, 0, point)).to_bytes().to_vec();
+            players[0].connection.send(&ClientMessage::SignedMove(SignedMove::new(point, signature))).await.unwrap();
+
+            for player in &mut players {
+                received_move(player).await;
+            }
+
+            // Both messages were logged along with the events of the game.
+            let log = fs::read(dir.join(hex::encode(game_id)).with_extension("log")).unwrap();
+
+            for text in [&b"Good luck!"[..], b"Thanks!"] {
+                assert!(log.windows(text.len()).any(|window| window == text));
+            }
+        }).await;
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn players_that_run_out_of_time_forfeit() {
+        task::LocalSet::new().run_until(async {
+            let hall = LocalHall::new(Some(Clock { move_limit_secs: 1 }), None);
+            let mut players = seat_players(&hall, [None, None]).await;
+
+            play_move(&mut players, 0, Point::new(0, 0)).await;
+
+            // Player B is asked for a move, and never makes it.
+            your_turn(&mut players[1]).await;
+
+            for player in &mut players {
+                let ServerMessage::Forfeit(forfeit) = player.connection.recv().await.unwrap() else {
+                    panic!("expected the forfeit");
+                };
+                assert!(forfeit.verify(&player.referee));
+                assert_eq!((forfeit.player, forfeit.move_index), (Player::B, 1));
+                assert_eq!(forfeit.chain_hash, player.client.chain_hash);
+            }
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn players_do_not_get_more_time_by_resuming_their_seat() {
+        task::LocalSet::new().run_until(async {
+            let hall = LocalHall::new(Some(Clock { move_limit_secs: 2 }), None);
+            let mut players = seat_players(&hall, [None, None]).await;
+
+            play_move(&mut players, 0, Point::new(0, 0)).await;
+            your_turn(&mut players[1]).await;
+
+            // Bob reconnects shortly before his time runs out, and is asked for the move again.
+            time::sleep(Duration::from_millis(1500)).await;
+            resume(&hall, &mut players[1], 1).await;
+            your_turn(&mut players[1]).await;
+
+            // He forfeits once the time for the move runs out, rather than a full move limit later.
+            for player in &mut players {
+                let forfeit = time::timeout(Duration::from_secs(1), player.connection.recv()).await;
+                let Ok(Ok(ServerMessage::Forfeit(forfeit))) = forfeit else {
+                    panic!("expected the forfeit within the time left for the move");
+                };
+                assert_eq!((forfeit.player, forfeit.move_index), (Player::B, 1));
+            }
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn spectators_verify_the_moves_made_before_and_after_they_start_watching() {
+        task::LocalSet::new().run_until(async {
+            let hall = LocalHall::new(None, None);
+            let mut players = seat_players(&hall, [None, None]).await;
+
+            play_move(&mut players, 0, Point::new(0, 0)).await;
+
+            let mut spectator = hall.connect();
+            protocol::greet(&mut spectator).await.unwrap();
+            spectator.send(&ClientMessage::Watch(players[0].metadata.game_id)).await.unwrap();
+
+            let ServerMessage::Spectating { metadata, pin, receipt, .. } = spectator.recv().await.unwrap() else {
+                panic!("expected to be watching");
+            };
+            assert_eq!(metadata.hash(), players[0].metadata.hash());
+
+            let mut client = Client::from_init_receipt(&receipt, &metadata.hash());
+            client.pin(pin);
+
+            play_move(&mut players, 1, Point::new(1, 1)).await;
+
+            for _ in 0..2 {
+                let ServerMessage::Move(receipt) = spectator.recv().await.unwrap() else {
+                    panic!("expected the receipt of the move");
+                };
+                let VmResponse::Accepted { .. } = client.verify_receipt(&receipt) else {
+                    panic!("the move was rejected");
+                };
+            }
+
+            assert_eq!(client.chain_hash, players[0].client.chain_hash);
+        }).await;
+    }
+}
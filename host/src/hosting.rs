@@ -0,0 +1,135 @@
+use std::time::Instant;
+
+use risc0_zkvm::{SessionReceipt, Result};
+use game::{TicTacToe, State, Point, VmResponse};
+
+use crate::{server::Server, client::Client, stats::GameStats};
+
+// How many moves in a row can be rejected before `play` gives up on the
+// players, which would otherwise be asked forever if they keep making the
+// same illegal move, such as a buggy bot or a replayed corpus entry.
+pub const MAX_REJECTIONS: u32 = 3;
+
+// The players of a game hosted by `play`, which pick its moves, such as bots,
+// a prompt or the connections of remote players. This, `game::events::GameObserver`
+// and `prover::Backend` are how applications host games of their own.
+pub trait Players {
+    // The move of the player on turn in `game`, which is in progress.
+    // An error ends the game without a result.
+    fn next_move(&mut self, game: &TicTacToe) -> Result<Point>;
+
+    // Called with the receipt of every move once it has been verified, which
+    // proves either that the move was made or why it was rejected. The players
+    // are asked for another move after a rejected one.
+    fn on_receipt(&mut self, _receipt: &SessionReceipt, _response: &VmResponse) { }
+}
+
+// A game played to its end, with the receipts of its moves kept in `Server::receipts`.
+pub struct PlayedGame {
+    pub server: Server,
+    pub init: SessionReceipt,
+    pub result: SessionReceipt
+}
+
+// Proves the initial state of the game of `server`, each move that `players`
+// make and the result, with each receipt verified by a client the way the
+// clients of the players would, and what it took recorded in `stats`. Fails
+// if a receipt fails to be proven or verified, or the players fail to move,
+// or make `MAX_REJECTIONS` illegal moves in a row.
+pub fn play(mut server: Server, players: &mut impl Players, stats: &mut GameStats) -> Result<PlayedGame> {
+    let match_hash = server.metadata.hash();
+    let init = server.prove_init()?;
+
+    let mut client = Client::try_from_init_receipt(&init, &match_hash)?;
+    client.try_pin(server.pin())?;
+
+    let mut rejections = 0;
+
+    while server.game.state() == State::InProgress {
+        let point = players.next_move(&server.game)?;
+        let (receipt, proof) = server.execute_move(point)?;
+
+        let start = Instant::now();
+        let response = client.try_verify_receipt(&receipt)?;
+        stats.record(server.move_index, proof, start.elapsed());
+
+        players.on_receipt(&receipt, &response);
+
+        match response {
+            VmResponse::Accepted { game, .. } => {
+                server.receipts.push(receipt);
+                server.apply_move(point, game);
+
+                rejections = 0;
+            },
+            VmResponse::Rejected { .. } => {
+                rejections += 1;
+
+                anyhow::ensure!(rejections < MAX_REJECTIONS, "the players made {MAX_REJECTIONS} illegal moves in a row");
+            }
+        }
+    }
+
+    let result = server.finalize()?;
+    let journal = client.try_verify_final_receipt(&result, &match_hash)?;
+
+    anyhow::ensure!(journal.state == server.game.state(), "the result does not match the moves");
+
+    Ok(PlayedGame { server, init, result })
+}
+
+#[cfg(test)]
+mod tests {
+    use game::{Player, metadata::MatchMetadata};
+
+    use super::*;
+
+    #[test]
+    fn players_that_keep_making_illegal_moves_are_given_up_on() {
+        // Always plays out of bounds.
+        struct Stuck(u32);
+
+        impl Players for Stuck {
+            fn next_move(&mut self, _game: &TicTacToe) -> Result<Point> {
+                self.0 += 1;
+
+                Ok(Point::new(3, 3))
+            }
+        }
+
+        let mut stuck = Stuck(0);
+        let mut stats = GameStats::new("standard", crate::config::ProverBackend::Local);
+
+        let error = play(Server::new(MatchMetadata::default()), &mut stuck, &mut stats).err().unwrap();
+
+        assert!(error.to_string().contains("illegal moves in a row"));
+        assert_eq!(stuck.0, MAX_REJECTIONS);
+    }
+
+    #[test]
+    fn hosted_games_ask_again_after_a_rejected_move() {
+        // Plays the moves in order, recording which of them were accepted.
+        struct Script(Vec<Point>, Vec<bool>);
+
+        impl Players for Script {
+            fn next_move(&mut self, _game: &TicTacToe) -> Result<Point> {
+                Ok(self.0.remove(0))
+            }
+
+            fn on_receipt(&mut self, _receipt: &SessionReceipt, response: &VmResponse) {
+                self.1.push(matches!(response, VmResponse::Accepted { .. }));
+            }
+        }
+
+        let moves = [(0, 0), (0, 0), (1, 0), (0, 1), (1, 1), (0, 2)];
+        let mut script = Script(moves.map(|(x, y)| Point::new(x, y)).to_vec(), vec![]);
+
+        let mut stats = GameStats::new("standard", crate::config::ProverBackend::Local);
+        let played = play(Server::new(MatchMetadata::default()), &mut script, &mut stats).unwrap();
+
+        assert_eq!(script.1, [true, false, true, true, true, true]);
+        assert_eq!(played.server.game.state(), State::Winner(Player::A));
+        assert_eq!(played.server.receipts.len(), 5);
+        assert_eq!(stats.moves.len(), 6);
+    }
+}
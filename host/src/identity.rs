@@ -0,0 +1,126 @@
+use std::{fs, io, path::Path};
+
+use ed25519_dalek::{SigningKey, Signer, Signature, VerifyingKey, SECRET_KEY_LENGTH};
+use rand::rngs::OsRng;
+
+use crate::protocol::invalid_data;
+
+// Read from the working directory when no other file is given, see `ClientConfig::identity`.
+pub const IDENTITY_FILE: &str = "identity.key";
+
+// Sent by the server to a client registering with a public key, which signs it
+// along with the "login" prefix to prove that it holds the secret key.
+pub use wire::Challenge;
+
+// The ed25519 key that a player is known by on servers, which signs their chat
+// messages and is bound into the terms of their matches. It is kept in a file,
+// as the hex encoded secret key, so that the player keeps it across sessions.
+pub struct Identity {
+    key: SigningKey
+}
+
+impl Identity {
+    pub fn generate() -> Self {
+        Self { key: SigningKey::generate(&mut OsRng) }
+    }
+
+    // Reads the key from `path`, or generates one and writes it there if the
+    // file does not exist yet.
+    pub fn load_or_generate(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                let secret = hex::decode(text.trim()).map_err(invalid_data)?;
+                let secret: [u8; SECRET_KEY_LENGTH] = secret.try_into()
+                    .map_err(|_| invalid_data("identity keys are 32 hex encoded bytes"))?;
+
+                Ok(Self { key: SigningKey::from_bytes(&secret) })
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                let identity = Self::generate();
+                identity.write(path)?;
+
+                Ok(identity)
+            },
+            Err(error) => Err(error)
+        }
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.key.verifying_key().to_bytes()
+    }
+
+    pub fn key(&self) -> &SigningKey {
+        &self.key
+    }
+
+    // The answer to the challenge of the server, see `verify_login`.
+    pub fn sign_login(&self, challenge: &Challenge) -> Vec<u8> {
+        self.key.sign(&login_message(challenge)).to_bytes().to_vec()
+    }
+
+    // Only readable by the owner, as anyone holding the key can play as the player.
+    fn write(&self, path: &Path) -> io::Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        io::Write::write_all(&mut options.open(path)?, hex::encode(self.key.to_bytes()).as_bytes())
+    }
+}
+
+// Whether the signature answers the challenge with the secret key of `public_key`.
+pub fn verify_login(public_key: &[u8; 32], challenge: &Challenge, signature: &[u8]) -> bool {
+    let Ok(public_key) = VerifyingKey::from_bytes(public_key) else {
+        return false;
+    };
+
+    let Ok(signature) = <[u8; 64]>::try_from(signature) else {
+        return false;
+    };
+
+    public_key.verify_strict(&login_message(challenge), &Signature::from_bytes(&signature)).is_ok()
+}
+
+// "login" || challenge
+// The prefix keeps the signature from being passed off as one over a move or a chat message.
+fn login_message(challenge: &Challenge) -> [u8; 5 + 32] {
+    let mut message = [0u8; 5 + 32];
+
+    message[0..5].copy_from_slice(b"login");
+    message[5..].copy_from_slice(challenge);
+
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn identities_are_kept_across_sessions() {
+        let path = env::temp_dir().join(format!("zk-tic-tac-toe-{}.key", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let identity = Identity::load_or_generate(&path).unwrap();
+        let loaded = Identity::load_or_generate(&path).unwrap();
+        assert_eq!(loaded.public_key(), identity.public_key());
+
+        let challenge = [7; 32];
+        let signature = loaded.sign_login(&challenge);
+
+        assert!(verify_login(&identity.public_key(), &challenge, &signature));
+        assert!(!verify_login(&identity.public_key(), &[8; 32], &signature));
+        assert!(!verify_login(&Identity::generate().public_key(), &challenge, &signature));
+
+        fs::write(&path, "not a key").unwrap();
+        assert_eq!(Identity::load_or_generate(&path).err().unwrap().kind(), io::ErrorKind::InvalidData);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
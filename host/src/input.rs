@@ -0,0 +1,178 @@
+use std::io::{self, Write};
+
+use game::Point;
+
+pub const HELP: &str = "\
+Moves can be entered as \"x y\" or \"x,y\", where \"0 0\" is the top leftmost cell, \
+or as a column letter and row number, where \"a1\" is the top leftmost cell and \"b2\" the middle one.
+\"resign\" gives the game to the opponent, \"quit\" leaves it unfinished and \"help\" shows this again.
+In a game on a server, \"takeback\" asks the opponent to take back your last move and their reply, \
+\"draw\" offers them a draw and \"say\" followed by a message sends it to the opponent and the spectators.";
+
+// What a player entered at the prompt, see `parse_command`.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Command {
+    Move(Point),
+    // Only accepted by `wait_for_input_or_challenge`.
+    Challenge,
+    // Only accepted by `wait_for_input_on_server`, as are `OfferDraw` and `Chat`.
+    TakeBack,
+    OfferDraw,
+    // A message to the opponent, with the case that it was entered in.
+    Chat(String),
+    Resign,
+    Quit
+}
+
+// Prompts until the player enters a valid move or command, showing the help
+// if asked for it. Fails if standard input cannot be read or ends, as the game
+// cannot go on without it.
+pub fn wait_for_input() -> io::Result<Command> {
+    wait_for_command(|_| false)
+}
+
+// Like `wait_for_input`, but also accepts "challenge".
+pub fn wait_for_input_or_challenge() -> io::Result<Command> {
+    wait_for_command(|command| matches!(command, Command::Challenge))
+}
+
+// Like `wait_for_input`, but also accepts "takeback", "draw" and "say".
+pub fn wait_for_input_on_server() -> io::Result<Command> {
+    wait_for_command(|command| matches!(command, Command::TakeBack | Command::OfferDraw | Command::Chat(_)))
+}
+
+// Asks the player a yes or no question until they answer it.
+pub fn confirm(question: &str) -> io::Result<bool> {
+    let stdin = io::stdin();
+    let mut line = String::with_capacity(4);
+
+    loop {
+        print!("{question} [y/n] ");
+        io::stdout().flush()?;
+
+        line.clear();
+        read_line(&stdin, &mut line)?;
+
+        match line.trim().to_ascii_lowercase().as_str() {
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => { }
+        }
+    }
+}
+
+// `extra` tells the commands accepted along with the moves, "resign" and "quit".
+fn wait_for_command(extra: fn(&Command) -> bool) -> io::Result<Command> {
+    let stdin = io::stdin();
+    let mut line = String::with_capacity(10);
+
+    loop {
+        line.clear();
+        read_line(&stdin, &mut line)?;
+
+        match parse_command(&line) {
+            Some(command @ (Command::Challenge | Command::TakeBack | Command::OfferDraw | Command::Chat(_))) if !extra(&command) => {
+                println!("That cannot be done now. Try again...")
+            },
+            Some(command) => return Ok(command),
+            None if line.trim().eq_ignore_ascii_case("help") => println!("{HELP}"),
+            None => println!("Bad input, enter \"help\" to see how to make a move. Try again...")
+        }
+    }
+}
+
+fn read_line(stdin: &io::Stdin, line: &mut String) -> io::Result<()> {
+    if stdin.read_line(line)? == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the input ended before the game did"));
+    }
+
+    Ok(())
+}
+
+// Parses a move, see `parse_point`, or one of the commands, ignoring case
+// and the whitespace around it.
+pub fn parse_command(line: &str) -> Option<Command> {
+    let line = line.trim();
+
+    if let Some((command, text)) = line.split_once(char::is_whitespace) {
+        if command.eq_ignore_ascii_case("say") {
+            return Some(Command::Chat(text.trim().to_string()));
+        }
+    }
+
+    match line.to_ascii_lowercase().as_str() {
+        "challenge" => Some(Command::Challenge),
+        "takeback" => Some(Command::TakeBack),
+        "draw" => Some(Command::OfferDraw),
+        "resign" => Some(Command::Resign),
+        "quit" => Some(Command::Quit),
+        line => parse_point(line).map(Command::Move)
+    }
+}
+
+// Parses a point in the form of "x y" or "x,y", with any whitespace around
+// the coordinates, or in the form of "b2", with the column as a letter and the
+// row numbered from 1. Coordinates can have more than one digit, so that the
+// game rejects the ones that are out of bounds rather than the parser.
+pub fn parse_point(line: &str) -> Option<Point> {
+    let line = line.trim();
+
+    let coordinates: Vec<&str> = line
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    match coordinates[..] {
+        [x, y] => Some(Point::new(parse_coordinate(x)?, parse_coordinate(y)?)),
+        [square] => parse_square(square),
+        _ => None
+    }
+}
+
+fn parse_coordinate(coordinate: &str) -> Option<usize> {
+    if coordinate.is_empty() || !coordinate.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    coordinate.parse().ok()
+}
+
+fn parse_square(square: &str) -> Option<Point> {
+    let column = square.chars().next().filter(char::is_ascii_alphabetic)?;
+    let row = parse_coordinate(&square[1..])?;
+
+    let x = (column.to_ascii_lowercase() as u8 - b'a') as usize;
+
+    Some(Point::new(x, row.checked_sub(1)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_are_parsed_in_every_form() {
+        for line in ["1 2", "1,2", " 1 ,  2 \n", "1\t2", "B3", "b3"] {
+            assert_eq!(parse_point(line), Some(Point::new(1, 2)), "{line:?}");
+        }
+
+        assert_eq!(parse_point("10 12"), Some(Point::new(10, 12)));
+        assert_eq!(parse_point("c10"), Some(Point::new(2, 9)));
+
+        for line in ["", "1", "1 2 3", "1,,", "-1 2", "+1 2", "x y", "a0", "1b", "é1"] {
+            assert_eq!(parse_point(line), None, "{line:?}");
+        }
+    }
+
+    #[test]
+    fn commands_ignore_case() {
+        assert_eq!(parse_command(" Resign\n"), Some(Command::Resign));
+        assert_eq!(parse_command("QUIT"), Some(Command::Quit));
+        assert_eq!(parse_command("challenge"), Some(Command::Challenge));
+        assert_eq!(parse_command("Draw"), Some(Command::OfferDraw));
+        assert_eq!(parse_command("a1"), Some(Command::Move(Point::new(0, 0))));
+        assert_eq!(parse_command("help"), None);
+        assert_eq!(parse_command("Say  Good Game \n"), Some(Command::Chat("Good Game".into())));
+        assert_eq!(parse_command("say"), None);
+    }
+}
@@ -0,0 +1,83 @@
+use std::{io, path::Path, thread, time::Duration};
+
+use risc0_zkvm::sha::Digest;
+use game::{State, VmResponse, render::{Style, Frame}};
+
+use crate::{
+    render::Renderer, server::Server, client::Client,
+    export::{ExportedReceipt, JournalFormat}
+};
+
+// How long each move is shown for when replaying a game.
+const REPLAY_DELAY: Duration = Duration::from_secs(1);
+
+// Plays back a game saved with "--save", verifying each of its receipts
+// against the hash chain before showing the move that it proves.
+pub fn replay(path: &Path) -> io::Result<()> {
+    let server = Server::load(path)?;
+
+    let renderer = Renderer::new(Style {
+        frame: Frame::Ascii,
+        glyphs: server.metadata.glyphs()
+    });
+
+    let mut client = Client::for_match(&server.metadata);
+    client.pin(server.pin());
+
+    println!("Replaying game {}.", hex::encode(server.metadata.game_id));
+    println!("{}", renderer.render(&server.metadata.initial_game(), None));
+
+    for receipt in &server.receipts {
+        thread::sleep(REPLAY_DELAY);
+
+        match client.verify_receipt(receipt) {
+            VmResponse::Accepted { game, player, point, .. } => {
+                let player = server.metadata.player(player);
+                println!("{} ({}) played {}.", player.name, player.symbol, point);
+
+                println!("{}", renderer.render(&game, Some(point)));
+            },
+            VmResponse::Rejected { player, point, error, .. } => {
+                println!("{} attempted {}: {}", server.metadata.player(player).name, point, error);
+            }
+        }
+    }
+
+    // The receipts must lead to the state that the game was saved in.
+    assert_eq!(client.state_hash, server.game.state_hash(), "Saved game state mismatch!");
+
+    match client.game_state {
+        State::Winner(player) => println!("{} won!", server.metadata.player(player).name),
+        State::Stalemate => println!("Stalemate!"),
+        State::InProgress => println!("The game was saved before it ended.")
+    }
+
+    Ok(())
+}
+
+// Verifies an exported receipt against the guest with `image_id`, checks that it
+// follows from the state with `prev_hash`, if given, and returns what it proves
+// in `format`, or why it could not be verified.
+pub fn verify(file: &Path, image_id: Option<Digest>, prev_hash: Option<&Digest>, format: JournalFormat) -> Result<String, String> {
+    let exported = ExportedReceipt::read(file)
+        .map_err(|error| format!("Failed to read the receipt: {error}"))?;
+
+    let image_id = image_id.unwrap_or_else(|| exported.kind.image_id());
+
+    let proven = exported.verify_against(image_id)
+        .map_err(|error| format!("Receipt verification failed: {error}"))?;
+
+    if let Some(prev_hash) = prev_hash {
+        match proven.prev_state_hash() {
+            Some(hash) if hash == prev_hash => { },
+            Some(hash) => return Err(format!(
+                "The move was made on the state {}, not {}.",
+                hex::encode(hash.as_bytes()),
+                hex::encode(prev_hash.as_bytes())
+            )),
+            None => return Err("Only move receipts are linked to a previous state.".into())
+        }
+    }
+
+    Ok(proven.format(format))
+}
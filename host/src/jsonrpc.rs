@@ -0,0 +1,247 @@
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use serde_json::{json, Value};
+use game::{Point, Journal, metadata::GameId};
+
+use crate::{games::{self, Games, GameError, EncodedReceipt, NewGame}, export};
+
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+// Errors of the game itself, such as a game that does not exist.
+const GAME_ERROR: i32 = -32000;
+
+#[derive(Deserialize)]
+struct Request {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    // Requests without an ID are notifications, which are not answered.
+    id: Option<Value>
+}
+
+#[derive(Serialize)]
+struct Response {
+    jsonrpc: &'static str,
+    #[serde(flatten)]
+    outcome: Outcome,
+    id: Value
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Result(Value),
+    Error(Error)
+}
+
+#[derive(Serialize, Debug)]
+struct Error {
+    code: i32,
+    message: String
+}
+
+#[derive(Deserialize)]
+struct MakeMove {
+    game_id: String,
+    x: usize,
+    y: usize
+}
+
+#[derive(Deserialize)]
+struct GetReceipt {
+    game_id: String,
+    move_index: u32
+}
+
+#[derive(Deserialize)]
+struct GetState {
+    game_id: String
+}
+
+#[cfg(feature = "sqlite")]
+#[derive(Deserialize)]
+struct GetLeaderboard {
+    #[serde(default = "default_limit")]
+    limit: usize
+}
+
+// Answers a JSON-RPC 2.0 request or batch of requests, returning
+// nothing if they were all notifications.
+pub fn handle(games: &Games, body: &str) -> Option<String> {
+    let response = match serde_json::from_str(body) {
+        Ok(Value::Array(requests)) if !requests.is_empty() => {
+            let responses: Vec<_> = requests.into_iter()
+                .filter_map(|request| handle_request(games, request))
+                .collect();
+
+            if responses.is_empty() {
+                return None;
+            }
+
+            serde_json::to_value(responses)
+        },
+        Ok(request) => serde_json::to_value(handle_request(games, request)?),
+        Err(error) => serde_json::to_value(response(
+            Value::Null,
+            Outcome::Error(Error::new(PARSE_ERROR, error))
+        ))
+    };
+
+    Some(response.expect("responses are always serializable").to_string())
+}
+
+fn handle_request(games: &Games, request: Value) -> Option<Response> {
+    let request: Request = match serde_json::from_value(request) {
+        Ok(request) => request,
+        Err(error) => return Some(response(Value::Null, Outcome::Error(Error::new(INVALID_REQUEST, error))))
+    };
+
+    let result = match request.jsonrpc.as_str() {
+        "2.0" => call(games, &request.method, request.params),
+        _ => Err(Error::new(INVALID_REQUEST, "jsonrpc must be \"2.0\""))
+    };
+
+    let outcome = match result {
+        Ok(result) => Outcome::Result(result),
+        Err(error) => Outcome::Error(error)
+    };
+
+    request.id.map(|id| response(id, outcome))
+}
+
+fn call(games: &Games, method: &str, params: Value) -> Result<Value, Error> {
+    match method {
+        "new_game" => {
+            let NewGame { player_a, player_b } = params_from(params)?;
+
+            let metadata = games::new_match(player_a, player_b);
+            let game_id = hex::encode(metadata.game_id);
+            let receipt = games.create(metadata)?;
+
+            Ok(json!({ "game_id": game_id, "receipt": EncodedReceipt::from(&receipt) }))
+        },
+        "make_move" => {
+            let MakeMove { game_id, x, y } = params_from(params)?;
+            let receipt = games.play(parse_game_id(&game_id)?, Point::new(x, y))?;
+
+            Ok(json!(EncodedReceipt::from(&receipt)))
+        },
+        "get_receipt" => {
+            let GetReceipt { game_id, move_index } = params_from(params)?;
+            let receipt = games.receipt(parse_game_id(&game_id)?, move_index)?;
+
+            Ok(json!(EncodedReceipt::from(&receipt)))
+        },
+        // The journal of a move receipt decoded, see `export::journal_json`.
+        "get_journal" => {
+            let GetReceipt { game_id, move_index } = params_from(params)?;
+            let receipt = games.receipt(parse_game_id(&game_id)?, move_index)?;
+
+            let journal = Journal::decode(&receipt.receipt.journal).map_err(|error| Error::new(GAME_ERROR, error))?;
+
+            Ok(export::journal_json(&journal))
+        },
+        "get_state" => {
+            let GetState { game_id } = params_from(params)?;
+            let summary = games.summary(parse_game_id(&game_id)?)?;
+
+            Ok(json!({
+                "state": summary.state,
+                "next_player": summary.next_player,
+                "state_hash": hex::encode(summary.state_hash.as_bytes()),
+                "moves": summary.moves
+            }))
+        },
+        #[cfg(feature = "sqlite")]
+        "get_leaderboard" => {
+            let GetLeaderboard { limit } = params_from(params)?;
+
+            Ok(json!(games.leaderboard(limit)?))
+        },
+        _ => Err(Error::new(METHOD_NOT_FOUND, format!("no such method: {method}")))
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn default_limit() -> usize {
+    10
+}
+
+fn response(id: Value, outcome: Outcome) -> Response {
+    Response { jsonrpc: "2.0", outcome, id }
+}
+
+fn params_from<T: DeserializeOwned>(params: Value) -> Result<T, Error> {
+    serde_json::from_value(params).map_err(|error| Error::new(INVALID_PARAMS, error))
+}
+
+fn parse_game_id(game_id: &str) -> Result<GameId, Error> {
+    hex::decode(game_id).ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| Error::new(INVALID_PARAMS, "game_id must be 32 hex encoded bytes"))
+}
+
+impl Error {
+    fn new(code: i32, message: impl ToString) -> Self {
+        Self { code, message: message.to_string() }
+    }
+}
+
+impl From<GameError> for Error {
+    fn from(error: GameError) -> Self {
+        Self::new(GAME_ERROR, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_code(response: &str) -> i64 {
+        let response: Value = serde_json::from_str(response).unwrap();
+
+        response["error"]["code"].as_i64().unwrap()
+    }
+
+    #[test]
+    fn malformed_requests_are_answered_with_errors() {
+        let games = Games::spawn();
+
+        let response = handle(&games, "{").unwrap();
+        assert_eq!(error_code(&response), PARSE_ERROR as i64);
+
+        let response = handle(&games, r#"{"jsonrpc":"2.0","method":"resign","id":1}"#).unwrap();
+        assert_eq!(error_code(&response), METHOD_NOT_FOUND as i64);
+
+        let response = handle(&games, r#"{"jsonrpc":"2.0","method":"get_state","params":{},"id":1}"#).unwrap();
+        assert_eq!(error_code(&response), INVALID_PARAMS as i64);
+    }
+
+    #[test]
+    fn unknown_games_are_game_errors() {
+        let games = Games::spawn();
+        let game_id = hex::encode([1; 32]);
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "method": "get_state",
+            "params": { "game_id": game_id },
+            "id": "state"
+        });
+
+        let response: Value = serde_json::from_str(&handle(&games, &request.to_string()).unwrap()).unwrap();
+
+        assert_eq!(response["id"], "state");
+        assert_eq!(response["error"]["code"], GAME_ERROR);
+    }
+
+    #[test]
+    fn notifications_are_not_answered() {
+        let games = Games::spawn();
+        let request = r#"[{"jsonrpc":"2.0","method":"get_state","params":{"game_id":""}}]"#;
+
+        assert_eq!(handle(&games, request), None);
+    }
+}
@@ -0,0 +1,63 @@
+pub mod render;
+pub use verifier::{client, export, evm, solana, near};
+pub mod input;
+pub mod error;
+pub mod protocol;
+pub mod websocket;
+pub mod lobby;
+pub mod remote;
+pub mod identity;
+pub mod config;
+pub mod settlement;
+pub mod logging;
+pub mod args;
+// Everything that proves or executes the guests, which the default `prover`
+// feature builds. Without it, the crate only plays on servers and verifies.
+#[cfg(feature = "prover")]
+pub mod server;
+#[cfg(feature = "prover")]
+pub mod prover;
+#[cfg(feature = "prover")]
+pub mod hosting;
+#[cfg(feature = "prover")]
+pub mod games;
+#[cfg(feature = "prover")]
+pub mod jsonrpc;
+#[cfg(feature = "prover")]
+pub mod peer;
+#[cfg(feature = "prover")]
+pub mod hall;
+#[cfg(feature = "prover")]
+pub mod recovery;
+#[cfg(feature = "prover")]
+pub mod tui;
+#[cfg(feature = "prover")]
+pub mod stats;
+#[cfg(feature = "prover")]
+pub mod bot;
+#[cfg(feature = "prover")]
+pub mod selfplay;
+#[cfg(feature = "prover")]
+pub mod tournament;
+#[cfg(feature = "prover")]
+pub mod corpus;
+#[cfg(feature = "prover")]
+pub mod consistency;
+#[cfg(feature = "prover")]
+pub mod local;
+#[cfg(feature = "prover")]
+pub mod bench;
+#[cfg(feature = "prover")]
+pub mod inspect;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "rest")]
+pub mod rest;
+#[cfg(feature = "p2p")]
+pub mod p2p;
+#[cfg(feature = "sqlite")]
+pub mod store;
+#[cfg(feature = "gui")]
+pub mod gui;
+#[cfg(feature = "bonsai")]
+pub mod bonsai;
@@ -0,0 +1,195 @@
+use std::{collections::HashMap, fmt};
+
+use game::metadata::{MatchMetadata, PlayerInfo};
+
+use crate::client::GuestPin;
+
+pub use wire::{Terms, OpenGame};
+
+pub type PlayerId = u32;
+
+// Where players wait to be paired. A player opens a game with the terms they
+// want to play on, which another player can then join.
+#[derive(Default)]
+pub struct Lobby {
+    players: HashMap<PlayerId, PlayerInfo>,
+    games: Vec<(PlayerId, OpenGame)>,
+    next_id: u32
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LobbyError {
+    UnknownPlayer,
+    NotFound,
+    OwnGame,
+    // The terms name a guest other than the one of this build, which the server proves moves with.
+    OtherGuest
+}
+
+impl Lobby {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, info: PlayerInfo) -> PlayerId {
+        let player = self.next_id();
+        self.players.insert(player, info);
+
+        player
+    }
+
+    // Removes the player along with the games they opened, returning the IDs of those.
+    pub fn unregister(&mut self, player: PlayerId) -> Vec<u32> {
+        self.players.remove(&player);
+
+        let mut closed = vec![];
+
+        self.games.retain(|(host, game)| {
+            if *host == player {
+                closed.push(game.id);
+            }
+
+            *host != player
+        });
+
+        closed
+    }
+
+    pub fn open(&mut self, player: PlayerId, terms: Terms) -> Result<u32, LobbyError> {
+        let host = self.players.get(&player).ok_or(LobbyError::UnknownPlayer)?.clone();
+        check_guest(&terms)?;
+        let id = self.next_id();
+
+        self.games.push((player, OpenGame { id, host, terms }));
+
+        Ok(id)
+    }
+
+    // The games that can be joined, oldest first.
+    pub fn open_games(&self) -> impl Iterator<Item = &OpenGame> {
+        self.games.iter().map(|(_, game)| game)
+    }
+
+    // Pairs the player with the one that opened the game, on its terms.
+    // Both players then leave the lobby.
+    pub fn join(&mut self, player: PlayerId, game_id: u32) -> Result<MatchMetadata, LobbyError> {
+        let mut info = self.players.get(&player).ok_or(LobbyError::UnknownPlayer)?.clone();
+
+        let (host, game) = self.games.iter()
+            .find(|(_, game)| game.id == game_id)
+            .cloned()
+            .ok_or(LobbyError::NotFound)?;
+
+        if host == player {
+            return Err(LobbyError::OwnGame);
+        }
+
+        check_guest(&game.terms)?;
+
+        // Both players would look the same on the board otherwise.
+        if info.symbol == game.host.symbol {
+            let defaults = MatchMetadata::default();
+
+            info.symbol = if game.host.symbol == defaults.player_b.symbol {
+                defaults.player_a.symbol
+            } else {
+                defaults.player_b.symbol
+            };
+        }
+
+        self.unregister(host);
+        self.unregister(player);
+
+        Ok(MatchMetadata::new(rand::random(), game.host, info)
+            .with_ruleset(game.terms.ruleset)
+            .with_image_id(game.terms.image_id)
+            .with_first_player(game.terms.first_player)
+            .with_nonce(rand::random()))
+    }
+
+    fn next_id(&mut self) -> u32 {
+        self.next_id += 1;
+
+        self.next_id
+    }
+}
+
+// Moves are proven by the guest of this build, so the image ID that the
+// players agree on has to be the one of its `make_move`.
+fn check_guest(terms: &Terms) -> Result<(), LobbyError> {
+    if terms.image_id != GuestPin::local().make_move_id {
+        return Err(LobbyError::OtherGuest);
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for LobbyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownPlayer => write!(f, "Player is not registered in the lobby."),
+            Self::NotFound => write!(f, "Game is not open anymore."),
+            Self::OwnGame => write!(f, "Players can't join their own games."),
+            Self::OtherGuest => write!(f, "Games on this server are proven by the guest of its build.")
+        }
+    }
+}
+
+impl std::error::Error for LobbyError { }
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm::sha::Digest;
+    use game::Player;
+
+    use super::*;
+
+    #[test]
+    fn joined_games_follow_the_agreed_terms() {
+        let mut lobby = Lobby::new();
+
+        let host = lobby.register(PlayerInfo::new("Alice", "X"));
+        let guest = lobby.register(PlayerInfo::new("Bob", "X"));
+
+        let terms = Terms { first_player: Player::B, ..Terms::local() };
+        let game_id = lobby.open(host, terms).unwrap();
+
+        assert_eq!(lobby.join(host, game_id), Err(LobbyError::OwnGame));
+
+        let metadata = lobby.join(guest, game_id).unwrap();
+
+        assert_eq!(metadata.player_a.name, "Alice");
+        assert_eq!(metadata.player_b.name, "Bob");
+        assert_eq!(metadata.player_b.symbol, "O");
+        assert_eq!(metadata.first_player, Player::B);
+        assert_eq!(metadata.image_id, Some(terms.image_id));
+
+        assert_eq!(lobby.open_games().count(), 0);
+        assert_eq!(lobby.join(guest, game_id), Err(LobbyError::UnknownPlayer));
+    }
+
+    #[test]
+    fn games_are_closed_when_their_host_leaves() {
+        let mut lobby = Lobby::new();
+
+        let host = lobby.register(PlayerInfo::new("Alice", "X"));
+        let guest = lobby.register(PlayerInfo::new("Bob", "O"));
+
+        let game_id = lobby.open(host, Terms::local()).unwrap();
+        assert_eq!(lobby.open_games().map(|game| game.id).collect::<Vec<_>>(), vec![game_id]);
+
+        assert_eq!(lobby.unregister(host), vec![game_id]);
+        assert_eq!(lobby.join(guest, game_id), Err(LobbyError::NotFound));
+    }
+
+    #[test]
+    fn games_proven_by_another_guest_are_refused() {
+        let mut lobby = Lobby::new();
+        let host = lobby.register(PlayerInfo::new("Alice", "X"));
+
+        let terms = Terms { image_id: Digest::from([7; 8]), ..Terms::local() };
+
+        assert_eq!(lobby.open(host, terms), Err(LobbyError::OtherGuest));
+        assert_eq!(lobby.open_games().count(), 0);
+    }
+}
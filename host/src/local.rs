@@ -0,0 +1,805 @@
+use std::{io::{self, Write}, path::{Path, PathBuf}, time::Instant};
+
+use clap::{Args, ValueEnum};
+use crossterm::tty::IsTty;
+use ed25519_dalek::{SigningKey, Signer};
+use rand::rngs::OsRng;
+use risc0_zkvm::SessionReceipt;
+use game::{
+    State, Player, Point, VmResponse,
+    render::{Style, Frame}, events::GameObserver,
+    metadata::{MatchMetadata, Ruleset}, commitment::Reveal,
+    simultaneous::{RoundResponse, RoundOutcome},
+    auth::{self, SignedMove}, engine, coin_flip,
+    disclosure::Region,
+    fraud::{MoveClaim, FraudJournal}
+};
+
+use crate::{
+    render::Renderer, server::Server, client::Client, input::{self, Command as Input}, tui,
+    export::{ExportedReceipt, ReceiptKind}, config::Config, stats::GameStats,
+    bot::{Bot, Strategy}, error::PlayError, args::player_info
+};
+
+// The options of `host play`, which plays a game between two players taking turns on this machine.
+#[derive(Args)]
+pub struct PlayArgs {
+    #[arg(num_args = 0..=2, help = "The names and symbols of the players, as \"name:symbol\"")]
+    players: Vec<String>,
+    #[arg(long, value_enum, help = "How moves are made and proven [default: standard]")]
+    variant: Option<Variant>,
+    #[arg(long, default_value_t = 2, help = "The number of moves proven together in the batch variant")]
+    batch_size: usize,
+    #[arg(
+        long,
+        value_parser = parse_hint,
+        help = "Discloses \"row:N\", \"column:N\" or \"cell:X,Y\" of the board to the current player \
+            before each turn in the private variant"
+    )]
+    hint: Option<Region>,
+    #[arg(long, help = "Proves to the opponent whenever a player has a winning move, without revealing it")]
+    threats: bool,
+    #[arg(long, help = "Proves that the game finished in at most this many moves, without revealing them")]
+    max_moves: Option<u32>,
+    #[arg(long, help = "Picks the player that moves first with a coin flip that neither player can bias")]
+    coin_flip: bool,
+    #[arg(long, help = "Writes the receipts of the initial state, of each move and of the result to this directory")]
+    export: Option<PathBuf>,
+    #[arg(long, help = "Writes the game along with its receipts to this file once it has ended")]
+    save: Option<PathBuf>,
+    #[arg(long, help = "Prompts for the moves line by line instead of showing the board full screen")]
+    plain: bool,
+    #[arg(long, help = "Prints what it took to prove and verify each move once the game has ended")]
+    stats: bool,
+    #[arg(long, help = "Writes what it took to prove and verify each move to this file as JSON")]
+    stats_json: Option<PathBuf>,
+    #[arg(long, value_enum, help = "Plays against the computer, which picks its moves at random or never loses")]
+    against: Option<Strategy>,
+    #[arg(long, requires = "against", help = "Lets the computer move first")]
+    computer_first: bool
+}
+
+// The variants of the game, which determine how moves are proven, see `Mode`.
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum)]
+pub enum Variant {
+    Standard,
+    // Each move is committed to before it is revealed.
+    Blind,
+    // Both players commit to a move at the same time, with both moves revealed together.
+    Simultaneous,
+    // The players sign their moves, with the signatures verified inside the VM.
+    Signed,
+    // Receipts reveal neither the board nor the moves.
+    Private,
+    // Moves are proven together in batches, see `PlayArgs::batch_size`.
+    Batch,
+    // Only moves proven to be optimal are accepted.
+    Optimal,
+    // Moves are exchanged without proofs, with a player entering "challenge"
+    // instead of a move to have the opponent's last move proven.
+    Optimistic
+}
+
+// How moves are submitted to the server, selected with a command line flag.
+enum Mode {
+    Standard,
+    // Each move is committed to before it is revealed.
+    Blind,
+    // Both players commit to a move at the same time.
+    Simultaneous,
+    // Each move is signed by the player making it, holding
+    // the signing keys of player A and player B, in that order.
+    Signed(Box<[SigningKey; 2]>),
+    // Receipts only reveal salted state hashes, not the board or the moves.
+    // The region, if any, is disclosed to the current player before each turn.
+    Private([u8; 32], Option<Region>),
+    // Moves are proven together in batches of the given size.
+    Batch(usize),
+    // Every move must be optimal, as a provably perfect bot would play.
+    Optimal,
+    // Moves are accepted without proofs, which are only
+    // produced for a move that is challenged by a player.
+    Optimistic
+}
+
+// Announces the result of the game once it has ended.
+struct Announcer(MatchMetadata);
+
+pub fn play(args: PlayArgs, config: &Config) -> Result<(), PlayError> {
+    println!("
+Tic-Tac-Toe using the Risc0 VM.\n
+On each turn the current player has to input the coordinates \
+of the cell they want to fill in the form of \"x y\" where \"0 0\" \
+points to the top leftmost cell. For example: if the player wants \
+to fill the cell in the middle, they must provide the following input: \"1 1\". \
+\"1,1\" and \"b2\" work too, and \"help\" lists the other forms along with the \
+\"resign\" and \"quit\" commands.
+
+Players can be given a name and a symbol by passing them as \"name:symbol\" \
+arguments, e.g. \"host play Alice:A Bob:B\". The variants of the game and the \
+other options are listed by \"host play --help\".
+
+When the standard variant is played in a terminal, the board is shown full \
+screen instead, with the cell chosen with the arrow keys and Enter, unless \
+\"--plain\" is given. With \"--against random\" or \"--against minimax\", the \
+second player is the computer.
+    ");
+
+    let mode = mode(&args, config);
+
+    if args.against.is_some() && !matches!(mode, Mode::Standard | Mode::Blind | Mode::Signed(_) | Mode::Optimal) {
+        println!("Only the standard, blind, signed and optimal variants can be played against the computer.");
+
+        return Ok(());
+    }
+
+    let threats = args.threats;
+
+    let variant = variant(&args, config).to_possible_value().expect("no variant is skipped");
+    let mut stats = GameStats::new(variant.get_name(), config.prover.backend);
+    let mut metadata = metadata_from_args(&args.players);
+    let bot = args.against.map(|strategy| Bot { player: Player::B, strategy });
+
+    if bot.is_some() {
+        if args.players.len() < 2 {
+            metadata.player_b.name = "Computer".to_string();
+        }
+
+        if args.computer_first {
+            metadata.first_player = Player::B;
+        }
+    }
+
+    if let Mode::Signed(keys) = &mode {
+        metadata.player_a.public_key = Some(keys[0].verifying_key().to_bytes());
+        metadata.player_b.public_key = Some(keys[1].verifying_key().to_bytes());
+    }
+    
+    if let Mode::Simultaneous = mode {
+        metadata.ruleset = Ruleset::Simultaneous;
+    }
+
+    if args.coin_flip {
+        metadata.first_player = flip_coin(&metadata.game_id)?;
+
+        println!("{} moves first.", metadata.player(metadata.first_player).name);
+    }
+
+    // The full screen interface only plays the standard variant and shows the result itself.
+    let full_screen = matches!(mode, Mode::Standard) && !args.plain && !threats && io::stdout().is_tty();
+
+    let mut server = Server::new(metadata);
+
+    if !full_screen {
+        server.subscribe(Announcer(server.metadata.clone()));
+    }
+
+    let renderer = Renderer::new(Style {
+        frame: Frame::Ascii,
+        glyphs: server.metadata.glyphs()
+    });
+
+    let (mut player_a, mut player_b) = match &mode {
+        Mode::Private(salt, _) => {
+            let initial_hash = server.game.salted_hash(salt);
+
+            (Client::with_initial_hash(initial_hash), Client::with_initial_hash(initial_hash))
+        },
+        _ => {
+            let receipt = server.prove_init()?;
+            let match_hash = server.metadata.hash();
+
+            export(args.export.as_deref(), "init.receipt", ReceiptKind::Init, &receipt)?;
+
+            (
+                Client::from_init_receipt(&receipt, &match_hash),
+                Client::from_init_receipt(&receipt, &match_hash)
+            )
+        }
+    };
+
+    player_a.pin(server.pin());
+    player_b.pin(server.pin());
+
+    // Both clients track the plain state hash in these modes,
+    // so they can verify draw claims and threats against it.
+    let plain_hashes = matches!(
+        mode,
+        Mode::Standard | Mode::Blind | Mode::Signed(_) | Mode::Optimal | Mode::Optimistic
+    );
+
+    if full_screen {
+        let finished = tui::play(&mut server, &mut player_a, &mut player_b, args.export.as_deref(), &mut stats, bot)
+            .expect("the terminal interface failed");
+
+        if !finished {
+            println!("The game was abandoned.");
+
+            return Ok(());
+        }
+    }
+
+    while let State::InProgress = server.game.state() {
+        if plain_hashes && server.game.is_draw() {
+            println!("Neither player can win anymore. Proving the draw...");
+
+            let receipt = server.prove_draw()?;
+            player_a.verify_draw_receipt(&receipt);
+            player_b.verify_draw_receipt(&receipt);
+
+            println!("Stalemate!");
+
+            break;
+        }
+
+        println!("{}", renderer.render(&server.pending_game(), server.last_move));
+
+        if threats && plain_hashes && server.game.winning_move().is_some() {
+            let receipt = server.prove_threat()?;
+            player_a.verify_threat_receipt(&receipt);
+
+            let journal = player_b.verify_threat_receipt(&receipt);
+            println!("{} has a winning move!", server.metadata.player(journal.player).name);
+        }
+
+        let turn = match &mode {
+            Mode::Simultaneous => play_round(&mut server, &mut player_a, &mut player_b, &mut stats),
+            Mode::Private(salt, hint) => {
+                play_private_turn(&mut server, &mut player_a, &mut player_b, salt, *hint, &mut stats)
+            },
+            Mode::Batch(size) => {
+                play_batched_turn(&mut server, &mut player_a, &mut player_b, *size, &mut stats)
+            },
+            Mode::Optimistic => play_optimistic_turn(&mut server, &mut player_a, &mut player_b),
+            _ => play_turn(&mut server, &mut player_a, &mut player_b, &mode, args.export.as_deref(), &mut stats, bot)
+        };
+
+        // A resignation is not proven, as the guest only knows of moves,
+        // so the game is left unfinished like one that the players quit.
+        match turn {
+            Err(PlayError::Resigned(player)) => {
+                let winner = match player {
+                    Player::A => Player::B,
+                    Player::B => Player::A
+                };
+
+                println!(
+                    "{} resigned, so {} wins.",
+                    server.metadata.player(player).name,
+                    server.metadata.player(winner).name
+                );
+
+                break;
+            },
+            Err(PlayError::Quit) => {
+                println!("The game was abandoned.");
+
+                break;
+            },
+            turn => turn?
+        }
+    }
+
+    println!("{}", renderer.render(&server.game, server.last_move));
+
+    let finished = server.game.state() != State::InProgress;
+
+    if finished && !matches!(mode, Mode::Simultaneous | Mode::Private(..)) {
+        println!("Proving the result of the whole game...");
+
+        let match_hash = server.metadata.hash();
+
+        let receipt = server.finalize()?;
+        player_a.verify_final_receipt(&receipt, &match_hash);
+
+        export(args.export.as_deref(), "result.receipt", ReceiptKind::Result, &receipt)?;
+
+        let journal = player_b.verify_final_receipt(&receipt, &match_hash);
+        let game_id = hex::encode(journal.game_id);
+
+        match journal.state {
+            State::Winner(player) => println!(
+                "Verified that {} won game {} in {} moves.",
+                server.metadata.player(player).name,
+                game_id,
+                journal.move_count
+            ),
+            _ => println!("Verified that game {} ended in a stalemate.", game_id)
+        }
+
+        match args.max_moves {
+            Some(max_moves) if server.moves.len() as u32 <= max_moves => {
+                let receipt = server.prove_length(max_moves)?;
+                player_a.verify_length_receipt(&receipt, &match_hash);
+                player_b.verify_length_receipt(&receipt, &match_hash);
+
+                println!("Verified that the game finished in at most {} moves.", max_moves);
+            },
+            Some(max_moves) => println!("The game took more than {} moves.", max_moves),
+            None => { }
+        }
+    }
+
+    if args.stats {
+        println!("{stats}");
+    }
+
+    if let Some(path) = &args.stats_json {
+        stats.write_json(path).map_err(PlayError::Output)?;
+    }
+
+    if let Some(path) = args.save.map(|path| config.save_path(&path)) {
+        server.save(&path).map_err(PlayError::Output)?;
+
+        println!("Saved the game to {}.", path.display());
+    }
+
+    player_a.on_game_ended();
+    player_b.on_game_ended();
+
+    Ok(())
+}
+
+impl GameObserver for Announcer {
+    fn on_state_change(&mut self, _previous: State, current: State) {
+        match current {
+            State::Stalemate => println!("Stalemate!"),
+            State::Winner(player) => println!("{} wins!", self.0.player(player).name),
+            State::InProgress => unreachable!()
+        }
+    }
+}
+
+fn play_turn(
+    server: &mut Server,
+    player_a: &mut Client,
+    player_b: &mut Client,
+    mode: &Mode,
+    export_to: Option<&Path>,
+    stats: &mut GameStats,
+    bot: Option<Bot>
+) -> Result<(), PlayError> {
+    let current = server.game.current_player();
+    let player = server.metadata.player(current);
+    print!("{} ({}) turn: ", player.name, player.symbol);
+
+    io::stdout().flush().map_err(PlayError::Output)?;
+
+    let point = match bot.and_then(|bot| bot.next_move(&server.game)) {
+        Some(point) => {
+            println!("{} {}", point.x(), point.y());
+
+            point
+        },
+        None => read_move(current)?
+    };
+    let match_hash = server.metadata.hash();
+
+    let result = match mode {
+        // In blind mode the clients first receive a commitment
+        // to the move which is only later revealed by the receipt.
+        Mode::Blind => {
+            let reveal = Reveal::new(point, rand::random());
+            let commitment = reveal.commitment(&server.metadata.game_id, current, server.move_index);
+
+            println!("Committed to move {}.", hex::encode(commitment.as_bytes()));
+
+            server.execute_blind_move(commitment, reveal)
+                .map(|(receipt, proof)| (receipt, proof, Some(commitment)))
+        },
+        Mode::Signed(keys) => {
+            let key = match current {
+                Player::A => &keys[0],
+                Player::B => &keys[1]
+            };
+
+            let message = auth::move_message(&server.metadata.game_id, server.move_index, point);
+            let signed = SignedMove::new(point, key.sign(&message).to_bytes().to_vec());
+
+            server.execute_signed_move(signed).map(|(receipt, proof)| (receipt, proof, None))
+        },
+        Mode::Optimal => {
+            if !engine::is_optimal(&server.game, point) {
+                println!("{point} is not an optimal move.\nTry again!");
+
+                return Ok(());
+            }
+
+            server.execute_optimal_move(point).map(|(receipt, proof)| (receipt, proof, None))
+        },
+        Mode::Standard | Mode::Simultaneous | Mode::Private(..) | Mode::Batch(_) | Mode::Optimistic => {
+            server.execute_move(point).map(|(receipt, proof)| {
+                println!("{proof}");
+
+                (receipt, proof, None)
+            })
+        }
+    };
+
+    let (receipt, proof, commitment) = match result {
+        Ok(result) => result,
+        Err(error) => { 
+            println!("{error}\nTry again!");
+
+            return Ok(());
+        }
+    };
+
+    let verify = |client: &mut Client| match (mode, &commitment) {
+        (Mode::Signed(_), _) => client.verify_signed_receipt(&receipt, &match_hash),
+        (Mode::Optimal, _) => client.verify_optimal_receipt(&receipt),
+        (_, Some(commitment)) => client.verify_blind_receipt(&receipt, commitment),
+        (_, None) => client.verify_receipt(&receipt)
+    };
+
+    let start = Instant::now();
+    verify(player_a);
+    stats.record(server.move_index, proof, start.elapsed());
+
+    // Kept to be saved along with the game, see `inspect::replay`.
+    if let Mode::Standard = mode {
+        server.receipts.push(receipt.clone());
+    }
+
+    match verify(player_b) {
+        VmResponse::Accepted { game, player, point, .. } => {
+            let player = server.metadata.player(player);
+            println!("{} ({}) played {}.", player.name, player.symbol, point);
+
+            // Only receipts of the standard guest can be verified on their own.
+            if let Mode::Standard = mode {
+                let name = format!("move-{}.receipt", server.move_index);
+                export(export_to, &name, ReceiptKind::Move, &receipt)?;
+            }
+
+            server.apply_move(point, game);
+        },
+        VmResponse::Rejected { error, .. } => println!("{error}\nTry again!")
+    }
+
+    Ok(())
+}
+
+// Writes the receipt to a file in the directory given with "--export", if any.
+fn export(dir: Option<&Path>, name: &str, kind: ReceiptKind, receipt: &SessionReceipt) -> Result<(), PlayError> {
+    let Some(dir) = dir else {
+        return Ok(());
+    };
+
+    let path = dir.join(name);
+
+    ExportedReceipt::new(kind, receipt.clone()).write(&path).map_err(PlayError::Output)?;
+    println!("Exported the receipt to {}.", path.display());
+
+    Ok(())
+}
+
+fn play_optimistic_turn(server: &mut Server, player_a: &mut Client, player_b: &mut Client) -> Result<(), PlayError> {
+    let current = server.game.current_player();
+    let player = server.metadata.player(current);
+    print!("{} ({}) turn (or \"challenge\"): ", player.name, player.symbol);
+
+    io::stdout().flush().map_err(PlayError::Output)?;
+
+    let point = match input::wait_for_input_or_challenge().map_err(PlayError::Input)? {
+        Input::Move(point) => point,
+        Input::Challenge => {
+            return match current {
+                Player::A => challenge(server, player_a, player_b),
+                Player::B => challenge(server, player_b, player_a)
+            };
+        },
+        Input::Resign => return Err(PlayError::Resigned(current)),
+        Input::Quit => return Err(PlayError::Quit),
+        Input::TakeBack | Input::OfferDraw | Input::Chat(_) => {
+            unreachable!("`wait_for_input_or_challenge` does not accept take-backs, draw offers or chat")
+        }
+    };
+
+    let claim = match MoveClaim::new(&server.game, server.move_index, point) {
+        Ok(claim) => claim,
+        Err(error) => {
+            println!("{error}\nTry again!");
+
+            return Ok(());
+        }
+    };
+
+    player_a.accept_claim(claim);
+    player_b.accept_claim(claim);
+
+    let mut game = server.game;
+    game.make_move(point).expect("claimed moves are legal");
+
+    server.apply_move(point, game);
+
+    Ok(())
+}
+
+// Disputes the last move claimed by the opponent of `challenger`. If the claim
+// is invalid the challenger proves it, otherwise the server proves the move.
+fn challenge(server: &Server, challenger: &Client, opponent: &Client) -> Result<(), PlayError> {
+    let Some(claim) = challenger.claims.last().copied() else {
+        println!("There is no move to challenge.");
+
+        return Ok(());
+    };
+
+    let game = challenger.replay_claims(server.metadata.initial_game(), claim.move_index)
+        .expect("earlier claims were not challenged");
+
+    if FraudJournal::new(&game, claim).is_some() {
+        let receipt = Server::prove_fraud(&game, claim)?;
+        let fraud = opponent.verify_fraud_receipt(&receipt, &claim);
+
+        panic!("Fraud proven: {fraud}");
+    }
+
+    let receipt = server.prove_claim(claim.move_index)?;
+    challenger.verify_claim_receipt(&receipt, &claim);
+    opponent.verify_claim_receipt(&receipt, &claim);
+
+    println!("Proved that {} was played legally.", claim.point);
+
+    Ok(())
+}
+
+fn play_private_turn(
+    server: &mut Server,
+    player_a: &mut Client,
+    player_b: &mut Client,
+    salt: &[u8; 32],
+    hint: Option<Region>,
+    stats: &mut GameStats
+) -> Result<(), PlayError> {
+    let current = server.game.current_player();
+    let player = server.metadata.player(current);
+
+    if let Some(region) = hint {
+        let receipt = server.disclose(region, salt)?;
+
+        let client = match current {
+            Player::A => &*player_a,
+            Player::B => &*player_b
+        };
+
+        let cells: Vec<String> = client.verify_disclosure(&receipt)
+            .cells
+            .into_iter()
+            .map(|(point, cell)| match cell {
+                Some(owner) => format!("{point}: {}", server.metadata.player(owner).symbol),
+                None => format!("{point}: empty")
+            })
+            .collect();
+
+        println!("Hint for {}, {}: {}", player.name, region, cells.join(", "));
+    }
+
+    print!("{} ({}) turn: ", player.name, player.symbol);
+
+    io::stdout().flush().map_err(PlayError::Output)?;
+
+    let point = read_move(current)?;
+
+    let (receipt, proof) = match server.execute_private_move(point, salt) {
+        Ok(result) => result,
+        Err(error) => {
+            println!("{error}\nTry again!");
+
+            return Ok(());
+        }
+    };
+
+    let start = Instant::now();
+    player_a.verify_private_receipt(&receipt);
+    stats.record(server.move_index, proof, start.elapsed());
+
+    let journal = player_b.verify_private_receipt(&receipt);
+
+    // Since the journal doesn't contain the board, the server
+    // plays the now verified move on its own copy of the game.
+    let mut game = server.game;
+
+    match game.make_move(point) {
+        Ok(()) => {
+            assert!(journal.accepted, "Receipt rejected a legal move!");
+
+            server.apply_move(point, game);
+        },
+        Err(error) => println!("{error}\nTry again!")
+    }
+
+    Ok(())
+}
+
+// Queues the move and only proves the pending moves once
+// there are `size` of them or the pending game has ended.
+fn play_batched_turn(
+    server: &mut Server,
+    player_a: &mut Client,
+    player_b: &mut Client,
+    size: usize,
+    stats: &mut GameStats
+) -> Result<(), PlayError> {
+    let current = server.pending_game().current_player();
+    let player = server.metadata.player(current);
+    print!("{} ({}) turn: ", player.name, player.symbol);
+
+    io::stdout().flush().map_err(PlayError::Output)?;
+
+    if let Err(error) = server.queue_move(read_move(current)?) {
+        println!("{error}\nTry again!");
+
+        return Ok(());
+    }
+
+    if server.pending.len() < size && server.pending_game().state() == State::InProgress {
+        return Ok(());
+    }
+
+    println!("Proving {} moves...", server.pending.len());
+
+    let (receipt, proof) = match server.execute_batch() {
+        Ok(result) => result,
+        Err(error) => {
+            println!("{error}\nTry again!");
+
+            return Ok(());
+        }
+    };
+
+    // Recorded under the index of the first move of the batch.
+    let start = Instant::now();
+    player_a.verify_batch_receipt(&receipt);
+    stats.record(server.move_index, proof, start.elapsed());
+
+    let journal = player_b.verify_batch_receipt(&receipt);
+
+    server.apply_batch(&journal.points);
+
+    Ok(())
+}
+
+// Plays a round of the simultaneous variant in which both players
+// commit to their moves before either of them is revealed.
+fn play_round(
+    server: &mut Server,
+    player_a: &mut Client,
+    player_b: &mut Client,
+    stats: &mut GameStats
+) -> Result<(), PlayError> {
+    let mut reveals = Vec::with_capacity(2);
+
+    let game_id = server.metadata.game_id;
+    let mut commitments = Vec::with_capacity(2);
+
+    for (current, move_index) in [Player::A, Player::B].into_iter().zip(server.move_index..) {
+        let player = server.metadata.player(current);
+        print!("{} ({}) move: ", player.name, player.symbol);
+
+        io::stdout().flush().map_err(PlayError::Output)?;
+
+        let reveal = Reveal::new(read_move(current)?, rand::random());
+        let commitment = reveal.commitment(&game_id, current, move_index);
+        println!("Committed to move {}.", hex::encode(commitment.as_bytes()));
+
+        reveals.push(reveal);
+        commitments.push(commitment);
+    }
+
+    let reveals = [reveals[0], reveals[1]];
+    let commitments = [commitments[0], commitments[1]];
+
+    let (receipt, proof) = match server.execute_round(commitments, reveals) {
+        Ok(result) => result,
+        Err(error) => {
+            println!("{error}\nTry again!");
+
+            return Ok(());
+        }
+    };
+
+    let start = Instant::now();
+    player_a.verify_round_receipt(&receipt, &commitments);
+    stats.record(server.move_index, proof, start.elapsed());
+
+    match player_b.verify_round_receipt(&receipt, &commitments) {
+        RoundResponse::Resolved { game, points, outcome, .. } => {
+            if outcome == RoundOutcome::Collision {
+                println!("Both players picked {}, so it stays vacant.", points[0]);
+            }
+
+            server.apply_round(points, outcome, game);
+        },
+        RoundResponse::Rejected { player, error, .. } => {
+            println!("{}: {error}\nTry again!", server.metadata.player(player).name);
+        }
+    }
+
+    Ok(())
+}
+
+// Both players commit to a random value and reveal it once they
+// have received the opponent's commitment. Returns the first player.
+fn flip_coin(game_id: &[u8; 32]) -> Result<Player, PlayError> {
+    let values: [[u8; 32]; 2] = rand::random();
+    let commitments = [
+        coin_flip::commitment(game_id, Player::A, &values[0]),
+        coin_flip::commitment(game_id, Player::B, &values[1])
+    ];
+
+    let receipt = Server::flip_coin(game_id, commitments, values)?;
+
+    // Each player verifies the receipt against the commitments they received.
+    let first_player = Client::verify_coin_flip(&receipt, game_id, &commitments);
+    assert_eq!(first_player, Client::verify_coin_flip(&receipt, game_id, &commitments));
+
+    Ok(first_player)
+}
+
+fn parse_hint(arg: &str) -> Result<Region, String> {
+    let region = arg.split_once(':').and_then(|(kind, value)| match kind {
+        "row" => value.parse().ok().map(Region::Row),
+        "column" => value.parse().ok().map(Region::Column),
+        "cell" => {
+            let (x, y) = value.split_once(',')?;
+
+            Some(Region::Cell(Point::new(x.parse().ok()?, y.parse().ok()?)))
+        },
+        _ => None
+    });
+
+    region.ok_or_else(|| format!("invalid region \"{arg}\""))
+}
+
+// The variant given on the command line, or the one of the configured ruleset.
+fn variant(args: &PlayArgs, config: &Config) -> Variant {
+    args.variant.unwrap_or(match config.game.ruleset {
+        Ruleset::Standard => Variant::Standard,
+        Ruleset::Simultaneous => Variant::Simultaneous
+    })
+}
+
+fn mode(args: &PlayArgs, config: &Config) -> Mode {
+    match variant(args, config) {
+        Variant::Standard => Mode::Standard,
+        Variant::Blind => Mode::Blind,
+        Variant::Simultaneous => Mode::Simultaneous,
+        Variant::Signed => Mode::Signed(Box::new([
+            SigningKey::generate(&mut OsRng),
+            SigningKey::generate(&mut OsRng)
+        ])),
+        Variant::Private => Mode::Private(rand::random(), args.hint),
+        Variant::Batch => Mode::Batch(args.batch_size),
+        Variant::Optimal => Mode::Optimal,
+        Variant::Optimistic => Mode::Optimistic
+    }
+}
+
+// Builds the match metadata from the "name" or "name:symbol"
+// command line arguments, falling back to the defaults.
+fn metadata_from_args(players: &[String]) -> MatchMetadata {
+    let mut metadata = MatchMetadata {
+        game_id: rand::random(),
+        nonce: rand::random(),
+        ..MatchMetadata::default()
+    };
+
+    for (info, arg) in [&mut metadata.player_a, &mut metadata.player_b].into_iter().zip(players) {
+        *info = player_info(arg, &info.symbol);
+    }
+
+    metadata
+}
+
+// Reads the move of `player`, or ends the game if they resign or quit.
+fn read_move(player: Player) -> Result<Point, PlayError> {
+    match input::wait_for_input().map_err(PlayError::Input)? {
+        Input::Move(point) => Ok(point),
+        Input::Resign => Err(PlayError::Resigned(player)),
+        Input::Quit => Err(PlayError::Quit),
+        Input::Challenge | Input::TakeBack | Input::OfferDraw | Input::Chat(_) => {
+            unreachable!("`wait_for_input` only accepts moves, resign and quit")
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use serde::Deserialize;
+use tracing_subscriber::{EnvFilter, fmt::format::FmtSpan};
+
+// How the servers log, see `init`.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug, Default, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    // One JSON object per line, for log collectors.
+    Json
+}
+
+// Logs at the info level, or at the levels given by `RUST_LOG`. The spans
+// around executing, proving and verifying are logged when they close, along
+// with how long they took and the game ID and move index that they belong to.
+pub fn init(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE);
+
+    match format {
+        LogFormat::Text => subscriber.init(),
+        LogFormat::Json => subscriber.json().init()
+    }
+}
@@ -1,197 +1,264 @@
-use std::io::{self, Write};
-
-use methods::{MAKE_MOVE_ELF, MAKE_MOVE_ID};
-use risc0_zkvm::{
-    serde::{from_slice, to_vec},
-    sha::{Sha256, Impl, Digest},
-    Executor, ExecutorEnv, SessionReceipt, Result
+use std::{fmt, fs, path::PathBuf, process, thread};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use risc0_zkvm::sha::Digest;
+use game::{metadata::GameId, clock::Clock};
+use host::{
+    args::{self, parse_digest, parse_game_id}, local::{self, PlayArgs, Variant}, bench, inspect, remote, hall, protocol::Transport, export::JournalFormat,
+    config::Config, prover, stats::GameStats, logging::{self, LogFormat},
+    bot::Strategy, selfplay, corpus, consistency, tournament::{Tournament, Entrant, Format},
+    identity::Identity, recovery::{Recovery, DEFAULT_CHECKPOINT_INTERVAL}
 };
-use game::{TicTacToe, State, Player, Point, VmResponse};
 
-struct Server {
-    game: TicTacToe
+// Plays a local game when run without a command, taking the options of `play`.
+#[derive(Parser)]
+#[command(name = "host", about = "Tic-Tac-Toe using the Risc0 VM.", args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[arg(long, global = true, help = "The configuration file to read instead of tic-tac-toe.toml")]
+    config: Option<PathBuf>,
+    #[command(flatten)]
+    play: PlayArgs
 }
 
-struct Client {
-    game_state: State,
-    state_hash: Digest
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Plays a game between two players taking turns on this machine")]
+    Play(PlayArgs),
+    #[command(about = "Runs the lobby server that remote players connect to")]
+    Serve {
+        #[arg(help = "The address to listen on [default: 0.0.0.0:7878]")]
+        address: Option<String>,
+        #[arg(long, value_enum, help = "How clients connect [default: tcp]")]
+        transport: Option<Transport>,
+        #[arg(long, value_enum, help = "How the server logs, text or one JSON object per line [default: text]")]
+        log_format: Option<LogFormat>,
+        #[arg(long, help = "The seconds that players have for each move before they forfeit the game")]
+        move_limit: Option<u32>,
+        #[arg(long, help = "The directory to keep the games being played in, to resume them after a restart")]
+        recovery: Option<PathBuf>,
+        #[arg(long, help = "How many moves apart the games kept for recovery are saved whole [default: 5]")]
+        checkpoint_interval: Option<u32>
+    },
+    #[command(about = "Plays or watches a game on a lobby server")]
+    Connect {
+        #[arg(help = "The address of the server [default: 127.0.0.1:7878]")]
+        address: Option<String>,
+        #[arg(default_value = "Player:X", help = "The name and symbol of the player, as \"name:symbol\"")]
+        player: String,
+        #[arg(long, value_parser = parse_game_id, help = "Watches the game with this ID instead of playing")]
+        watch: Option<GameId>
+    },
+    #[command(about = "Verifies a receipt exported with --export and shows what it proves")]
+    Verify {
+        file: PathBuf,
+        #[arg(long, value_parser = parse_digest, help = "The image ID of the guest to verify against [default: the one of this build]")]
+        image_id: Option<Digest>,
+        #[arg(long, value_parser = parse_digest, help = "Checks that the move was made on the state with this hash")]
+        prev_hash: Option<Digest>,
+        #[arg(long, value_enum, default_value_t = JournalFormat::Text, help = "Shows a sentence, or the decoded journal as JSON")]
+        journal_format: JournalFormat
+    },
+    #[command(about = "Plays back a game saved with --save, verifying each of its receipts")]
+    Replay {
+        file: PathBuf
+    },
+    #[command(about = "Proves the moves of a game, showing what it takes to prove and verify each of them")]
+    Bench {
+        #[arg(long, default_value_t = bench::MOVES.len(), help = "The number of moves to prove")]
+        moves: usize,
+        #[arg(
+            long = "variant",
+            value_enum,
+            default_values_t = [Variant::Standard],
+            help = "The variants to prove the moves in, given once for each"
+        )]
+        variants: Vec<Variant>,
+        #[arg(long, help = "Compares the variants with those written by --json in another build, such as with another state hasher")]
+        compare: Vec<PathBuf>,
+        #[arg(long, help = "Writes what it took to prove and verify each move of each variant to this file as JSON")]
+        json: Option<PathBuf>
+    },
+    #[command(about = "Plays games between two bots end to end, proving and verifying every receipt")]
+    Selfplay {
+        #[arg(long, default_value_t = 10, help = "The number of games to play")]
+        games: usize,
+        #[arg(long, value_enum, default_value_t = Strategy::Random, help = "How the first bot picks its moves")]
+        player_a: Strategy,
+        #[arg(long, value_enum, default_value_t = Strategy::Random, help = "How the second bot picks its moves")]
+        player_b: Strategy,
+        #[arg(long, help = "Writes the outcomes and what it took to prove and verify each move to this file as JSON")]
+        json: Option<PathBuf>
+    },
+    #[command(about = "Proves and verifies the games of a file of move sequences, reporting on each of them as JSON")]
+    Simulate {
+        #[arg(help = "The games, one per line, as moves such as \"a1 b2 1,0\" optionally followed by \"=> A\", \"=> B\" or \"=> draw\"")]
+        file: PathBuf,
+        #[arg(long, help = "Writes the report to this file instead of printing it")]
+        json: Option<PathBuf>
+    },
+    #[command(about = "Runs every move of every reachable game through the guest, comparing its journals with the game")]
+    Consistency {
+        #[arg(long, help = "The number of guests run at once [default: the number of CPUs]")]
+        threads: Option<usize>
+    },
+    #[command(about = "Plays a tournament between bots, keeping the receipts of every match")]
+    Tournament {
+        #[arg(num_args = 2.., required = true, value_parser = parse_entrant, help = "The players, as \"name:strategy\"")]
+        players: Vec<Entrant>,
+        #[arg(long, value_enum, default_value_t = Format::RoundRobin, help = "How the matches are scheduled")]
+        format: Format,
+        #[arg(long, default_value = "tournament", help = "The directory that the games and receipts of the matches are written to")]
+        dir: PathBuf
+    }
 }
 
 fn main() {
-    println!("
-Tic-Tac-Toe using the Risc0 VM.\n
-On each turn the current player has to input the coordinates \
-of the cell they want to fill in the form of \"x y\" where \"0 0\" \
-points to the top leftmost cell. For example: if the player wants \
-to fill the cell in the middle, they must provide the following input: \"1 1\".
-    ");
-    
-    let mut server = Server::new();
-
-    let mut player_a = Client::new();
-    let mut player_b = Client::new();
-
-    while let State::InProgress = server.game.state() {
-        server.game.print_board();
-
-        match server.game.current_player() {
-            Player::A => print!("Player 1 turn: "),
-            Player::B => print!("Player 2 turn: "),
-        };
-
-        io::stdout().flush().unwrap();
-
-        let point = Server::wait_for_input();
-        let receipt = match server.execute_move(point) {
-            Ok(receipt) => receipt,
-            Err(error) => { 
-                println!("{error}\nTry again!");
-
-                continue;
+    let cli = Cli::parse();
+    let config = exit_on_error(Config::load(cli.config.as_deref()));
+    prover::configure(&config.prover);
+
+    match cli.command.unwrap_or(Command::Play(cli.play)) {
+        Command::Play(args) => exit_on_error(local::play(args, &config)),
+        Command::Serve { address, transport, log_format, move_limit, recovery, checkpoint_interval } => {
+            let address = address.unwrap_or(config.server.address);
+            let transport = transport.unwrap_or(config.server.transport);
+            let clock = move_limit.or(config.server.move_limit).map(|move_limit_secs| Clock { move_limit_secs });
+            let interval = checkpoint_interval.or(config.storage.checkpoint_interval)
+                .unwrap_or(DEFAULT_CHECKPOINT_INTERVAL);
+            let recovery = recovery.or(config.storage.recovery)
+                .map(|dir| exit_on_error(Recovery::open(dir, interval).map_err(because("Failed to open the recovery directory"))));
+
+            logging::init(log_format.unwrap_or(config.server.log_format));
+
+            runtime().block_on(hall::run(&address, transport, clock, recovery))
+        },
+        Command::Connect { address, player, watch } => {
+            let address = address.unwrap_or(config.client.server);
+
+            let result = match watch {
+                Some(game_id) => runtime().block_on(remote::watch(&address, game_id)),
+                None => {
+                    let identity = exit_on_error(
+                        Identity::load_or_generate(&config.client.identity).map_err(because("Failed to read the identity key"))
+                    );
+
+                    runtime().block_on(remote::play(&address, args::player_info(&player, "X"), &identity))
+                }
+            };
+
+            exit_on_error(result)
+        },
+        Command::Verify { file, image_id, prev_hash, journal_format } => {
+            println!("{}", exit_on_error(inspect::verify(&file, image_id, prev_hash.as_ref(), journal_format)));
+        },
+        Command::Replay { file } => {
+            exit_on_error(inspect::replay(&config.save_path(&file)).map_err(because("Failed to load the game")))
+        },
+        Command::Bench { moves, variants, compare, json } => {
+            exit_on_error(bench::run(moves, &variants, &compare, json.as_deref(), &config))
+        },
+        Command::Selfplay { games, player_a, player_b, json } => {
+            let mut stats = GameStats::new("standard", config.prover.backend);
+            let outcomes = selfplay::play(games, [player_a, player_b], &mut stats);
+
+            println!("{outcomes}");
+
+            if !stats.moves.is_empty() {
+                println!("Proved {} moves in {:.2?}.", stats.moves.len(), stats.total().proving);
             }
-        };
 
-        player_a.verify_receipt(&receipt);
-        player_b.verify_receipt(&receipt);
+            if let Some(path) = json {
+                exit_on_error(outcomes.write_json(&stats, path).map_err(because("Failed to write the outcomes")));
+            }
+        },
+        Command::Simulate { file, json } => {
+            let games = exit_on_error(corpus::read(&file).map_err(because("Failed to read the games")));
 
-        let resp: VmResponse = from_slice(&receipt.journal).unwrap();
-        server.game = resp.game;
-    }
+            let mut stats = GameStats::new("standard", config.prover.backend);
+            let reports = corpus::run(&games, &mut stats);
+            let report = corpus::to_json(&reports, &stats);
 
-    match server.game.state() {
-        State::Stalemate => println!("Stalemate!"),
-        State::Winner(Player::A) => println!("Player 1 wins!"),
-        State::Winner(Player::B) => println!("Player 2 wins!"),
-        State::InProgress => unreachable!()
-    }
+            match json {
+                Some(path) => exit_on_error(fs::write(path, report).map_err(because("Failed to write the report"))),
+                None => println!("{report}")
+            }
 
-    player_a.on_game_ended();
-    player_b.on_game_ended();
-}
+            if !reports.iter().all(|report| report.passed()) {
+                process::exit(1);
+            }
+        },
+        Command::Consistency { threads } => {
+            let threads = threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, usize::from));
+            let games = consistency::reachable_games();
 
-impl Server {
-    pub fn new() -> Self {
-        Self {
-            game: TicTacToe::new()
-        }
-    }
+            println!("Running 10 moves on each of {} games on {threads} threads, without proving them.", games.len());
 
-    pub fn execute_move(&self, point: Point) -> Result<SessionReceipt> {
-        let env = ExecutorEnv::builder()
-            .add_input(&to_vec(&self.game)?)
-            .add_input(&to_vec(&point)?)
-            .build();
+            let moves = games.len() * 10;
+            let mismatches = consistency::check(&games, threads, |checked| if checked % 1000 == 0 {
+                println!("Checked {checked} of {moves} moves.");
+            });
+            let mismatches = exit_on_error(mismatches.map_err(because("Failed to run the guest")));
 
-        let mut executor = Executor::from_elf(env, MAKE_MOVE_ELF)?;
-        let session = executor.run()?;
+            for mismatch in &mismatches {
+                println!("{mismatch}\n");
+            }
 
-        session.prove()
-    }
+            println!("The guest and the game disagree on {} of {moves} moves.", mismatches.len());
+
+            if !mismatches.is_empty() {
+                process::exit(1);
+            }
+        },
+        Command::Tournament { players, format, dir } => {
+            exit_on_error(fs::create_dir_all(&dir).map_err(because("Failed to create the tournament directory")));
 
-    pub fn wait_for_input() -> Point {
-        let stdin = io::stdin();
-        let mut line = String::with_capacity(4);
+            let mut tournament = Tournament::new(format, &dir);
+
+            for player in players {
+                tournament.register(player);
+            }
 
-        loop {
-            stdin.read_line(&mut line).unwrap();
-            
-            let line_trimmed = line.trim_end();
-            let bytes = line_trimmed.as_bytes();
+            let mut stats = GameStats::new("standard", config.prover.backend);
+            let winner = exit_on_error(tournament.run(&mut stats).map_err(because("Failed to play the tournament")));
 
-            if bytes.len() == 3 && bytes[1] == ' ' as u8 &&
-                is_ascii_num(bytes[0]) && is_ascii_num(bytes[2])
-            {
-                let x = line_trimmed[0..1].parse().unwrap();
-                let y = line_trimmed[2..3].parse().unwrap();
+            println!("\n{tournament}\n");
 
-                return Point::new(x, y);
+            if let Some(winner) = winner {
+                println!("{} won the tournament.", tournament.entrants[winner].name);
             }
 
-            println!("Bad input. Try again...");
-            line.clear();
+            println!(
+                "Proved {} matches of {} moves in {:.2?}, with their receipts written to {}.",
+                tournament.matches,
+                stats.moves.len(),
+                stats.total().proving,
+                dir.display()
+            );
         }
     }
 }
 
-impl Client {
-    pub fn new() -> Self {
-        Self {
-            state_hash: TicTacToe::initial_hash(),
-            game_state: State::InProgress
-        }
-    }
-
-    pub fn verify_receipt(&mut self, receipt: &SessionReceipt) {
-        assert_eq!(self.game_state, State::InProgress, "Game has already ended!");
-
-        receipt.verify(MAKE_MOVE_ID)
-            .expect("receipt verification failed");
-
-        let resp: VmResponse = from_slice(&receipt.journal).unwrap();
-        assert_eq!(self.state_hash, resp.prev_state_hash, "Game state hash mismatch!");
+fn parse_entrant(arg: &str) -> Result<Entrant, String> {
+    let (name, strategy) = arg.split_once(':').ok_or_else(|| format!("players must be given as \"name:strategy\", not \"{arg}\""))?;
 
-        self.game_state = resp.game.state();
-        self.state_hash = *Impl::hash_bytes(&resp.game.as_bytes());
-    }
-
-    pub fn on_game_ended(self) {
-        assert_ne!(
-            self.game_state,
-            State::InProgress,
-            "Server signaled that the game has ended but the client state does not reflect that!"
-        );
-    }
+    Ok(Entrant { name: name.to_string(), strategy: Strategy::from_str(strategy, true)? })
 }
 
-fn is_ascii_num(byte: u8) -> bool {
-    byte >= 48 && byte <= 57
+// The value of `result`, or else prints why the command could not go on and exits with a failure.
+fn exit_on_error<T>(result: Result<T, impl fmt::Display>) -> T {
+    result.unwrap_or_else(|error| {
+        eprintln!("{error}");
+        process::exit(1);
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    #[should_panic = "Game state hash mismatch!"]
-    fn server_cannot_manipulate_game_state() {
-        let mut server = Server::new();
-
-        let mut player_a = Client::new();
-        let mut player_b = Client::new();
-
-        let receipt = server.execute_move(Point::new(1, 1)).unwrap();
-
-        player_a.verify_receipt(&receipt);
-        player_b.verify_receipt(&receipt);
-
-        let resp: VmResponse = from_slice(&receipt.journal).unwrap();
-        server.game = resp.game;
-
-        server.game.make_move(Point::new(2, 1)).unwrap();
-
-        let receipt = server.execute_move(Point::new(0, 1)).unwrap();
-
-        player_a.verify_receipt(&receipt);
-    }
-
-    #[test]
-    #[should_panic = "Game state hash mismatch!"]
-    fn server_cannot_send_an_old_receipt() {
-        let mut server = Server::new();
-
-        let mut player_a = Client::new();
-        let mut player_b = Client::new();
-
-        let receipt = server.execute_move(Point::new(1, 1)).unwrap();
-
-        player_a.verify_receipt(&receipt);
-        player_b.verify_receipt(&receipt);
-
-        let resp: VmResponse = from_slice(&receipt.journal).unwrap();
-        server.game = resp.game;
-
-        server.execute_move(Point::new(0, 1)).unwrap();
+// Says what failed along with why, for `exit_on_error`.
+fn because<E: fmt::Display>(what: &'static str) -> impl FnOnce(E) -> String {
+    move |error| format!("{what}: {error}")
+}
 
-        player_a.verify_receipt(&receipt);
-    }
+fn runtime() -> tokio::runtime::Runtime {
+    exit_on_error(tokio::runtime::Runtime::new().map_err(because("Failed to start the runtime")))
 }
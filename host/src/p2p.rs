@@ -0,0 +1,115 @@
+use std::{io, iter};
+
+use async_trait::async_trait;
+use futures::{AsyncRead, AsyncWrite, AsyncReadExt, AsyncWriteExt};
+use libp2p::{
+    core::upgrade, identity, noise, tcp, yamux, PeerId, Swarm, Transport,
+    request_response::{self, Codec, ProtocolName, ProtocolSupport},
+    swarm::SwarmBuilder
+};
+
+use crate::{peer::PeerMessage, protocol::{MAX_FRAME_SIZE, invalid_data}};
+
+pub const LISTEN_ADDRESS: &str = "/ip4/0.0.0.0/tcp/7879";
+
+pub type Behaviour = request_response::Behaviour<GameCodec>;
+
+#[derive(Clone)]
+pub struct GameProtocol;
+
+// Every message is a request that is answered with `PeerMessage::Ack`,
+// in frames of the same format as `protocol::Connection`.
+#[derive(Clone)]
+pub struct GameCodec;
+
+// A swarm of a new identity, speaking the game protocol over TCP.
+pub fn swarm() -> Swarm<Behaviour> {
+    let keypair = identity::Keypair::generate_ed25519();
+    let peer_id = PeerId::from(keypair.public());
+
+    let transport = tcp::tokio::Transport::new(tcp::Config::default())
+        .upgrade(upgrade::Version::V1)
+        .authenticate(noise::Config::new(&keypair).expect("keypair is valid"))
+        .multiplex(yamux::Config::default())
+        .boxed();
+
+    let behaviour = Behaviour::new(
+        GameCodec,
+        iter::once((GameProtocol, ProtocolSupport::Full)),
+        request_response::Config::default()
+    );
+
+    SwarmBuilder::with_tokio_executor(transport, behaviour, peer_id).build()
+}
+
+impl ProtocolName for GameProtocol {
+    fn protocol_name(&self) -> &[u8] {
+        b"/zk-tic-tac-toe/1"
+    }
+}
+
+#[async_trait]
+impl Codec for GameCodec {
+    type Protocol = GameProtocol;
+    type Request = PeerMessage;
+    type Response = PeerMessage;
+
+    async fn read_request<T>(&mut self, _: &GameProtocol, io: &mut T) -> io::Result<PeerMessage>
+        where T: AsyncRead + Unpin + Send
+    {
+        read_frame(io).await
+    }
+
+    async fn read_response<T>(&mut self, _: &GameProtocol, io: &mut T) -> io::Result<PeerMessage>
+        where T: AsyncRead + Unpin + Send
+    {
+        read_frame(io).await
+    }
+
+    async fn write_request<T>(
+        &mut self,
+        _: &GameProtocol,
+        io: &mut T,
+        message: PeerMessage
+    ) -> io::Result<()>
+        where T: AsyncWrite + Unpin + Send
+    {
+        write_frame(io, &message).await
+    }
+
+    async fn write_response<T>(
+        &mut self,
+        _: &GameProtocol,
+        io: &mut T,
+        message: PeerMessage
+    ) -> io::Result<()>
+        where T: AsyncWrite + Unpin + Send
+    {
+        write_frame(io, &message).await
+    }
+}
+
+async fn read_frame(io: &mut (impl AsyncRead + Unpin)) -> io::Result<PeerMessage> {
+    let mut len = [0; 4];
+    io.read_exact(&mut len).await?;
+
+    let len = u32::from_be_bytes(len);
+
+    if len > MAX_FRAME_SIZE {
+        return Err(invalid_data("frame is too large"));
+    }
+
+    let mut bytes = vec![0; len as usize];
+    io.read_exact(&mut bytes).await?;
+
+    bincode::deserialize(&bytes).map_err(invalid_data)
+}
+
+async fn write_frame(io: &mut (impl AsyncWrite + Unpin), message: &PeerMessage) -> io::Result<()> {
+    let bytes = bincode::serialize(message).map_err(invalid_data)?;
+
+    io.write_all(&(bytes.len() as u32).to_be_bytes()).await?;
+    io.write_all(&bytes).await?;
+
+    io.close().await
+}
@@ -0,0 +1,130 @@
+use serde::{Serialize, Deserialize};
+use risc0_zkvm::{SessionReceipt, Result};
+use game::{Player, VmResponse, metadata::MatchMetadata};
+
+use crate::{server::Server, client::{Client, GuestPin}};
+
+// Sent between two peers that play without a server.
+#[derive(Serialize, Deserialize)]
+pub enum PeerMessage {
+    // Proposes a match to the peer that was dialed, with the receipt proving its initial state.
+    Propose {
+        metadata: Box<MatchMetadata>,
+        pin: GuestPin,
+        receipt: SessionReceipt
+    },
+    // The receipt of a move by the sender.
+    Move(SessionReceipt),
+    // Acknowledges any other message.
+    Ack
+}
+
+// One side of a game without a server, which proves its own moves and verifies
+// those of the other peer. The game that moves are proven against only advances
+// with verified receipts, so both peers always agree on it.
+pub struct Peer {
+    pub player: Player,
+    pub server: Server,
+    pub client: Client
+}
+
+impl Peer {
+    // Starts the match of `metadata` as player A, returning the proposal for the other peer.
+    pub fn propose(metadata: MatchMetadata) -> Result<(Self, PeerMessage)> {
+        let server = Server::new(metadata);
+        let receipt = server.prove_init()?;
+        let client = Client::from_init_receipt(&receipt, &server.metadata.hash());
+
+        let proposal = PeerMessage::Propose {
+            metadata: Box::new(server.metadata.clone()),
+            pin: server.pin(),
+            receipt
+        };
+
+        Ok((Self { player: Player::A, server, client }, proposal))
+    }
+
+    // Joins the match proposed by the other peer as player B.
+    pub fn accept(metadata: MatchMetadata, pin: GuestPin, receipt: &SessionReceipt) -> Self {
+        let mut client = Client::from_init_receipt(receipt, &metadata.hash());
+        client.pin(pin);
+
+        Self {
+            player: Player::B,
+            server: Server::new(metadata),
+            client
+        }
+    }
+
+    pub fn is_our_turn(&self) -> bool {
+        self.server.game.current_player() == self.player
+    }
+
+    // Verifies the receipt of a move by `player`, which is applied if it was accepted.
+    pub fn apply(&mut self, receipt: &SessionReceipt, player: Player) -> VmResponse {
+        let response = self.client.verify_receipt(receipt);
+
+        match response {
+            VmResponse::Accepted { player: mover, game, point, .. } => {
+                assert_eq!(mover, player, "Move made by the wrong player!");
+
+                self.server.apply_move(point, game);
+            },
+            VmResponse::Rejected { player: mover, .. } => {
+                assert_eq!(mover, player, "Move made by the wrong player!");
+            }
+        }
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use game::{State, Point};
+
+    use super::*;
+
+    #[test]
+    fn peers_verify_each_others_moves() {
+        let (mut player_a, proposal) = Peer::propose(MatchMetadata::default()).unwrap();
+
+        let PeerMessage::Propose { metadata, pin, receipt } = proposal else {
+            panic!("expected a proposal");
+        };
+        let mut player_b = Peer::accept(*metadata, pin, &receipt);
+
+        for point in [(0, 0), (0, 1), (1, 0), (1, 1), (2, 0)] {
+            let (mover, other) = if player_a.is_our_turn() {
+                (&mut player_a, &mut player_b)
+            } else {
+                (&mut player_b, &mut player_a)
+            };
+
+            let proof = mover.server.move_proof(Point::new(point.0, point.1));
+            let (receipt, _) = proof.prove().unwrap();
+
+            mover.apply(&receipt, mover.player);
+            other.apply(&receipt, mover.player);
+        }
+
+        assert_eq!(player_a.server.chain_hash, player_b.server.chain_hash);
+        assert_eq!(player_b.server.game.state(), State::Winner(Player::A));
+    }
+
+    #[test]
+    #[should_panic = "Move made by the wrong player!"]
+    fn peer_cannot_move_for_the_other() {
+        let (player_a, proposal) = Peer::propose(MatchMetadata::default()).unwrap();
+
+        let PeerMessage::Propose { metadata, pin, receipt } = proposal else {
+            panic!("expected a proposal");
+        };
+        let mut player_b = Peer::accept(*metadata, pin, &receipt);
+
+        let (receipt, _) = player_a.server.move_proof(Point::new(1, 1)).prove().unwrap();
+
+        // Player B must not accept player A's move as its own.
+        player_b.apply(&receipt, Player::B);
+    }
+}
@@ -0,0 +1,309 @@
+use std::{io, sync::{Arc, Weak}, time::Duration};
+
+use async_trait::async_trait;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt}, sync::{Mutex, mpsc}, time,
+    net::{TcpStream, tcp::{OwnedReadHalf, OwnedWriteHalf}}
+};
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+
+// The messages themselves are shared with the browser client, see the `wire` crate.
+pub use wire::{ServerMessage, ClientMessage, SessionToken, Hello, Incompatible, PROTOCOL_VERSION};
+
+// The address that clients connect to and the one that the server
+// listens on by default, which accepts players from the local network.
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+pub const LISTEN_ADDRESS: &str = "0.0.0.0:7878";
+
+// How clients connect to the server.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    Tcp,
+    // Browsers connect over WebSockets, receiving messages as JSON.
+    #[value(name = "websocket")]
+    WebSocket
+}
+
+// Larger frames are refused before allocating a buffer for them.
+pub const MAX_FRAME_SIZE: u32 = 64 * 1024 * 1024;
+
+// How often both sides of a connection send a heartbeat, an empty frame over
+// TCP or a ping over WebSockets, so that the other side can tell a connection
+// that was lost without being closed, such as when the network went away, from
+// one that is only quiet, such as while a move is being proven.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+// How long a connection can go without a message or a heartbeat before it is
+// taken for lost, after which clients reconnect and resume their seat.
+pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(20);
+
+// A connection that messages can be exchanged over, whatever the transport:
+// `Connection` over TCP, `websocket::WebSocketConnection` for browsers and
+// `InProcess` within the same process. The games of the hall are played over
+// any of them, see `hall::LocalHall` for the one that tests connect to.
+#[async_trait]
+pub trait Channel: Send {
+    async fn send<M: Serialize + Sync>(&mut self, message: &M) -> io::Result<()>;
+    async fn recv<T: DeserializeOwned>(&mut self) -> io::Result<T>;
+}
+
+// A socket carrying messages in frames, each of which is the big endian
+// length of the message followed by its bytes. Empty frames are heartbeats,
+// see `HEARTBEAT_INTERVAL`, which are sent for as long as the connection exists.
+pub struct Connection {
+    reader: OwnedReadHalf,
+    // Shared with the task sending the heartbeats, so that frames are written whole.
+    writer: Arc<Mutex<OwnedWriteHalf>>,
+    // What has been read of the frames that have not been received yet, so
+    // that `recv` can be cancelled, such as by a timeout, without losing any.
+    buffer: Vec<u8>
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+
+        let (reader, writer) = stream.into_split();
+        let writer = Arc::new(Mutex::new(writer));
+
+        tokio::spawn(send_heartbeats(Arc::downgrade(&writer)));
+
+        Ok(Self { reader, writer, buffer: vec![] })
+    }
+
+    // The next frame that has been read whole, without its length.
+    fn take_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let Some(len) = self.buffer.get(..4) else {
+            return Ok(None);
+        };
+
+        let len = u32::from_be_bytes(len.try_into().expect("the length is four bytes long"));
+
+        if len > MAX_FRAME_SIZE {
+            return Err(invalid_data("frame is too large"));
+        }
+
+        let end = 4 + len as usize;
+
+        if self.buffer.len() < end {
+            self.buffer.reserve(end - self.buffer.len());
+
+            return Ok(None);
+        }
+
+        let frame = self.buffer[4..end].to_vec();
+        self.buffer.drain(..end);
+
+        Ok(Some(frame))
+    }
+}
+
+#[async_trait]
+impl Channel for Connection {
+    async fn send<M: Serialize + Sync>(&mut self, message: &M) -> io::Result<()> {
+        let bytes = bincode::serialize(message).map_err(invalid_data)?;
+
+        if bytes.len() > MAX_FRAME_SIZE as usize {
+            return Err(invalid_data("frame is too large"));
+        }
+
+        let mut writer = self.writer.lock().await;
+
+        writer.write_all(&(bytes.len() as u32).to_be_bytes()).await?;
+        writer.write_all(&bytes).await
+    }
+
+    // Skips heartbeats, failing with `TimedOut` once not even those arrive in time.
+    async fn recv<T: DeserializeOwned>(&mut self) -> io::Result<T> {
+        loop {
+            match self.take_frame()? {
+                Some(frame) if frame.is_empty() => continue,
+                Some(frame) => return bincode::deserialize(&frame).map_err(invalid_data),
+                None => { }
+            }
+
+            let read = time::timeout(HEARTBEAT_TIMEOUT, self.reader.read_buf(&mut self.buffer)).await
+                .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "the other side stopped responding"))??;
+
+            if read == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+        }
+    }
+}
+
+// One end of a connection within the same process, created by `in_process`. The
+// messages are still serialized, so that they go through what they would over
+// the network. There are no heartbeats, as the other end cannot be lost without
+// being dropped, which fails receiving with `UnexpectedEof`, like a closed socket.
+pub struct InProcess {
+    sender: mpsc::UnboundedSender<Vec<u8>>,
+    receiver: mpsc::UnboundedReceiver<Vec<u8>>
+}
+
+pub fn in_process() -> (InProcess, InProcess) {
+    let (a_sender, a_receiver) = mpsc::unbounded_channel();
+    let (b_sender, b_receiver) = mpsc::unbounded_channel();
+
+    (InProcess { sender: a_sender, receiver: b_receiver }, InProcess { sender: b_sender, receiver: a_receiver })
+}
+
+#[async_trait]
+impl Channel for InProcess {
+    async fn send<M: Serialize + Sync>(&mut self, message: &M) -> io::Result<()> {
+        let bytes = bincode::serialize(message).map_err(invalid_data)?;
+
+        self.sender.send(bytes).map_err(|_| io::ErrorKind::BrokenPipe.into())
+    }
+
+    async fn recv<T: DeserializeOwned>(&mut self) -> io::Result<T> {
+        let bytes = self.receiver.recv().await.ok_or(io::ErrorKind::UnexpectedEof)?;
+
+        bincode::deserialize(&bytes).map_err(invalid_data)
+    }
+}
+
+// Stops once the connection has been dropped or the heartbeat fails to be sent.
+async fn send_heartbeats(writer: Weak<Mutex<OwnedWriteHalf>>) {
+    let mut interval = time::interval(HEARTBEAT_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let Some(writer) = writer.upgrade() else {
+            return;
+        };
+
+        if writer.lock().await.write_all(&0u32.to_be_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+// Sends the `Hello` of this build and reads the one of the other side, which
+// does the same, failing with why they cannot play together if they cannot.
+// Both the client and the server greet each other on connecting, before
+// anything else, so that both sides can tell their users why.
+pub async fn greet(channel: &mut impl Channel) -> io::Result<Hello> {
+    let ours = Hello::local();
+    channel.send(&ours).await?;
+
+    let theirs: Hello = channel.recv().await?;
+    ours.check(&theirs).map_err(invalid_data)?;
+
+    Ok(theirs)
+}
+
+pub fn invalid_data(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+    use game::Point;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn messages_arrive_in_whole_frames() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let mut sender = Connection::new(stream).unwrap();
+        let mut receiver = Connection::new(listener.accept().await.unwrap().0).unwrap();
+
+        sender.send(&ClientMessage::Move(Point::new(1, 2))).await.unwrap();
+        sender.send(&ClientMessage::Move(Point::new(0, 0))).await.unwrap();
+
+        let ClientMessage::Move(point) = receiver.recv().await.unwrap() else {
+            panic!("expected a move");
+        };
+        assert_eq!(point, Point::new(1, 2));
+
+        let ClientMessage::Move(point) = receiver.recv().await.unwrap() else {
+            panic!("expected a move");
+        };
+        assert_eq!(point, Point::new(0, 0));
+    }
+
+    #[tokio::test]
+    async fn heartbeats_and_cancelled_receives_lose_no_messages() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut sender = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let mut receiver = Connection::new(listener.accept().await.unwrap().0).unwrap();
+
+        let frame = bincode::serialize(&ClientMessage::Move(Point::new(2, 1))).unwrap();
+
+        // A heartbeat and half of a message, and the receive given up on before the rest arrives.
+        sender.write_all(&0u32.to_be_bytes()).await.unwrap();
+        sender.write_all(&(frame.len() as u32).to_be_bytes()).await.unwrap();
+        sender.write_all(&frame[..2]).await.unwrap();
+
+        let receive = time::timeout(Duration::from_millis(100), receiver.recv::<ClientMessage>());
+        assert!(receive.await.is_err());
+
+        sender.write_all(&frame[2..]).await.unwrap();
+
+        let ClientMessage::Move(point) = receiver.recv().await.unwrap() else {
+            panic!("expected a move");
+        };
+        assert_eq!(point, Point::new(2, 1));
+    }
+
+    #[tokio::test]
+    async fn builds_greet_each_other() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let mut client = Connection::new(stream).unwrap();
+        let mut server = Connection::new(listener.accept().await.unwrap().0).unwrap();
+
+        let (client_hello, server_hello) = tokio::join!(greet(&mut client), greet(&mut server));
+        assert_eq!(client_hello.unwrap(), Hello::local());
+        assert_eq!(server_hello.unwrap(), Hello::local());
+
+        // Another build of the guest.
+        let other = Hello { journal_version: Hello::local().journal_version + 1, ..Hello::local() };
+        client.send(&other).await.unwrap();
+
+        let error = greet(&mut server).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("journals of version"));
+    }
+
+    #[tokio::test]
+    async fn in_process_channels_close_like_sockets() {
+        let (mut client, mut server) = in_process();
+
+        let (client_hello, server_hello) = tokio::join!(greet(&mut client), greet(&mut server));
+        assert_eq!(client_hello.unwrap(), Hello::local());
+        assert_eq!(server_hello.unwrap(), Hello::local());
+
+        client.send(&ClientMessage::Move(Point::new(1, 2))).await.unwrap();
+        drop(client);
+
+        // What was sent before the other end was dropped is still received.
+        let ClientMessage::Move(point) = server.recv().await.unwrap() else {
+            panic!("expected a move");
+        };
+        assert_eq!(point, Point::new(1, 2));
+
+        let error = server.recv::<ClientMessage>().await.err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let error = server.send(&ServerMessage::Refused("Gone.".into())).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+    }
+
+    #[tokio::test]
+    async fn oversized_frames_are_refused() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut sender = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let mut receiver = Connection::new(listener.accept().await.unwrap().0).unwrap();
+
+        sender.write_all(&(MAX_FRAME_SIZE + 1).to_be_bytes()).await.unwrap();
+
+        let error = receiver.recv::<ClientMessage>().await.err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
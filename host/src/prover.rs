@@ -0,0 +1,270 @@
+use std::{
+    collections::VecDeque, panic, time::Instant,
+    sync::{Arc, RwLock, mpsc::{self, Receiver}}, thread::{self, JoinHandle}
+};
+
+use tracing::{debug, warn, info_span};
+use methods::GuestMethod;
+use risc0_zkvm::{serde::to_vec, Executor, ExecutorEnv, Session, SessionReceipt, Result};
+
+use crate::{config::{ProverConfig, ProverBackend}, server::{MoveProof, ProofStats}};
+#[cfg(feature = "bonsai")]
+use crate::bonsai;
+
+// How many times proving a session is attempted before giving up on it.
+pub const PROVING_ATTEMPTS: u32 = 3;
+
+// Where the receipts of this process are proven, see `set_backend`.
+// Locally until one is set.
+static BACKEND: RwLock<Option<Arc<dyn Backend>>> = RwLock::new(None);
+
+// Proves the sessions that the guests have been run in, which applications
+// hosting games can implement to prove them elsewhere, such as on a cluster
+// of their own, see `set_backend`.
+pub trait Backend: Send + Sync {
+    // Proves `session`, which `method` was run in on `input`, calling
+    // `progress` with the number of segments proven so far, if it can.
+    fn prove(
+        &self,
+        method: GuestMethod,
+        input: &[u8],
+        session: &Session,
+        progress: &mut dyn FnMut(Progress)
+    ) -> Result<SessionReceipt>;
+}
+
+// Proves sessions on this machine, one segment after the other.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Local;
+
+// How many of the segments of a session have been proven.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Progress {
+    pub proven: usize,
+    pub segments: usize
+}
+
+// The input of a guest method, in the order in which it reads it.
+#[derive(Default)]
+pub struct Input(Vec<u8>);
+
+// Proves moves on threads of their own while the game goes on, and hands out
+// the results in the order in which the moves were queued, which is the order
+// of their move indexes, so that clients can verify them one after the other.
+#[derive(Default)]
+pub struct ProofQueue {
+    proofs: VecDeque<QueuedProof>
+}
+
+struct QueuedProof {
+    move_index: u32,
+    prover: Proving,
+    progress: Receiver<Progress>,
+    latest: Option<Progress>
+}
+
+// The move index of a queued move and the result of proving it.
+pub type ProvenMove = (u32, Result<(SessionReceipt, ProofStats)>);
+
+type Proving = JoinHandle<Result<(SessionReceipt, ProofStats)>>;
+
+// Selects the backend of the configuration, see `set_backend`. The
+// configuration has been validated, so the Bonsai settings are there.
+pub fn configure(config: &ProverConfig) {
+    match config.backend {
+        ProverBackend::Local => set_backend(Local),
+        #[cfg(feature = "bonsai")]
+        ProverBackend::Bonsai => set_backend(bonsai::Client::new(
+            config.bonsai_url.as_deref().expect("the configuration has been validated"),
+            config.bonsai_api_key.as_deref().expect("the configuration has been validated")
+        )),
+        #[cfg(not(feature = "bonsai"))]
+        ProverBackend::Bonsai => unreachable!("the configuration has been validated")
+    }
+}
+
+// Selects the backend that every receipt of this process is proven with from then on.
+pub fn set_backend(backend: impl Backend + 'static) {
+    *BACKEND.write().expect("the prover lock is never poisoned") = Some(Arc::new(backend));
+}
+
+fn backend() -> Arc<dyn Backend> {
+    BACKEND.read().expect("the prover lock is never poisoned")
+        .clone()
+        .unwrap_or_else(|| Arc::new(Local))
+}
+
+// Proves the session of `method` on `input`, see `prove_with_progress`.
+pub fn prove(method: GuestMethod, input: Input) -> Result<(SessionReceipt, ProofStats)> {
+    prove_with_progress(method, input, &mut |_| { })
+}
+
+// Runs `method` on `input` and proves the session with the configured backend,
+// reporting the progress after every segment, or only once done with Bonsai.
+// The session is always executed locally first, which takes a fraction of the
+// time of proving it, to count its cycles and fail early on guest errors.
+// Execution is deterministic, so it is not retried, but proving is, up to
+// `PROVING_ATTEMPTS` times, as it can fail on a lack of memory or a timeout.
+pub fn prove_with_progress(
+    method: GuestMethod,
+    input: Input,
+    progress: &mut dyn FnMut(Progress)
+) -> Result<(SessionReceipt, ProofStats)> {
+    let start = Instant::now();
+    let backend = backend();
+
+    let execution = info_span!("execute", ?method).entered();
+
+    let env = ExecutorEnv::builder().add_input(&input.0).build();
+    let mut executor = Executor::from_elf(env, method.elf())?;
+    let session = executor.run()?;
+
+    let mut cycles = 0;
+
+    for segment in &session.segments {
+        cycles += 1 << segment.resolve()?.po2;
+    }
+
+    let segments = session.segments.len();
+    execution.exit();
+
+    let _proving = info_span!("prove", ?method, cycles, segments).entered();
+
+    let mut attempt = 1;
+
+    let receipt = loop {
+        progress(Progress { proven: 0, segments });
+
+        match backend.prove(method, &input.0, &session, progress) {
+            Ok(receipt) => break receipt,
+            Err(error) if attempt < PROVING_ATTEMPTS => {
+                warn!(%error, attempt, "Failed to prove the session, trying again.");
+                attempt += 1;
+            },
+            Err(error) => return Err(error)
+        }
+    };
+
+    let stats = ProofStats {
+        cycles,
+        segments,
+        duration: start.elapsed(),
+        receipt_size: to_vec(&receipt)?.len() * 4
+    };
+
+    Ok((receipt, stats))
+}
+
+// Runs `method` on `input` without proving the session, for checks of the
+// guests that only need the journal, see `consistency`. Nothing is proven
+// by it, so the journal must not be trusted like that of a receipt.
+pub fn execute(method: GuestMethod, input: Input) -> Result<Vec<u8>> {
+    let env = ExecutorEnv::builder().add_input(&input.0).build();
+    let session = Executor::from_elf(env, method.elf())?.run()?;
+
+    Ok(session.journal)
+}
+
+impl Backend for Local {
+    fn prove(
+        &self,
+        _method: GuestMethod,
+        _input: &[u8],
+        session: &Session,
+        progress: &mut dyn FnMut(Progress)
+    ) -> Result<SessionReceipt> {
+        let segments = session.segments.len();
+        let mut receipts = Vec::with_capacity(segments);
+
+        // Proven one by one like `Session::prove` does, to report on each of them.
+        for segment in &session.segments {
+            receipts.push(segment.resolve()?.prove()?);
+            debug!(proven = receipts.len(), segments, "Proved a segment.");
+            progress(Progress { proven: receipts.len(), segments });
+        }
+
+        Ok(SessionReceipt { segments: receipts, journal: session.journal.clone() })
+    }
+}
+
+impl Progress {
+    pub fn fraction(&self) -> f32 {
+        self.proven as f32 / self.segments.max(1) as f32
+    }
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Adds a value serialized with `risc0_zkvm::serde::to_vec`.
+    pub fn add_words(mut self, words: &[u32]) -> Self {
+        self.0.extend(words.iter().flat_map(|word| word.to_le_bytes()));
+        self
+    }
+
+    pub fn add_bytes(mut self, bytes: &[u8]) -> Self {
+        self.0.extend_from_slice(bytes);
+        self
+    }
+}
+
+impl ProofQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, proof: MoveProof) {
+        let (sender, progress) = mpsc::channel();
+
+        // The receiver is gone once the queue has been cleared, which the prover does not care about.
+        let prover = thread::spawn(move || proof.prove_with_progress(|progress| {
+            let _ = sender.send(progress);
+        }));
+
+        self.proofs.push_back(QueuedProof { move_index: proof.move_index(), prover, progress, latest: None });
+    }
+
+    // The move index and result of the earliest move, if it has been proven.
+    pub fn try_next(&mut self) -> Option<ProvenMove> {
+        match self.proofs.front() {
+            Some(queued) if queued.prover.is_finished() => self.wait(),
+            _ => None
+        }
+    }
+
+    // Waits for the earliest move to be proven.
+    pub fn wait(&mut self) -> Option<ProvenMove> {
+        let queued = self.proofs.pop_front()?;
+
+        match queued.prover.join() {
+            Ok(result) => Some((queued.move_index, result)),
+            Err(panic) => panic::resume_unwind(panic)
+        }
+    }
+
+    // How far proving the earliest move has got, once its session has been executed.
+    pub fn progress(&mut self) -> Option<Progress> {
+        let queued = self.proofs.front_mut()?;
+
+        if let Some(progress) = queued.progress.try_iter().last() {
+            queued.latest = Some(progress);
+        }
+
+        queued.latest
+    }
+
+    // Gives up on the queued moves, whose provers are left to finish on their own.
+    pub fn clear(&mut self) {
+        self.proofs.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.proofs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.proofs.is_empty()
+    }
+}
@@ -0,0 +1,335 @@
+use std::{fs::{self, File, OpenOptions}, io::{self, Write}, path::{Path, PathBuf}};
+
+use serde::{Serialize, Deserialize};
+use ed25519_dalek::SigningKey;
+use risc0_zkvm::{SessionReceipt, sha::Digest};
+use game::{Point, chat::ChatMessage, metadata::GameId};
+
+use crate::{server::Server, identity::Identity, protocol::{ServerMessage, SessionToken, invalid_data}};
+
+// Must be incremented whenever the layout of `SavedMatch` or `Logged` changes
+// so that games saved by other versions are refused, see `server::SAVE_VERSION`.
+pub const RECOVERY_VERSION: u32 = 3;
+
+// How many `Move` and `TakenBack` messages are sent between checkpoints when
+// no other interval is given, see `StorageConfig::checkpoint_interval`.
+pub const DEFAULT_CHECKPOINT_INTERVAL: u32 = 5;
+
+// The key that forfeits are signed with, which is kept so that the clients of
+// recovered games can still verify the forfeits announced after a restart.
+const REFEREE_FILE: &str = "referee.key";
+
+// The games being played on a lobby server, kept in a directory so that they are
+// resumed after the server restarts, see `StorageConfig::recovery`. Each game is
+// kept in files named after its ID: the receipt of its initial state, which is
+// written once, a checkpoint of the whole game, which is replaced every
+// `interval` messages, and a log of what happened to it since, which every
+// event is appended to as it happens. Recovering a game replays the log on top
+// of the checkpoint, so that nothing that was proven has to be proven again.
+pub struct Recovery {
+    dir: PathBuf,
+    interval: u32
+}
+
+// A game along with what each of its players were sent, in bincode. The
+// version is always the first field, so it can be checked before the rest.
+#[derive(Serialize, Deserialize)]
+struct SavedMatch {
+    version: u32,
+    // As written by `Server::to_bytes`, which includes the moves that were received but not proven yet.
+    server: Vec<u8>,
+    seats: Vec<SavedSeat>
+}
+
+// What a player was sent in a game, so that the messages that they missed
+// can be sent again when they resume their seat, see `hall::Seat`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedSeat {
+    pub token: SessionToken,
+    // The `Move` and `TakenBack` messages sent to the player.
+    pub sent: Vec<ServerMessage>,
+    // The head of the hash chain that the client of the player has verified
+    // before each of the messages, and after the last one.
+    pub chain_hashes: Vec<Digest>
+}
+
+// What happened to a game, as appended to its log.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum Event {
+    // A move was received from the player on turn, which is proven next.
+    Received(Point),
+    // The move was proven, and the receipt sent to both players in a `Move` message.
+    Proved { point: Point, receipt: SessionReceipt },
+    // The players agreed to take back moves, see `Server::take_back`.
+    TakenBack { move_index: u32 },
+    // A chat message was relayed, which has no effect on the game and is
+    // only logged so that it can be attributed to the player it names.
+    Chat(ChatMessage)
+}
+
+// An event along with how many messages the players had been sent before
+// it, so that the events that a checkpoint covers already are skipped, in
+// case the server stopped before the log was cleared after it.
+#[derive(Serialize, Deserialize)]
+struct Logged {
+    sent: u32,
+    event: Event
+}
+
+// A game read back by `Recovery::load`.
+pub struct RecoveredMatch {
+    pub server: Server,
+    pub init: SessionReceipt,
+    pub seats: Vec<SavedSeat>
+}
+
+impl Recovery {
+    // Creates the directory if it doesn't exist yet. Games are checkpointed
+    // every `interval` messages, and after every one if it is zero.
+    pub fn open(dir: impl Into<PathBuf>, interval: u32) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self { dir, interval: interval.max(1) })
+    }
+
+    // Whether a game is checkpointed after the players were sent `sent` messages.
+    pub fn is_due(&self, sent: usize) -> bool {
+        sent % self.interval as usize == 0
+    }
+
+    // Generated when the directory is first used.
+    pub fn referee(&self) -> io::Result<SigningKey> {
+        Ok(Identity::load_or_generate(self.dir.join(REFEREE_FILE))?.key().clone())
+    }
+
+    // Written once the game has started, before the game itself is saved.
+    pub fn start(&self, game_id: &GameId, init: &SessionReceipt) -> io::Result<()> {
+        write(&self.path(game_id, "init"), &bincode::serialize(init).map_err(invalid_data)?)
+    }
+
+    // Replaces the checkpoint of the game, and then clears its log.
+    pub fn checkpoint(&self, server: &Server, seats: Vec<SavedSeat>) -> io::Result<()> {
+        let game_id = &server.metadata.game_id;
+        let saved = SavedMatch { version: RECOVERY_VERSION, server: server.to_bytes()?, seats };
+
+        write(&self.path(game_id, "match"), &bincode::serialize(&saved).map_err(invalid_data)?)?;
+
+        match File::options().write(true).open(self.path(game_id, "log")) {
+            Ok(log) => log.set_len(0),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(error)
+        }
+    }
+
+    // Appends the event to the log of the game, in a frame of its own that is
+    // dropped on recovering if the server stopped before it was written whole.
+    pub fn append(&self, game_id: &GameId, sent: usize, event: Event) -> io::Result<()> {
+        let bytes = bincode::serialize(&Logged { sent: sent as u32, event }).map_err(invalid_data)?;
+
+        let mut log = OpenOptions::new().create(true).append(true).open(self.path(game_id, "log"))?;
+        log.write_all(&[&(bytes.len() as u32).to_le_bytes()[..], &bytes].concat())?;
+        log.sync_data()
+    }
+
+    // Forgets a game once it has ended or was aborted.
+    pub fn remove(&self, game_id: &GameId) -> io::Result<()> {
+        for extension in ["match", "log", "init"] {
+            match fs::remove_file(self.path(game_id, extension)) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+                _ => { }
+            }
+        }
+
+        Ok(())
+    }
+
+    // The files of the games that have been saved, to be read with `load`.
+    pub fn saved(&self) -> io::Result<Vec<PathBuf>> {
+        let mut paths = vec![];
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+
+            if path.extension().map_or(false, |extension| extension == "match") {
+                paths.push(path);
+            }
+        }
+
+        Ok(paths)
+    }
+
+    pub fn load(&self, path: &Path) -> io::Result<RecoveredMatch> {
+        let bytes = fs::read(path)?;
+
+        let version: u32 = bincode::deserialize(&bytes).map_err(invalid_data)?;
+
+        if version != RECOVERY_VERSION {
+            return Err(invalid_data(format!("unsupported recovery version {version}")));
+        }
+
+        let saved: SavedMatch = bincode::deserialize(&bytes).map_err(invalid_data)?;
+        let init = bincode::deserialize(&fs::read(path.with_extension("init"))?).map_err(invalid_data)?;
+
+        let mut recovered = RecoveredMatch { server: Server::from_bytes(&saved.server)?, init, seats: saved.seats };
+
+        let log = match fs::read(path.with_extension("log")) {
+            Ok(log) => log,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => vec![],
+            Err(error) => return Err(error)
+        };
+
+        for logged in read_log(&log) {
+            if logged.sent as usize >= recovered.sent() {
+                recovered.replay(logged.event);
+            }
+        }
+
+        Ok(recovered)
+    }
+
+    fn path(&self, game_id: &GameId, extension: &str) -> PathBuf {
+        self.dir.join(hex::encode(game_id)).with_extension(extension)
+    }
+}
+
+impl RecoveredMatch {
+    // How many messages the players had been sent.
+    fn sent(&self) -> usize {
+        self.seats.first().map_or(0, |seat| seat.sent.len())
+    }
+
+    // Applies the event to the game the way the hall did when it happened.
+    fn replay(&mut self, event: Event) {
+        let server = &mut self.server;
+
+        let message = match event {
+            Event::Received(point) => {
+                server.pending = vec![point];
+
+                return;
+            },
+            Event::Proved { point, receipt } => {
+                server.pending.clear();
+
+                let mut game = server.game;
+
+                if game.make_move(point).is_ok() {
+                    server.apply_move(point, game);
+                }
+
+                server.receipts.push(receipt.clone());
+
+                ServerMessage::Move(receipt)
+            },
+            Event::TakenBack { move_index } => {
+                server.take_back(move_index);
+
+                ServerMessage::TakenBack { move_index, receipts: server.receipts.len() as u32 }
+            },
+            Event::Chat(_) => return
+        };
+
+        for seat in &mut self.seats {
+            seat.sent.push(message.clone());
+            seat.chain_hashes.push(server.chain_hash);
+        }
+    }
+}
+
+// The events of a log, up to the first one that was not written whole.
+fn read_log(mut log: &[u8]) -> Vec<Logged> {
+    let mut events = vec![];
+
+    while log.len() >= 4 {
+        let len = u32::from_le_bytes(log[..4].try_into().expect("the length is four bytes long")) as usize;
+
+        let Some(frame) = log.get(4..4 + len) else {
+            break;
+        };
+
+        let Ok(logged) = bincode::deserialize(frame) else {
+            break;
+        };
+
+        events.push(logged);
+        log = &log[4 + len..];
+    }
+
+    events
+}
+
+// Replaces the file only once the whole of it has been written and flushed
+// to disk, so that a crash while writing it leaves the one written before.
+fn write(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+
+    let mut file = File::create(&partial)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+
+    fs::rename(partial, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use game::{Player, metadata::MatchMetadata};
+
+    use super::*;
+
+    #[test]
+    fn logged_events_are_replayed_on_the_last_checkpoint() {
+        let dir = env::temp_dir().join(format!("zk-tic-tac-toe-{}.recovery", std::process::id()));
+        let recovery = Recovery::open(&dir, 2).unwrap();
+
+        let server = Server::new(MatchMetadata { game_id: [3; 32], ..MatchMetadata::default() });
+        let game_id = server.metadata.game_id;
+
+        let init = SessionReceipt { segments: vec![], journal: vec![7] };
+        let receipt = SessionReceipt { segments: vec![], journal: vec![8] };
+        let seat = SavedSeat { token: [5; 32], sent: vec![], chain_hashes: vec![server.chain_hash] };
+
+        recovery.start(&game_id, &init).unwrap();
+        recovery.checkpoint(&server, vec![seat.clone(), seat]).unwrap();
+
+        recovery.append(&game_id, 0, Event::Received(Point::new(1, 1))).unwrap();
+        recovery.append(&game_id, 0, Event::Proved { point: Point::new(1, 1), receipt: receipt.clone() }).unwrap();
+        recovery.append(&game_id, 1, Event::Received(Point::new(0, 0))).unwrap();
+
+        // Chat is logged without being sent again or changing the game.
+        let chat = ChatMessage { game_id, move_index: 1, player: Player::B, text: "gg".into(), signature: vec![] };
+        recovery.append(&game_id, 1, Event::Chat(chat)).unwrap();
+
+        let saved = recovery.saved().unwrap();
+        assert_eq!(saved.len(), 1);
+
+        let recovered = recovery.load(&saved[0]).unwrap();
+        assert_eq!(recovered.server.moves, vec![Point::new(1, 1)]);
+        assert_eq!(recovered.server.pending, vec![Point::new(0, 0)]);
+        assert_eq!(recovered.server.receipts.len(), 1);
+        assert_eq!(recovered.init.journal, vec![7]);
+        assert_eq!(recovered.seats[1].sent.len(), 1);
+        assert_eq!(recovered.seats[1].chain_hashes, vec![server.chain_hash, recovered.server.chain_hash]);
+
+        // Events that the checkpoint covers are skipped, as is an event that was not written whole.
+        recovery.checkpoint(&recovered.server, recovered.seats.clone()).unwrap();
+        recovery.append(&game_id, 0, Event::Proved { point: Point::new(1, 1), receipt }).unwrap();
+        OpenOptions::new().append(true).open(dir.join(hex::encode(game_id)).with_extension("log")).unwrap()
+            .write_all(&[100, 0, 0, 0, 1]).unwrap();
+
+        let reloaded = recovery.load(&saved[0]).unwrap();
+        assert_eq!(reloaded.server.moves, vec![Point::new(1, 1)]);
+        assert_eq!(reloaded.seats[0].sent.len(), 1);
+
+        // The referee stays the same across restarts.
+        assert_eq!(recovery.referee().unwrap().to_bytes(), recovery.referee().unwrap().to_bytes());
+
+        recovery.remove(&game_id).unwrap();
+        assert!(recovery.saved().unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
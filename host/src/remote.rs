@@ -0,0 +1,544 @@
+use std::{io::{self, Write}, time::Duration};
+
+use ed25519_dalek::{SigningKey, Signer};
+use tokio::{net::TcpStream, task, time};
+use risc0_zkvm::sha::Digest;
+use game::{
+    TicTacToe, State, Player, VmResponse, GameJournal,
+    metadata::{MatchMetadata, PlayerInfo, GameId}, render::{Style, Frame}, clock::Forfeit,
+    chat::{self, ChatMessage, MAX_CHAT_LENGTH}, auth::{self, SignedMove, Concession}
+};
+
+use crate::{
+    client::Client, render::Renderer, input::{self, Command}, lobby::{Terms, OpenGame}, identity::Identity,
+    protocol::{self, Channel, Connection, ServerMessage, ClientMessage, SessionToken, invalid_data}
+};
+
+// How long to wait before each attempt to reconnect to the server.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+
+// Registers the player in the lobby of the server at `address` and plays the
+// game they open or join, verifying every receipt that the server sends,
+// followed by the rematches that both players agree to. The player registers
+// with the public key of their identity, which signs their moves and chat messages.
+// Fails if the server cannot be played on, or the connection is lost before
+// the player is seated, or if the server sends what it should not.
+pub async fn play(address: &str, info: PlayerInfo, identity: &Identity) -> io::Result<()> {
+    let info = info.with_public_key(identity.public_key());
+    let mut connection = connect(address).await?;
+
+    connection.send(&ClientMessage::Register(info)).await?;
+
+    let mut welcome = loop {
+        match connection.recv().await? {
+            ServerMessage::Challenge(challenge) => {
+                println!("Signing in with the key {}...", hex::encode(identity.public_key()));
+                connection.send(&ClientMessage::Authenticate(identity.sign_login(&challenge))).await?;
+            },
+            ServerMessage::Lobby(games) => {
+                let request = blocking(move || choose_game(&games)).await??;
+                connection.send(&request).await?;
+            },
+            ServerMessage::Refused(reason) => println!("{reason}"),
+            welcome => break welcome
+        }
+    };
+
+    let mut previous = None;
+
+    loop {
+        let Some(metadata) = play_game(address, &mut connection, identity.key(), welcome, previous).await? else {
+            return Ok(());
+        };
+
+        match connection.recv().await {
+            Ok(ServerMessage::RematchOffered) => { },
+            Ok(ServerMessage::Refused(reason)) => {
+                println!("{reason}");
+
+                return Ok(());
+            },
+            _ => return Ok(())
+        }
+
+        let question = "Play a rematch, with the other player moving first?";
+        let accepted = blocking(move || input::confirm(question)).await?.unwrap_or(false);
+
+        if connection.send(&ClientMessage::AcceptRematch(accepted)).await.is_err() || !accepted {
+            return Ok(());
+        }
+
+        println!("Waiting for the other player to accept the rematch...");
+
+        welcome = match connection.recv().await {
+            Ok(ServerMessage::Refused(reason)) => {
+                println!("{reason}");
+
+                return Ok(());
+            },
+            Ok(welcome) => welcome,
+            Err(error) => {
+                println!("Lost the connection to the server ({error}).");
+
+                return Ok(());
+            }
+        };
+        previous = Some(metadata);
+    }
+}
+
+// Plays the game that the player was welcomed to, returning its terms once
+// it has ended, or nothing if the player left it. For a rematch, `previous`
+// holds the terms of the last game, which the new terms have to follow from.
+async fn play_game(
+    address: &str,
+    connection: &mut Connection,
+    key: &SigningKey,
+    welcome: ServerMessage,
+    previous: Option<MatchMetadata>
+) -> io::Result<Option<MatchMetadata>> {
+    let ServerMessage::Welcome { player, metadata, pin, receipt, token, clock, referee } = welcome else {
+        return Err(invalid_data("expected to be seated in a game"));
+    };
+
+    if let Some(previous) = previous {
+        if *metadata != previous.rematch(metadata.nonce) {
+            return Err(invalid_data("the terms of the rematch do not follow from the last game"));
+        }
+    }
+
+    let match_hash = metadata.hash();
+
+    // The receipts are verified locally, the server is not trusted with the game state.
+    let mut client = Client::try_from_init_receipt(&receipt, &match_hash).map_err(invalid_data)?;
+    client.try_pin(pin).map_err(invalid_data)?;
+
+    if metadata.image_id != Some(client.pin.make_move_id) {
+        return Err(invalid_data("the terms of the match are not the ones proven by the server"));
+    }
+
+    let info = metadata.player(player);
+    println!("Joined game {} as {} ({}).", hex::encode(metadata.game_id), info.name, info.symbol);
+
+    if let Some(clock) = clock {
+        println!("Each move has to be made within {} seconds.", clock.move_limit_secs);
+    }
+
+    let renderer = renderer(&metadata);
+
+    let mut boards = vec![metadata.initial_game()];
+    println!("{}", renderer.render(&boards[0], None));
+
+    // The number of `Move` and `TakenBack` messages received, so that the
+    // server only sends the missed ones on resuming, see `ClientMessage::Resume`.
+    let mut received = 0;
+
+    loop {
+        let message = match connection.recv().await {
+            Ok(message) => message,
+            Err(error) => {
+                println!("Lost the connection to the server ({error}), reconnecting...");
+
+                *connection = resume(address, token, received, client.chain_hash).await?;
+                continue;
+            }
+        };
+
+        match message {
+            ServerMessage::YourTurn => {
+                // The player is still on turn after chatting, so they are asked again.
+                let request = loop {
+                    print!("Your turn: ");
+                    io::stdout().flush()?;
+
+                    match blocking(input::wait_for_input_on_server).await? {
+                        Ok(Command::Move(point)) => {
+                            let message = auth::move_message(&metadata.game_id, client.move_index, point);
+
+                            break ClientMessage::SignedMove(SignedMove::new(point, key.sign(&message).to_bytes().to_vec()));
+                        },
+                        Ok(Command::TakeBack) => break ClientMessage::RequestTakeBack,
+                        Ok(Command::OfferDraw) => {
+                            break ClientMessage::SignedOfferDraw(sign_concession(key, &metadata.game_id, client.move_index, Concession::Draw));
+                        },
+                        Ok(Command::Resign) => {
+                            break ClientMessage::SignedResign(sign_concession(key, &metadata.game_id, client.move_index, Concession::Resign));
+                        },
+                        Ok(Command::Chat(text)) if text.len() > MAX_CHAT_LENGTH => {
+                            println!("Chat messages can be at most {MAX_CHAT_LENGTH} bytes long.");
+                        },
+                        Ok(Command::Chat(text)) => {
+                            let chat = sign_chat(key, &metadata.game_id, client.move_index, player, text);
+
+                            // A lost connection is noticed again when sending the move.
+                            let _ = connection.send(&ClientMessage::Chat(chat)).await;
+                        },
+                        Ok(_) => {
+                            println!("Left the game.");
+
+                            return Ok(None);
+                        },
+                        Err(error) => {
+                            println!("Failed to read the next move: {error}");
+
+                            return Ok(None);
+                        }
+                    }
+                };
+
+                // The server asks again once the seat is resumed otherwise.
+                if connection.send(&request).await.is_err() {
+                    *connection = resume(address, token, received, client.chain_hash).await?;
+                }
+            },
+            ServerMessage::TakeBackRequested => {
+                let question = "Your opponent asks to take back their last move and your reply. Agree?";
+                let accepted = blocking(move || input::confirm(question)).await?.unwrap_or(false);
+
+                if connection.send(&ClientMessage::AcceptTakeBack(accepted)).await.is_err() {
+                    *connection = resume(address, token, received, client.chain_hash).await?;
+                }
+            },
+            ServerMessage::DrawOffered => {
+                let question = "Your opponent offers a draw. Agree?";
+                let accepted = blocking(move || input::confirm(question)).await?.unwrap_or(false);
+
+                let answer = match accepted {
+                    true => ClientMessage::SignedAcceptDraw(sign_concession(key, &metadata.game_id, client.move_index, Concession::Draw)),
+                    false => ClientMessage::AcceptDraw(false)
+                };
+
+                if connection.send(&answer).await.is_err() {
+                    *connection = resume(address, token, received, client.chain_hash).await?;
+                }
+            },
+            ServerMessage::TakenBack { move_index, .. } => {
+                received += 1;
+
+                taken_back(&renderer, &mut boards, &mut client, move_index)?;
+            },
+            ServerMessage::Move(receipt) => {
+                received += 1;
+
+                played(&metadata, &renderer, &mut boards, client.try_verify_receipt(&receipt).map_err(invalid_data)?);
+            },
+            ServerMessage::Chat(chat) => chatted(&metadata, &chat),
+            // A declined take-back or draw or a refused move, after which the server asks for a move again.
+            ServerMessage::Refused(reason) => println!("{reason}"),
+            // Resuming again would be refused again, as the client is where it was.
+            ServerMessage::ResumeRefused(reason) => {
+                println!("{reason}");
+
+                return Ok(None);
+            },
+            ServerMessage::Finished(receipt) => {
+                finished(&metadata, client.try_verify_final_receipt(&receipt, &match_hash).map_err(invalid_data)?)?;
+
+                break;
+            },
+            ServerMessage::Forfeit(forfeit) => {
+                let winner = client.try_verify_forfeit(&forfeit, &referee, metadata.first_player).map_err(invalid_data)?;
+                forfeited(&metadata, &forfeit, winner);
+
+                break;
+            },
+            ending @ (ServerMessage::Resigned { .. } | ServerMessage::Drawn { .. }) => {
+                // A server that ends the game for a player is not played on any longer.
+                if !ended_early(&metadata, client.move_index, &ending) {
+                    return Ok(None);
+                }
+
+                break;
+            },
+            _ => return Err(invalid_data("already seated in a game"))
+        }
+    }
+
+    Ok(Some(*metadata))
+}
+
+// Follows the game as a spectator, verifying every receipt just like the players do.
+// Fails if the game is not being played, or the connection to the server is lost.
+pub async fn watch(address: &str, game_id: GameId) -> io::Result<()> {
+    let mut connection = connect(address).await?;
+
+    connection.send(&ClientMessage::Watch(game_id)).await?;
+
+    let (metadata, pin, receipt, referee) = match connection.recv().await? {
+        ServerMessage::Spectating { metadata, pin, receipt, referee, .. } => (metadata, pin, receipt, referee),
+        ServerMessage::Refused(reason) => return Err(io::Error::new(io::ErrorKind::NotFound, reason)),
+        _ => return Err(invalid_data("expected to watch a game"))
+    };
+
+    let match_hash = metadata.hash();
+
+    let mut client = Client::try_from_init_receipt(&receipt, &match_hash).map_err(invalid_data)?;
+    client.try_pin(pin).map_err(invalid_data)?;
+
+    if client.game_id != game_id {
+        return Err(invalid_data("the server sent the receipts of another game"));
+    }
+
+    println!(
+        "Watching {} ({}) against {} ({}).",
+        metadata.player_a.name, metadata.player_a.symbol,
+        metadata.player_b.name, metadata.player_b.symbol
+    );
+
+    let renderer = renderer(&metadata);
+
+    let mut boards = vec![metadata.initial_game()];
+    println!("{}", renderer.render(&boards[0], None));
+
+    loop {
+        match connection.recv().await? {
+            ServerMessage::Move(receipt) => {
+                played(&metadata, &renderer, &mut boards, client.try_verify_receipt(&receipt).map_err(invalid_data)?);
+            },
+            ServerMessage::TakenBack { move_index, .. } => {
+                taken_back(&renderer, &mut boards, &mut client, move_index)?;
+            },
+            ServerMessage::Chat(chat) => chatted(&metadata, &chat),
+            ServerMessage::Finished(receipt) => {
+                finished(&metadata, client.try_verify_final_receipt(&receipt, &match_hash).map_err(invalid_data)?)?;
+
+                break;
+            },
+            ServerMessage::Forfeit(forfeit) => {
+                let winner = client.try_verify_forfeit(&forfeit, &referee, metadata.first_player).map_err(invalid_data)?;
+                forfeited(&metadata, &forfeit, winner);
+
+                break;
+            },
+            ending @ (ServerMessage::Resigned { .. } | ServerMessage::Drawn { .. }) => {
+                ended_early(&metadata, client.move_index, &ending);
+
+                break;
+            },
+            _ => return Err(invalid_data("spectators only receive receipts"))
+        }
+    }
+
+    Ok(())
+}
+
+fn renderer(metadata: &MatchMetadata) -> Renderer {
+    Renderer::new(Style {
+        frame: Frame::Ascii,
+        glyphs: metadata.glyphs()
+    })
+}
+
+// `boards` holds the board after every accepted move, to go back to on a take-back.
+fn played(metadata: &MatchMetadata, renderer: &Renderer, boards: &mut Vec<TicTacToe>, response: VmResponse) {
+    match response {
+        VmResponse::Accepted { game, player, point, .. } => {
+            let player = metadata.player(player);
+            println!("{} ({}) played {}.", player.name, player.symbol, point);
+
+            boards.push(game);
+            println!("{}", renderer.render(&game, Some(point)));
+        },
+        VmResponse::Rejected { error, .. } => println!("{error}")
+    }
+}
+
+fn taken_back(renderer: &Renderer, boards: &mut Vec<TicTacToe>, client: &mut Client, move_index: u32) -> io::Result<()> {
+    client.try_take_back(move_index).map_err(invalid_data)?;
+    boards.truncate(move_index as usize + 1);
+
+    println!("The players agreed to take back the last two moves.");
+    println!("{}", renderer.render(&boards[move_index as usize], None));
+
+    Ok(())
+}
+
+fn sign_chat(key: &SigningKey, game_id: &GameId, move_index: u32, player: Player, text: String) -> ChatMessage {
+    let message = chat::chat_message(game_id, move_index, player, &text);
+
+    ChatMessage {
+        game_id: *game_id,
+        move_index,
+        player,
+        text,
+        signature: key.sign(&message).to_bytes().to_vec()
+    }
+}
+
+// Shows the chat message if it was signed by the player it names, with the
+// key that they were seated with, or marked as unsigned if they were seated
+// without one, and drops it otherwise.
+fn chatted(metadata: &MatchMetadata, chat: &ChatMessage) {
+    let info = metadata.player(chat.player);
+
+    let mark = match info.public_key {
+        Some(public_key) if chat.verify(&public_key) => "",
+        None if chat.text.len() <= MAX_CHAT_LENGTH => " (unsigned)",
+        _ => {
+            println!("Dropped a chat message that was not signed by {}.", info.name);
+
+            return;
+        }
+    };
+
+    if chat.game_id == metadata.game_id {
+        println!("{} ({}){mark}: {}", info.name, info.symbol, chat.text);
+    } else {
+        println!("Dropped a chat message that was not sent in this game.");
+    }
+}
+
+// Fails if the result proves that the game is still in progress.
+fn finished(metadata: &MatchMetadata, journal: GameJournal) -> io::Result<()> {
+    match journal.state {
+        State::Winner(player) => println!(
+            "Verified that {} won in {} moves.",
+            metadata.player(player).name,
+            journal.move_count
+        ),
+        State::Stalemate => println!("Verified that the game ended in a stalemate."),
+        State::InProgress => return Err(invalid_data("the server ended a game that is still in progress"))
+    }
+
+    Ok(())
+}
+
+fn forfeited(metadata: &MatchMetadata, forfeit: &Forfeit, winner: Player) {
+    println!(
+        "{} ran out of time after {} seconds, so {} wins. The forfeit was signed by the server.",
+        metadata.player(forfeit.player).name,
+        forfeit.clock.move_limit_secs,
+        metadata.player(winner).name
+    );
+}
+
+// Tells that the game ended before it was over, with the resignation of
+// `resigned` or a draw that the players agreed to, which nothing proves.
+// Signed over the move index that the client has verified, which is the one
+// of the server as long as the move on turn has not been made.
+fn sign_concession(key: &SigningKey, game_id: &GameId, move_index: u32, concession: Concession) -> Vec<u8> {
+    key.sign(&auth::concession_message(concession, game_id, move_index)).to_bytes().to_vec()
+}
+
+// Shows how the game ended early, once every player that ended it has signed
+// it with the key that they were seated with, at this point of the game.
+// Returns whether they did, as otherwise the server made it up.
+fn ended_early(metadata: &MatchMetadata, move_index: u32, ending: &ServerMessage) -> bool {
+    let (concession, signatures) = match ending {
+        ServerMessage::Resigned { player, signature } => (Concession::Resign, vec![(*player, signature)]),
+        ServerMessage::Drawn { signatures: [a, b] } => (Concession::Draw, vec![(Player::A, a), (Player::B, b)]),
+        _ => unreachable!("only resignations and draws end a game early")
+    };
+
+    for (player, signature) in signatures {
+        let info = metadata.player(player);
+
+        if !auth::is_conceded(info.public_key, signature.as_deref(), concession, &metadata.game_id, move_index) {
+            println!("The server ended the game for {}, who did not sign it, so it cannot be trusted.", info.name);
+
+            return false;
+        }
+    }
+
+    match ending {
+        ServerMessage::Resigned { player, .. } => println!(
+            "{} resigned, so {} wins.",
+            metadata.player(*player).name,
+            metadata.player(player.flip()).name
+        ),
+        _ => println!("The players agreed to a draw.")
+    }
+
+    true
+}
+
+// Connects to the server and greets it, see `protocol::greet`. Fails with
+// why if the server runs a build whose receipts this one cannot verify.
+async fn connect(address: &str) -> io::Result<Connection> {
+    let stream = TcpStream::connect(address).await?;
+    let mut connection = Connection::new(stream)?;
+
+    protocol::greet(&mut connection).await.map_err(cannot_play)?;
+
+    Ok(connection)
+}
+
+// Connects to the server again and takes the seat of `token` back, from the
+// head of the hash chain that the client verified last, for as long as it takes
+// the server to accept the connection. The server can have been restarted
+// with another build meanwhile, so it is greeted again, and given up on if it cannot be played on.
+async fn resume(address: &str, token: SessionToken, received: u32, chain_hash: Digest) -> io::Result<Connection> {
+    loop {
+        time::sleep(RECONNECT_INTERVAL).await;
+
+        let Ok(mut connection) = TcpStream::connect(address).await.and_then(Connection::new) else {
+            continue;
+        };
+
+        match protocol::greet(&mut connection).await {
+            Ok(_) => { },
+            Err(error) if error.kind() == io::ErrorKind::InvalidData => return Err(cannot_play(error)),
+            Err(_) => continue
+        }
+
+        if connection.send(&ClientMessage::Resume { token, received, chain_hash }).await.is_ok() {
+            return Ok(connection);
+        }
+    }
+}
+
+fn cannot_play(error: io::Error) -> io::Error {
+    match error.kind() {
+        io::ErrorKind::InvalidData => invalid_data(format!("cannot play on this server: {error}")),
+        _ => error
+    }
+}
+
+// Runs `f` on the blocking pool, such as to read the input of the player.
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> io::Result<T> {
+    task::spawn_blocking(f).await.map_err(|error| io::Error::new(io::ErrorKind::Other, error))
+}
+
+// Asks the player which of the games proven by the same guest build to join,
+// if any. Otherwise a new game is opened, or the list of games is refreshed.
+fn choose_game(games: &[OpenGame]) -> io::Result<ClientMessage> {
+    let terms = Terms::local();
+    let games: Vec<_> = games.iter()
+        .filter(|game| game.terms.image_id == terms.image_id)
+        .collect();
+
+    for (index, game) in games.iter().enumerate() {
+        println!(
+            "{}: {} ({}), {:?} moves first",
+            index + 1, game.host.name, game.host.symbol, game.terms.first_player
+        );
+    }
+
+    loop {
+        print!("Number of the game to join, \"r\" to refresh or nothing to open a game: ");
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+
+        if io::stdin().read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        match line.trim() {
+            "" => {
+                println!("Waiting for another player to join...");
+
+                return Ok(ClientMessage::Open(terms));
+            },
+            "r" => return Ok(ClientMessage::ListGames),
+            number => {
+                let game = number.parse::<usize>().ok()
+                    .and_then(|number| games.get(number.checked_sub(1)?));
+
+                if let Some(game) = game {
+                    return Ok(ClientMessage::Join(game.id));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::env;
+
+use game::{TicTacToe, Player, Point, render::Style};
+
+const RESET: &str = "\x1b[0m";
+const PLAYER_A_COLOR: &str = "\x1b[31m";
+const PLAYER_B_COLOR: &str = "\x1b[34m";
+const HIGHLIGHT: &str = "\x1b[1;7m";
+
+pub struct Renderer {
+    style: Style,
+    color: bool
+}
+
+impl Renderer {
+    pub fn new(style: Style) -> Self {
+        Self {
+            style,
+            color: color_enabled()
+        }
+    }
+
+    pub fn render(&self, game: &TicTacToe, last_move: Option<Point>) -> String {
+        let glyphs = &self.style.glyphs;
+
+        game.render_with(self.style.frame, glyphs.width, |point, player| {
+            let glyph = glyphs.get(player);
+
+            if !self.color {
+                return glyph.to_string();
+            }
+
+            let color = match player {
+                Some(Player::A) => PLAYER_A_COLOR,
+                Some(Player::B) => PLAYER_B_COLOR,
+                None => return glyph.to_string()
+            };
+
+            if last_move == Some(point) {
+                format!("{HIGHLIGHT}{color}{glyph}{RESET}")
+            } else {
+                format!("{color}{glyph}{RESET}")
+            }
+        })
+    }
+}
+
+// Colors are enabled unless the NO_COLOR environment variable
+// is set to a non-empty value (https://no-color.org).
+pub fn color_enabled() -> bool {
+    !matches!(env::var_os("NO_COLOR"), Some(value) if !value.is_empty())
+}
@@ -0,0 +1,143 @@
+use axum::{
+    Router, Json,
+    routing::{get, post},
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response}
+};
+use serde::{Serialize, Deserialize};
+use tokio::task;
+use game::{Point, metadata::GameId};
+
+use crate::games::{self, Games, GameError, EncodedReceipt, NewGame};
+
+#[derive(Serialize, Deserialize)]
+pub struct CreatedGame {
+    pub game_id: String,
+    pub receipt: EncodedReceipt
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Move {
+    pub x: usize,
+    pub y: usize
+}
+
+pub struct ApiError(StatusCode, String);
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String
+}
+
+// `POST /games`, `POST /games/:id/moves` and `GET /games/:id/receipts/:n`,
+// where `n` is the index of a move.
+pub fn router(games: Games) -> Router {
+    Router::new()
+        .route("/games", post(create_game))
+        .route("/games/:id/moves", post(make_move))
+        .route("/games/:id/receipts/:n", get(get_receipt))
+        .with_state(games)
+}
+
+async fn create_game(
+    State(games): State<Games>,
+    body: Option<Json<NewGame>>
+) -> Result<(StatusCode, Json<CreatedGame>), ApiError> {
+    let Json(NewGame { player_a, player_b }) = body.unwrap_or_default();
+
+    let metadata = games::new_match(player_a, player_b);
+    let game_id = hex::encode(metadata.game_id);
+
+    let receipt = request(games, move |games| games.create(metadata)).await?;
+
+    Ok((StatusCode::CREATED, Json(CreatedGame { game_id, receipt: EncodedReceipt::from(&receipt) })))
+}
+
+async fn make_move(
+    State(games): State<Games>,
+    Path(id): Path<String>,
+    Json(Move { x, y }): Json<Move>
+) -> Result<(StatusCode, Json<EncodedReceipt>), ApiError> {
+    let game_id = parse_game_id(&id)?;
+    let receipt = request(games, move |games| games.play(game_id, Point::new(x, y))).await?;
+
+    Ok((StatusCode::CREATED, Json(EncodedReceipt::from(&receipt))))
+}
+
+async fn get_receipt(
+    State(games): State<Games>,
+    Path((id, n)): Path<(String, u32)>
+) -> Result<Json<EncodedReceipt>, ApiError> {
+    let game_id = parse_game_id(&id)?;
+    let receipt = request(games, move |games| games.receipt(game_id, n)).await?;
+
+    Ok(Json(EncodedReceipt::from(&receipt)))
+}
+
+// Runs a request against the games on the blocking pool, since it may have to wait for a proof.
+async fn request<T: Send + 'static>(
+    games: Games,
+    request: impl FnOnce(&Games) -> Result<T, GameError> + Send + 'static
+) -> Result<T, ApiError> {
+    task::spawn_blocking(move || request(&games))
+        .await
+        .map_err(|error| ApiError(StatusCode::INTERNAL_SERVER_ERROR, error.to_string()))?
+        .map_err(ApiError::from)
+}
+
+fn parse_game_id(id: &str) -> Result<GameId, ApiError> {
+    hex::decode(id).ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ApiError(StatusCode::BAD_REQUEST, "Game ID must be 32 hex encoded bytes.".into()))
+}
+
+impl From<GameError> for ApiError {
+    fn from(error: GameError) -> Self {
+        let status = match error {
+            GameError::NotFound | GameError::NoSuchReceipt(_) => StatusCode::NOT_FOUND,
+            GameError::Ended => StatusCode::CONFLICT,
+            GameError::Prover(_) | GameError::Storage(_) | GameError::Stopped => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+
+        Self(status, error.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(ErrorBody { error: self.1 })).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn status(request: Request<Body>) -> StatusCode {
+        router(Games::spawn()).oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn unknown_games_are_not_found() {
+        let uri = format!("/games/{}/receipts/0", hex::encode([1; 32]));
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+
+        assert_eq!(status(request).await, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn malformed_game_ids_are_bad_requests() {
+        let request = Request::post("/games/abc/moves")
+            .header("Content-Type", "application/json")
+            .body(Body::from(r#"{"x":1,"y":1}"#))
+            .unwrap();
+
+        assert_eq!(status(request).await, StatusCode::BAD_REQUEST);
+    }
+}
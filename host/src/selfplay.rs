@@ -0,0 +1,132 @@
+use std::{any::Any, fmt, fs, io, path::Path, panic::{self, AssertUnwindSafe}, time::Instant};
+
+use serde::Serialize;
+use risc0_zkvm::Result;
+use game::{State, Player, metadata::MatchMetadata};
+
+use crate::{server::Server, hosting, bot::{Bot, Strategy}, stats::{GameStats, MoveStats}};
+
+pub use crate::hosting::PlayedGame;
+
+// What came of the games that two bots played against each other, see `play`.
+#[derive(Serialize, Default, Debug)]
+pub struct Outcomes {
+    pub games: usize,
+    pub wins_a: usize,
+    pub wins_b: usize,
+    pub stalemates: usize,
+    // Of the games in which a receipt failed to be proven or verified.
+    pub failures: Vec<String>
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    #[serde(flatten)]
+    outcomes: &'a Outcomes,
+    moves: &'a [MoveStats],
+    total: MoveStats
+}
+
+// Plays `games` games between bots with the given strategies end to end,
+// proving the initial state, every move and the result of each game and having
+// the clients of both bots verify them, with what it took recorded in `stats`.
+// A failed game is counted and the next one started, to soak test the whole
+// pipeline with.
+pub fn play(games: usize, strategies: [Strategy; 2], stats: &mut GameStats) -> Outcomes {
+    let bots = [
+        Bot { player: Player::A, strategy: strategies[0] },
+        Bot { player: Player::B, strategy: strategies[1] }
+    ];
+
+    let mut outcomes = Outcomes::default();
+
+    for game in 1..=games {
+        let start = Instant::now();
+
+        let metadata = MatchMetadata {
+            game_id: rand::random(),
+            nonce: rand::random(),
+            ..MatchMetadata::default()
+        };
+
+        let result = match panic::catch_unwind(AssertUnwindSafe(|| play_game(metadata, bots, stats))) {
+            Ok(result) => result.map(|played| played.server.game.state()).map_err(|error| error.to_string()),
+            Err(panic) => Err(panic_message(panic))
+        };
+
+        match &result {
+            Ok(State::Winner(player)) => println!("Game {game}: player {player:?} won in {:.2?}.", start.elapsed()),
+            Ok(_) => println!("Game {game}: stalemate in {:.2?}.", start.elapsed()),
+            Err(error) => println!("Game {game} failed: {error}")
+        }
+
+        outcomes.record(result);
+    }
+
+    outcomes
+}
+
+// Plays a game between `bots` with the given terms, see `hosting::play`.
+pub fn play_game(metadata: MatchMetadata, mut bots: [Bot; 2], stats: &mut GameStats) -> Result<PlayedGame> {
+    hosting::play(Server::new(metadata), &mut bots, stats)
+}
+
+impl Outcomes {
+    pub fn record(&mut self, result: std::result::Result<State, String>) {
+        self.games += 1;
+
+        match result {
+            Ok(State::Winner(Player::A)) => self.wins_a += 1,
+            Ok(State::Winner(Player::B)) => self.wins_b += 1,
+            Ok(_) => self.stalemates += 1,
+            Err(error) => self.failures.push(error)
+        }
+    }
+
+    // Along with what it took to prove and verify the moves of all games.
+    pub fn write_json(&self, stats: &GameStats, path: impl AsRef<Path>) -> io::Result<()> {
+        let report = Report { outcomes: self, moves: &stats.moves, total: stats.total() };
+
+        fs::write(path, serde_json::to_string_pretty(&report).expect("outcomes are always serializable"))
+    }
+}
+
+impl fmt::Display for Outcomes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} games: player A won {}, player B won {}, {} stalemates, {} failed.",
+            self.games,
+            self.wins_a,
+            self.wins_b,
+            self.stalemates,
+            self.failures.len()
+        )
+    }
+}
+
+// The message of a failed verification, which the clients panic with.
+pub(crate) fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => panic.downcast_ref::<&str>().map_or("unknown panic", |message| message).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_games_are_counted_with_their_errors() {
+        let mut outcomes = Outcomes::default();
+
+        outcomes.record(Ok(State::Winner(Player::B)));
+        outcomes.record(Ok(State::Stalemate));
+        outcomes.record(Err("Receipt verification failed!".to_string()));
+
+        assert_eq!((outcomes.games, outcomes.wins_a, outcomes.wins_b, outcomes.stalemates), (3, 0, 1, 1));
+        assert_eq!(outcomes.failures, ["Receipt verification failed!"]);
+        assert_eq!(outcomes.to_string(), "3 games: player A won 0, player B won 1, 1 stalemates, 1 failed.");
+    }
+}
@@ -0,0 +1,1351 @@
+use std::{fmt, fs, io, path::Path, time::Duration};
+
+use serde::{Serialize, Deserialize};
+use ed25519_dalek::{SigningKey, Signer};
+use tokio::task;
+use tracing::info_span;
+use methods::GuestMethod;
+use risc0_zkvm::{
+    serde::to_vec,
+    sha::Digest,
+    SessionReceipt, Result
+};
+use game::{
+    TicTacToe, Player, Point, MoveError, Journal,
+    events::GameObserver, metadata::{MatchMetadata, GameId}, commitment::Reveal,
+    simultaneous::RoundOutcome, auth::SignedMove,
+    disclosure::Region, fraud::MoveClaim, clock::{self, Clock, Forfeit}
+};
+
+use crate::{client::GuestPin, protocol::invalid_data, prover::{self, Input, Progress}};
+
+// Must be incremented whenever the layout of `SavedGame` changes
+// so that games saved by other versions are refused.
+pub const SAVE_VERSION: u32 = 1;
+
+pub struct Server {
+    pub game: TicTacToe,
+    pub move_index: u32,
+    pub chain_hash: Digest,
+    // All accepted moves in the order in which they were played.
+    pub moves: Vec<Point>,
+    // Moves that have been checked but not yet proven, see `queue_move`.
+    pub pending: Vec<Point>,
+    pub metadata: MatchMetadata,
+    pub last_move: Option<Point>,
+    // The receipts proven for the game, kept to be saved along with it.
+    pub receipts: Vec<SessionReceipt>,
+    observers: Vec<Box<dyn GameObserver>>
+}
+
+// A game as written to disk by `Server::save`, in bincode. The version is
+// always the first field so that it can be checked before the rest is decoded.
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    version: u32,
+    metadata: MatchMetadata,
+    game: TicTacToe,
+    move_index: u32,
+    chain_hash: Digest,
+    moves: Vec<Point>,
+    pending: Vec<Point>,
+    last_move: Option<Point>,
+    receipts: Vec<SessionReceipt>
+}
+
+// A move to be proven with `make_move`.
+#[derive(Clone, Copy, Debug)]
+pub struct MoveProof {
+    game: TicTacToe,
+    point: Point,
+    game_id: GameId,
+    move_index: u32,
+    chain_hash: Digest
+}
+
+// What it took to prove a receipt.
+#[derive(Clone, Copy, Debug)]
+pub struct ProofStats {
+    // Padded to a power of two in every segment, which is what the prover pays for.
+    pub cycles: u64,
+    pub segments: usize,
+    pub duration: Duration,
+    pub receipt_size: usize
+}
+
+impl Server {
+    pub fn new(metadata: MatchMetadata) -> Self {
+        let game = metadata.initial_game();
+
+        Self {
+            game,
+            move_index: 0,
+            chain_hash: game.state_hash(),
+            moves: vec![],
+            pending: vec![],
+            metadata,
+            last_move: None,
+            receipts: vec![],
+            observers: vec![]
+        }
+    }
+
+    // Writes the game to `path`, replacing it only once the whole game has been written.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let partial = path.as_ref().with_extension("partial");
+
+        fs::write(&partial, self.to_bytes()?)?;
+        fs::rename(partial, path)
+    }
+
+    // Reads a game written by `save`. Observers are not saved
+    // and have to be subscribed to the loaded game again.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    // The game as `save` writes it, for games kept along with other state.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let saved = SavedGame {
+            version: SAVE_VERSION,
+            metadata: self.metadata.clone(),
+            game: self.game,
+            move_index: self.move_index,
+            chain_hash: self.chain_hash,
+            moves: self.moves.clone(),
+            pending: self.pending.clone(),
+            last_move: self.last_move,
+            receipts: self.receipts.clone()
+        };
+
+        bincode::serialize(&saved).map_err(invalid_data)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let version: u32 = bincode::deserialize(bytes).map_err(invalid_data)?;
+
+        if version != SAVE_VERSION {
+            return Err(invalid_data(format!("unsupported save version {version}")));
+        }
+
+        let saved: SavedGame = bincode::deserialize(bytes).map_err(invalid_data)?;
+
+        Ok(Self {
+            game: saved.game,
+            move_index: saved.move_index,
+            chain_hash: saved.chain_hash,
+            moves: saved.moves,
+            pending: saved.pending,
+            metadata: saved.metadata,
+            last_move: saved.last_move,
+            receipts: saved.receipts,
+            observers: vec![]
+        })
+    }
+
+    // The guest build that this server proves moves with.
+    pub fn pin(&self) -> GuestPin {
+        GuestPin::local()
+    }
+
+    // The forfeit of the player on turn, who ran out of time on the `clock`,
+    // signed by the `referee` enforcing it.
+    pub fn forfeit(&self, clock: Clock, referee: &SigningKey) -> Forfeit {
+        let player = self.game.current_player();
+
+        let message = clock::forfeit_message(&self.metadata.game_id, self.move_index, &self.chain_hash, player, clock);
+
+        Forfeit {
+            game_id: self.metadata.game_id,
+            move_index: self.move_index,
+            chain_hash: self.chain_hash,
+            player,
+            clock,
+            signature: referee.sign(&message).to_bytes().to_vec()
+        }
+    }
+
+    pub fn subscribe(&mut self, observer: impl GameObserver + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    // Replaces the current game with the verified result of playing `point`
+    // and notifies all subscribed observers.
+    pub fn apply_move(&mut self, point: Point, game: TicTacToe) {
+        let player = self.game.current_player();
+        let previous = self.game.state();
+
+        self.chain_hash = game::chain_hash(
+            &self.chain_hash,
+            self.move_index,
+            point,
+            &game.state_hash()
+        );
+        self.move_index += 1;
+        self.moves.push(point);
+
+        self.game = game;
+        self.last_move = Some(point);
+
+        for observer in &mut self.observers {
+            observer.on_move(player, point, &self.game);
+
+            if previous != self.game.state() {
+                observer.on_state_change(previous, self.game.state());
+            }
+        }
+    }
+
+    // Returns to the state after the first `move_index` moves, which both players
+    // agreed to, dropping the receipts of the moves made or attempted since. The
+    // state is replayed from the initial one, so that the hash chain goes on
+    // from the head that the clients verified back then, as does `finalize`.
+    pub fn take_back(&mut self, move_index: u32) {
+        assert!(move_index <= self.move_index, "only moves that were made can be taken back");
+
+        self.moves.truncate(move_index as usize);
+        self.pending.clear();
+
+        let mut game = self.metadata.initial_game();
+        let mut chain_hash = game.state_hash();
+
+        for (index, point) in self.moves.iter().enumerate() {
+            game.make_move(*point).expect("accepted moves are legal");
+            chain_hash = game::chain_hash(&chain_hash, index as u32, *point, &game.state_hash());
+        }
+
+        let kept = self.receipts.iter()
+            .position(|receipt| matches!(Journal::decode(&receipt.journal), Ok(journal) if journal.move_index >= move_index))
+            .unwrap_or(self.receipts.len());
+        self.receipts.truncate(kept);
+
+        self.game = game;
+        self.move_index = move_index;
+        self.chain_hash = chain_hash;
+        self.last_move = self.moves.last().copied();
+    }
+
+    // Applies the verified result of a simultaneous round. Rounds are not
+    // recorded in the move list since `finalize_game` only plays sequential games.
+    pub fn apply_round(&mut self, points: [Point; 2], outcome: RoundOutcome, game: TicTacToe) {
+        let previous = self.game.state();
+
+        self.chain_hash = game::simultaneous::round_chain_hash(
+            &self.chain_hash,
+            self.move_index,
+            points,
+            &game.state_hash()
+        );
+        self.move_index += 2;
+
+        self.game = game;
+
+        for observer in &mut self.observers {
+            if outcome == RoundOutcome::Placed {
+                observer.on_move(Player::A, points[0], &self.game);
+                observer.on_move(Player::B, points[1], &self.game);
+            }
+
+            if previous != self.game.state() {
+                observer.on_state_change(previous, self.game.state());
+            }
+        }
+    }
+
+    // Proves `point` against the current game and reports what it took.
+    pub fn execute_move(&self, point: Point) -> Result<(SessionReceipt, ProofStats)> {
+        self.move_proof(point).prove()
+    }
+
+    // Like `execute_move`, calling `progress` after each segment has been proven,
+    // which can send it on to another thread to show a progress bar with.
+    pub fn execute_move_with_progress(
+        &self,
+        point: Point,
+        progress: impl FnMut(Progress)
+    ) -> Result<(SessionReceipt, ProofStats)> {
+        self.move_proof(point).prove_with_progress(progress)
+    }
+
+    // The inputs for proving `point` against the current game, to prove it on another thread.
+    pub fn move_proof(&self, point: Point) -> MoveProof {
+        MoveProof {
+            game: self.game,
+            point,
+            game_id: self.metadata.game_id,
+            move_index: self.move_index,
+            chain_hash: self.chain_hash
+        }
+    }
+
+    // Proves the move with the given index after the fact, replaying the move
+    // log to get the state and the hash chain before it. Fails for moves that
+    // have not been made.
+    pub fn prove_claim(&self, move_index: u32) -> Result<SessionReceipt> {
+        anyhow::ensure!((move_index as usize) < self.moves.len(), "move {move_index} has not been made");
+
+        let mut game = self.metadata.initial_game();
+        let mut chain_hash = game.state_hash();
+
+        for (index, point) in self.moves[..move_index as usize].iter().enumerate() {
+            game.make_move(*point).expect("logged moves are legal");
+            chain_hash = game::chain_hash(&chain_hash, index as u32, *point, &game.state_hash());
+        }
+
+        let proof = MoveProof {
+            game,
+            point: self.moves[move_index as usize],
+            game_id: self.metadata.game_id,
+            move_index,
+            chain_hash
+        };
+
+        proof.prove().map(|(receipt, _)| receipt)
+    }
+
+    // Proves that `claim` is invalid for `game`, which is the state it was made against.
+    pub fn prove_fraud(game: &TicTacToe, claim: MoveClaim) -> Result<SessionReceipt> {
+        let input = Input::new()
+            .add_words(&to_vec(&claim)?)
+            .add_bytes(&game.as_bytes());
+
+        Self::prove(input, GuestMethod::ProveFraud)
+    }
+
+    // Proves that `reveal` opens `commitment` before applying the revealed move.
+    pub fn execute_blind_move(
+        &self,
+        commitment: Digest,
+        reveal: Reveal
+    ) -> Result<(SessionReceipt, ProofStats)> {
+        let input = Input::new()
+            .add_words(&to_vec(&commitment)?)
+            .add_words(&to_vec(&reveal)?)
+            .add_words(&to_vec(&self.metadata.game_id)?)
+            .add_words(&to_vec(&self.move_index)?)
+            .add_words(&to_vec(&self.chain_hash)?)
+            .add_bytes(&self.game.as_bytes());
+
+        Self::prove_with_stats(input, GuestMethod::RevealMove)
+    }
+
+    // Proves that the move was signed by the player whose turn it is.
+    pub fn execute_signed_move(&self, signed: SignedMove) -> Result<(SessionReceipt, ProofStats)> {
+        let input = Input::new()
+            .add_words(&to_vec(&self.metadata)?)
+            .add_words(&to_vec(&signed)?)
+            .add_words(&to_vec(&self.move_index)?)
+            .add_words(&to_vec(&self.chain_hash)?)
+            .add_bytes(&self.game.as_bytes());
+
+        Self::prove_with_stats(input, GuestMethod::MakeSignedMove)
+    }
+
+    // Proves that the move is optimal for the current player, according to
+    // a minimax search of the current position run inside the VM.
+    pub fn execute_optimal_move(&self, point: Point) -> Result<(SessionReceipt, ProofStats)> {
+        let input = Input::new()
+            .add_words(&to_vec(&point)?)
+            .add_words(&to_vec(&self.metadata.game_id)?)
+            .add_words(&to_vec(&self.move_index)?)
+            .add_words(&to_vec(&self.chain_hash)?)
+            .add_bytes(&self.game.as_bytes());
+
+        Self::prove_with_stats(input, GuestMethod::MakeOptimalMove)
+    }
+
+    // The game with all pending moves played.
+    pub fn pending_game(&self) -> TicTacToe {
+        let mut game = self.game;
+
+        for point in &self.pending {
+            game.make_move(*point).expect("pending moves are checked when queued");
+        }
+
+        game
+    }
+
+    // Checks the move against the pending game and queues it to
+    // be proven together with other moves by `execute_batch`.
+    pub fn queue_move(&mut self, point: Point) -> std::result::Result<(), MoveError> {
+        self.pending_game().make_move(point)?;
+        self.pending.push(point);
+
+        Ok(())
+    }
+
+    // Queues the move like `queue_move` and returns the inputs for proving it on
+    // top of the moves queued before it, so that players can go on playing
+    // while their moves are being proven, see `prover::ProofQueue`.
+    pub fn queue_proof(&mut self, point: Point) -> std::result::Result<MoveProof, MoveError> {
+        let mut game = self.game;
+        let mut chain_hash = self.chain_hash;
+        let mut move_index = self.move_index;
+
+        for pending in &self.pending {
+            game.make_move(*pending).expect("pending moves are checked when queued");
+            chain_hash = game::chain_hash(&chain_hash, move_index, *pending, &game.state_hash());
+            move_index += 1;
+        }
+
+        self.queue_move(point)?;
+
+        Ok(MoveProof { game, point, game_id: self.metadata.game_id, move_index, chain_hash })
+    }
+
+    // Applies the verified result of the earliest move queued by `queue_proof`.
+    pub fn apply_pending_move(&mut self, point: Point, game: TicTacToe) {
+        assert_eq!(self.pending.first(), Some(&point), "moves are applied in the order they were queued");
+
+        self.pending.remove(0);
+        self.apply_move(point, game);
+    }
+
+    // Proves all pending moves in a single executor session.
+    pub fn execute_batch(&self) -> Result<(SessionReceipt, ProofStats)> {
+        let input = Input::new()
+            .add_words(&to_vec(&self.metadata.game_id)?)
+            .add_words(&to_vec(&self.pending)?)
+            .add_words(&to_vec(&self.move_index)?)
+            .add_words(&to_vec(&self.chain_hash)?)
+            .add_bytes(&self.game.as_bytes());
+
+        Self::prove_with_stats(input, GuestMethod::MakeMoves)
+    }
+
+    // Applies the verified moves of a batch one by one, which also
+    // notifies observers of each of them, and clears the pending moves.
+    pub fn apply_batch(&mut self, points: &[Point]) {
+        for point in points {
+            let mut game = self.game;
+            game.make_move(*point).expect("batch moves have been verified");
+
+            self.apply_move(*point, game);
+        }
+
+        self.pending.clear();
+    }
+
+    // Proves the move while only revealing salted state hashes.
+    pub fn execute_private_move(&self, point: Point, salt: &[u8; 32]) -> Result<(SessionReceipt, ProofStats)> {
+        let input = Input::new()
+            .add_words(&to_vec(&point)?)
+            .add_words(&to_vec(salt)?)
+            .add_words(&to_vec(&self.move_index)?)
+            .add_bytes(&self.game.as_bytes());
+
+        Self::prove_with_stats(input, GuestMethod::MakePrivateMove)
+    }
+
+    // Proves the contents of `region` of the private game with the given salt.
+    pub fn disclose(&self, region: Region, salt: &[u8; 32]) -> Result<SessionReceipt> {
+        let input = Input::new()
+            .add_words(&to_vec(salt)?)
+            .add_words(&to_vec(&region)?)
+            .add_bytes(&self.game.as_bytes());
+
+        Self::prove(input, GuestMethod::DiscloseRegion)
+    }
+
+    // Resolves a round of the simultaneous variant, where both
+    // players reveal the moves that they have committed to.
+    pub fn execute_round(
+        &self,
+        commitments: [Digest; 2],
+        reveals: [Reveal; 2]
+    ) -> Result<(SessionReceipt, ProofStats)> {
+        let input = Input::new()
+            .add_words(&to_vec(&commitments)?)
+            .add_words(&to_vec(&reveals)?)
+            .add_words(&to_vec(&self.metadata.game_id)?)
+            .add_words(&to_vec(&self.move_index)?)
+            .add_words(&to_vec(&self.chain_hash)?)
+            .add_bytes(&self.game.as_bytes());
+
+        Self::prove_with_stats(input, GuestMethod::ResolveMoves)
+    }
+
+    // Proves that the game can only end in a stalemate from here on.
+    pub fn prove_draw(&self) -> Result<SessionReceipt> {
+        let input = Input::new()
+            .add_bytes(&self.game.as_bytes());
+
+        Self::prove(input, GuestMethod::ProveDraw)
+    }
+
+    // Proves that the current player can win on their next
+    // move without revealing which move that is.
+    pub fn prove_threat(&self) -> Result<SessionReceipt> {
+        let input = Input::new()
+            .add_bytes(&self.game.as_bytes());
+
+        Self::prove(input, GuestMethod::ProveThreat)
+    }
+
+    // Proves the outcome of a coin flip for the first player of the match
+    // `game_id`, where `values` open the players' `commitments`.
+    pub fn flip_coin(
+        game_id: &GameId,
+        commitments: [Digest; 2],
+        values: [[u8; 32]; 2]
+    ) -> Result<SessionReceipt> {
+        let input = Input::new()
+            .add_words(&to_vec(game_id)?)
+            .add_words(&to_vec(&commitments)?)
+            .add_words(&to_vec(&values)?);
+
+        Self::prove(input, GuestMethod::FlipCoin)
+    }
+
+    // Proves that the finished match took at most `max_moves` moves.
+    pub fn prove_length(&self, max_moves: u32) -> Result<SessionReceipt> {
+        let input = Input::new()
+            .add_words(&to_vec(&self.metadata)?)
+            .add_words(&to_vec(&self.moves)?)
+            .add_words(&to_vec(&max_moves)?);
+
+        Self::prove(input, GuestMethod::ProveLength)
+    }
+
+    // Proves that the initial game was set up according to the match metadata.
+    pub fn prove_init(&self) -> Result<SessionReceipt> {
+        Self::prove_init_of(&self.metadata)
+    }
+
+    // Proves the result of the whole match from its initial state
+    // in a single receipt instead of one receipt per move.
+    pub fn finalize(&self) -> Result<SessionReceipt> {
+        Self::finalize_moves(&self.metadata, &self.moves)
+    }
+
+    // The async versions of `execute_move`, `prove_init` and `finalize`, which prove
+    // on the blocking pool of the runtime so that it keeps serving I/O meanwhile.
+    pub async fn execute_move_async(&self, point: Point) -> Result<(SessionReceipt, ProofStats)> {
+        let proof = self.move_proof(point);
+
+        task::spawn_blocking(move || proof.prove()).await?
+    }
+
+    pub async fn prove_init_async(&self) -> Result<SessionReceipt> {
+        let metadata = self.metadata.clone();
+
+        task::spawn_blocking(move || Self::prove_init_of(&metadata)).await?
+    }
+
+    pub async fn finalize_async(&self) -> Result<SessionReceipt> {
+        let metadata = self.metadata.clone();
+        let moves = self.moves.clone();
+
+        task::spawn_blocking(move || Self::finalize_moves(&metadata, &moves)).await?
+    }
+
+    fn prove_init_of(metadata: &MatchMetadata) -> Result<SessionReceipt> {
+        let _span = info_span!("init", game_id = %hex::encode(metadata.game_id)).entered();
+
+        let input = Input::new()
+            .add_words(&to_vec(metadata)?);
+
+        Self::prove(input, GuestMethod::InitGame)
+    }
+
+    fn finalize_moves(metadata: &MatchMetadata, moves: &[Point]) -> Result<SessionReceipt> {
+        let _span = info_span!("finalize", game_id = %hex::encode(metadata.game_id), moves = moves.len()).entered();
+
+        let input = Input::new()
+            .add_words(&to_vec(metadata)?)
+            .add_words(&to_vec(moves)?);
+
+        Self::prove(input, GuestMethod::FinalizeGame)
+    }
+
+    fn prove(input: Input, method: GuestMethod) -> Result<SessionReceipt> {
+        Self::prove_with_stats(input, method).map(|(receipt, _)| receipt)
+    }
+
+    fn prove_with_stats(input: Input, method: GuestMethod) -> Result<(SessionReceipt, ProofStats)> {
+        prover::prove(method, input)
+    }
+}
+
+impl MoveProof {
+    pub fn move_index(&self) -> u32 {
+        self.move_index
+    }
+
+    pub fn prove(&self) -> Result<(SessionReceipt, ProofStats)> {
+        self.prove_with_progress(|_| { })
+    }
+
+    pub fn prove_with_progress(&self, mut progress: impl FnMut(Progress)) -> Result<(SessionReceipt, ProofStats)> {
+        let _span = info_span!("move", game_id = %hex::encode(self.game_id), move_index = self.move_index).entered();
+
+        prover::prove_with_progress(GuestMethod::MakeMove, self.input()?, &mut progress)
+    }
+
+    // Runs the guest without proving it, for the journal that it commits.
+    pub fn execute(&self) -> Result<Vec<u8>> {
+        prover::execute(GuestMethod::MakeMove, self.input()?)
+    }
+
+    fn input(&self) -> Result<Input> {
+        Ok(Input::new()
+            .add_words(&to_vec(&self.point)?)
+            .add_words(&to_vec(&self.game_id)?)
+            .add_words(&to_vec(&self.move_index)?)
+            .add_words(&to_vec(&self.chain_hash)?)
+            .add_bytes(&self.game.as_bytes()))
+    }
+}
+
+impl fmt::Display for ProofStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Proved in {:.2?}: {} cycles in {} segment(s), {} byte receipt.",
+            self.duration, self.cycles, self.segments, self.receipt_size
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use rand::rngs::OsRng;
+    use game::{
+        State, VmResponse, engine, coin_flip, auth, metadata::PlayerInfo,
+        simultaneous::RoundResponse, fraud::Fraud
+    };
+
+    use super::*;
+    use crate::client::{Client, Refusal};
+
+    #[test]
+    #[should_panic = "Game state hash mismatch!"]
+    fn server_cannot_manipulate_game_state() {
+        let mut server = Server::new(MatchMetadata::default());
+
+        let mut player_a = Client::new();
+        let mut player_b = Client::new();
+
+        let (receipt, _) = server.execute_move(Point::new(1, 1)).unwrap();
+
+        player_a.verify_receipt(&receipt);
+        let resp = player_b.verify_receipt(&receipt);
+
+        let VmResponse::Accepted { game, .. } = resp else {
+            panic!("move was rejected");
+        };
+        server.apply_move(Point::new(1, 1), game);
+
+        server.game.make_move(Point::new(2, 1)).unwrap();
+
+        let (receipt, _) = server.execute_move(Point::new(0, 1)).unwrap();
+
+        player_a.verify_receipt(&receipt);
+    }
+
+    #[test]
+    #[should_panic = "Game state hash mismatch!"]
+    fn server_cannot_send_an_old_receipt() {
+        let mut server = Server::new(MatchMetadata::default());
+
+        let mut player_a = Client::new();
+        let mut player_b = Client::new();
+
+        let (receipt, _) = server.execute_move(Point::new(1, 1)).unwrap();
+
+        player_a.verify_receipt(&receipt);
+        let resp = player_b.verify_receipt(&receipt);
+
+        let VmResponse::Accepted { game, .. } = resp else {
+            panic!("move was rejected");
+        };
+        server.apply_move(Point::new(1, 1), game);
+
+        server.execute_move(Point::new(0, 1)).unwrap();
+
+        player_a.verify_receipt(&receipt);
+    }
+
+    #[test]
+    fn refused_receipt_leaves_the_client_as_it_was() {
+        let server = Server::new(MatchMetadata::default());
+
+        let mut client = Client::new();
+        let (receipt, _) = server.execute_move(Point::new(1, 1)).unwrap();
+
+        assert!(client.try_verify_receipt(&receipt).is_ok());
+
+        let state_hash = client.state_hash;
+        let refusal = client.try_verify_receipt(&receipt).unwrap_err();
+
+        assert_eq!(refusal.to_string(), "Game state hash mismatch!");
+        assert_eq!((client.state_hash, client.move_index), (state_hash, 1));
+    }
+
+    #[test]
+    fn init_receipt_sets_up_the_agreed_game() {
+        let metadata = MatchMetadata::default().with_first_player(Player::B);
+        let mut server = Server::new(metadata);
+
+        let receipt = server.prove_init().unwrap();
+        let mut player = Client::from_init_receipt(&receipt, &server.metadata.hash());
+
+        assert_eq!(player.state_hash, TicTacToe::starting_with(Player::B).state_hash());
+
+        let (receipt, _) = server.execute_move(Point::new(1, 1)).unwrap();
+
+        let VmResponse::Accepted { game, player: mover, .. } = player.verify_receipt(&receipt) else {
+            panic!("move was rejected");
+        };
+        assert_eq!(mover, Player::B);
+
+        server.apply_move(Point::new(1, 1), game);
+    }
+
+    #[test]
+    #[should_panic(expected = "Guest build mismatch!")]
+    fn client_refuses_a_different_guest_build() {
+        let mut player = Client::new();
+
+        player.pin(GuestPin {
+            make_move_id: GuestMethod::ProveDraw.image_id().into(),
+            ..Server::new(MatchMetadata::default()).pin()
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn init_receipt_must_match_metadata() {
+        let server = Server::new(MatchMetadata::default());
+        let receipt = server.prove_init().unwrap();
+
+        let other = MatchMetadata::default().with_first_player(Player::B);
+        Client::from_init_receipt(&receipt, &other.hash());
+    }
+
+    #[test]
+    #[should_panic(expected = "Game state hash mismatch!")]
+    fn receipt_cannot_be_replayed_into_another_match() {
+        let server = Server::new(MatchMetadata::default().with_nonce([1; 16]));
+        let other = Server::new(MatchMetadata::default().with_nonce([2; 16]));
+
+        let receipt = other.prove_init().unwrap();
+        let mut player = Client::from_init_receipt(&receipt, &other.metadata.hash());
+
+        // Same board and move, but a different match.
+        let (receipt, _) = server.execute_move(Point::new(1, 1)).unwrap();
+        player.verify_receipt(&receipt);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game ID mismatch!")]
+    fn receipt_cannot_be_routed_to_another_game() {
+        let server = Server::new(MatchMetadata {
+            game_id: [1; 32],
+            ..MatchMetadata::default()
+        });
+        let mut player = Client::new();
+
+        let (receipt, _) = server.execute_move(Point::new(1, 1)).unwrap();
+        player.verify_receipt(&receipt);
+    }
+
+    #[test]
+    fn illegal_move_produces_verifiable_rejection() {
+        let mut server = Server::new(MatchMetadata::default());
+
+        let mut player_a = Client::new();
+        let mut player_b = Client::new();
+
+        let (receipt, _) = server.execute_move(Point::new(1, 1)).unwrap();
+
+        player_a.verify_receipt(&receipt);
+        let resp = player_b.verify_receipt(&receipt);
+
+        let VmResponse::Accepted { game, .. } = resp else {
+            panic!("move was rejected");
+        };
+        server.apply_move(Point::new(1, 1), game);
+
+        let (receipt, _) = server.execute_move(Point::new(1, 1)).unwrap();
+
+        player_a.verify_receipt(&receipt);
+        let resp = player_b.verify_receipt(&receipt);
+
+        assert!(matches!(
+            resp,
+            VmResponse::Rejected { error: MoveError::CellOccupied, .. }
+        ));
+
+        // The rejection must not advance the state tracked by the clients.
+        let (receipt, _) = server.execute_move(Point::new(0, 0)).unwrap();
+
+        player_a.verify_receipt(&receipt);
+        player_b.verify_receipt(&receipt);
+    }
+
+    #[test]
+    fn taking_back_moves_returns_to_the_same_hash_chain() {
+        let metadata = MatchMetadata { game_id: [1; 32], ..MatchMetadata::default() };
+        let mut server = Server::new(metadata.clone());
+        let mut replayed = Server::new(metadata);
+
+        for (index, point) in [(1, 1), (0, 0), (2, 2), (0, 2)].into_iter().enumerate() {
+            let point = Point::new(point.0, point.1);
+
+            let mut game = server.game;
+            game.make_move(point).unwrap();
+            server.apply_move(point, game);
+
+            if index < 2 {
+                replayed.apply_move(point, game);
+            }
+        }
+
+        server.take_back(2);
+
+        assert_eq!(server.moves, replayed.moves);
+        assert_eq!(server.move_index, 2);
+        assert_eq!(server.chain_hash, replayed.chain_hash);
+        assert_eq!(server.game.state_hash(), replayed.game.state_hash());
+        assert_eq!(server.last_move, Some(Point::new(0, 0)));
+    }
+
+    #[test]
+    fn saved_games_are_loaded_as_they_were() {
+        let path = env::temp_dir().join(format!("zk-tic-tac-toe-{}.game", std::process::id()));
+
+        let mut server = Server::new(MatchMetadata { game_id: [1; 32], ..MatchMetadata::default() });
+
+        for point in [(1, 1), (0, 0)] {
+            let point = Point::new(point.0, point.1);
+
+            let mut game = server.game;
+            game.make_move(point).unwrap();
+
+            server.apply_move(point, game);
+        }
+
+        server.save(&path).unwrap();
+        let loaded = Server::load(&path).unwrap();
+
+        assert_eq!(loaded.metadata, server.metadata);
+        assert_eq!(loaded.moves, server.moves);
+        assert_eq!(loaded.move_index, 2);
+        assert_eq!(loaded.chain_hash, server.chain_hash);
+        assert_eq!(loaded.last_move, Some(Point::new(0, 0)));
+        assert_eq!(loaded.game.state_hash(), server.game.state_hash());
+
+        // Files written by other versions are refused instead of being misread.
+        std::fs::write(&path, 0u32.to_le_bytes()).unwrap();
+        assert_eq!(Server::load(&path).err().unwrap().kind(), io::ErrorKind::InvalidData);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn saved_receipts_replay_the_game() {
+        let path = env::temp_dir().join(format!("zk-tic-tac-toe-{}.replay", std::process::id()));
+
+        let mut server = Server::new(MatchMetadata { game_id: [2; 32], ..MatchMetadata::default() });
+
+        for point in [(1, 1), (1, 1), (0, 0)] {
+            let point = Point::new(point.0, point.1);
+            let (receipt, _) = server.execute_move(point).unwrap();
+            server.receipts.push(receipt);
+
+            let mut game = server.game;
+
+            if game.make_move(point).is_ok() {
+                server.apply_move(point, game);
+            }
+        }
+
+        server.save(&path).unwrap();
+        let loaded = Server::load(&path).unwrap();
+
+        let mut client = Client::for_match(&loaded.metadata);
+        let responses: Vec<_> = loaded.receipts.iter()
+            .map(|receipt| client.verify_receipt(receipt))
+            .collect();
+
+        assert!(matches!(responses[1], VmResponse::Rejected { error: MoveError::CellOccupied, .. }));
+        assert_eq!(client.move_index, 2);
+        assert_eq!(client.state_hash, server.game.state_hash());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn observers_are_notified_of_moves() {
+        use std::{rc::Rc, cell::RefCell};
+
+        #[derive(Default)]
+        struct Recorder {
+            moves: Vec<(Player, Point)>,
+            states: Vec<State>
+        }
+
+        struct Observer(Rc<RefCell<Recorder>>);
+
+        impl GameObserver for Observer {
+            fn on_move(&mut self, player: Player, point: Point, _game: &TicTacToe) {
+                self.0.borrow_mut().moves.push((player, point));
+            }
+
+            fn on_state_change(&mut self, _previous: State, current: State) {
+                self.0.borrow_mut().states.push(current);
+            }
+        }
+
+        let recorder = Rc::new(RefCell::new(Recorder::default()));
+
+        let mut server = Server::new(MatchMetadata::default());
+        server.subscribe(Observer(recorder.clone()));
+
+        for point in [(0, 0), (0, 1), (1, 0), (1, 1), (2, 0)] {
+            let point = Point::new(point.0, point.1);
+
+            let mut game = server.game;
+            game.make_move(point).unwrap();
+
+            server.apply_move(point, game);
+        }
+
+        let recorder = recorder.borrow();
+
+        assert_eq!(recorder.moves.len(), 5);
+        assert_eq!(recorder.moves[0], (Player::A, Point::new(0, 0)));
+        assert_eq!(recorder.moves[1], (Player::B, Point::new(0, 1)));
+        assert_eq!(recorder.states, vec![State::Winner(Player::A)]);
+    }
+
+    #[test]
+    fn coin_flip_combines_both_values() {
+        let game_id = [1; 32];
+        let values = [[2; 32], [3; 32]];
+        let commitments = [
+            coin_flip::commitment(&game_id, Player::A, &values[0]),
+            coin_flip::commitment(&game_id, Player::B, &values[1])
+        ];
+
+        let receipt = Server::flip_coin(&game_id, commitments, values).unwrap();
+
+        assert_eq!(Client::verify_coin_flip(&receipt, &game_id, &commitments), Player::B);
+    }
+
+    #[test]
+    #[should_panic]
+    fn coin_flip_must_open_commitments() {
+        let values = [[2; 32], [3; 32]];
+        let commitments = [coin_flip::commitment(&[1; 32], Player::A, &values[0]), coin_flip::commitment(&[1; 32], Player::B, &[4; 32])];
+
+        Server::flip_coin(&[1; 32], commitments, values).unwrap();
+    }
+
+    #[test]
+    fn coin_flip_refuses_copied_commitments() {
+        let game_id = [1; 32];
+        let value = [2; 32];
+        let commitment = coin_flip::commitment(&game_id, Player::A, &value);
+
+        // Player B resends the commitment of player A and then reveals the value of player A.
+        assert!(Server::flip_coin(&game_id, [commitment; 2], [value; 2]).is_err());
+
+        // Nor does the value of player A open a commitment of player B.
+        assert_ne!(commitment, coin_flip::commitment(&game_id, Player::B, &value));
+        assert_ne!(commitment, coin_flip::commitment(&[2; 32], Player::A, &value));
+    }
+
+    #[test]
+    fn blind_move_reveals_commitment() {
+        let mut server = Server::new(MatchMetadata::default());
+        let mut player = Client::new();
+
+        let reveal = Reveal::new(Point::new(1, 1), [7; 32]);
+        let commitment = reveal.commitment(&server.metadata.game_id, Player::A, 0);
+
+        let (receipt, _) = server.execute_blind_move(commitment, reveal).unwrap();
+        let resp = player.verify_blind_receipt(&receipt, &commitment);
+
+        let VmResponse::Accepted { game, point, .. } = resp else {
+            panic!("move was rejected");
+        };
+        assert_eq!(point, Point::new(1, 1));
+
+        server.apply_move(point, game);
+    }
+
+    #[test]
+    #[should_panic]
+    fn blind_move_must_match_commitment() {
+        let server = Server::new(MatchMetadata::default());
+
+        let commitment = Reveal::new(Point::new(1, 1), [7; 32]).commitment(&server.metadata.game_id, Player::A, 0);
+        let reveal = Reveal::new(Point::new(0, 0), [7; 32]);
+
+        server.execute_blind_move(commitment, reveal).unwrap();
+    }
+
+    #[test]
+    fn blind_move_commitments_only_open_for_their_game_player_and_move() {
+        let server = Server::new(MatchMetadata::default());
+        let game_id = server.metadata.game_id;
+        let reveal = Reveal::new(Point::new(1, 1), [7; 32]);
+
+        // Committed for the second move of the game, by the other player, or in another game.
+        for commitment in [
+            reveal.commitment(&game_id, Player::A, 1),
+            reveal.commitment(&game_id, Player::B, 0),
+            reveal.commitment(&[9; 32], Player::A, 0)
+        ] {
+            assert!(server.execute_blind_move(commitment, reveal).is_err());
+        }
+    }
+
+    fn signed_move(key: &SigningKey, server: &Server, point: Point) -> SignedMove {
+        let message = auth::move_message(&server.metadata.game_id, server.move_index, point);
+
+        SignedMove::new(point, key.sign(&message).to_bytes().to_vec())
+    }
+
+    fn signed_server(keys: &[SigningKey; 2]) -> Server {
+        let metadata = MatchMetadata::new(
+            [1; 32],
+            PlayerInfo::new("Alice", "X").with_public_key(keys[0].verifying_key().to_bytes()),
+            PlayerInfo::new("Bob", "O").with_public_key(keys[1].verifying_key().to_bytes())
+        );
+
+        Server::new(metadata)
+    }
+
+    #[test]
+    fn signed_move_is_authorized_by_player_key() {
+        let keys = [SigningKey::generate(&mut OsRng), SigningKey::generate(&mut OsRng)];
+        let server = signed_server(&keys);
+
+        let receipt = server.prove_init().unwrap();
+        let mut player = Client::from_init_receipt(&receipt, &server.metadata.hash());
+
+        let signed = signed_move(&keys[0], &server, Point::new(1, 1));
+        let (receipt, _) = server.execute_signed_move(signed).unwrap();
+
+        let resp = player.verify_signed_receipt(&receipt, &server.metadata.hash());
+        assert!(matches!(resp, VmResponse::Accepted { player: Player::A, .. }));
+    }
+
+    #[test]
+    #[should_panic]
+    fn signed_move_by_other_player_is_rejected() {
+        let keys = [SigningKey::generate(&mut OsRng), SigningKey::generate(&mut OsRng)];
+        let server = signed_server(&keys);
+
+        // It is player A's turn but the move is signed with the key of player B.
+        let signed = signed_move(&keys[1], &server, Point::new(1, 1));
+        server.execute_signed_move(signed).unwrap();
+    }
+
+    #[test]
+    fn queued_moves_are_proven_on_top_of_each_other() {
+        let mut server = Server::new(MatchMetadata::default());
+
+        server.queue_proof(Point::new(0, 0)).unwrap();
+        let queued = server.queue_proof(Point::new(1, 1)).unwrap();
+
+        assert_eq!(queued.move_index(), 1);
+        assert_eq!(server.queue_proof(Point::new(0, 0)).err(), Some(MoveError::CellOccupied));
+
+        let mut game = server.game;
+        game.make_move(Point::new(0, 0)).unwrap();
+        server.apply_pending_move(Point::new(0, 0), game);
+
+        assert_eq!(format!("{queued:?}"), format!("{:?}", server.move_proof(Point::new(1, 1))));
+        assert_eq!(server.pending, vec![Point::new(1, 1)]);
+    }
+
+    #[test]
+    fn batch_commits_intermediate_hashes() {
+        let mut server = Server::new(MatchMetadata::default());
+        let mut player = Client::new();
+
+        for point in [(0, 0), (0, 1), (1, 0)] {
+            server.queue_move(Point::new(point.0, point.1)).unwrap();
+        }
+
+        assert_eq!(server.queue_move(Point::new(0, 0)), Err(MoveError::CellOccupied));
+
+        let (receipt, _) = server.execute_batch().unwrap();
+        let journal = player.verify_batch_receipt(&receipt);
+
+        assert_eq!(journal.points.len(), 3);
+        assert_eq!(journal.state_hashes.len(), 3);
+        assert_eq!(journal.rejected, None);
+
+        server.apply_batch(&journal.points);
+
+        assert_eq!(server.chain_hash, player.chain_hash);
+        assert_eq!(server.game.state_hash(), player.state_hash);
+
+        // Moves proven in a batch are taken back like those proven one by one.
+        server.take_back(1);
+        player.take_back(1);
+
+        assert_eq!(server.chain_hash, player.chain_hash);
+        assert_eq!(server.game.state_hash(), player.state_hash);
+    }
+
+    #[test]
+    fn batches_and_rounds_of_another_game_are_refused() {
+        let mut server = Server::new(MatchMetadata { game_id: [1; 32], ..MatchMetadata::default() });
+        let mut player = Client::new();
+
+        server.queue_move(Point::new(0, 0)).unwrap();
+
+        let (receipt, _) = server.execute_batch().unwrap();
+        let refusal = player.try_verify_batch_receipt(&receipt).unwrap_err();
+
+        assert_eq!(refusal.to_string(), "Game ID mismatch!");
+        assert_eq!(player.move_index, 0);
+
+        let reveals = [Reveal::new(Point::new(0, 0), [1; 32]), Reveal::new(Point::new(2, 2), [2; 32])];
+        let commitments = [reveals[0].commitment(&[1; 32], Player::A, 0), reveals[1].commitment(&[1; 32], Player::B, 1)];
+
+        let (receipt, _) = server.execute_round(commitments, reveals).unwrap();
+        let refusal = player.try_verify_round_receipt(&receipt, &commitments).unwrap_err();
+
+        assert_eq!(refusal.to_string(), "Game ID mismatch!");
+        assert_eq!(player.move_index, 0);
+    }
+
+    #[test]
+    fn optimal_move_is_proven() {
+        let mut server = Server::new(MatchMetadata::default());
+        let mut player = Client::new();
+
+        let point = engine::best_move(&server.game).unwrap();
+        let (receipt, _) = server.execute_optimal_move(point).unwrap();
+
+        let VmResponse::Accepted { game, .. } = player.verify_optimal_receipt(&receipt) else {
+            panic!("move was rejected");
+        };
+        server.apply_move(point, game);
+    }
+
+    #[test]
+    #[should_panic]
+    fn suboptimal_move_cannot_be_proven() {
+        let mut server = Server::new(MatchMetadata::default());
+
+        for point in [Point::new(1, 1), Point::new(0, 1)] {
+            let mut game = server.game;
+            game.make_move(point).unwrap();
+
+            server.apply_move(point, game);
+        }
+
+        // Player B's reply on the edge loses, but only if
+        // player A doesn't block that same row instead.
+        server.execute_optimal_move(Point::new(2, 1)).unwrap();
+    }
+
+    #[test]
+    fn private_move_reveals_only_hashes() {
+        let salt = [3; 32];
+        let server = Server::new(MatchMetadata::default());
+        let mut spectator = Client::with_initial_hash(TicTacToe::new().salted_hash(&salt));
+
+        let (receipt, _) = server.execute_private_move(Point::new(1, 1), &salt).unwrap();
+        let journal = spectator.verify_private_receipt(&receipt);
+
+        let mut game = TicTacToe::new();
+        game.make_move(Point::new(1, 1)).unwrap();
+
+        assert!(journal.accepted);
+        assert_eq!(journal.state, State::InProgress);
+        assert_eq!(journal.new_state_hash, game.salted_hash(&salt));
+    }
+
+    #[test]
+    fn disclosure_reveals_only_the_region() {
+        let salt = [3; 32];
+        let mut server = Server::new(MatchMetadata::default());
+        let mut spectator = Client::with_initial_hash(TicTacToe::new().salted_hash(&salt));
+
+        for point in [Point::new(1, 1), Point::new(0, 0)] {
+            let (receipt, _) = server.execute_private_move(point, &salt).unwrap();
+            spectator.verify_private_receipt(&receipt);
+
+            let mut game = server.game;
+            game.make_move(point).unwrap();
+            server.apply_move(point, game);
+        }
+
+        let receipt = server.disclose(Region::Row(1), &salt).unwrap();
+        let journal = spectator.verify_disclosure(&receipt);
+
+        assert_eq!(journal.cells, vec![
+            (Point::new(0, 1), None),
+            (Point::new(1, 1), Some(Player::A)),
+            (Point::new(2, 1), None)
+        ]);
+    }
+
+    #[test]
+    fn simultaneous_round_reveals_both_commitments() {
+        let mut server = Server::new(MatchMetadata::default());
+        let mut player = Client::new();
+
+        let reveals = [
+            Reveal::new(Point::new(0, 0), [1; 32]),
+            Reveal::new(Point::new(2, 2), [2; 32])
+        ];
+        let game_id = server.metadata.game_id;
+        let commitments = [reveals[0].commitment(&game_id, Player::A, 0), reveals[1].commitment(&game_id, Player::B, 1)];
+
+        let (receipt, _) = server.execute_round(commitments, reveals).unwrap();
+        let resp = player.verify_round_receipt(&receipt, &commitments);
+
+        let RoundResponse::Resolved { game, points, outcome, .. } = resp else {
+            panic!("round was rejected");
+        };
+        assert_eq!(outcome, RoundOutcome::Placed);
+
+        server.apply_round(points, outcome, game);
+
+        assert_eq!(server.game.get(Point::new(0, 0)), Some(Player::A));
+        assert_eq!(server.game.get(Point::new(2, 2)), Some(Player::B));
+        assert_eq!(server.chain_hash, player.chain_hash);
+    }
+
+    #[test]
+    fn simultaneous_rounds_refuse_copied_commitments() {
+        let server = Server::new(MatchMetadata::default());
+        let reveal = Reveal::new(Point::new(0, 0), [1; 32]);
+        let commitment = reveal.commitment(&server.metadata.game_id, Player::A, 0);
+
+        // Player B resends the commitment of player A and then reveals the move of player A.
+        assert!(server.execute_round([commitment; 2], [reveal; 2]).is_err());
+    }
+
+    #[test]
+    fn whole_game_receipt_matches_move_receipts() {
+        let mut server = Server::new(MatchMetadata::default());
+
+        let mut player_a = Client::new();
+        let mut player_b = Client::new();
+
+        for point in [(0, 0), (0, 1), (1, 0), (1, 1), (2, 0)] {
+            let (receipt, _) = server.execute_move(Point::new(point.0, point.1)).unwrap();
+
+            player_a.verify_receipt(&receipt);
+            let resp = player_b.verify_receipt(&receipt);
+
+            let VmResponse::Accepted { game, point, .. } = resp else {
+                panic!("move was rejected");
+            };
+            server.apply_move(point, game);
+        }
+
+        let receipt = server.finalize().unwrap();
+        let match_hash = server.metadata.hash();
+
+        player_a.verify_final_receipt(&receipt, &match_hash);
+        let journal = player_b.verify_final_receipt(&receipt, &match_hash);
+
+        assert_eq!(journal.move_count, 5);
+        assert_eq!(journal.state, State::Winner(Player::A));
+    }
+
+    #[test]
+    fn length_receipt_bounds_the_number_of_moves() {
+        let mut server = Server::new(MatchMetadata::default());
+        let mut player = Client::new();
+
+        for point in [(0, 0), (0, 1), (1, 0), (1, 1), (2, 0)] {
+            let (receipt, _) = server.execute_move(Point::new(point.0, point.1)).unwrap();
+
+            let VmResponse::Accepted { game, point, .. } = player.verify_receipt(&receipt) else {
+                panic!("move was rejected");
+            };
+            server.apply_move(point, game);
+        }
+
+        let receipt = server.prove_length(6).unwrap();
+        let journal = player.verify_length_receipt(&receipt, &server.metadata.hash());
+
+        assert_eq!(journal.max_moves, 6);
+        assert_eq!(journal.state, State::Winner(Player::A));
+        assert!(server.prove_length(4).is_err());
+    }
+
+    #[test]
+    fn challenged_claim_is_proven_from_the_move_log() {
+        let mut server = Server::new(MatchMetadata::default());
+        let mut player = Client::new();
+
+        for point in [Point::new(1, 1), Point::new(0, 0)] {
+            let claim = MoveClaim::new(&server.game, server.move_index, point).unwrap();
+            player.accept_claim(claim);
+
+            let mut game = server.game;
+            game.make_move(point).unwrap();
+            server.apply_move(point, game);
+        }
+
+        let receipt = server.prove_claim(1).unwrap();
+        player.verify_claim_receipt(&receipt, &player.claims[1]);
+
+        // Neither moves that were not made nor claims that were not accepted can be replayed.
+        assert!(server.prove_claim(2).is_err());
+        let replayed = player.replay_claims(server.metadata.initial_game(), 2).unwrap();
+        assert_eq!(replayed.state_hash(), server.game.state_hash());
+        assert!(player.replay_claims(server.metadata.initial_game(), 3).is_none());
+    }
+
+    #[test]
+    fn invalid_claim_is_proven_fraudulent() {
+        let game = TicTacToe::new();
+        let mut player = Client::new();
+
+        let mut claim = MoveClaim::new(&game, 0, Point::new(1, 1)).unwrap();
+        claim.new_state_hash = game.state_hash();
+        player.accept_claim(claim);
+
+        let receipt = Server::prove_fraud(&game, claim).unwrap();
+        assert_eq!(player.verify_fraud_receipt(&receipt, &claim), Fraud::WrongResult);
+    }
+
+    #[test]
+    fn fraud_proven_on_a_made_up_game_is_refused() {
+        let game = TicTacToe::new();
+        let mut player = Client::new();
+
+        let claim = MoveClaim::new(&game, 0, Point::new(1, 1)).unwrap();
+        player.accept_claim(claim);
+
+        // The honest claim does not start from a board that was never played.
+        let mut made_up = game;
+        made_up.make_move(Point::new(0, 0)).unwrap();
+
+        let receipt = Server::prove_fraud(&made_up, claim).unwrap();
+        assert!(matches!(player.try_verify_fraud_receipt(&receipt, &claim), Err(Refusal::Mismatch(_))));
+    }
+
+    #[test]
+    fn forfeits_are_signed_by_the_referee() {
+        let server = Server::new(MatchMetadata::default().with_first_player(Player::B));
+        let referee = SigningKey::generate(&mut OsRng);
+
+        let forfeit = server.forfeit(Clock { move_limit_secs: 30 }, &referee);
+        assert_eq!(forfeit.player, Player::B);
+        assert!(!forfeit.verify(&SigningKey::generate(&mut OsRng).verifying_key().to_bytes()));
+
+        let mut client = Client::for_match(&server.metadata);
+        let winner = client.verify_forfeit(&forfeit, &referee.verifying_key().to_bytes(), Player::B);
+
+        assert_eq!(winner, Player::A);
+        assert_eq!(client.game_state, State::Winner(Player::A));
+    }
+}
@@ -0,0 +1,191 @@
+use std::{fmt, io, process::Command};
+
+use game::metadata::MatchMetadata;
+use verifier::{cosmwasm::{ExecuteMsg, QueryMsg, GameResponse, Wager}, export::ExportedReceipt};
+
+use crate::config::{SettlementConfig, ConfigError};
+
+// Submits the messages of the CosmWasm settlement contract in contracts/cosmwasm
+// through the command line interface of the chain, such as `wasmd`, which holds
+// the key that signs them, so that the host needs no Cosmos SDK of its own.
+pub struct Submitter {
+    cli: String,
+    contract: String,
+    from: String,
+    node: Option<String>,
+    chain_id: Option<String>
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    // The CLI could not be run, or the message could not be encoded.
+    Io(io::Error),
+    // The CLI failed, with what it printed to standard error, such as the
+    // contract refusing the message or the key missing.
+    Failed(String),
+    // What the CLI printed is not what a query returns.
+    Response(serde_json::Error)
+}
+
+impl From<io::Error> for SubmitError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl Submitter {
+    pub fn new(config: &SettlementConfig) -> Result<Self, ConfigError> {
+        Ok(Self {
+            cli: config.cli.clone(),
+            contract: config.contract.clone().ok_or(ConfigError::Settlement("the address of the contract"))?,
+            from: config.from.clone().ok_or(ConfigError::Settlement("a key to sign with"))?,
+            node: config.node.clone(),
+            chain_id: config.chain_id.clone()
+        })
+    }
+
+    // Commits to the terms of a match, which a result has to match to settle it,
+    // and to what the players stake on it, if anything.
+    pub fn commit(&self, metadata: &MatchMetadata, wager: Option<Wager>) -> Result<String, SubmitError> {
+        self.execute(&ExecuteMsg::commit(metadata, wager), &self.from, None)
+    }
+
+    // Locks the stake of the player whose key is `from`, see `Wager`.
+    pub fn stake(&self, metadata: &MatchMetadata, from: &str, wager: &Wager) -> Result<String, SubmitError> {
+        let funds = format!("{}{}", wager.amount, wager.denom);
+
+        self.execute(&ExecuteMsg::stake(metadata), from, Some(&funds))
+    }
+
+    // Returns the stake of the player whose key is `from` once the deadline of
+    // the wager has passed without the game being settled.
+    pub fn refund(&self, metadata: &MatchMetadata, from: &str) -> Result<String, SubmitError> {
+        self.execute(&ExecuteMsg::refund(metadata), from, None)
+    }
+
+    // The contract verifies the receipt itself before recording the winner
+    // and paying out the stakes.
+    pub fn settle(&self, exported: &ExportedReceipt) -> Result<String, SubmitError> {
+        self.execute(&ExecuteMsg::settle(exported)?, &self.from, None)
+    }
+
+    pub fn game(&self, metadata: &MatchMetadata) -> Result<GameResponse, SubmitError> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            data: GameResponse
+        }
+
+        let query = QueryMsg::Game { game_id: hex::encode(metadata.game_id) };
+
+        let mut command = Command::new(&self.cli);
+        command
+            .args(["query", "wasm", "contract-state", "smart", &self.contract])
+            .arg(serde_json::to_string(&query).expect("queries are always serializable"))
+            .args(["--output", "json"]);
+
+        if let Some(node) = &self.node {
+            command.args(["--node", node]);
+        }
+
+        let output = run(command)?;
+        let response: Response = serde_json::from_str(&output).map_err(SubmitError::Response)?;
+
+        Ok(response.data)
+    }
+
+    // The address of a key of the CLI, such as the account of a player.
+    pub fn address(&self, key: &str) -> Result<String, SubmitError> {
+        let mut command = Command::new(&self.cli);
+        command.args(["keys", "show", key, "--address"]);
+
+        Ok(run(command)?.trim().to_string())
+    }
+
+    // Returns what the CLI printed, the transaction as JSON.
+    fn execute(&self, msg: &ExecuteMsg, from: &str, funds: Option<&str>) -> Result<String, SubmitError> {
+        run(self.execute_command(msg, from, funds)?)
+    }
+
+    fn execute_command(&self, msg: &ExecuteMsg, from: &str, funds: Option<&str>) -> io::Result<Command> {
+        let mut command = Command::new(&self.cli);
+
+        command
+            .args(["tx", "wasm", "execute", &self.contract])
+            .arg(serde_json::to_string(msg)?)
+            .args(["--from", from, "--gas", "auto", "--yes", "--output", "json"]);
+
+        if let Some(funds) = funds {
+            command.args(["--amount", funds]);
+        }
+
+        if let Some(node) = &self.node {
+            command.args(["--node", node]);
+        }
+
+        if let Some(chain_id) = &self.chain_id {
+            command.args(["--chain-id", chain_id]);
+        }
+
+        Ok(command)
+    }
+}
+
+fn run(mut command: Command) -> Result<String, SubmitError> {
+    let output = command.output()?;
+
+    if !output.status.success() {
+        return Err(SubmitError::Failed(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "Failed to run the CLI of the chain: {}", error),
+            Self::Failed(output) => write!(f, "The CLI of the chain failed: {}", output),
+            Self::Response(error) => write!(f, "Unexpected response from the contract: {}", error)
+        }
+    }
+}
+
+impl std::error::Error for SubmitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Response(error) => Some(error),
+            Self::Failed(_) => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_are_submitted_through_the_cli() {
+        let config = SettlementConfig {
+            contract: Some("wasm1contract".into()),
+            from: Some("server".into()),
+            node: Some("http://localhost:26657".into()),
+            ..SettlementConfig::default()
+        };
+
+        let submitter = Submitter::new(&config).unwrap();
+        let msg = ExecuteMsg::Stake { game_id: "01".into() };
+
+        let command = submitter.execute_command(&msg, "alice", Some("100ustake")).unwrap();
+        let args: Vec<_> = command.get_args().map(|arg| arg.to_str().unwrap()).collect();
+
+        assert_eq!(command.get_program(), "wasmd");
+        assert_eq!(args[..7], [
+            "tx", "wasm", "execute", "wasm1contract", r#"{"stake":{"game_id":"01"}}"#, "--from", "alice"
+        ]);
+        assert!(args.ends_with(&["--amount", "100ustake", "--node", "http://localhost:26657"]));
+
+        let config = SettlementConfig { from: None, ..config };
+        assert!(matches!(Submitter::new(&config), Err(ConfigError::Settlement(_))));
+    }
+}
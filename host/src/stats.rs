@@ -0,0 +1,273 @@
+use std::{fmt, fs, io, path::Path, time::Duration};
+
+use serde::{Serialize, Serializer, Deserialize};
+use game::hasher::STATE_HASHER;
+
+use crate::{config::ProverBackend, server::ProofStats};
+
+// What it took to prove and verify each move of a game, to compare variants,
+// state hashers and provers with. Printed as a table, or written as JSON
+// along with the totals of the game.
+#[derive(Serialize, Clone, Debug)]
+pub struct GameStats {
+    pub variant: String,
+    pub state_hasher: &'static str,
+    pub prover: ProverBackend,
+    pub moves: Vec<MoveStats>
+}
+
+#[derive(Serialize, Clone, Copy, Default, Debug)]
+pub struct MoveStats {
+    pub move_index: u32,
+    // Padded to a power of two in every segment, see `ProofStats`.
+    pub cycles: u64,
+    pub segments: usize,
+    #[serde(rename = "proving_ms", serialize_with = "milliseconds")]
+    pub proving: Duration,
+    // By the client of one player.
+    #[serde(rename = "verification_ms", serialize_with = "milliseconds")]
+    pub verification: Duration,
+    pub receipt_size: usize
+}
+
+// The averages of a game per move, which `bench` compares the variants with,
+// along with those of other builds, such as with another state hasher.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Summary {
+    pub variant: String,
+    pub state_hasher: String,
+    pub prover: ProverBackend,
+    pub moves: usize,
+    pub cycles: u64,
+    pub proving_ms: f64,
+    pub verification_ms: f64,
+    pub receipt_size: usize
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    #[serde(flatten)]
+    stats: &'a GameStats,
+    total: MoveStats,
+    summary: Summary
+}
+
+// Only the summary of a report, to read the reports of other builds with.
+#[derive(Deserialize)]
+struct SummaryOf {
+    summary: Summary
+}
+
+impl GameStats {
+    pub fn new(variant: impl Into<String>, prover: ProverBackend) -> Self {
+        Self {
+            variant: variant.into(),
+            state_hasher: hasher_name(STATE_HASHER),
+            prover,
+            moves: vec![]
+        }
+    }
+
+    pub fn record(&mut self, move_index: u32, proof: ProofStats, verification: Duration) {
+        self.moves.push(MoveStats {
+            move_index,
+            cycles: proof.cycles,
+            segments: proof.segments,
+            proving: proof.duration,
+            verification,
+            receipt_size: proof.receipt_size
+        });
+    }
+
+    // The sums over all moves, with the number of moves as the move index.
+    pub fn total(&self) -> MoveStats {
+        self.moves.iter().fold(
+            MoveStats { move_index: self.moves.len() as u32, ..MoveStats::default() },
+            |total, stats| MoveStats {
+                move_index: total.move_index,
+                cycles: total.cycles + stats.cycles,
+                segments: total.segments + stats.segments,
+                proving: total.proving + stats.proving,
+                verification: total.verification + stats.verification,
+                receipt_size: total.receipt_size + stats.receipt_size
+            }
+        )
+    }
+
+    pub fn summary(&self) -> Summary {
+        let total = self.total();
+        let moves = self.moves.len().max(1);
+
+        Summary {
+            variant: self.variant.clone(),
+            state_hasher: self.state_hasher.to_string(),
+            prover: self.prover,
+            moves: self.moves.len(),
+            cycles: total.cycles / moves as u64,
+            proving_ms: total.proving.as_secs_f64() * 1000.0 / moves as f64,
+            verification_ms: total.verification.as_secs_f64() * 1000.0 / moves as f64,
+            receipt_size: total.receipt_size / moves
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.report()).expect("statistics are always serializable")
+    }
+
+    pub fn write_json(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+
+    fn report(&self) -> Report<'_> {
+        Report { stats: self, total: self.total(), summary: self.summary() }
+    }
+}
+
+// The reports of several games as a JSON array, as `bench` writes them for
+// each variant that it proves.
+pub fn write_runs(runs: &[GameStats], path: impl AsRef<Path>) -> io::Result<()> {
+    let reports: Vec<Report> = runs.iter().map(GameStats::report).collect();
+
+    fs::write(path, serde_json::to_string_pretty(&reports).expect("statistics are always serializable"))
+}
+
+// The summaries of the reports written by `write_runs`, such as by another build.
+pub fn read_summaries(path: impl AsRef<Path>) -> io::Result<Vec<Summary>> {
+    let reports: Vec<SummaryOf> = serde_json::from_slice(&fs::read(path)?)?;
+
+    Ok(reports.into_iter().map(|report| report.summary).collect())
+}
+
+// A table of summaries, one configuration per row.
+pub fn summary_table(summaries: &[Summary]) -> String {
+    let mut table = format!(
+        "{:<12} {:<9} {:<7} {:>6} {:>10} {:>11} {:>11} {:>10}",
+        "variant", "hasher", "prover", "moves", "cycles", "proving", "verifying", "receipt"
+    );
+
+    for summary in summaries {
+        table += &format!(
+            "\n{:<12} {:<9} {:<7} {:>6} {:>10} {:>11} {:>11} {:>10}",
+            summary.variant,
+            summary.state_hasher,
+            match summary.prover {
+                ProverBackend::Local => "local",
+                ProverBackend::Bonsai => "bonsai"
+            },
+            summary.moves,
+            summary.cycles,
+            format!("{:.2}ms", summary.proving_ms),
+            format!("{:.2}ms", summary.verification_ms),
+            format!("{} B", summary.receipt_size)
+        );
+    }
+
+    table
+}
+
+impl fmt::Display for GameStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} variant, {} state hashes, proven {}:",
+            self.variant,
+            self.state_hasher,
+            match self.prover {
+                ProverBackend::Local => "locally",
+                ProverBackend::Bonsai => "by Bonsai"
+            }
+        )?;
+        writeln!(f, "{:>6} {:>10} {:>9} {:>11} {:>11} {:>10}", "move", "cycles", "segments", "proving", "verifying", "receipt")?;
+
+        for stats in &self.moves {
+            writeln!(f, "{:>6} {}", stats.move_index, Row(stats))?;
+        }
+
+        write!(f, "{:>6} {}", "total", Row(&self.total()))
+    }
+}
+
+// The columns of a move after its index.
+struct Row<'a>(&'a MoveStats);
+
+impl fmt::Display for Row<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>10} {:>9} {:>11} {:>11} {:>10}",
+            self.0.cycles,
+            self.0.segments,
+            format!("{:.2?}", self.0.proving),
+            format!("{:.2?}", self.0.verification),
+            format!("{} B", self.0.receipt_size)
+        )
+    }
+}
+
+fn milliseconds<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+fn hasher_name(id: u32) -> &'static str {
+    match id {
+        0 => "sha256",
+        1 => "blake2b",
+        2 => "poseidon",
+        _ => "unknown"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn totals_are_written_along_with_the_moves() {
+        let mut stats = GameStats::new("standard", ProverBackend::Local);
+
+        for move_index in 0..2 {
+            let proof = ProofStats {
+                cycles: 1 << 20,
+                segments: 1,
+                duration: Duration::from_secs(10),
+                receipt_size: 200_000
+            };
+
+            stats.record(move_index, proof, Duration::from_millis(20));
+        }
+
+        let total = stats.total();
+
+        assert_eq!(total.move_index, 2);
+        assert_eq!(total.cycles, 1 << 21);
+        assert_eq!(total.proving, Duration::from_secs(20));
+
+        let json: serde_json::Value = serde_json::from_str(&stats.to_json()).unwrap();
+
+        assert_eq!(json["variant"], "standard");
+        assert_eq!(json["prover"], "local");
+        assert_eq!(json["moves"][1]["verification_ms"], 20.0);
+        assert_eq!(json["total"]["receipt_size"], 400_000);
+        assert!(stats.to_string().ends_with("total    2097152         2      20.00s     40.00ms   400000 B"));
+        assert_eq!(json["summary"]["cycles"], 1 << 20);
+        assert_eq!(json["summary"]["proving_ms"], 10_000.0);
+    }
+
+    #[test]
+    fn summaries_are_read_back_from_the_runs_of_bench() {
+        let path = std::env::temp_dir().join(format!("bench-{}.json", std::process::id()));
+
+        let mut stats = GameStats::new("signed", ProverBackend::Local);
+        let proof = ProofStats { cycles: 1 << 19, segments: 1, duration: Duration::from_secs(4), receipt_size: 1000 };
+        stats.record(0, proof, Duration::from_millis(8));
+
+        write_runs(&[GameStats::new("standard", ProverBackend::Local), stats.clone()], &path).unwrap();
+        let summaries = read_summaries(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].moves, 0);
+        assert_eq!(summaries[1], stats.summary());
+        assert!(summary_table(&summaries).lines().nth(2).unwrap().starts_with("signed       sha256    local        1     524288"));
+    }
+}
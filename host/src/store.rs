@@ -0,0 +1,511 @@
+use std::{path::Path, time::{SystemTime, UNIX_EPOCH}};
+
+use rusqlite::{Connection, OptionalExtension, Row, params, types::Type};
+use serde::{Serialize, de::DeserializeOwned};
+use methods::GuestMethod;
+use risc0_zkvm::SessionReceipt;
+use game::{State, Player, Point, GameJournal, metadata::{MatchMetadata, GameId}};
+
+use crate::{games::MoveReceipt, protocol::invalid_data};
+
+pub type Result<T> = rusqlite::Result<T>;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS games (
+        game_id BLOB PRIMARY KEY,
+        player_a TEXT NOT NULL,
+        player_b TEXT NOT NULL,
+        state TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        metadata BLOB NOT NULL,
+        init_receipt BLOB NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS moves (
+        game_id BLOB NOT NULL REFERENCES games (game_id),
+        move_index INTEGER NOT NULL,
+        player TEXT NOT NULL,
+        x INTEGER NOT NULL,
+        y INTEGER NOT NULL,
+        error TEXT,
+        recorded_at INTEGER NOT NULL,
+        journal BLOB NOT NULL,
+        receipt BLOB NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS results (
+        game_id BLOB PRIMARY KEY REFERENCES games (game_id),
+        state TEXT NOT NULL,
+        move_count INTEGER NOT NULL,
+        recorded_at INTEGER NOT NULL,
+        receipt BLOB NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS ratings (
+        player TEXT PRIMARY KEY,
+        rating REAL NOT NULL,
+        wins INTEGER NOT NULL,
+        draws INTEGER NOT NULL,
+        losses INTEGER NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS games_by_player_a ON games (player_a);
+    CREATE INDEX IF NOT EXISTS games_by_player_b ON games (player_b);
+    CREATE INDEX IF NOT EXISTS moves_by_game ON moves (game_id, move_index);
+    CREATE INDEX IF NOT EXISTS ratings_by_rating ON ratings (rating);
+";
+
+// The rating of players that have not finished a rated game yet.
+pub const INITIAL_RATING: f64 = 1500.0;
+
+// How far a single game can move a rating.
+const K_FACTOR: f64 = 32.0;
+
+// A record of every game and every move proven for it, with their receipts,
+// so that past games can be looked up by player and audited later on.
+pub struct Store {
+    connection: Connection
+}
+
+// A recorded game along with the receipt of its initial state.
+#[derive(Clone, Debug)]
+pub struct GameRecord {
+    pub metadata: MatchMetadata,
+    pub state: State,
+    // In seconds since the Unix epoch.
+    pub created_at: u64,
+    pub init: SessionReceipt
+}
+
+// A recorded move, including rejected ones, whose receipt proves the rejection.
+#[derive(Clone, Debug)]
+pub struct MoveRecord {
+    pub move_index: u32,
+    pub player: Player,
+    pub point: Point,
+    pub error: Option<String>,
+    pub recorded_at: u64,
+    pub receipt: SessionReceipt
+}
+
+// The Elo rating of a player, along with the results it is based on.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct Rating {
+    pub player: String,
+    pub rating: f64,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32
+}
+
+// The result of a game as proven by its `finalize_game` receipt, which is the
+// only way that ratings are updated, so that they are backed by proofs rather
+// than by what the players or the server claim.
+#[derive(Debug)]
+pub struct VerifiedResult {
+    metadata: MatchMetadata,
+    journal: GameJournal,
+    receipt: SessionReceipt
+}
+
+impl VerifiedResult {
+    // Verifies `receipt` against the guest of this build and checks that it
+    // proves the result of the match with the given terms.
+    pub fn verify(metadata: &MatchMetadata, receipt: SessionReceipt) -> risc0_zkvm::Result<Self> {
+        receipt.verify(GuestMethod::FinalizeGame.image_id())?;
+
+        let journal = GameJournal::decode(&receipt.journal).map_err(|error| invalid_data(error.to_string()))?;
+
+        if journal.match_hash != metadata.hash() || journal.game_id != metadata.game_id {
+            return Err(invalid_data("the receipt proves the result of another match").into());
+        }
+
+        if journal.state == State::InProgress {
+            return Err(invalid_data("the receipt proves a game that has not ended").into());
+        }
+
+        Ok(Self { metadata: metadata.clone(), journal, receipt })
+    }
+}
+
+impl Store {
+    // Opens the database at `path`, creating it if it doesn't exist yet.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Self { connection })
+    }
+
+    pub fn record_game(&self, metadata: &MatchMetadata, init: &SessionReceipt) -> Result<()> {
+        self.connection.execute(
+            "INSERT INTO games (game_id, player_a, player_b, state, created_at, metadata, init_receipt)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                &metadata.game_id[..],
+                metadata.player_a.name,
+                metadata.player_b.name,
+                state_name(State::InProgress),
+                now(),
+                encode(metadata)?,
+                encode(init)?
+            ]
+        )?;
+
+        Ok(())
+    }
+
+    // Records the move of `player` and the state of the game after it.
+    pub fn record_move(
+        &self,
+        game_id: &GameId,
+        player: Player,
+        point: Point,
+        receipt: &MoveReceipt,
+        state: State
+    ) -> Result<()> {
+        self.connection.execute(
+            "INSERT INTO moves (game_id, move_index, player, x, y, error, recorded_at, journal, receipt)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                &game_id[..],
+                receipt.move_index.expect("initial states are recorded with the game"),
+                player_name(player),
+                point.x(),
+                point.y(),
+                receipt.error.map(|error| error.to_string()),
+                now(),
+                receipt.receipt.journal,
+                encode(&receipt.receipt)?
+            ]
+        )?;
+
+        self.connection.execute(
+            "UPDATE games SET state = ?2 WHERE game_id = ?1",
+            params![&game_id[..], state_name(state)]
+        )?;
+
+        Ok(())
+    }
+
+    pub fn game(&self, game_id: &GameId) -> Result<Option<GameRecord>> {
+        self.connection.query_row(
+            "SELECT metadata, state, created_at, init_receipt FROM games WHERE game_id = ?1",
+            params![&game_id[..]],
+            game_record
+        ).optional()
+    }
+
+    // The games that a player with the given name took part in, oldest first.
+    pub fn games_of(&self, player: &str) -> Result<Vec<GameRecord>> {
+        let mut statement = self.connection.prepare(
+            "SELECT metadata, state, created_at, init_receipt FROM games
+             WHERE player_a = ?1 OR player_b = ?1
+             ORDER BY created_at"
+        )?;
+
+        let records = statement.query_map(params![player], game_record)?;
+
+        records.collect()
+    }
+
+    // Records a proven result and updates the ratings of both players from it,
+    // unless the game has been rated before. Returns whether it was rated.
+    pub fn record_result(&self, result: &VerifiedResult) -> Result<bool> {
+        let transaction = self.connection.unchecked_transaction()?;
+
+        let inserted = transaction.execute(
+            "INSERT OR IGNORE INTO results (game_id, state, move_count, recorded_at, receipt)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                &result.journal.game_id[..],
+                state_name(result.journal.state),
+                result.journal.move_count,
+                now(),
+                encode(&result.receipt)?
+            ]
+        )?;
+
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        let players = [&result.metadata.player_a.name, &result.metadata.player_b.name];
+        let mut ratings = vec![];
+
+        for player in players {
+            let rating = rating_of(&transaction, player)?.unwrap_or_else(|| Rating::new(player));
+            ratings.push(rating);
+        }
+
+        let updated = elo([ratings[0].rating, ratings[1].rating], result.journal.state);
+
+        for ((mut rating, updated), player) in ratings.into_iter().zip(updated).zip([Player::A, Player::B]) {
+            rating.rating = updated;
+
+            match result.journal.state {
+                State::Winner(winner) if winner == player => rating.wins += 1,
+                State::Winner(_) => rating.losses += 1,
+                _ => rating.draws += 1
+            }
+
+            transaction.execute(
+                "INSERT OR REPLACE INTO ratings (player, rating, wins, draws, losses) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![rating.player, rating.rating, rating.wins, rating.draws, rating.losses]
+            )?;
+        }
+
+        transaction.commit()?;
+
+        Ok(true)
+    }
+
+    pub fn rating(&self, player: &str) -> Result<Option<Rating>> {
+        rating_of(&self.connection, player)
+    }
+
+    // The `limit` highest rated players, highest first.
+    pub fn leaderboard(&self, limit: usize) -> Result<Vec<Rating>> {
+        let mut statement = self.connection.prepare(
+            "SELECT player, rating, wins, draws, losses FROM ratings
+             ORDER BY rating DESC
+             LIMIT ?1"
+        )?;
+
+        let ratings = statement.query_map(params![limit as i64], rating_record)?;
+
+        ratings.collect()
+    }
+
+    // The moves of the game in the order in which they were proven.
+    pub fn moves(&self, game_id: &GameId) -> Result<Vec<MoveRecord>> {
+        let mut statement = self.connection.prepare(
+            "SELECT move_index, player, x, y, error, recorded_at, receipt FROM moves
+             WHERE game_id = ?1
+             ORDER BY rowid"
+        )?;
+
+        let records = statement.query_map(params![&game_id[..]], |row| {
+            Ok(MoveRecord {
+                move_index: row.get(0)?,
+                player: parse_player(row, 1)?,
+                point: Point::new(row.get(2)?, row.get(3)?),
+                error: row.get(4)?,
+                recorded_at: row.get(5)?,
+                receipt: decode(row, 6)?
+            })
+        })?;
+
+        records.collect()
+    }
+}
+
+impl Rating {
+    fn new(player: &str) -> Self {
+        Self { player: player.to_string(), rating: INITIAL_RATING, wins: 0, draws: 0, losses: 0 }
+    }
+}
+
+fn rating_of(connection: &Connection, player: &str) -> Result<Option<Rating>> {
+    connection.query_row(
+        "SELECT player, rating, wins, draws, losses FROM ratings WHERE player = ?1",
+        params![player],
+        rating_record
+    ).optional()
+}
+
+fn rating_record(row: &Row) -> Result<Rating> {
+    Ok(Rating {
+        player: row.get(0)?,
+        rating: row.get(1)?,
+        wins: row.get(2)?,
+        draws: row.get(3)?,
+        losses: row.get(4)?
+    })
+}
+
+// The ratings of players A and B after a game with the given result.
+fn elo([a, b]: [f64; 2], state: State) -> [f64; 2] {
+    let expected = 1.0 / (1.0 + 10f64.powf((b - a) / 400.0));
+
+    let score = match state {
+        State::Winner(Player::A) => 1.0,
+        State::Winner(Player::B) => 0.0,
+        _ => 0.5
+    };
+
+    let change = K_FACTOR * (score - expected);
+
+    [a + change, b - change]
+}
+
+fn game_record(row: &Row) -> Result<GameRecord> {
+    let state: String = row.get(1)?;
+
+    Ok(GameRecord {
+        metadata: decode(row, 0)?,
+        state: parse_state(&state).ok_or_else(|| invalid_text(1, state.clone()))?,
+        created_at: row.get(2)?,
+        init: decode(row, 3)?
+    })
+}
+
+fn encode(value: &impl Serialize) -> Result<Vec<u8>> {
+    bincode::serialize(value).map_err(|error| rusqlite::Error::ToSqlConversionFailure(error))
+}
+
+fn decode<T: DeserializeOwned>(row: &Row, column: usize) -> Result<T> {
+    let bytes: Vec<u8> = row.get(column)?;
+
+    bincode::deserialize(&bytes)
+        .map_err(|error| rusqlite::Error::FromSqlConversionFailure(column, Type::Blob, error))
+}
+
+fn invalid_text(column: usize, text: String) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(column, Type::Text, text.into())
+}
+
+fn player_name(player: Player) -> &'static str {
+    match player {
+        Player::A => "A",
+        Player::B => "B"
+    }
+}
+
+fn parse_player(row: &Row, column: usize) -> Result<Player> {
+    let name: String = row.get(column)?;
+
+    match name.as_str() {
+        "A" => Ok(Player::A),
+        "B" => Ok(Player::B),
+        _ => Err(invalid_text(column, name))
+    }
+}
+
+// States are kept as text so that games can be queried by their result.
+fn state_name(state: State) -> &'static str {
+    match state {
+        State::InProgress => "in progress",
+        State::Winner(Player::A) => "won by A",
+        State::Winner(Player::B) => "won by B",
+        State::Stalemate => "stalemate"
+    }
+}
+
+fn parse_state(name: &str) -> Option<State> {
+    [
+        State::InProgress,
+        State::Winner(Player::A),
+        State::Winner(Player::B),
+        State::Stalemate
+    ].into_iter().find(|state| state_name(*state) == name)
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm::sha::Digest;
+    use game::{MoveError, metadata::PlayerInfo};
+
+    use super::*;
+
+    fn receipt(journal: &[u8]) -> SessionReceipt {
+        SessionReceipt { segments: vec![], journal: journal.to_vec() }
+    }
+
+    fn move_receipt(move_index: u32, journal: &[u8], error: Option<MoveError>) -> MoveReceipt {
+        MoveReceipt { move_index: Some(move_index), receipt: receipt(journal), error }
+    }
+
+    #[test]
+    fn games_are_found_by_player() {
+        let store = Store::in_memory().unwrap();
+        let metadata = MatchMetadata { game_id: [1; 32], ..MatchMetadata::default() };
+
+        store.record_game(&metadata, &receipt(&[0])).unwrap();
+        let accepted = move_receipt(0, &[1], None);
+        store.record_move(&metadata.game_id, Player::A, Point::new(1, 1), &accepted, State::InProgress)
+            .unwrap();
+
+        let rejected = move_receipt(1, &[2], Some(MoveError::CellOccupied));
+        store.record_move(&metadata.game_id, Player::B, Point::new(1, 1), &rejected, State::Stalemate)
+            .unwrap();
+
+        let games = store.games_of(&metadata.player_b.name).unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].metadata, metadata);
+        assert_eq!(games[0].state, State::Stalemate);
+        assert_eq!(games[0].init.journal, vec![0]);
+
+        assert!(store.games_of("Nobody").unwrap().is_empty());
+        assert!(store.game(&[2; 32]).unwrap().is_none());
+
+        let moves = store.moves(&metadata.game_id).unwrap();
+        assert_eq!(moves.len(), 2);
+        assert_eq!(moves[1].player, Player::B);
+        assert_eq!(moves[1].point, Point::new(1, 1));
+        assert_eq!(moves[1].error, Some(MoveError::CellOccupied.to_string()));
+        assert_eq!(moves[1].receipt.journal, vec![2]);
+    }
+
+    // Stands in for a verified receipt, which cannot be proven in unit tests.
+    fn result(metadata: &MatchMetadata, state: State) -> VerifiedResult {
+        let journal = GameJournal {
+            version: 0,
+            hasher: 0,
+            game_id: metadata.game_id,
+            match_hash: metadata.hash(),
+            move_count: 5,
+            initial_state_hash: Digest::default(),
+            final_state_hash: Digest::default(),
+            chain_hash: Digest::default(),
+            state
+        };
+
+        VerifiedResult { metadata: metadata.clone(), journal, receipt: receipt(&[]) }
+    }
+
+    #[test]
+    fn ratings_are_updated_once_per_result() {
+        let store = Store::in_memory().unwrap();
+
+        let first = MatchMetadata {
+            game_id: [1; 32],
+            player_a: PlayerInfo::new("Alice", "X"),
+            player_b: PlayerInfo::new("Bob", "O"),
+            ..MatchMetadata::default()
+        };
+
+        let second = MatchMetadata { game_id: [2; 32], ..first.clone() };
+
+        for metadata in [&first, &second] {
+            store.record_game(metadata, &receipt(&[0])).unwrap();
+        }
+
+        assert!(store.record_result(&result(&first, State::Winner(Player::A))).unwrap());
+        assert!(!store.record_result(&result(&first, State::Winner(Player::A))).unwrap());
+
+        let alice = store.rating("Alice").unwrap().unwrap();
+        assert_eq!(alice.rating, INITIAL_RATING + 16.0);
+        assert_eq!((alice.wins, alice.draws, alice.losses), (1, 0, 0));
+
+        assert!(store.record_result(&result(&second, State::Stalemate)).unwrap());
+
+        let leaderboard = store.leaderboard(10).unwrap();
+        assert_eq!(leaderboard.len(), 2);
+        assert_eq!(leaderboard[0].player, "Alice");
+        assert_eq!(leaderboard[1].losses, 1);
+        assert!((leaderboard[0].rating + leaderboard[1].rating - 2.0 * INITIAL_RATING).abs() < 1e-9);
+        assert!(store.rating("Nobody").unwrap().is_none());
+    }
+}
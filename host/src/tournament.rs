@@ -0,0 +1,275 @@
+use std::{fmt, path::PathBuf};
+
+use risc0_zkvm::Result;
+use game::{State, Player, metadata::{MatchMetadata, PlayerInfo}};
+
+use crate::{
+    bot::{Bot, Strategy}, selfplay, stats::GameStats,
+    export::{ExportedReceipt, ReceiptKind}
+};
+
+// How the matches of a tournament are scheduled.
+#[derive(Clone, Copy, PartialEq, Debug, clap::ValueEnum)]
+pub enum Format {
+    // Every player plays every other player once.
+    RoundRobin,
+    // Single elimination, with the winner of each match going through to the next round.
+    Bracket
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Entrant {
+    pub name: String,
+    pub strategy: Strategy
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct Standing {
+    pub name: String,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32
+}
+
+// A tournament between bots, in which every match is proven and verified end
+// to end like a game of `selfplay`. The receipt chain of each match is written
+// to `dir`: the game with the receipts of its moves as `match-N.bin`, which
+// `replay` verifies, along with the receipts of its initial state and result.
+pub struct Tournament {
+    pub format: Format,
+    pub entrants: Vec<Entrant>,
+    pub standings: Vec<Standing>,
+    pub matches: usize,
+    dir: PathBuf
+}
+
+// The pairings of each round of a round robin between `entrants` players, by
+// the circle method. With an odd number of players, one of them sits out each
+// round. The first player alternates between rounds.
+pub fn round_robin(entrants: usize) -> Vec<Vec<[usize; 2]>> {
+    let mut seats: Vec<Option<usize>> = (0..entrants).map(Some).collect();
+
+    if entrants % 2 == 1 {
+        seats.push(None);
+    }
+
+    let mut rounds = vec![];
+
+    for round in 0..seats.len().saturating_sub(1) {
+        let pairings = (0..seats.len() / 2)
+            .filter_map(|seat| match (seats[seat], seats[seats.len() - 1 - seat]) {
+                (Some(a), Some(b)) if round % 2 == 0 => Some([a, b]),
+                (Some(a), Some(b)) => Some([b, a]),
+                _ => None
+            })
+            .collect();
+
+        rounds.push(pairings);
+        seats[1..].rotate_right(1);
+    }
+
+    rounds
+}
+
+impl Tournament {
+    pub fn new(format: Format, dir: impl Into<PathBuf>) -> Self {
+        Self { format, entrants: vec![], standings: vec![], matches: 0, dir: dir.into() }
+    }
+
+    pub fn register(&mut self, entrant: Entrant) {
+        self.standings.push(Standing { name: entrant.name.clone(), ..Standing::default() });
+        self.entrants.push(entrant);
+    }
+
+    // Plays all matches of the tournament, returning the winner of a bracket.
+    pub fn run(&mut self, stats: &mut GameStats) -> Result<Option<usize>> {
+        match self.format {
+            Format::RoundRobin => {
+                for (round, pairings) in round_robin(self.entrants.len()).into_iter().enumerate() {
+                    for players in pairings {
+                        self.play_match(round + 1, players, stats)?;
+                    }
+                }
+
+                Ok(None)
+            },
+            Format::Bracket => self.run_bracket(stats).map(Some)
+        }
+    }
+
+    // Players are seeded in the order in which they registered. With an odd
+    // number of players left, the last one goes through to the next round.
+    fn run_bracket(&mut self, stats: &mut GameStats) -> Result<usize> {
+        let mut remaining: Vec<usize> = (0..self.entrants.len()).collect();
+        let mut round = 1;
+
+        while remaining.len() > 1 {
+            let mut winners = vec![];
+
+            for pairing in remaining.chunks(2) {
+                match *pairing {
+                    [a, b] => winners.push(self.play_knockout(round, [a, b], stats)?),
+                    [bye] => {
+                        println!("Round {round}: {} goes through without playing.", self.entrants[bye].name);
+                        winners.push(bye);
+                    },
+                    _ => unreachable!()
+                }
+            }
+
+            remaining = winners;
+            round += 1;
+        }
+
+        Ok(remaining[0])
+    }
+
+    // A drawn match is played again with the other player moving first, after
+    // which the higher seed goes through if it is drawn again.
+    fn play_knockout(&mut self, round: usize, [a, b]: [usize; 2], stats: &mut GameStats) -> Result<usize> {
+        for players in [[a, b], [b, a]] {
+            match self.play_match(round, players, stats)? {
+                State::Winner(Player::A) => return Ok(players[0]),
+                State::Winner(Player::B) => return Ok(players[1]),
+                _ => { }
+            }
+        }
+
+        Ok(a)
+    }
+
+    fn play_match(&mut self, round: usize, players: [usize; 2], stats: &mut GameStats) -> Result<State> {
+        let [a, b] = players.map(|index| self.entrants[index].clone());
+
+        let metadata = MatchMetadata {
+            game_id: rand::random(),
+            nonce: rand::random(),
+            player_a: PlayerInfo::new(&a.name, "X"),
+            player_b: PlayerInfo::new(&b.name, "O"),
+            ..MatchMetadata::default()
+        };
+
+        let bots = [
+            Bot { player: Player::A, strategy: a.strategy },
+            Bot { player: Player::B, strategy: b.strategy }
+        ];
+
+        let played = selfplay::play_game(metadata, bots, stats)?;
+        let state = played.server.game.state();
+
+        self.matches += 1;
+
+        let name = format!("match-{}", self.matches);
+        played.server.save(self.dir.join(format!("{name}.bin")))?;
+        ExportedReceipt::new(ReceiptKind::Init, played.init).write(self.dir.join(format!("{name}.init.receipt")))?;
+        ExportedReceipt::new(ReceiptKind::Result, played.result).write(self.dir.join(format!("{name}.result.receipt")))?;
+
+        match state {
+            State::Winner(Player::A) => println!("Round {round}: {} beat {} in {name}.", a.name, b.name),
+            State::Winner(Player::B) => println!("Round {round}: {} beat {} in {name}.", b.name, a.name),
+            _ => println!("Round {round}: {} drew with {} in {name}.", a.name, b.name)
+        }
+
+        self.record(players, state);
+
+        Ok(state)
+    }
+
+    fn record(&mut self, [a, b]: [usize; 2], state: State) {
+        match state {
+            State::Winner(Player::A) => {
+                self.standings[a].wins += 1;
+                self.standings[b].losses += 1;
+            },
+            State::Winner(Player::B) => {
+                self.standings[b].wins += 1;
+                self.standings[a].losses += 1;
+            },
+            _ => {
+                self.standings[a].draws += 1;
+                self.standings[b].draws += 1;
+            }
+        }
+    }
+
+    // The standings by points, with ties in the order in which the players registered.
+    pub fn ranking(&self) -> Vec<&Standing> {
+        let mut ranking: Vec<_> = self.standings.iter().collect();
+        ranking.sort_by_key(|standing| std::cmp::Reverse(standing.points()));
+
+        ranking
+    }
+}
+
+impl Standing {
+    // Two for a win and one for a draw.
+    pub fn points(&self) -> u32 {
+        2 * self.wins + self.draws
+    }
+}
+
+impl fmt::Display for Tournament {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>4} {:<16} {:>6} {:>4} {:>5} {:>4}", "rank", "player", "points", "won", "drawn", "lost")?;
+
+        for (rank, standing) in self.ranking().into_iter().enumerate() {
+            write!(
+                f,
+                "\n{:>4} {:<16} {:>6} {:>4} {:>5} {:>4}",
+                rank + 1,
+                standing.name,
+                standing.points(),
+                standing.wins,
+                standing.draws,
+                standing.losses
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn everyone_plays_everyone_once() {
+        for entrants in 2..=7 {
+            let rounds = round_robin(entrants);
+            let mut pairs = HashSet::new();
+
+            for pairings in &rounds {
+                let mut playing = HashSet::new();
+
+                for &[a, b] in pairings {
+                    assert!(playing.insert(a) && playing.insert(b), "a player plays twice in a round");
+                    assert!(pairs.insert((a.min(b), a.max(b))), "a pair plays twice");
+                }
+            }
+
+            assert_eq!(pairs.len(), entrants * (entrants - 1) / 2);
+        }
+    }
+
+    #[test]
+    fn standings_are_ranked_by_points() {
+        let mut tournament = Tournament::new(Format::RoundRobin, "tournament");
+
+        for name in ["Alice", "Bob", "Carol"] {
+            tournament.register(Entrant { name: name.to_string(), strategy: Strategy::Random });
+        }
+
+        tournament.record([0, 1], State::Winner(Player::B));
+        tournament.record([1, 2], State::Stalemate);
+        tournament.record([2, 0], State::Winner(Player::A));
+
+        let ranking: Vec<_> = tournament.ranking().into_iter().map(|standing| &standing.name).collect();
+
+        assert_eq!(ranking, ["Bob", "Carol", "Alice"]);
+        assert_eq!(tournament.standings[1].points(), 3);
+        assert_eq!(tournament.standings[0].losses, 2);
+    }
+}
@@ -0,0 +1,568 @@
+use std::{io::{self, Stdout}, panic, path::Path, thread, time::{Duration, Instant}};
+
+use crossterm::{
+    cursor, execute, terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers}
+};
+use ratatui::{
+    Terminal, Frame,
+    backend::{Backend, CrosstermBackend},
+    layout::{Layout, Constraint, Direction, Rect},
+    style::{Style, Color, Modifier},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, List, ListItem}
+};
+use risc0_zkvm::SessionReceipt;
+use game::{State, Player, Point, MoveError, VmResponse};
+
+use crate::{
+    server::Server, client::Client, render, prover::{ProofQueue, ProvenMove, Progress},
+    export::{ExportedReceipt, ReceiptKind}, stats::GameStats, bot::Bot
+};
+
+// How often the spinner advances while a move is being proven.
+const TICK: Duration = Duration::from_millis(100);
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+// Of the progress bar of the move being proven, in characters.
+const PROGRESS_WIDTH: usize = 16;
+
+const HELP: &str = "←↑↓→ choose a cell  Enter play  PgUp/PgDn scroll receipts  q quit";
+
+// A full screen view of a local game, in place of the line based prompt of
+// `input`. The terminal is restored when it is dropped, and before the panic
+// message of a failed verification is printed.
+struct Tui<'a> {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+    view: View,
+    // Where the receipts are exported to, if anywhere.
+    export: Option<&'a Path>,
+    stats: &'a mut GameStats
+}
+
+struct View {
+    cursor: Point,
+    moves: Vec<PlayedMove>,
+    log: Vec<String>,
+    // How many lines the receipt log is scrolled up from its end.
+    scroll: usize,
+    tick: usize,
+    // Of the move being proven, see `ProofQueue::progress`.
+    progress: Option<Progress>,
+    finished: bool,
+    color: bool
+}
+
+struct PlayedMove {
+    player: Player,
+    point: Point,
+    verification: Verification
+}
+
+enum Verification {
+    Proving,
+    // By the clients of both players.
+    Verified,
+    // The receipt proves that the move was illegal.
+    Rejected(MoveError)
+}
+
+// What a key press asks for.
+enum Action {
+    Play(Point),
+    Quit,
+    None
+}
+
+// Plays the standard variant until the game ends or neither player can win
+// anymore. Each move is proven on another thread while the players go on
+// playing, and its receipt is verified by both clients once the moves before
+// it have been. The receipts are kept in `Server::receipts` and exported to
+// `export`, if given, with what it took to prove and verify them recorded in
+// `stats`. The moves of `bot`, if given, are queued as soon as it is its turn.
+// Returns false if the players quit the game before that.
+pub fn play(
+    server: &mut Server,
+    player_a: &mut Client,
+    player_b: &mut Client,
+    export: Option<&Path>,
+    stats: &mut GameStats,
+    bot: Option<Bot>
+) -> io::Result<bool> {
+    let mut tui = Tui::new(export, stats)?;
+    let mut queue = ProofQueue::new();
+
+    tui.view.log.push(format!(
+        "Verified the initial state of game {}.",
+        hex::encode(server.metadata.game_id)
+    ));
+
+    loop {
+        let game = server.pending_game();
+        let playing = game.state() == State::InProgress && !game.is_draw();
+
+        if !playing && queue.is_empty() {
+            break;
+        }
+
+        if let Some(point) = bot.and_then(|bot| bot.next_move(&game)).filter(|_| playing) {
+            tui.queue_move(server, &mut queue, point);
+
+            continue;
+        }
+
+        tui.draw(server)?;
+
+        if event::poll(TICK)? {
+            if let Event::Key(key) = event::read()? {
+                match tui.handle_key(&key, playing) {
+                    Action::Play(point) => tui.queue_move(server, &mut queue, point),
+                    Action::Quit => return Ok(false),
+                    Action::None => { }
+                }
+            }
+        }
+
+        while let Some(proven) = queue.try_next() {
+            tui.dispatch(server, player_a, player_b, &mut queue, proven)?;
+        }
+
+        tui.view.progress = queue.progress();
+
+        tui.view.tick += 1;
+    }
+
+    tui.view.finished = true;
+    tui.wait_for_key(server)?;
+
+    Ok(true)
+}
+
+impl<'a> Tui<'a> {
+    fn new(export: Option<&'a Path>, stats: &'a mut GameStats) -> io::Result<Self> {
+        let hook = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            let _ = restore();
+            hook(info);
+        }));
+
+        terminal::enable_raw_mode()?;
+
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+
+        Ok(Self {
+            terminal: Terminal::new(CrosstermBackend::new(stdout))?,
+            view: View {
+                cursor: Point::new(1, 1),
+                moves: vec![],
+                log: vec![],
+                scroll: 0,
+                tick: 0,
+                progress: None,
+                finished: false,
+                color: render::color_enabled()
+            },
+            export,
+            stats
+        })
+    }
+
+    fn draw(&mut self, server: &Server) -> io::Result<()> {
+        self.terminal.draw(|frame| self.view.render(frame, server))?;
+
+        Ok(())
+    }
+
+    // Moves the cursor or scrolls the receipt log. Cells are only chosen while the game goes on.
+    fn handle_key(&mut self, key: &KeyEvent, playing: bool) -> Action {
+        if key.kind != KeyEventKind::Press {
+            return Action::None;
+        }
+
+        let (x, y) = (self.view.cursor.x(), self.view.cursor.y());
+
+        self.view.cursor = match key.code {
+            KeyCode::Left => Point::new(x.saturating_sub(1), y),
+            KeyCode::Right => Point::new((x + 1).min(2), y),
+            KeyCode::Up => Point::new(x, y.saturating_sub(1)),
+            KeyCode::Down => Point::new(x, (y + 1).min(2)),
+            KeyCode::Enter | KeyCode::Char(' ') if playing => return Action::Play(self.view.cursor),
+            _ if is_quit(key) => return Action::Quit,
+            _ => {
+                self.view.scroll_log(key.code);
+
+                return Action::None;
+            }
+        };
+
+        Action::None
+    }
+
+    // Checks the move against the moves queued so far and queues it to be proven on top of them.
+    fn queue_move(&mut self, server: &mut Server, queue: &mut ProofQueue, point: Point) {
+        let player = server.pending_game().current_player();
+
+        match server.queue_proof(point) {
+            Ok(proof) => {
+                queue.push(proof);
+                self.view.moves.push(PlayedMove { player, point, verification: Verification::Proving });
+            },
+            Err(error) => self.view.log.push(format!("Cannot play {point}: {error}"))
+        }
+    }
+
+    // Has both clients verify the receipt of the earliest queued move. Should it
+    // fail to be proven, the moves queued after it are taken back, since they
+    // were played on top of it.
+    fn dispatch(
+        &mut self,
+        server: &mut Server,
+        player_a: &mut Client,
+        player_b: &mut Client,
+        queue: &mut ProofQueue,
+        (move_index, result): ProvenMove
+    ) -> io::Result<()> {
+        let point = server.pending[0];
+
+        let (receipt, proof) = match result {
+            Ok(result) => result,
+            Err(error) => {
+                self.view.log.push(format!("Proving {point} failed: {error}"));
+                self.take_back(server, queue);
+
+                return Ok(());
+            }
+        };
+
+        self.view.log.push(format!("Receipt of move {move_index}: {proof}"));
+
+        let start = Instant::now();
+        player_a.verify_receipt(&receipt);
+        self.stats.record(move_index, proof, start.elapsed());
+        server.receipts.push(receipt.clone());
+
+        let verification = match player_b.verify_receipt(&receipt) {
+            VmResponse::Accepted { game, point, new_state_hash, .. } => {
+                self.view.log.push(format!(
+                    "Verified by both players, state hash {}.",
+                    hex::encode(&new_state_hash.as_bytes()[..8])
+                ));
+
+                self.export(&format!("move-{move_index}.receipt"), &receipt)?;
+
+                server.apply_pending_move(point, game);
+
+                Verification::Verified
+            },
+            VmResponse::Rejected { error, .. } => {
+                self.view.log.push(format!("Verified that the move was rejected: {error}"));
+
+                Verification::Rejected(error)
+            }
+        };
+
+        let rejected = matches!(verification, Verification::Rejected(_));
+
+        if let Some(played) = self.view.moves.iter_mut().find(|played| played.is_proving()) {
+            played.verification = verification;
+        }
+
+        if rejected {
+            self.take_back(server, queue);
+        }
+
+        Ok(())
+    }
+
+    // Drops the moves that are still queued, to be played again.
+    fn take_back(&mut self, server: &mut Server, queue: &mut ProofQueue) {
+        if !queue.is_empty() {
+            self.view.log.push(format!("Took back the {} move(s) played since.", queue.len()));
+        }
+
+        queue.clear();
+        server.pending.clear();
+        self.view.moves.retain(|played| !played.is_proving());
+    }
+
+    fn export(&mut self, name: &str, receipt: &SessionReceipt) -> io::Result<()> {
+        let Some(dir) = self.export else {
+            return Ok(());
+        };
+
+        let path = dir.join(name);
+
+        ExportedReceipt::new(ReceiptKind::Move, receipt.clone()).write(&path)?;
+        self.view.log.push(format!("Exported the receipt to {}.", path.display()));
+
+        Ok(())
+    }
+
+    // Keeps the final board on screen until a key other than a scrolling one is pressed.
+    fn wait_for_key(&mut self, server: &Server) -> io::Result<()> {
+        loop {
+            self.draw(server)?;
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.view.scroll_log(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Tui<'_> {
+    fn drop(&mut self) {
+        let _ = restore();
+
+        if !thread::panicking() {
+            let _ = panic::take_hook();
+        }
+    }
+}
+
+impl PlayedMove {
+    fn is_proving(&self) -> bool {
+        matches!(self.verification, Verification::Proving)
+    }
+}
+
+impl View {
+    fn render<B: Backend>(&self, frame: &mut Frame<B>, server: &Server) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(13), Constraint::Min(4), Constraint::Length(1)].as_ref())
+            .split(frame.size());
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(34), Constraint::Min(20)].as_ref())
+            .split(rows[0]);
+
+        let mut board = self.board(server);
+        board.push(Spans::default());
+        board.extend(self.status(server));
+
+        let title = format!("Game {}", hex::encode(&server.metadata.game_id[..4]));
+
+        frame.render_widget(Paragraph::new(board).block(block(title)), columns[0]);
+        frame.render_widget(self.move_list(server, columns[1]), columns[1]);
+        frame.render_widget(self.receipt_log(rows[1]), rows[1]);
+        frame.render_widget(Paragraph::new(HELP), rows[2]);
+    }
+
+    fn board(&self, server: &Server) -> Vec<Spans<'static>> {
+        let glyphs = server.metadata.glyphs();
+        let game = server.pending_game();
+
+        let line = |left: &str, middle: &str, right: &str| {
+            let segment = "─".repeat(glyphs.width + 2);
+
+            Spans::from(format!("{left}{segment}{middle}{segment}{middle}{segment}{right}"))
+        };
+
+        let mut lines = vec![line("┌", "┬", "┐")];
+
+        for y in 0..3 {
+            let mut spans = vec![Span::raw("│")];
+
+            for x in 0..3 {
+                let point = Point::new(x, y);
+                let mut style = self.player_style(game.get(point));
+
+                if server.last_move == Some(point) {
+                    style = style.add_modifier(Modifier::BOLD);
+                }
+
+                if point == self.cursor && !self.finished {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+
+                spans.push(Span::styled(format!(" {} ", glyphs.get(game.get(point))), style));
+                spans.push(Span::raw("│"));
+            }
+
+            lines.push(Spans::from(spans));
+            lines.push(if y == 2 { line("└", "┴", "┘") } else { line("├", "┼", "┤") });
+        }
+
+        lines
+    }
+
+    // Whose turn it is, or the result once every move has been verified,
+    // followed by the move being proven, if any.
+    fn status(&self, server: &Server) -> Vec<Spans<'static>> {
+        let metadata = &server.metadata;
+        let game = server.pending_game();
+
+        let text = match server.game.state() {
+            State::Winner(player) if self.finished => format!("{} wins! Press any key.", metadata.player(player).name),
+            State::Stalemate if self.finished => "Stalemate! Press any key.".into(),
+            _ if self.finished => "Neither player can win anymore. Press any key.".into(),
+            _ if game.state() == State::InProgress && !game.is_draw() => {
+                let player = metadata.player(game.current_player());
+
+                format!("{} ({}) to move", player.name, player.symbol)
+            },
+            _ => "Waiting for the last receipts…".into()
+        };
+
+        let mut lines = vec![Spans::from(text)];
+
+        if let Some(played) = self.moves.iter().find(|played| played.is_proving()) {
+            lines.push(Spans::from(format!(
+                "{} Proving {}'s move {}…",
+                self.spinner(),
+                metadata.player(played.player).name,
+                played.point
+            )));
+
+            if let Some(progress) = self.progress {
+                let filled = (progress.fraction() * PROGRESS_WIDTH as f32).round() as usize;
+
+                lines.push(Spans::from(format!(
+                    "{}{} {}/{} segments",
+                    "█".repeat(filled),
+                    "░".repeat(PROGRESS_WIDTH - filled),
+                    progress.proven,
+                    progress.segments
+                )));
+            }
+        }
+
+        lines
+    }
+
+    fn move_list(&self, server: &Server, area: Rect) -> List<'static> {
+        let height = area.height.saturating_sub(2) as usize;
+        let skipped = self.moves.len().saturating_sub(height);
+
+        let items: Vec<ListItem> = self.moves.iter().enumerate().skip(skipped).map(|(index, played)| {
+            let player = server.metadata.player(played.player);
+
+            let (status, color) = match &played.verification {
+                Verification::Proving => (format!("{} proving", self.spinner()), Color::Yellow),
+                Verification::Verified => ("✓ verified".into(), Color::Green),
+                Verification::Rejected(error) => (format!("✗ rejected: {error}"), Color::Red)
+            };
+
+            ListItem::new(Spans::from(vec![
+                Span::raw(format!("{:>2}. {} ({}) {}  ", index + 1, player.name, player.symbol, played.point)),
+                Span::styled(status, self.color_style(color))
+            ]))
+        }).collect();
+
+        List::new(items).block(block("Moves".into()))
+    }
+
+    fn receipt_log(&self, area: Rect) -> Paragraph<'static> {
+        let height = area.height.saturating_sub(2) as usize;
+        let end = self.log.len() - self.scroll.min(self.log.len());
+        let start = end.saturating_sub(height);
+
+        let lines: Vec<Spans> = self.log[start..end].iter()
+            .map(|line| Spans::from(line.clone()))
+            .collect();
+
+        Paragraph::new(lines).block(block("Receipts".into()))
+    }
+
+    // Returns false if the key does not scroll the receipt log.
+    fn scroll_log(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::PageUp => self.scroll = (self.scroll + 1).min(self.log.len().saturating_sub(1)),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_sub(1),
+            _ => return false
+        }
+
+        true
+    }
+
+    fn spinner(&self) -> char {
+        SPINNER[self.tick % SPINNER.len()]
+    }
+
+    fn player_style(&self, player: Option<Player>) -> Style {
+        match player {
+            Some(Player::A) => self.color_style(Color::Red),
+            Some(Player::B) => self.color_style(Color::Blue),
+            None => Style::default()
+        }
+    }
+
+    fn color_style(&self, color: Color) -> Style {
+        if self.color {
+            Style::default().fg(color)
+        } else {
+            Style::default()
+        }
+    }
+}
+
+fn block(title: String) -> Block<'static> {
+    Block::default().borders(Borders::ALL).title(title)
+}
+
+fn is_quit(key: &KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => true,
+        KeyCode::Char('c') => key.modifiers.contains(KeyModifiers::CONTROL),
+        _ => false
+    }
+}
+
+fn restore() -> io::Result<()> {
+    terminal::disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, cursor::Show)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+    use game::metadata::MatchMetadata;
+
+    use super::*;
+
+    #[test]
+    fn moves_are_shown_with_their_verification() {
+        let mut server = Server::new(MatchMetadata::default());
+        let mut game = server.game;
+        game.make_move(Point::new(1, 1)).unwrap();
+        server.apply_move(Point::new(1, 1), game);
+
+        let view = View {
+            cursor: Point::new(0, 0),
+            moves: vec![
+                PlayedMove { player: Player::A, point: Point::new(1, 1), verification: Verification::Verified },
+                PlayedMove { player: Player::B, point: Point::new(1, 1), verification: Verification::Rejected(MoveError::CellOccupied) },
+                PlayedMove { player: Player::B, point: Point::new(0, 0), verification: Verification::Proving }
+            ],
+            log: vec!["Receipt of move 0".into()],
+            scroll: 0,
+            tick: 0,
+            progress: Some(Progress { proven: 1, segments: 4 }),
+            finished: false,
+            color: false
+        };
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|frame| view.render(frame, &server)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let screen: String = (0..buffer.area.height)
+            .flat_map(|y| (0..buffer.area.width).map(move |x| (x, y)))
+            .map(|(x, y)| buffer.get(x, y).symbol.clone())
+            .collect();
+
+        assert!(screen.contains("│ X │"));
+        assert!(screen.contains("1. Player 1 (X) 1 1  ✓ verified"));
+        assert!(screen.contains("2. Player 2 (O) 1 1  ✗ rejected"));
+        assert!(screen.contains("⠋ Proving Player 2's move 0 0…"));
+        assert!(screen.contains("████░░░░░░░░░░░░ 1/4 segments"));
+        assert!(screen.contains("Receipt of move 0"));
+    }
+}
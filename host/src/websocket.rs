@@ -0,0 +1,89 @@
+use std::{io, sync::{Arc, Weak}};
+
+use async_trait::async_trait;
+use futures::{SinkExt, StreamExt, stream::{SplitSink, SplitStream}};
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::{net::TcpStream, sync::Mutex, time};
+use tokio_tungstenite::{WebSocketStream, tungstenite::{self, Message}};
+
+use crate::protocol::{Channel, invalid_data, HEARTBEAT_INTERVAL, HEARTBEAT_TIMEOUT};
+
+type Socket = WebSocketStream<TcpStream>;
+
+// A WebSocket carrying one JSON encoded message per text message,
+// so that browser based clients can take part in a game. Pings are sent as
+// heartbeats, which browsers answer without being asked to.
+pub struct WebSocketConnection {
+    stream: SplitStream<Socket>,
+    // Shared with the task sending the pings.
+    sink: Arc<Mutex<SplitSink<Socket, Message>>>
+}
+
+impl WebSocketConnection {
+    // Completes the handshake of a client connecting to the server.
+    pub async fn accept(stream: TcpStream) -> io::Result<Self> {
+        let socket = tokio_tungstenite::accept_async(stream).await
+            .map_err(|error| invalid_data(error.to_string()))?;
+
+        let (sink, stream) = socket.split();
+        let sink = Arc::new(Mutex::new(sink));
+
+        tokio::spawn(send_pings(Arc::downgrade(&sink)));
+
+        Ok(Self { stream, sink })
+    }
+}
+
+#[async_trait]
+impl Channel for WebSocketConnection {
+    async fn send<M: Serialize + Sync>(&mut self, message: &M) -> io::Result<()> {
+        let text = serde_json::to_string(message)?;
+
+        self.sink.lock().await.send(Message::Text(text)).await.map_err(into_io)
+    }
+
+    // Fails with `TimedOut` once neither messages nor pongs arrive in time.
+    async fn recv<T: DeserializeOwned>(&mut self) -> io::Result<T> {
+        loop {
+            let message = time::timeout(HEARTBEAT_TIMEOUT, self.stream.next()).await
+                .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "the other side stopped responding"))?
+                .ok_or(io::ErrorKind::UnexpectedEof)?
+                .map_err(into_io)?;
+
+            match message {
+                Message::Text(text) => return Ok(serde_json::from_str(&text)?),
+                Message::Binary(bytes) => return Ok(serde_json::from_slice(&bytes)?),
+                Message::Close(_) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                // Pings are answered by the socket itself.
+                _ => { }
+            }
+        }
+    }
+}
+
+// Stops once the connection has been dropped or the ping fails to be sent.
+async fn send_pings(sink: Weak<Mutex<SplitSink<Socket, Message>>>) {
+    let mut interval = time::interval(HEARTBEAT_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let Some(sink) = sink.upgrade() else {
+            return;
+        };
+
+        if sink.lock().await.send(Message::Ping(vec![])).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn into_io(error: tungstenite::Error) -> io::Error {
+    match error {
+        tungstenite::Error::Io(error) => error,
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+            io::ErrorKind::UnexpectedEof.into()
+        },
+        error => invalid_data(error.to_string())
+    }
+}
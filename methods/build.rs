@@ -1,3 +1,20 @@
+use std::{collections::HashMap, env};
+
+use risc0_build::GuestOptions;
+
 fn main() {
-    risc0_build::embed_methods();
+    // The guests have to hash states the same way as the host.
+    let features = ["poseidon", "blake2b"]
+        .into_iter()
+        .filter(|feature| env::var_os(format!("CARGO_FEATURE_{}", feature.to_uppercase())).is_some())
+        .map(String::from)
+        .collect();
+
+    // Also the build script of the crates of the game variants in `variants`,
+    // each of which embeds the guest package of the same name.
+    let guest = format!("{}-guest", env::var("CARGO_PKG_NAME").unwrap());
+
+    risc0_build::embed_methods_with_options(HashMap::from([
+        (guest.as_str(), GuestOptions { features, ..Default::default() })
+    ]));
 }
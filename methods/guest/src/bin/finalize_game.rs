@@ -0,0 +1,16 @@
+#![no_main]
+
+use risc0_zkvm::guest::env;
+use game::{GameJournal, Point, State, metadata::MatchMetadata};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let metadata: MatchMetadata = env::read();
+    let moves: Vec<Point> = env::read();
+
+    let journal = GameJournal::play(&metadata, &moves).unwrap();
+    assert_ne!(journal.state, State::InProgress, "Game has not finished.");
+
+    env::commit(&journal);
+}
@@ -0,0 +1,14 @@
+#![no_main]
+
+use risc0_zkvm::{guest::env, sha::Digest};
+use game::coin_flip::FlipJournal;
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let game_id: [u8; 32] = env::read();
+    let commitments: [Digest; 2] = env::read();
+    let values: [[u8; 32]; 2] = env::read();
+
+    env::commit(&FlipJournal::new(game_id, commitments, values));
+}
@@ -0,0 +1,12 @@
+#![no_main]
+
+use risc0_zkvm::guest::env;
+use game::{InitJournal, metadata::MatchMetadata};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let metadata: MatchMetadata = env::read();
+
+    env::commit(&InitJournal::new(&metadata));
+}
@@ -0,0 +1,23 @@
+#![no_main]
+
+use risc0_zkvm::{guest::env, sha::Digest};
+use game::{VmResponse, Journal, TicTacToe, STATE_SIZE, Point};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let point: Point = env::read();
+    let game_id: [u8; 32] = env::read();
+    let move_index: u32 = env::read();
+    let prev_chain_hash: Digest = env::read();
+
+    // The game is read as raw bytes, after the other inputs
+    // since it doesn't fill a whole number of words.
+    let mut bytes = [0; STATE_SIZE];
+    env::read_slice(&mut bytes);
+    let mut game = TicTacToe::from_bytes(&bytes).expect("Invalid game state.");
+
+    let response = VmResponse::play(&mut game, point, game_id);
+
+    env::commit(&Journal::new(move_index, prev_chain_hash, response));
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use risc0_zkvm::guest::env;
+use game::{DrawJournal, TicTacToe, STATE_SIZE};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let mut bytes = [0; STATE_SIZE];
+    env::read_slice(&mut bytes);
+    let game = TicTacToe::from_bytes(&bytes).expect("Invalid game state.");
+
+    assert!(game.is_draw(), "Game can still be won.");
+
+    env::commit(&DrawJournal::new(game.state_hash()));
+}
@@ -0,0 +1,18 @@
+#![no_main]
+
+use risc0_zkvm::guest::env;
+use game::{TicTacToe, STATE_SIZE, fraud::{MoveClaim, FraudJournal}};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let claim: MoveClaim = env::read();
+
+    let mut bytes = [0; STATE_SIZE];
+    env::read_slice(&mut bytes);
+    let game = TicTacToe::from_bytes(&bytes).expect("Invalid game state.");
+
+    let journal = FraudJournal::new(&game, claim).expect("Claim is valid.");
+
+    env::commit(&journal);
+}
@@ -0,0 +1,18 @@
+#![no_main]
+
+use risc0_zkvm::guest::env;
+use game::{GameJournal, LengthJournal, Point, State, metadata::MatchMetadata};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let metadata: MatchMetadata = env::read();
+    let moves: Vec<Point> = env::read();
+    let max_moves: u32 = env::read();
+
+    let game = GameJournal::play(&metadata, &moves).unwrap();
+    assert_ne!(game.state, State::InProgress, "Game has not finished.");
+    assert!(game.move_count <= max_moves, "Game took more than {} moves.", max_moves);
+
+    env::commit(&LengthJournal::new(&game, max_moves));
+}
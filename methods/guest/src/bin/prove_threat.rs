@@ -0,0 +1,16 @@
+#![no_main]
+
+use risc0_zkvm::guest::env;
+use game::{ThreatJournal, TicTacToe, STATE_SIZE};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let mut bytes = [0; STATE_SIZE];
+    env::read_slice(&mut bytes);
+    let game = TicTacToe::from_bytes(&bytes).expect("Invalid game state.");
+
+    assert!(game.winning_move().is_some(), "There is no winning move.");
+
+    env::commit(&ThreatJournal::new(game.state_hash(), game.current_player()));
+}
@@ -1 +1,99 @@
 include!(concat!(env!("OUT_DIR"), "/methods.rs"));
+
+#[cfg(feature = "blind")]
+use methods_blind::{REVEAL_MOVE_ELF, REVEAL_MOVE_ID};
+#[cfg(feature = "simultaneous")]
+use methods_simultaneous::{RESOLVE_MOVES_ELF, RESOLVE_MOVES_ID};
+#[cfg(feature = "signed")]
+use methods_signed::{MAKE_SIGNED_MOVE_ELF, MAKE_SIGNED_MOVE_ID};
+#[cfg(feature = "private")]
+use methods_private::{MAKE_PRIVATE_MOVE_ELF, MAKE_PRIVATE_MOVE_ID, DISCLOSE_REGION_ELF, DISCLOSE_REGION_ID};
+#[cfg(feature = "batch")]
+use methods_batch::{MAKE_MOVES_ELF, MAKE_MOVES_ID};
+#[cfg(feature = "optimal")]
+use methods_optimal::{MAKE_OPTIMAL_MOVE_ELF, MAKE_OPTIMAL_MOVE_ID};
+
+// The guest methods, each of which is built into its own ELF. The methods
+// that only some game variants need are only listed with the feature of the
+// same name, which is the only one that builds their guests.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GuestMethod {
+    FlipCoin,
+    InitGame,
+    MakeMove,
+    FinalizeGame,
+    ProveDraw,
+    ProveThreat,
+    ProveLength,
+    ProveFraud,
+    #[cfg(feature = "blind")]
+    RevealMove,
+    #[cfg(feature = "simultaneous")]
+    ResolveMoves,
+    #[cfg(feature = "signed")]
+    MakeSignedMove,
+    #[cfg(feature = "private")]
+    MakePrivateMove,
+    #[cfg(feature = "private")]
+    DiscloseRegion,
+    #[cfg(feature = "batch")]
+    MakeMoves,
+    #[cfg(feature = "optimal")]
+    MakeOptimalMove
+}
+
+impl GuestMethod {
+    pub fn elf(self) -> &'static [u8] {
+        match self {
+            Self::FlipCoin => FLIP_COIN_ELF,
+            Self::InitGame => INIT_GAME_ELF,
+            Self::MakeMove => MAKE_MOVE_ELF,
+            Self::FinalizeGame => FINALIZE_GAME_ELF,
+            Self::ProveDraw => PROVE_DRAW_ELF,
+            Self::ProveThreat => PROVE_THREAT_ELF,
+            Self::ProveLength => PROVE_LENGTH_ELF,
+            Self::ProveFraud => PROVE_FRAUD_ELF,
+            #[cfg(feature = "blind")]
+            Self::RevealMove => REVEAL_MOVE_ELF,
+            #[cfg(feature = "simultaneous")]
+            Self::ResolveMoves => RESOLVE_MOVES_ELF,
+            #[cfg(feature = "signed")]
+            Self::MakeSignedMove => MAKE_SIGNED_MOVE_ELF,
+            #[cfg(feature = "private")]
+            Self::MakePrivateMove => MAKE_PRIVATE_MOVE_ELF,
+            #[cfg(feature = "private")]
+            Self::DiscloseRegion => DISCLOSE_REGION_ELF,
+            #[cfg(feature = "batch")]
+            Self::MakeMoves => MAKE_MOVES_ELF,
+            #[cfg(feature = "optimal")]
+            Self::MakeOptimalMove => MAKE_OPTIMAL_MOVE_ELF
+        }
+    }
+
+    pub fn image_id(self) -> [u32; 8] {
+        match self {
+            Self::FlipCoin => FLIP_COIN_ID,
+            Self::InitGame => INIT_GAME_ID,
+            Self::MakeMove => MAKE_MOVE_ID,
+            Self::FinalizeGame => FINALIZE_GAME_ID,
+            Self::ProveDraw => PROVE_DRAW_ID,
+            Self::ProveThreat => PROVE_THREAT_ID,
+            Self::ProveLength => PROVE_LENGTH_ID,
+            Self::ProveFraud => PROVE_FRAUD_ID,
+            #[cfg(feature = "blind")]
+            Self::RevealMove => REVEAL_MOVE_ID,
+            #[cfg(feature = "simultaneous")]
+            Self::ResolveMoves => RESOLVE_MOVES_ID,
+            #[cfg(feature = "signed")]
+            Self::MakeSignedMove => MAKE_SIGNED_MOVE_ID,
+            #[cfg(feature = "private")]
+            Self::MakePrivateMove => MAKE_PRIVATE_MOVE_ID,
+            #[cfg(feature = "private")]
+            Self::DiscloseRegion => DISCLOSE_REGION_ID,
+            #[cfg(feature = "batch")]
+            Self::MakeMoves => MAKE_MOVES_ID,
+            #[cfg(feature = "optimal")]
+            Self::MakeOptimalMove => MAKE_OPTIMAL_MOVE_ID
+        }
+    }
+}
@@ -0,0 +1,19 @@
+#![no_main]
+
+use risc0_zkvm::{guest::env, sha::Digest};
+use game::{BatchJournal, TicTacToe, STATE_SIZE, Point};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let game_id: [u8; 32] = env::read();
+    let moves: Vec<Point> = env::read();
+    let move_index: u32 = env::read();
+    let prev_chain_hash: Digest = env::read();
+
+    let mut bytes = [0; STATE_SIZE];
+    env::read_slice(&mut bytes);
+    let mut game = TicTacToe::from_bytes(&bytes).expect("Invalid game state.");
+
+    env::commit(&BatchJournal::play(&mut game, game_id, &moves, move_index, prev_chain_hash));
+}
@@ -0,0 +1,31 @@
+#![no_main]
+
+use risc0_zkvm::{guest::env, sha::Digest};
+use game::{VmResponse, Journal, TicTacToe, STATE_SIZE, commitment::Reveal};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let commitment: Digest = env::read();
+    let reveal: Reveal = env::read();
+    let game_id: [u8; 32] = env::read();
+    let move_index: u32 = env::read();
+    let prev_chain_hash: Digest = env::read();
+
+    let mut bytes = [0; STATE_SIZE];
+    env::read_slice(&mut bytes);
+    let mut game = TicTacToe::from_bytes(&bytes).expect("Invalid game state.");
+
+    assert_eq!(
+        reveal.commitment(&game_id, game.current_player(), move_index),
+        commitment,
+        "Revealed move does not match the commitment."
+    );
+
+    let response = VmResponse::play(&mut game, reveal.point, game_id);
+
+    env::commit(
+        &Journal::new(move_index, prev_chain_hash, response)
+            .with_commitment(commitment)
+    );
+}
@@ -0,0 +1,3 @@
+// The ELFs and image IDs of the guests of a game variant, which the
+// `GuestMethod` registry of the `methods` crate lists.
+include!(concat!(env!("OUT_DIR"), "/methods.rs"));
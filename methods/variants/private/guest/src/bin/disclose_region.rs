@@ -0,0 +1,19 @@
+#![no_main]
+
+use risc0_zkvm::guest::env;
+use game::{TicTacToe, STATE_SIZE, disclosure::{Region, DisclosureJournal}};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let salt: [u8; 32] = env::read();
+    let region: Region = env::read();
+
+    let mut bytes = [0; STATE_SIZE];
+    env::read_slice(&mut bytes);
+    let game = TicTacToe::from_bytes(&bytes).expect("Invalid game state.");
+
+    let journal = DisclosureJournal::new(&game, &salt, region).unwrap();
+
+    env::commit(&journal);
+}
@@ -0,0 +1,18 @@
+#![no_main]
+
+use risc0_zkvm::guest::env;
+use game::{PrivateJournal, TicTacToe, STATE_SIZE, Point};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let point: Point = env::read();
+    let salt: [u8; 32] = env::read();
+    let move_index: u32 = env::read();
+
+    let mut bytes = [0; STATE_SIZE];
+    env::read_slice(&mut bytes);
+    let mut game = TicTacToe::from_bytes(&bytes).expect("Invalid game state.");
+
+    env::commit(&PrivateJournal::play(&mut game, point, &salt, move_index));
+}
@@ -0,0 +1,36 @@
+#![no_main]
+
+use risc0_zkvm::{guest::env, sha::Digest};
+use game::{
+    VmResponse, Journal, TicTacToe, STATE_SIZE,
+    metadata::MatchMetadata, auth::SignedMove
+};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let metadata: MatchMetadata = env::read();
+    let signed: SignedMove = env::read();
+    let move_index: u32 = env::read();
+    let prev_chain_hash: Digest = env::read();
+
+    let mut bytes = [0; STATE_SIZE];
+    env::read_slice(&mut bytes);
+    let mut game = TicTacToe::from_bytes(&bytes).expect("Invalid game state.");
+
+    let public_key = metadata.player(game.current_player())
+        .public_key
+        .expect("Player has no public key.");
+
+    assert!(
+        signed.verify(&public_key, &metadata.game_id, move_index),
+        "Invalid move signature."
+    );
+
+    let response = VmResponse::play(&mut game, signed.point, metadata.game_id);
+
+    env::commit(
+        &Journal::new(move_index, prev_chain_hash, response)
+            .with_match_hash(metadata.hash())
+    );
+}
@@ -0,0 +1,45 @@
+#![no_main]
+
+use risc0_zkvm::{guest::env, sha::Digest};
+use game::{
+    TicTacToe, Player, STATE_SIZE,
+    commitment::Reveal,
+    simultaneous::{RoundResponse, RoundJournal}
+};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let commitments: [Digest; 2] = env::read();
+    let reveals: [Reveal; 2] = env::read();
+    let game_id: [u8; 32] = env::read();
+    let move_index: u32 = env::read();
+    let prev_chain_hash: Digest = env::read();
+
+    let mut bytes = [0; STATE_SIZE];
+    env::read_slice(&mut bytes);
+    let mut game = TicTacToe::from_bytes(&bytes).expect("Invalid game state.");
+
+    // Were player B to send the commitment of player A, and then reveal the same
+    // move, they would collide every round. Their commitments cannot open
+    // to the same move anyway, but are refused before opening them.
+    assert_ne!(commitments[0], commitments[1], "Both players made the same commitment.");
+
+    // The move of player B is the second one of the round.
+    let players = [(Player::A, move_index), (Player::B, move_index + 1)];
+
+    for (((player, index), reveal), commitment) in players.into_iter().zip(&reveals).zip(&commitments) {
+        assert_eq!(
+            reveal.commitment(&game_id, player, index),
+            *commitment,
+            "Revealed move does not match the commitment."
+        );
+    }
+
+    let response = RoundResponse::play(
+        &mut game,
+        [reveals[0].point, reveals[1].point]
+    );
+
+    env::commit(&RoundJournal::new(game_id, move_index, prev_chain_hash, commitments, response));
+}
@@ -0,0 +1,69 @@
+use std::{path::PathBuf, process};
+
+use clap::Parser;
+use risc0_zkvm::sha::Digest;
+use verifier::export::{ExportedReceipt, JournalFormat};
+
+#[derive(Parser)]
+#[command(name = "verify-receipt", about = "Verifies a receipt exported by the host and shows what it proves.")]
+struct Args {
+    file: PathBuf,
+    #[arg(long, value_parser = parse_digest, help = "The image ID of the guest to verify against [default: the one of this build]")]
+    image_id: Option<Digest>,
+    #[arg(long, value_parser = parse_digest, help = "Checks that the move was made on the state with this hash")]
+    prev_hash: Option<Digest>,
+    #[arg(long, help = "Prints the decoded journal as JSON")]
+    json: bool
+}
+
+// Verifies a receipt exported by the host, without the prover or the guests,
+// so that it can be built where the toolchain that builds them is not at hand:
+//
+//     cargo build --release -p verifier --no-default-features --features clap --bin verify-receipt
+//
+// Built that way, the image ID of the guest that proved the receipt has to be
+// given. Passing the hash of the state that a move was made on checks that
+// the receipt follows from it.
+fn main() {
+    let args = Args::parse();
+    let format = if args.json { JournalFormat::Json } else { JournalFormat::Text };
+
+    let exported = ExportedReceipt::read(&args.file)
+        .unwrap_or_else(|error| fail(&format!("Failed to read the receipt: {error}")));
+
+    let image_id = args.image_id.unwrap_or_else(|| default_image_id(&exported));
+
+    let proven = exported.verify_against(image_id)
+        .unwrap_or_else(|error| fail(&format!("Receipt verification failed: {error}")));
+
+    if let Some(prev_hash) = args.prev_hash {
+        match proven.prev_state_hash() {
+            Some(hash) if *hash == prev_hash => { },
+            Some(_) => fail("The move was made on another state than the given one."),
+            None => fail("Only move receipts are linked to a previous state.")
+        }
+    }
+
+    println!("{}", proven.format(format));
+}
+
+#[cfg(feature = "guests")]
+fn default_image_id(exported: &ExportedReceipt) -> Digest {
+    exported.kind.image_id()
+}
+
+#[cfg(not(feature = "guests"))]
+fn default_image_id(_: &ExportedReceipt) -> Digest {
+    fail("This build has no guests to verify against, so --image-id has to be given.")
+}
+
+fn parse_digest(arg: &str) -> Result<Digest, String> {
+    hex::decode(arg).ok()
+        .and_then(|bytes| Digest::try_from(bytes.as_slice()).ok())
+        .ok_or_else(|| "hashes and image IDs must be 32 hex encoded bytes".into())
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{message}");
+    process::exit(1);
+}
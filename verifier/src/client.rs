@@ -0,0 +1,657 @@
+use std::fmt;
+
+use tracing::{Span, info_span};
+use methods::GuestMethod;
+use risc0_zkvm::{sha::Digest, SessionReceipt};
+use game::{
+    TicTacToe, State, Player,
+    VmResponse, Journal, GameJournal, PrivateJournal, BatchJournal, DrawJournal,
+    ThreatJournal, InitJournal, LengthJournal, JournalError, JOURNAL_VERSION,
+    metadata::{MatchMetadata, GameId},
+    simultaneous::{RoundResponse, RoundJournal},
+    coin_flip::FlipJournal,
+    disclosure::DisclosureJournal,
+    fraud::{MoveClaim, FraudJournal, Fraud},
+    clock::Forfeit
+};
+
+pub use crate::receipt::GuestPin;
+
+pub struct Client {
+    pub game_state: State,
+    pub state_hash: Digest,
+    pub move_index: u32,
+    pub chain_hash: Digest,
+    // Receipts of moves made in other matches are refused.
+    pub game_id: GameId,
+    // The moves accepted without a proof, see `accept_claim`.
+    pub claims: Vec<MoveClaim>,
+    // The state and chain hashes before each verified move, see `take_back`.
+    pub history: Vec<(Digest, Digest)>,
+    // Move receipts are only accepted from this guest build, see `pin`.
+    pub pin: GuestPin
+}
+
+// Why the client refused a receipt, see the `try_` methods, which GUIs, browsers
+// and bots show to the player instead of the panics of the methods without it.
+#[derive(Debug)]
+pub enum Refusal {
+    // The seal does not prove the journal for the guest.
+    Invalid(String),
+    Journal(JournalError),
+    // The journal does not follow from what the client has verified, such as
+    // the previous state hash of a move or the match of a result.
+    Mismatch(&'static str)
+}
+
+impl GuestPin {
+    // The guest build that this binary was compiled with.
+    pub fn local() -> Self {
+        Self {
+            make_move_id: GuestMethod::MakeMove.image_id().into(),
+            journal_version: JOURNAL_VERSION
+        }
+    }
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Client {
+    pub fn new() -> Self {
+        Self {
+            state_hash: TicTacToe::initial_hash(),
+            game_state: State::InProgress,
+            move_index: 0,
+            chain_hash: game::initial_chain_hash(),
+            game_id: MatchMetadata::default().game_id,
+            claims: vec![],
+            history: vec![],
+            pin: GuestPin::local()
+        }
+    }
+
+    // Pins the guest build offered by the server for the rest of the match.
+    // It must be the one this client was built with, since it could
+    // otherwise be proving moves according to a different set of rules.
+    pub fn pin(&mut self, offered: GuestPin) {
+        self.try_pin(offered).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_pin(&mut self, offered: GuestPin) -> Result<(), Refusal> {
+        check(offered == GuestPin::local(), "Guest build mismatch!")?;
+
+        self.pin = offered;
+
+        Ok(())
+    }
+
+    // Verifies the coin flip for the match `game_id` which must have opened
+    // the `commitments` exchanged beforehand, and returns the first player.
+    pub fn verify_coin_flip(
+        receipt: &SessionReceipt,
+        game_id: &GameId,
+        commitments: &[Digest; 2]
+    ) -> Player {
+        Self::try_verify_coin_flip(receipt, game_id, commitments).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_coin_flip(
+        receipt: &SessionReceipt,
+        game_id: &GameId,
+        commitments: &[Digest; 2]
+    ) -> Result<Player, Refusal> {
+        receipt.verify(GuestMethod::FlipCoin.image_id())
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = FlipJournal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+
+        check(&journal.game_id == game_id, "Game ID mismatch!")?;
+        check(&journal.commitments == commitments, "Coin flip commitment mismatch!")?;
+        check(journal.commitments[0] != journal.commitments[1], "Copied coin flip commitment!")?;
+
+        Ok(journal.first_player)
+    }
+
+    // A client for a game whose initial state has been proven to follow from
+    // the match metadata with `match_hash`, instead of computing it locally.
+    pub fn from_init_receipt(receipt: &SessionReceipt, match_hash: &Digest) -> Self {
+        Self::try_from_init_receipt(receipt, match_hash).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_from_init_receipt(receipt: &SessionReceipt, match_hash: &Digest) -> Result<Self, Refusal> {
+        receipt.verify(GuestMethod::InitGame.image_id())
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = InitJournal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+        check(&journal.match_hash == match_hash, "Match metadata mismatch!")?;
+
+        Ok(Self {
+            state_hash: journal.state_hash,
+            chain_hash: journal.state_hash,
+            game_id: journal.game_id,
+            ..Self::new()
+        })
+    }
+
+    // A client for a game whose initial state is computed locally from
+    // the match metadata, such as a saved game being replayed.
+    pub fn for_match(metadata: &MatchMetadata) -> Self {
+        let state_hash = metadata.initial_game().state_hash();
+
+        Self {
+            state_hash,
+            chain_hash: state_hash,
+            game_id: metadata.game_id,
+            ..Self::new()
+        }
+    }
+
+    // A client for private games, which only knows the salted hash of
+    // the initial state and so can only verify private move receipts.
+    pub fn with_initial_hash(state_hash: Digest) -> Self {
+        Self {
+            state_hash,
+            ..Self::new()
+        }
+    }
+
+    // Verifies the receipt and returns its journal. The tracked state
+    // only advances if the move was accepted. A rejected move proves
+    // that the player attempted an illegal move against the same state.
+    pub fn verify_receipt(&mut self, receipt: &SessionReceipt) -> VmResponse {
+        self.try_verify_receipt(receipt).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    // Like `verify_receipt`, leaving the tracked state as it was if the receipt is refused.
+    pub fn try_verify_receipt(&mut self, receipt: &SessionReceipt) -> Result<VmResponse, Refusal> {
+        let _span = self.verify_span().entered();
+
+        check(self.game_state == State::InProgress, "Game has already ended!")?;
+
+        receipt.verify(self.pin.make_move_id)
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = Journal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+        check(journal.version == self.pin.journal_version, "Journal version mismatch!")?;
+        check(journal.commitment.is_none(), "Unexpected move commitment!")?;
+
+        self.try_apply_journal(journal)
+    }
+
+    // Verifies the receipt of a blind move, which must reveal
+    // the move that was committed to with `commitment`.
+    pub fn verify_blind_receipt(
+        &mut self,
+        receipt: &SessionReceipt,
+        commitment: &Digest
+    ) -> VmResponse {
+        self.try_verify_blind_receipt(receipt, commitment).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_blind_receipt(
+        &mut self,
+        receipt: &SessionReceipt,
+        commitment: &Digest
+    ) -> Result<VmResponse, Refusal> {
+        let _span = self.verify_span().entered();
+
+        check(self.game_state == State::InProgress, "Game has already ended!")?;
+
+        receipt.verify(GuestMethod::RevealMove.image_id())
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = Journal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+        check(journal.commitment.as_ref() == Some(commitment), "Move commitment mismatch!")?;
+
+        self.try_apply_journal(journal)
+    }
+
+    // Verifies the receipt of a signed move, which must have been authorized
+    // by a key from the match metadata with the hash `match_hash`.
+    pub fn verify_signed_receipt(
+        &mut self,
+        receipt: &SessionReceipt,
+        match_hash: &Digest
+    ) -> VmResponse {
+        self.try_verify_signed_receipt(receipt, match_hash).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_signed_receipt(
+        &mut self,
+        receipt: &SessionReceipt,
+        match_hash: &Digest
+    ) -> Result<VmResponse, Refusal> {
+        let _span = self.verify_span().entered();
+
+        check(self.game_state == State::InProgress, "Game has already ended!")?;
+
+        receipt.verify(GuestMethod::MakeSignedMove.image_id())
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = Journal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+        check(journal.match_hash.as_ref() == Some(match_hash), "Match metadata mismatch!")?;
+
+        self.try_apply_journal(journal)
+    }
+
+    // Verifies the receipt of a move that has been proven to be optimal.
+    pub fn verify_optimal_receipt(&mut self, receipt: &SessionReceipt) -> VmResponse {
+        self.try_verify_optimal_receipt(receipt).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_optimal_receipt(&mut self, receipt: &SessionReceipt) -> Result<VmResponse, Refusal> {
+        let _span = self.verify_span().entered();
+
+        check(self.game_state == State::InProgress, "Game has already ended!")?;
+
+        receipt.verify(GuestMethod::MakeOptimalMove.image_id())
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = Journal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+
+        self.try_apply_journal(journal)
+    }
+
+    // Accepts a move without a proof. It can be disputed later on, in
+    // which case the claim is checked against a receipt for the move.
+    pub fn accept_claim(&mut self, claim: MoveClaim) {
+        assert_eq!(self.game_state, State::InProgress, "Game has already ended!");
+        assert_eq!(self.state_hash, claim.prev_state_hash, "Game state hash mismatch!");
+        assert_eq!(self.move_index, claim.move_index, "Move index mismatch!");
+
+        self.chain_hash = game::chain_hash(
+            &self.chain_hash,
+            claim.move_index,
+            claim.point,
+            &claim.new_state_hash
+        );
+        self.state_hash = claim.new_state_hash;
+        self.game_state = claim.state;
+        self.move_index += 1;
+        self.claims.push(claim);
+    }
+
+    // The state before the claimed move with the given index, replayed from the
+    // moves of the claims accepted so far, if there were that many of them.
+    pub fn replay_claims(&self, initial: TicTacToe, move_index: u32) -> Option<TicTacToe> {
+        let mut game = initial;
+
+        for claim in self.claims.get(..move_index as usize)? {
+            game.make_move(claim.point).ok()?;
+        }
+
+        Some(game)
+    }
+
+    // Verifies the receipt of a move that was challenged, which
+    // must be the same transition as the claim accepted before.
+    pub fn verify_claim_receipt(&self, receipt: &SessionReceipt, claim: &MoveClaim) {
+        self.try_verify_claim_receipt(receipt, claim).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_claim_receipt(&self, receipt: &SessionReceipt, claim: &MoveClaim) -> Result<(), Refusal> {
+        receipt.verify(self.pin.make_move_id)
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = Journal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+
+        let VmResponse::Accepted { game_id, game, prev_state_hash, new_state_hash, point, .. } =
+            journal.response else {
+            return Err(Refusal::Mismatch("Claimed move was rejected!"));
+        };
+
+        check(self.game_id == game_id, "Game ID mismatch!")?;
+        check(journal.move_index == claim.move_index, "Move index mismatch!")?;
+        check(
+            (prev_state_hash, point, new_state_hash, game.state()) ==
+                (claim.prev_state_hash, claim.point, claim.new_state_hash, claim.state),
+            "Claim mismatch!"
+        )
+    }
+
+    // Verifies a proof that a claim which this client has accepted is invalid.
+    // The proof has to be made on the state that the claim was made on, as a
+    // game made up by the prover does not show that the claim was dishonest.
+    pub fn verify_fraud_receipt(&self, receipt: &SessionReceipt, claim: &MoveClaim) -> Fraud {
+        self.try_verify_fraud_receipt(receipt, claim).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_fraud_receipt(&self, receipt: &SessionReceipt, claim: &MoveClaim) -> Result<Fraud, Refusal> {
+        receipt.verify(GuestMethod::ProveFraud.image_id())
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = FraudJournal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+
+        check(&journal.claim == claim, "Claim mismatch!")?;
+        check(self.claims.contains(claim), "Claim was never accepted!")?;
+        check(journal.state_hash == claim.prev_state_hash, "Fraud was proven on another state!")?;
+
+        Ok(journal.fraud)
+    }
+
+    // Verifies the receipt of a batch of moves, advancing
+    // the tracked state past every accepted move in it.
+    pub fn verify_batch_receipt(&mut self, receipt: &SessionReceipt) -> BatchJournal {
+        self.try_verify_batch_receipt(receipt).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_batch_receipt(&mut self, receipt: &SessionReceipt) -> Result<BatchJournal, Refusal> {
+        check(self.game_state == State::InProgress, "Game has already ended!")?;
+
+        receipt.verify(GuestMethod::MakeMoves.image_id())
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = BatchJournal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+
+        check(self.game_id == journal.game_id, "Game ID mismatch!")?;
+        check(self.state_hash == journal.prev_state_hash, "Game state hash mismatch!")?;
+        check(self.move_index == journal.move_index, "Move index mismatch!")?;
+        check(self.chain_hash == journal.prev_chain_hash, "Hash chain mismatch!")?;
+        check(journal.points.len() == journal.state_hashes.len(), "State hash missing!")?;
+
+        // The state and the hash chain before each move, so that moves of the
+        // batch can be taken back like those proven one by one.
+        let mut history = Vec::with_capacity(journal.points.len());
+        let (mut state_hash, mut chain_hash) = (self.state_hash, self.chain_hash);
+
+        for (index, (point, new_state_hash)) in journal.points.iter().zip(&journal.state_hashes).enumerate() {
+            history.push((state_hash, chain_hash));
+
+            state_hash = *new_state_hash;
+            chain_hash = game::chain_hash(&chain_hash, self.move_index + index as u32, *point, &state_hash);
+        }
+
+        check(chain_hash == journal.chain_hash, "Hash chain mismatch!")?;
+
+        self.history.extend(history);
+        self.game_state = journal.game.state();
+        self.state_hash = journal.new_state_hash();
+        self.move_index += journal.points.len() as u32;
+        self.chain_hash = journal.chain_hash;
+
+        Ok(journal)
+    }
+
+    // Verifies the receipt of a private move. Only the salted state
+    // hashes and the state of the game are learned from it.
+    pub fn verify_private_receipt(&mut self, receipt: &SessionReceipt) -> PrivateJournal {
+        self.try_verify_private_receipt(receipt).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_private_receipt(&mut self, receipt: &SessionReceipt) -> Result<PrivateJournal, Refusal> {
+        check(self.game_state == State::InProgress, "Game has already ended!")?;
+
+        receipt.verify(GuestMethod::MakePrivateMove.image_id())
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = PrivateJournal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+
+        check(self.state_hash == journal.prev_state_hash, "Game state hash mismatch!")?;
+        check(self.move_index == journal.move_index, "Move index mismatch!")?;
+
+        if journal.accepted {
+            self.game_state = journal.state;
+            self.state_hash = journal.new_state_hash;
+            self.move_index += 1;
+        }
+
+        Ok(journal)
+    }
+
+    // Verifies a bound on the length of the match with `match_hash`
+    // against the state that this client has built up.
+    pub fn verify_length_receipt(
+        &self,
+        receipt: &SessionReceipt,
+        match_hash: &Digest
+    ) -> LengthJournal {
+        self.try_verify_length_receipt(receipt, match_hash).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_length_receipt(
+        &self,
+        receipt: &SessionReceipt,
+        match_hash: &Digest
+    ) -> Result<LengthJournal, Refusal> {
+        receipt.verify(GuestMethod::ProveLength.image_id())
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = LengthJournal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+
+        check(&journal.match_hash == match_hash, "Match metadata mismatch!")?;
+        check(self.game_id == journal.game_id, "Game ID mismatch!")?;
+        check(self.chain_hash == journal.chain_hash, "Hash chain mismatch!")?;
+        check(self.game_state == journal.state, "Game result mismatch!")?;
+        check(journal.max_moves >= self.move_index, "Move count exceeds the bound!")?;
+
+        Ok(journal)
+    }
+
+    // Verifies the disclosure of a region of the private game
+    // with the salted state hash that this client is tracking.
+    pub fn verify_disclosure(&self, receipt: &SessionReceipt) -> DisclosureJournal {
+        self.try_verify_disclosure(receipt).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_disclosure(&self, receipt: &SessionReceipt) -> Result<DisclosureJournal, Refusal> {
+        receipt.verify(GuestMethod::DiscloseRegion.image_id())
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = DisclosureJournal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+        check(self.state_hash == journal.state_hash, "Game state hash mismatch!")?;
+
+        Ok(journal)
+    }
+
+    // Verifies the receipt of a simultaneous round, which must reveal the
+    // moves that players A and B have committed to, in that order.
+    pub fn verify_round_receipt(
+        &mut self,
+        receipt: &SessionReceipt,
+        commitments: &[Digest; 2]
+    ) -> RoundResponse {
+        self.try_verify_round_receipt(receipt, commitments).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_round_receipt(
+        &mut self,
+        receipt: &SessionReceipt,
+        commitments: &[Digest; 2]
+    ) -> Result<RoundResponse, Refusal> {
+        check(self.game_state == State::InProgress, "Game has already ended!")?;
+
+        receipt.verify(GuestMethod::ResolveMoves.image_id())
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = RoundJournal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+        let resp = journal.response;
+
+        check(journal.game_id == self.game_id, "Game ID mismatch!")?;
+        check(&journal.commitments == commitments, "Move commitment mismatch!")?;
+        check(self.state_hash == resp.prev_state_hash(), "Game state hash mismatch!")?;
+        check(self.move_index == journal.move_index, "Move index mismatch!")?;
+        check(self.chain_hash == journal.prev_chain_hash, "Hash chain mismatch!")?;
+
+        if let RoundResponse::Resolved { game, new_state_hash, .. } = &resp {
+            self.game_state = game.state();
+            self.state_hash = *new_state_hash;
+            self.move_index += 2;
+            self.chain_hash = journal.chain_hash;
+        }
+
+        Ok(resp)
+    }
+
+    // Around the verification of a move receipt, for the logs of the host.
+    fn verify_span(&self) -> Span {
+        info_span!("verify", game_id = %hex::encode(self.game_id), move_index = self.move_index)
+    }
+
+    fn try_apply_journal(&mut self, journal: Journal) -> Result<VmResponse, Refusal> {
+        let resp = journal.response;
+
+        check(self.game_id == resp.game_id(), "Game ID mismatch!")?;
+        check(self.state_hash == resp.prev_state_hash(), "Game state hash mismatch!")?;
+        check(self.move_index == journal.move_index, "Move index mismatch!")?;
+        check(self.chain_hash == journal.prev_chain_hash, "Hash chain mismatch!")?;
+
+        if let VmResponse::Accepted { game, new_state_hash, .. } = &resp {
+            self.history.push((self.state_hash, self.chain_hash));
+            self.game_state = game.state();
+            self.state_hash = *new_state_hash;
+            self.move_index += 1;
+            self.chain_hash = journal.chain_hash;
+        }
+
+        Ok(resp)
+    }
+
+    // Verifies a claim that the current game can only end in a
+    // stalemate, in which case the game is considered to be over.
+    pub fn verify_draw_receipt(&mut self, receipt: &SessionReceipt) -> DrawJournal {
+        self.try_verify_draw_receipt(receipt).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_draw_receipt(&mut self, receipt: &SessionReceipt) -> Result<DrawJournal, Refusal> {
+        check(self.game_state == State::InProgress, "Game has already ended!")?;
+
+        receipt.verify(GuestMethod::ProveDraw.image_id())
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = DrawJournal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+
+        check(self.state_hash == journal.state_hash, "Game state hash mismatch!")?;
+
+        self.game_state = State::Stalemate;
+
+        Ok(journal)
+    }
+
+    pub fn verify_threat_receipt(&self, receipt: &SessionReceipt) -> ThreatJournal {
+        self.try_verify_threat_receipt(receipt).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_threat_receipt(&self, receipt: &SessionReceipt) -> Result<ThreatJournal, Refusal> {
+        receipt.verify(GuestMethod::ProveThreat.image_id())
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = ThreatJournal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+
+        check(self.state_hash == journal.state_hash, "Game state hash mismatch!")?;
+
+        Ok(journal)
+    }
+
+    // Verifies a final receipt of the match with `match_hash` against
+    // the state that this client has built up by verifying every move.
+    pub fn verify_final_receipt(
+        &self,
+        receipt: &SessionReceipt,
+        match_hash: &Digest
+    ) -> GameJournal {
+        self.try_verify_final_receipt(receipt, match_hash).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_final_receipt(
+        &self,
+        receipt: &SessionReceipt,
+        match_hash: &Digest
+    ) -> Result<GameJournal, Refusal> {
+        receipt.verify(GuestMethod::FinalizeGame.image_id())
+            .map_err(|error| Refusal::Invalid(error.to_string()))?;
+
+        let journal = GameJournal::decode(&receipt.journal).map_err(Refusal::Journal)?;
+
+        check(&journal.match_hash == match_hash, "Match metadata mismatch!")?;
+        check(self.game_id == journal.game_id, "Game ID mismatch!")?;
+        check(self.chain_hash == journal.chain_hash, "Hash chain mismatch!")?;
+        check(self.state_hash == journal.final_state_hash, "Game state hash mismatch!")?;
+        check(self.game_state == journal.state, "Game result mismatch!")?;
+
+        Ok(journal)
+    }
+
+    // Verifies that the player on turn ran out of time in the state that this
+    // client has verified, as signed by the `referee` enforcing the clock, and
+    // ends the game with their opponent as the winner, who is returned.
+    pub fn verify_forfeit(&mut self, forfeit: &Forfeit, referee: &[u8; 32], first_player: Player) -> Player {
+        self.try_verify_forfeit(forfeit, referee, first_player).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_verify_forfeit(
+        &mut self,
+        forfeit: &Forfeit,
+        referee: &[u8; 32],
+        first_player: Player
+    ) -> Result<Player, Refusal> {
+        check(self.game_state == State::InProgress, "Game has already ended!")?;
+        check(forfeit.verify(referee), "Forfeit signature mismatch!")?;
+        check(self.game_id == forfeit.game_id, "Game ID mismatch!")?;
+        check(self.move_index == forfeit.move_index, "Move index mismatch!")?;
+        check(self.chain_hash == forfeit.chain_hash, "Hash chain mismatch!")?;
+
+        let on_turn = match (first_player, forfeit.move_index % 2) {
+            (player, 0) => player,
+            (Player::A, _) => Player::B,
+            (Player::B, _) => Player::A
+        };
+
+        check(forfeit.player == on_turn, "Forfeit of a player that was not on turn!")?;
+
+        self.game_state = State::Winner(forfeit.winner());
+
+        Ok(forfeit.winner())
+    }
+
+    // Returns to the state verified after the first `move_index` moves, once
+    // both players have agreed to take back the moves since. It needs no
+    // receipt, as the receipts of those moves have proven that state already,
+    // and the hash chain goes on from it like `finalize_game` replays it.
+    pub fn take_back(&mut self, move_index: u32) {
+        self.try_take_back(move_index).unwrap_or_else(|refusal| panic!("{refusal}"))
+    }
+
+    pub fn try_take_back(&mut self, move_index: u32) -> Result<(), Refusal> {
+        check(self.game_state == State::InProgress, "Game has already ended!")?;
+        check(move_index < self.move_index, "No move to take back!")?;
+        check(self.history.len() == self.move_index as usize, "Moves taken back without a receipt!")?;
+
+        (self.state_hash, self.chain_hash) = self.history[move_index as usize];
+        self.history.truncate(move_index as usize);
+        self.move_index = move_index;
+
+        Ok(())
+    }
+
+    pub fn on_game_ended(self) {
+        assert_ne!(
+            self.game_state,
+            State::InProgress,
+            "Server signaled that the game has ended but the client state does not reflect that!"
+        );
+    }
+}
+
+impl fmt::Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(error) => write!(f, "receipt verification failed: {error}"),
+            Self::Journal(error) => write!(f, "{error}"),
+            Self::Mismatch(message) => write!(f, "{message}")
+        }
+    }
+}
+
+impl std::error::Error for Refusal { }
+
+fn check(condition: bool, mismatch: &'static str) -> Result<(), Refusal> {
+    if condition {
+        Ok(())
+    } else {
+        Err(Refusal::Mismatch(mismatch))
+    }
+}
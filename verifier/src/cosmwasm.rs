@@ -0,0 +1,83 @@
+use std::io;
+
+use serde::{Serialize, Deserialize};
+use game::{State, metadata::MatchMetadata};
+
+use crate::export::ExportedReceipt;
+
+// The messages of the CosmWasm settlement contract in contracts/cosmwasm,
+// shared with the host that submits them. Digests and game IDs are hex
+// encoded and receipts base64 encoded, see `ExportedReceipt::to_base64`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct InstantiateMsg {
+    // Of the `finalize_game` guest that results are verified against.
+    pub image_id: String
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    // Sent when a match starts, so that only a result of that match can settle the game.
+    Commit { game_id: String, match_hash: String, wager: Option<Wager> },
+    // Sent by a player of a wager along with the stake, which is held until the game is settled.
+    Stake { game_id: String },
+    // The exported receipt of the result of a committed game.
+    Settle { receipt: String },
+    // Sent by a player of a wager that has not been settled by its deadline,
+    // which returns their stake.
+    Refund { game_id: String }
+}
+
+// What both players stake on a game, from the accounts given. Once it is
+// settled, the winner is paid both stakes if both were locked, otherwise
+// every stake is returned, as it is after a stalemate. Stakes can no longer
+// be locked after the deadline, and the ones locked can be reclaimed then
+// if the game has not been settled, such as when the opponent never staked.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Wager {
+    pub player_a: String,
+    pub player_b: String,
+    pub denom: String,
+    // A decimal integer, as the amounts of coins are.
+    pub amount: String,
+    // In seconds since the Unix epoch, as the time of blocks is.
+    pub deadline: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Game { game_id: String }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct GameResponse {
+    pub match_hash: String,
+    pub wager: Option<Wager>,
+    // Whether players A and B locked their stakes.
+    pub staked: [bool; 2],
+    // Only set once the game is settled.
+    pub result: Option<State>
+}
+
+impl ExecuteMsg {
+    pub fn commit(metadata: &MatchMetadata, wager: Option<Wager>) -> Self {
+        Self::Commit {
+            game_id: hex::encode(metadata.game_id),
+            match_hash: hex::encode(metadata.hash().as_bytes()),
+            wager
+        }
+    }
+
+    pub fn stake(metadata: &MatchMetadata) -> Self {
+        Self::Stake { game_id: hex::encode(metadata.game_id) }
+    }
+
+    pub fn settle(exported: &ExportedReceipt) -> io::Result<Self> {
+        Ok(Self::Settle { receipt: exported.to_base64()? })
+    }
+
+    pub fn refund(metadata: &MatchMetadata) -> Self {
+        Self::Refund { game_id: hex::encode(metadata.game_id) }
+    }
+}
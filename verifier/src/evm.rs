@@ -0,0 +1,225 @@
+use std::io;
+
+use serde_json::{json, Value};
+use risc0_zkvm::sha::{Sha256, Digest, Impl};
+use game::{State, Player, GameJournal, metadata::MatchMetadata};
+
+use crate::export::{ExportedReceipt, ReceiptKind};
+
+// The first four bytes of keccak256("settle(address,bytes,bytes32,bytes)"), which
+// selects `TicTacToeSettlement.settle` in contracts/TicTacToeSettlement.sol.
+pub const SETTLE_SELECTOR: [u8; 4] = [0x05, 0x95, 0x37, 0xd0];
+
+// The first four bytes of keccak256("commit(bytes32,bytes32)"), which selects
+// `TicTacToeSettlement.commit`.
+pub const COMMIT_SELECTOR: [u8; 4] = [0xe3, 0xce, 0x09, 0x4d];
+
+// The journal is made of little endian words, one per byte of the game ID and
+// eight per digest, which is what the contract reads the state hasher, the
+// game ID, the hash of the match metadata and the result from.
+pub const HASHER_WORD: usize = 1;
+pub const GAME_ID_WORD: usize = 2;
+pub const MATCH_HASH_WORD: usize = 34;
+pub const STATE_WORD: usize = 67;
+
+const WORD_SIZE: usize = 32;
+
+// What settles the result of a game in the contract: the Groth16 proof that
+// wraps the `finalize_game` receipt and the journal that it commits to.
+// Risc0 0.15 cannot wrap receipts itself, so the seal and the digest of the
+// post state of the session come from a prover that can, such as Bonsai.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Settlement {
+    // Encoded as the RiscZero verifier contract expects it.
+    pub seal: Vec<u8>,
+    pub post_state_digest: Digest,
+    pub journal: Vec<u8>
+}
+
+// How the contract records the result of a game.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+    Stalemate = 1,
+    WonByA = 2,
+    WonByB = 3
+}
+
+impl Settlement {
+    // Fails if the receipt is not the result of a game, as the contract would
+    // reject any other journal. The receipt itself is not verified here.
+    pub fn new(exported: &ExportedReceipt, seal: Vec<u8>, post_state_digest: Digest) -> io::Result<Self> {
+        if exported.kind != ReceiptKind::Result {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "only the receipt of the result of a game can be settled"
+            ));
+        }
+
+        Ok(Self { seal, post_state_digest, journal: exported.receipt.journal.clone() })
+    }
+
+    // What the verifier contract checks the seal against, along with the image ID.
+    pub fn journal_digest(&self) -> Digest {
+        *Impl::hash_bytes(&self.journal)
+    }
+
+    // The ABI encoded call to `settle` the game as committed by the address
+    // `committer`, to be sent to the contract as is.
+    pub fn calldata(&self, committer: &[u8; 20]) -> Vec<u8> {
+        // Both `bytes` are in the tail, after the four words of the head.
+        let journal_offset = 4 * WORD_SIZE + padded_len(self.seal.len()) + WORD_SIZE;
+
+        let mut calldata = SETTLE_SELECTOR.to_vec();
+        calldata.extend(abi_address(committer));
+        calldata.extend(abi_uint(4 * WORD_SIZE));
+        calldata.extend(self.post_state_digest.as_bytes());
+        calldata.extend(abi_uint(journal_offset));
+        calldata.extend(abi_bytes(&self.seal));
+        calldata.extend(abi_bytes(&self.journal));
+
+        calldata
+    }
+
+    // The arguments of `settle` and the calldata, as 0x prefixed hex, for
+    // tools that encode the call themselves, such as `cast send`. The image ID
+    // is the one that the contract has to be deployed with.
+    pub fn to_json(&self, image_id: Digest, committer: &[u8; 20]) -> Value {
+        json!({
+            "imageId": hex_0x(image_id.as_bytes()),
+            "committer": hex_0x(committer),
+            "seal": hex_0x(&self.seal),
+            "postStateDigest": hex_0x(self.post_state_digest.as_bytes()),
+            "journal": hex_0x(&self.journal),
+            "journalDigest": hex_0x(self.journal_digest().as_bytes()),
+            "calldata": hex_0x(&self.calldata(committer))
+        })
+    }
+}
+
+// The ABI encoded call to `commit`, which has to be sent before the game is
+// played, since the contract only records results of committed matches. The
+// game is committed by the address that sends it, which settling it names. Along
+// with its arguments as 0x prefixed hex, for tools that encode the call themselves.
+pub fn commit_calldata(metadata: &MatchMetadata) -> Value {
+    let match_hash = metadata.hash();
+
+    let mut calldata = COMMIT_SELECTOR.to_vec();
+    calldata.extend(metadata.game_id);
+    calldata.extend(match_hash.as_bytes());
+
+    json!({
+        "gameId": hex_0x(&metadata.game_id),
+        "matchHash": hex_0x(match_hash.as_bytes()),
+        "calldata": hex_0x(&calldata)
+    })
+}
+
+impl Outcome {
+    pub fn of(journal: &GameJournal) -> Option<Self> {
+        match journal.state {
+            State::InProgress => None,
+            State::Stalemate => Some(Self::Stalemate),
+            State::Winner(Player::A) => Some(Self::WonByA),
+            State::Winner(Player::B) => Some(Self::WonByB)
+        }
+    }
+}
+
+fn abi_address(address: &[u8; 20]) -> [u8; WORD_SIZE] {
+    let mut word = [0; WORD_SIZE];
+    word[WORD_SIZE - 20..].copy_from_slice(address);
+
+    word
+}
+
+fn abi_uint(value: usize) -> [u8; WORD_SIZE] {
+    let mut word = [0; WORD_SIZE];
+    word[WORD_SIZE - 8..].copy_from_slice(&(value as u64).to_be_bytes());
+
+    word
+}
+
+// The length, then the bytes padded with zeros to a whole number of words.
+fn abi_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = abi_uint(bytes.len()).to_vec();
+    encoded.extend(bytes);
+    encoded.resize(WORD_SIZE + padded_len(bytes.len()), 0);
+
+    encoded
+}
+
+fn padded_len(len: usize) -> usize {
+    match len % WORD_SIZE {
+        0 => len,
+        rest => len + WORD_SIZE - rest
+    }
+}
+
+fn hex_0x(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm::SessionReceipt;
+    use game::hasher::STATE_HASHER;
+
+    use super::*;
+
+    #[test]
+    fn settlements_are_abi_encoded() {
+        // A journal with the game ID and a win of player B where the contract reads them.
+        let mut journal = vec![0; (STATE_WORD + 2) * 4];
+        journal[GAME_ID_WORD * 4] = 7;
+        journal[STATE_WORD * 4] = 2;
+        journal[(STATE_WORD + 1) * 4] = 1;
+
+        let receipt = SessionReceipt { segments: vec![], journal: journal.clone() };
+        let exported = ExportedReceipt::new(ReceiptKind::Result, receipt);
+
+        let settlement = Settlement::new(&exported, vec![9; 33], Digest::from([1; 8])).unwrap();
+        let calldata = settlement.calldata(&[3; 20]);
+
+        assert_eq!(calldata[..4], SETTLE_SELECTOR);
+
+        let word = |index: usize| &calldata[4 + index * WORD_SIZE..4 + (index + 1) * WORD_SIZE];
+
+        assert_eq!(word(0)[..12], [0; 12]);
+        assert_eq!(word(0)[12..], [3; 20]);
+        assert_eq!(word(1), abi_uint(128));
+        assert_eq!(word(2), Digest::from([1; 8]).as_bytes());
+        assert_eq!(word(3), abi_uint(128 + 32 + 64));
+        assert_eq!(word(4), abi_uint(33));
+        assert_eq!(word(5), [9; 32]);
+        assert_eq!(word(6)[..2], [9, 0]);
+        assert_eq!(word(7), abi_uint(journal.len()));
+        assert_eq!(&calldata[4 + 8 * WORD_SIZE..][..journal.len()], journal);
+        assert_eq!(calldata.len(), 4 + 8 * WORD_SIZE + padded_len(journal.len()));
+
+        let json = settlement.to_json(Digest::from([2; 8]), &[3; 20]);
+        assert_eq!(json["committer"], hex_0x(&[3; 20]));
+        assert_eq!(json["journalDigest"], hex_0x(Impl::hash_bytes(&journal).as_bytes()));
+
+        let move_receipt = ExportedReceipt::new(ReceiptKind::Move, exported.receipt);
+        assert!(Settlement::new(&move_receipt, vec![], Digest::from([1; 8])).is_err());
+    }
+
+    #[test]
+    fn commitments_are_where_the_contract_reads_them() {
+        let metadata = MatchMetadata { game_id: [7; 32], ..MatchMetadata::default() };
+        let journal = GameJournal::play(&metadata, &[]).unwrap();
+
+        let bytes: Vec<u8> = risc0_zkvm::serde::to_vec(&journal).unwrap().iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+
+        assert_eq!(bytes[HASHER_WORD * 4..(HASHER_WORD + 1) * 4], STATE_HASHER.to_le_bytes());
+        assert_eq!(bytes[GAME_ID_WORD * 4], 7);
+        assert_eq!(&bytes[MATCH_HASH_WORD * 4..(MATCH_HASH_WORD + 8) * 4], metadata.hash().as_bytes());
+        assert_eq!(bytes[STATE_WORD * 4], 0);
+
+        let json = commit_calldata(&metadata);
+        assert_eq!(json["calldata"], hex_0x(&[&COMMIT_SELECTOR[..], &[7; 32], metadata.hash().as_bytes()].concat()));
+    }
+}
@@ -0,0 +1,349 @@
+use std::{fs, fmt, io, path::Path};
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+#[cfg(feature = "guests")]
+use methods::GuestMethod;
+use risc0_zkvm::{Result, SessionReceipt, sha::Digest};
+use game::{State, VmResponse, Journal, GameJournal, InitJournal};
+
+// Must be incremented whenever the layout of `ExportedReceipt` changes.
+pub const EXPORT_VERSION: u32 = 1;
+
+// A single receipt written to a file, journal and seal, so that a player can
+// hand a third party the proof of a particular move or result. The version
+// is always the first field so that it can be checked before the rest is decoded.
+#[derive(Serialize, Deserialize)]
+pub struct ExportedReceipt {
+    version: u32,
+    pub kind: ReceiptKind,
+    pub receipt: SessionReceipt
+}
+
+// What an exported receipt proves, which determines the guest it is verified against.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ReceiptKind {
+    // The initial state of a match, see `Server::prove_init`.
+    Init,
+    Move,
+    // The result of a whole match, see `Server::finalize`.
+    Result
+}
+
+// How to show what a verified receipt proves.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum JournalFormat {
+    // A sentence, see `Proven`'s `Display`.
+    #[default]
+    Text,
+    // The decoded journal as pretty JSON, with the hashes hex encoded, for scripts.
+    Json
+}
+
+// The journal of a verified receipt.
+#[derive(Debug)]
+pub enum Proven {
+    Init(InitJournal),
+    Move(Journal),
+    Result(GameJournal)
+}
+
+impl ExportedReceipt {
+    pub fn new(kind: ReceiptKind, receipt: SessionReceipt) -> Self {
+        Self { version: EXPORT_VERSION, kind, receipt }
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes()?)
+    }
+
+    // Reads a receipt written by `write`, without verifying it.
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    // The receipt as a single line of text, in the same encoding as the file
+    // written by `write`, so that it can be pasted into a chat, an issue or the
+    // calldata of a transaction, and read back with `decode`.
+    pub fn to_base64(&self) -> io::Result<String> {
+        Ok(BASE64.encode(self.to_bytes()?))
+    }
+
+    pub fn to_hex(&self) -> io::Result<String> {
+        Ok(hex::encode(self.to_bytes()?))
+    }
+
+    // Reads a receipt encoded by `to_hex` or `to_base64`, ignoring the whitespace
+    // around it, without verifying it. Text that could be either is read as hex.
+    pub fn decode(text: &str) -> io::Result<Self> {
+        let text = text.trim();
+        let text = text.strip_prefix("0x").unwrap_or(text);
+
+        let bytes = match hex::decode(text) {
+            Ok(bytes) => bytes,
+            Err(_) => BASE64.decode(text).map_err(invalid_data)?
+        };
+
+        Self::from_bytes(&bytes)
+    }
+
+    fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        bincode::serialize(self).map_err(invalid_data)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let version: u32 = bincode::deserialize(bytes).map_err(invalid_data)?;
+
+        if version != EXPORT_VERSION {
+            return Err(invalid_data(format!("unsupported export version {version}")));
+        }
+
+        bincode::deserialize(bytes).map_err(invalid_data)
+    }
+
+    // Verifies the receipt against the guest build of this binary and
+    // decodes its journal. Whoever verifies it still has to check that
+    // the journal names the game and players they expect.
+    #[cfg(feature = "guests")]
+    pub fn verify(&self) -> Result<Proven> {
+        self.verify_against(self.kind.image_id())
+    }
+
+    // Like `verify`, but against the guest with `image_id`, such as one that the
+    // players agreed on, which can be another build than the one of this binary.
+    pub fn verify_against(&self, image_id: Digest) -> Result<Proven> {
+        self.receipt.verify(image_id)?;
+
+        let journal = &self.receipt.journal;
+        let malformed = |error: game::JournalError| invalid_data(error.to_string());
+
+        Ok(match self.kind {
+            ReceiptKind::Init => Proven::Init(InitJournal::decode(journal).map_err(malformed)?),
+            ReceiptKind::Move => Proven::Move(Journal::decode(journal).map_err(malformed)?),
+            ReceiptKind::Result => Proven::Result(GameJournal::decode(journal).map_err(malformed)?)
+        })
+    }
+}
+
+impl Proven {
+    // Every field of the journal, named as in the guest, with the digests and
+    // game IDs hex encoded so that tools without Risc0 can compare them.
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::Init(journal) => json!({
+                "kind": "init",
+                "version": journal.version,
+                "hasher": journal.hasher,
+                "match_hash": hex_digest(&journal.match_hash),
+                "game_id": hex::encode(journal.game_id),
+                "first_player": journal.first_player,
+                "ruleset": journal.ruleset,
+                "state_hash": hex_digest(&journal.state_hash)
+            }),
+            Self::Move(journal) => journal_json(journal),
+            Self::Result(journal) => json!({
+                "kind": "result",
+                "version": journal.version,
+                "hasher": journal.hasher,
+                "game_id": hex::encode(journal.game_id),
+                "match_hash": hex_digest(&journal.match_hash),
+                "move_count": journal.move_count,
+                "initial_state_hash": hex_digest(&journal.initial_state_hash),
+                "final_state_hash": hex_digest(&journal.final_state_hash),
+                "chain_hash": hex_digest(&journal.chain_hash),
+                "state": journal.state
+            })
+        }
+    }
+
+    // The hash of the state that a move was made on, which links
+    // its receipt to the one of the move before it.
+    pub fn prev_state_hash(&self) -> Option<&Digest> {
+        match self {
+            Self::Move(journal) => match &journal.response {
+                VmResponse::Accepted { prev_state_hash, .. } | VmResponse::Rejected { prev_state_hash, .. } => {
+                    Some(prev_state_hash)
+                }
+            },
+            Self::Init(_) | Self::Result(_) => None
+        }
+    }
+
+    pub fn format(&self, format: JournalFormat) -> String {
+        match format {
+            JournalFormat::Text => format!("Verified {self}."),
+            JournalFormat::Json => serde_json::to_string_pretty(&self.to_json())
+                .expect("journals are always serializable")
+        }
+    }
+}
+
+// The journal of a move receipt as JSON, see `Proven::to_json`. Whether the
+// move was accepted is told by `accepted`, with the `error` of a rejected one.
+pub fn journal_json(journal: &Journal) -> Value {
+    let response = match &journal.response {
+        VmResponse::Accepted { game_id, game, prev_state_hash, new_state_hash, player, point } => json!({
+            "accepted": true,
+            "game_id": hex::encode(game_id),
+            "player": player,
+            "point": point,
+            "prev_state_hash": hex_digest(prev_state_hash),
+            "new_state_hash": hex_digest(new_state_hash),
+            "state": game.state()
+        }),
+        VmResponse::Rejected { game_id, error, prev_state_hash, player, point } => json!({
+            "accepted": false,
+            "game_id": hex::encode(game_id),
+            "player": player,
+            "point": point,
+            "prev_state_hash": hex_digest(prev_state_hash),
+            "error": error.to_string()
+        })
+    };
+
+    json!({
+        "kind": "move",
+        "version": journal.version,
+        "hasher": journal.hasher,
+        "move_index": journal.move_index,
+        "prev_chain_hash": hex_digest(&journal.prev_chain_hash),
+        "chain_hash": hex_digest(&journal.chain_hash),
+        "commitment": journal.commitment.as_ref().map(hex_digest),
+        "match_hash": journal.match_hash.as_ref().map(hex_digest),
+        "response": response
+    })
+}
+
+fn hex_digest(digest: &Digest) -> String {
+    hex::encode(digest.as_bytes())
+}
+
+#[cfg(feature = "guests")]
+impl ReceiptKind {
+    // Of the guest of this build that proves the receipts of this kind.
+    pub fn image_id(self) -> Digest {
+        self.method().image_id().into()
+    }
+
+    fn method(self) -> GuestMethod {
+        match self {
+            Self::Init => GuestMethod::InitGame,
+            Self::Move => GuestMethod::MakeMove,
+            Self::Result => GuestMethod::FinalizeGame
+        }
+    }
+}
+
+impl fmt::Display for Proven {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Init(journal) => write!(
+                f,
+                "the initial state of game {}, with player {:?} moving first",
+                hex::encode(journal.game_id), journal.first_player
+            ),
+            Self::Move(journal) => match &journal.response {
+                VmResponse::Accepted { game_id, player, point, .. } => write!(
+                    f,
+                    "that player {:?} played {} as move {} of game {}",
+                    player, point, journal.move_index, hex::encode(game_id)
+                ),
+                VmResponse::Rejected { game_id, player, point, error, .. } => write!(
+                    f,
+                    "that player {:?} attempted {} in game {}, which was rejected with {:?}",
+                    player, point, hex::encode(game_id), error
+                )
+            },
+            Self::Result(journal) => match journal.state {
+                State::Winner(player) => write!(
+                    f,
+                    "that player {:?} won game {} in {} moves",
+                    player, hex::encode(journal.game_id), journal.move_count
+                ),
+                _ => write!(f, "that game {} ended in a stalemate", hex::encode(journal.game_id))
+            }
+        }
+    }
+}
+
+fn invalid_data(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn exported_receipts_are_read_back() {
+        let path = env::temp_dir().join(format!("zk-tic-tac-toe-{}.receipt", std::process::id()));
+        let receipt = SessionReceipt { segments: vec![], journal: vec![1, 2, 3] };
+
+        ExportedReceipt::new(ReceiptKind::Move, receipt).write(&path).unwrap();
+
+        let read = ExportedReceipt::read(&path).unwrap();
+        assert_eq!(read.kind, ReceiptKind::Move);
+        assert_eq!(read.receipt.journal, vec![1, 2, 3]);
+
+        fs::write(&path, 0u32.to_le_bytes()).unwrap();
+        assert_eq!(ExportedReceipt::read(&path).err().unwrap().kind(), io::ErrorKind::InvalidData);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn receipts_are_shared_as_text() {
+        let receipt = SessionReceipt { segments: vec![], journal: vec![1, 2, 3] };
+        let exported = ExportedReceipt::new(ReceiptKind::Result, receipt);
+
+        let base64 = exported.to_base64().unwrap();
+        let hex = exported.to_hex().unwrap();
+
+        for text in [base64.clone(), hex.clone(), format!("0x{hex}\n"), format!(" {base64} ")] {
+            let decoded = ExportedReceipt::decode(&text).unwrap();
+
+            assert_eq!(decoded.kind, ReceiptKind::Result);
+            assert_eq!(decoded.receipt.journal, vec![1, 2, 3]);
+        }
+
+        assert!(base64.len() < hex.len());
+        assert_eq!(ExportedReceipt::decode("not a receipt").err().unwrap().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn journals_are_shown_as_json() {
+        let journal = Journal {
+            version: game::JOURNAL_VERSION,
+            hasher: 0,
+            move_index: 2,
+            prev_chain_hash: Digest::from([1; 8]),
+            chain_hash: Digest::from([2; 8]),
+            commitment: None,
+            match_hash: None,
+            response: VmResponse::Rejected {
+                game_id: [3; 32],
+                error: game::MoveError::CellOccupied,
+                prev_state_hash: Digest::from([4; 8]),
+                player: game::Player::B,
+                point: game::Point::new(1, 1)
+            }
+        };
+
+        let proven = Proven::Move(journal);
+        assert_eq!(proven.prev_state_hash(), Some(&Digest::from([4; 8])));
+
+        let json = proven.to_json();
+
+        assert_eq!(json["move_index"], 2);
+        assert_eq!(json["chain_hash"], hex::encode(Digest::from([2; 8]).as_bytes()));
+        assert_eq!(json["commitment"], Value::Null);
+        assert_eq!(json["response"]["accepted"], false);
+        assert_eq!(json["response"]["game_id"], hex::encode([3; 32]));
+        assert_eq!(json["response"]["player"], "B");
+        assert_eq!(json["response"]["point"], json!({ "x": 1, "y": 1 }));
+    }
+}
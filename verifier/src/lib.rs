@@ -0,0 +1,10 @@
+#[cfg(feature = "guests")]
+pub mod client;
+pub mod receipt;
+pub mod export;
+pub mod evm;
+pub mod solana;
+pub mod near;
+pub mod cosmwasm;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,26 @@
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde_json::{json, Value};
+use risc0_zkvm::sha::Digest;
+
+use crate::evm::Settlement;
+
+// The JSON arguments of the `verify` method of a RISC Zero verifier contract
+// on NEAR, which takes byte strings as base64, as near-sdk's `Base64VecU8`
+// does, and digests as arrays of 32 numbers, as it does `[u8; 32]`.
+pub fn verify_args(settlement: &Settlement, image_id: Digest) -> Value {
+    json!({
+        "seal": BASE64.encode(&settlement.seal),
+        "image_id": image_id.as_bytes(),
+        "post_state_digest": settlement.post_state_digest.as_bytes(),
+        "journal_digest": settlement.journal_digest().as_bytes()
+    })
+}
+
+// Along with the journal, which contracts that settle results decode themselves,
+// as they would be called with it after the verifier answers.
+pub fn to_json(settlement: &Settlement, image_id: Digest) -> Value {
+    json!({
+        "args": verify_args(settlement, image_id),
+        "journal": BASE64.encode(&settlement.journal)
+    })
+}
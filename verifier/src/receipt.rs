@@ -0,0 +1,102 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+use risc0_zkvm::{SegmentReceipt, SessionReceipt, sha::Digest};
+use game::{Journal, JournalError, VmResponse};
+
+// The guest build that proves moves and the journal version it produces,
+// which both sides agree on at the start of a match, see `client::Client::pin`.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GuestPin {
+    pub make_move_id: Digest,
+    pub journal_version: u32
+}
+
+#[derive(Debug)]
+pub enum VerifyError {
+    // An argument that could not be decoded, such as an image ID that is not 32 bytes long.
+    Malformed(&'static str),
+    // The seal does not prove the journal for the image ID.
+    Invalid(String),
+    Journal(JournalError),
+    // The move was made against a different state than the expected one.
+    PrevHashMismatch
+}
+
+// Verifies the receipt of a single move, given as its parts, against the
+// guest with `image_id` and checks that the move was made on top of the
+// state with `expected_prev_hash`, without tracking a whole game like
+// `Client` does. The seal is the bincode encoding of the segment receipts
+// of the `SessionReceipt`, in the order they were proven.
+pub fn verify_receipt(
+    journal: &[u8],
+    seal: &[u8],
+    image_id: &[u8],
+    expected_prev_hash: &[u8]
+) -> Result<Journal, VerifyError> {
+    let image_id = Digest::try_from(image_id).map_err(|_| VerifyError::Malformed("image ID"))?;
+    let expected_prev_hash = Digest::try_from(expected_prev_hash)
+        .map_err(|_| VerifyError::Malformed("expected previous state hash"))?;
+
+    let segments: Vec<SegmentReceipt> = bincode::deserialize(seal)
+        .map_err(|_| VerifyError::Malformed("seal"))?;
+
+    let receipt = SessionReceipt { segments, journal: journal.to_vec() };
+
+    receipt.verify(image_id).map_err(|error| VerifyError::Invalid(error.to_string()))?;
+
+    let journal = Journal::decode(journal).map_err(VerifyError::Journal)?;
+
+    let prev_state_hash = match &journal.response {
+        VmResponse::Accepted { prev_state_hash, .. } => prev_state_hash,
+        VmResponse::Rejected { prev_state_hash, .. } => prev_state_hash
+    };
+
+    if prev_state_hash != &expected_prev_hash {
+        return Err(VerifyError::PrevHashMismatch);
+    }
+
+    Ok(journal)
+}
+
+// The seal of `receipt`, as `verify_receipt` takes it.
+pub fn encode_seal(receipt: &SessionReceipt) -> Vec<u8> {
+    bincode::serialize(&receipt.segments).expect("segment receipts are always serializable")
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(what) => write!(f, "Malformed {what}."),
+            Self::Invalid(error) => write!(f, "Receipt verification failed: {error}"),
+            Self::Journal(error) => write!(f, "{error}"),
+            Self::PrevHashMismatch => write!(f, "The move was made against a different state.")
+        }
+    }
+}
+
+impl std::error::Error for VerifyError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn malformed_parts_are_refused() {
+        let receipt = SessionReceipt { segments: vec![], journal: vec![] };
+        let seal = encode_seal(&receipt);
+
+        assert!(matches!(
+            verify_receipt(&[], &seal, &[0; 31], &[0; 32]),
+            Err(VerifyError::Malformed("image ID"))
+        ));
+        assert!(matches!(
+            verify_receipt(&[], &seal, &[0; 32], &[0; 8]),
+            Err(VerifyError::Malformed("expected previous state hash"))
+        ));
+        assert!(matches!(
+            verify_receipt(&[], &[1, 2, 3], &[0; 32], &[0; 32]),
+            Err(VerifyError::Malformed("seal"))
+        ));
+    }
+}
@@ -0,0 +1,127 @@
+use std::io;
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde_json::{json, Value};
+use risc0_zkvm::sha::{Sha256, Digest, Impl};
+
+use crate::evm::Settlement;
+
+// A Groth16 seal as the EVM verifier takes it: the points A, B and C with
+// big endian coordinates, which is also the layout of the alt_bn128 syscalls.
+pub const SEAL_SIZE: usize = 256;
+
+// The base field modulus of BN254, which the Y coordinate of A is negated in.
+const FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47
+];
+
+// The proof as the RISC Zero Groth16 verifier program on Solana takes it,
+// with A negated so that the program can check the pairing in a single call.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Proof {
+    pub pi_a: [u8; 64],
+    pub pi_b: [u8; 128],
+    pub pi_c: [u8; 64]
+}
+
+impl Proof {
+    pub fn from_seal(seal: &[u8]) -> io::Result<Self> {
+        if seal.len() != SEAL_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("a Groth16 seal is {SEAL_SIZE} bytes, not {}", seal.len())
+            ));
+        }
+
+        let mut proof = Self { pi_a: [0; 64], pi_b: [0; 128], pi_c: [0; 64] };
+        proof.pi_a.copy_from_slice(&seal[..64]);
+        proof.pi_b.copy_from_slice(&seal[64..192]);
+        proof.pi_c.copy_from_slice(&seal[192..]);
+
+        let y = negate(proof.pi_a[32..].try_into().unwrap());
+        proof.pi_a[32..].copy_from_slice(&y);
+
+        Ok(proof)
+    }
+}
+
+// The data of the `verify` instruction of the verifier program: the Anchor
+// discriminator followed by the Borsh encoded proof, image ID and journal
+// digest, all of which are fixed size arrays and so encoded as they are.
+pub fn instruction_data(settlement: &Settlement, image_id: Digest) -> io::Result<Vec<u8>> {
+    let proof = Proof::from_seal(&settlement.seal)?;
+
+    let mut data = discriminator("verify").to_vec();
+    data.extend(proof.pi_a);
+    data.extend(proof.pi_b);
+    data.extend(proof.pi_c);
+    data.extend(image_id.as_bytes());
+    data.extend(settlement.journal_digest().as_bytes());
+
+    Ok(data)
+}
+
+// The instruction data as base64, as transactions are built from by most
+// Solana clients, with the journal that the program leaves to the caller to check.
+pub fn to_json(settlement: &Settlement, image_id: Digest) -> io::Result<Value> {
+    Ok(json!({
+        "imageId": hex::encode(image_id.as_bytes()),
+        "journal": BASE64.encode(&settlement.journal),
+        "journalDigest": hex::encode(settlement.journal_digest().as_bytes()),
+        "instructionData": BASE64.encode(instruction_data(settlement, image_id)?)
+    }))
+}
+
+// Of an Anchor instruction, the first eight bytes of sha256("global:<name>").
+fn discriminator(instruction: &str) -> [u8; 8] {
+    let hash = Impl::hash_bytes(format!("global:{instruction}").as_bytes());
+
+    hash.as_bytes()[..8].try_into().unwrap()
+}
+
+// Modulo the field, where the negation of zero is zero.
+fn negate(y: [u8; 32]) -> [u8; 32] {
+    if y == [0; 32] {
+        return y;
+    }
+
+    let mut negated = [0; 32];
+    let mut borrow = 0;
+
+    for i in (0..32).rev() {
+        let difference = FIELD_MODULUS[i] as i16 - y[i] as i16 - borrow;
+        borrow = (difference < 0) as i16;
+        negated[i] = difference.rem_euclid(256) as u8;
+    }
+
+    negated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seals_are_split_for_the_verifier_program() {
+        let mut seal: Vec<u8> = (0..SEAL_SIZE).map(|byte| byte as u8).collect();
+        seal[32..64].copy_from_slice(&[0; 32]);
+        seal[63] = 1;
+
+        let settlement = Settlement { seal: seal.clone(), post_state_digest: Digest::from([0; 8]), journal: vec![1, 2] };
+        let data = instruction_data(&settlement, Digest::from([3; 8])).unwrap();
+
+        let mut modulus_less_one = FIELD_MODULUS;
+        modulus_less_one[31] -= 1;
+
+        assert_eq!(data.len(), 8 + SEAL_SIZE + 32 + 32);
+        assert_eq!(data[8..40], seal[..32]);
+        assert_eq!(data[40..72], modulus_less_one);
+        assert_eq!(data[72..8 + SEAL_SIZE], seal[64..]);
+        assert_eq!(data[8 + SEAL_SIZE + 32..], *settlement.journal_digest().as_bytes());
+
+        assert_eq!(negate(negate([7; 32])), [7; 32]);
+        assert_eq!(negate([0; 32]), [0; 32]);
+        assert!(Proof::from_seal(&seal[1..]).is_err());
+    }
+}
@@ -0,0 +1,294 @@
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use wasm_bindgen::prelude::*;
+use risc0_zkvm::{SessionReceipt, sha::Digest};
+use methods::GuestMethod;
+use game::{
+    TicTacToe, State, Player, Point, VmResponse,
+    metadata::MatchMetadata, clock::Forfeit, chat::ChatMessage,
+    auth::{self, Concession}
+};
+
+use crate::{client::{Client, GuestPin, Refusal}, receipt};
+
+// The client of a player or spectator in a browser, which verifies the receipts
+// that the server sends over the WebSocket transport itself, so that the server
+// is not trusted with the game state. Messages are passed in and out as JSON,
+// in the same encoding as the transport. A receipt that fails verification
+// throws, with the reason as the message of the error, and anything else that
+// fails to verify with the reason logged to the console.
+#[wasm_bindgen]
+pub struct BrowserClient {
+    client: Client,
+    metadata: MatchMetadata,
+    match_hash: Digest,
+    // The board after every accepted move, to go back to on a take-back.
+    boards: Vec<TicTacToe>,
+    // The key that the server signs forfeits on time with.
+    referee: [u8; 32]
+}
+
+// What a verified move receipt proves, see `BrowserClient::verify_move`.
+#[derive(Serialize)]
+struct VerifiedMove {
+    player: String,
+    x: usize,
+    y: usize,
+    // Why the move was rejected, if it was.
+    error: Option<String>,
+    // The symbols on the board after the move, row by row.
+    cells: Vec<String>,
+    state: State
+}
+
+// What a receipt verified on its own proves, see `verify_receipt`.
+#[derive(Serialize)]
+struct ProvenMove {
+    game_id: String,
+    move_index: u32,
+    player: Player,
+    x: usize,
+    y: usize,
+    error: Option<String>,
+    // Of the state after the move, unless it was rejected.
+    new_state_hash: Option<String>,
+    chain_hash: String
+}
+
+#[derive(Serialize)]
+struct VerifiedResult {
+    state: State,
+    winner: Option<String>,
+    move_count: u32
+}
+
+#[derive(Serialize)]
+struct VerifiedChat {
+    player: String,
+    text: String
+}
+
+// The bodies of the `Resigned` and `Drawn` messages, see `verify_ending`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Ending {
+    Resigned { player: Player, signature: Option<Vec<u8>> },
+    Drawn { signatures: [Option<Vec<u8>>; 2] }
+}
+
+#[derive(Serialize)]
+struct VerifiedForfeit {
+    // The player that ran out of time.
+    player: String,
+    winner: String,
+    move_limit_secs: u32
+}
+
+#[wasm_bindgen]
+impl BrowserClient {
+    // Verifies the receipt of the initial state from the `Welcome` or `Spectating`
+    // message, whose metadata, pin and referee key are given along with it.
+    #[wasm_bindgen(constructor)]
+    pub fn new(metadata: &str, pin: &str, receipt: &str, referee: &str) -> Result<BrowserClient, JsError> {
+        console_error_panic_hook::set_once();
+
+        let metadata: MatchMetadata = from_json(metadata)?;
+        let pin: GuestPin = from_json(pin)?;
+        let receipt: SessionReceipt = from_json(receipt)?;
+        let referee: [u8; 32] = from_json(referee)?;
+
+        let match_hash = metadata.hash();
+
+        let mut client = Client::try_from_init_receipt(&receipt, &match_hash).map_err(refused)?;
+        client.try_pin(pin).map_err(refused)?;
+        assert_eq!(metadata.image_id, Some(client.pin.make_move_id), "Match terms mismatch!");
+
+        let boards = vec![metadata.initial_game()];
+
+        Ok(Self { client, metadata, match_hash, boards, referee })
+    }
+
+    // Verifies the receipt of a `Move` message and returns the move it proves.
+    #[wasm_bindgen(js_name = verifyMove)]
+    pub fn verify_move(&mut self, receipt: &str) -> Result<String, JsError> {
+        let receipt: SessionReceipt = from_json(receipt)?;
+
+        let (player, point, error) = match self.client.try_verify_receipt(&receipt).map_err(refused)? {
+            VmResponse::Accepted { game, player, point, .. } => {
+                self.boards.push(game);
+
+                (player, point, None)
+            },
+            VmResponse::Rejected { player, point, error, .. } => (player, point, Some(error.to_string()))
+        };
+
+        to_json(&VerifiedMove {
+            player: self.metadata.player(player).name.clone(),
+            x: point.x(),
+            y: point.y(),
+            error,
+            cells: self.cells(),
+            state: self.client.game_state
+        })
+    }
+
+    // Verifies the receipt of the whole game from the `Finished` message.
+    #[wasm_bindgen(js_name = verifyResult)]
+    pub fn verify_result(&self, receipt: &str) -> Result<String, JsError> {
+        let receipt: SessionReceipt = from_json(receipt)?;
+        let journal = self.client.try_verify_final_receipt(&receipt, &self.match_hash).map_err(refused)?;
+
+        let winner = match journal.state {
+            State::Winner(player) => Some(self.metadata.player(player).name.clone()),
+            _ => None
+        };
+
+        to_json(&VerifiedResult { state: journal.state, winner, move_count: journal.move_count })
+    }
+
+    // Verifies the `Forfeit` message of a player that ran out of time.
+    #[wasm_bindgen(js_name = verifyForfeit)]
+    pub fn verify_forfeit(&mut self, forfeit: &str) -> Result<String, JsError> {
+        let forfeit: Forfeit = from_json(forfeit)?;
+        let winner = self.client.verify_forfeit(&forfeit, &self.referee, self.metadata.first_player);
+
+        to_json(&VerifiedForfeit {
+            player: self.metadata.player(forfeit.player).name.clone(),
+            winner: self.metadata.player(winner).name.clone(),
+            move_limit_secs: forfeit.clock.move_limit_secs
+        })
+    }
+
+    // Verifies that the `Chat` message was signed by the player it names.
+    #[wasm_bindgen(js_name = verifyChat)]
+    pub fn verify_chat(&self, chat: &str) -> Result<String, JsError> {
+        let chat: ChatMessage = from_json(chat)?;
+        let info = self.metadata.player(chat.player);
+
+        let signed = matches!(info.public_key, Some(public_key) if chat.verify(&public_key));
+
+        if !signed || chat.game_id != self.metadata.game_id {
+            return Err(JsError::new("the chat message was not signed by the player"));
+        }
+
+        to_json(&VerifiedChat { player: info.name.clone(), text: chat.text })
+    }
+
+    // Verifies that the players who ended the game with the `Resigned` or `Drawn`
+    // message signed it at this point of the game, if they were seated with a
+    // key, and returns the name of the player who resigned, if one did.
+    #[wasm_bindgen(js_name = verifyEnding)]
+    pub fn verify_ending(&self, ending: &str) -> Result<Option<String>, JsError> {
+        let (concession, signatures, resigned) = match from_json(ending)? {
+            Ending::Resigned { player, signature } => (Concession::Resign, vec![(player, signature)], Some(player)),
+            Ending::Drawn { signatures: [a, b] } => (Concession::Draw, vec![(Player::A, a), (Player::B, b)], None)
+        };
+
+        for (player, signature) in signatures {
+            let public_key = self.metadata.player(player).public_key;
+            let (game_id, move_index) = (&self.metadata.game_id, self.client.move_index);
+
+            if !auth::is_conceded(public_key, signature.as_deref(), concession, game_id, move_index) {
+                return Err(JsError::new("the game was ended for a player who did not sign it"));
+            }
+        }
+
+        Ok(resigned.map(|player| self.metadata.player(player).name.clone()))
+    }
+
+    // Goes back to the state of the `TakenBack` message, which this client has
+    // verified already, and returns the symbols on the board then.
+    #[wasm_bindgen(js_name = takeBack)]
+    pub fn take_back(&mut self, move_index: u32) -> Result<String, JsError> {
+        self.client.take_back(move_index);
+        self.boards.truncate(move_index as usize + 1);
+
+        to_json(&self.cells())
+    }
+
+    // Whether the terms of a `Welcome` message are those of a rematch of this
+    // game, see `MatchMetadata::rematch`.
+    #[wasm_bindgen(js_name = isRematch)]
+    pub fn is_rematch(&self, metadata: &str) -> Result<bool, JsError> {
+        let metadata: MatchMetadata = from_json(metadata)?;
+
+        Ok(metadata == self.metadata.rematch(metadata.nonce))
+    }
+
+    #[wasm_bindgen(js_name = gameId)]
+    pub fn game_id(&self) -> String {
+        hex::encode(self.metadata.game_id)
+    }
+
+    // The hash of the verified state, as the server cannot forge it.
+    #[wasm_bindgen(js_name = stateHash)]
+    pub fn state_hash(&self) -> String {
+        hex::encode(self.client.state_hash.as_bytes())
+    }
+
+    fn cells(&self) -> Vec<String> {
+        let glyphs = self.metadata.glyphs();
+
+        (0..3).flat_map(|y| (0..3).map(move |x| Point::new(x, y)))
+            .map(|point| glyphs.get(self.boards[self.boards.len() - 1].get(point)).trim().to_string())
+            .collect()
+    }
+}
+
+// The guest build that this client verifies receipts of, as JSON, whose image
+// ID is part of the terms of the games that it opens or joins.
+#[wasm_bindgen(js_name = guestPin)]
+pub fn guest_pin() -> Result<String, JsError> {
+    to_json(&GuestPin::local())
+}
+
+// Verifies the receipt of a single move given as its parts, for web apps that
+// do not play through a server, see `receipt::verify_receipt`. Returns what
+// it proves as JSON, or throws if it does not verify.
+#[wasm_bindgen(js_name = verifyReceipt)]
+pub fn verify_receipt(
+    journal: &[u8],
+    seal: &[u8],
+    image_id: &[u8],
+    expected_prev_hash: &[u8]
+) -> Result<String, JsError> {
+    let journal = receipt::verify_receipt(journal, seal, image_id, expected_prev_hash)
+        .map_err(|error| JsError::new(&error.to_string()))?;
+
+    let (game_id, player, point, error, new_state_hash) = match journal.response {
+        VmResponse::Accepted { game_id, player, point, new_state_hash, .. } => {
+            (game_id, player, point, None, Some(hex::encode(new_state_hash.as_bytes())))
+        },
+        VmResponse::Rejected { game_id, player, point, error, .. } => {
+            (game_id, player, point, Some(error.to_string()), None)
+        }
+    };
+
+    to_json(&ProvenMove {
+        game_id: hex::encode(game_id),
+        move_index: journal.move_index,
+        player,
+        x: point.x(),
+        y: point.y(),
+        error,
+        new_state_hash,
+        chain_hash: hex::encode(journal.chain_hash.as_bytes())
+    })
+}
+
+// The image ID of the guest that proves moves, to verify their receipts against.
+#[wasm_bindgen(js_name = makeMoveImageId)]
+pub fn make_move_image_id() -> Vec<u8> {
+    Digest::from(GuestMethod::MakeMove.image_id()).as_bytes().to_vec()
+}
+
+fn refused(refusal: Refusal) -> JsError {
+    JsError::new(&refusal.to_string())
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, JsError> {
+    serde_json::from_str(json).map_err(|error| JsError::new(&error.to_string()))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, JsError> {
+    serde_json::to_string(value).map_err(|error| JsError::new(&error.to_string()))
+}
@@ -0,0 +1,308 @@
+// Plays or watches a game on a server started with "--transport websocket",
+// verifying every receipt in the browser with the `verifier` crate compiled
+// to WebAssembly, see `BrowserClient`.
+import init, { BrowserClient, guestPin } from "./pkg/verifier.js";
+
+await init();
+
+// Of the messages of the `wire` crate, see `PROTOCOL_VERSION`.
+const PROTOCOL_VERSION = 5;
+
+const pin = JSON.parse(guestPin());
+const hello = { protocol_version: PROTOCOL_VERSION, journal_version: pin.journal_version, image_id: pin.make_move_id };
+const element = id => document.getElementById(id);
+
+let socket = null;
+let client = null;
+let myTurn = false;
+// Whether the server has greeted us with a `Hello` that this client can play with.
+let greeted = false;
+// Whether a rematch was accepted, so that the next game has to follow from the last one.
+let rematch = false;
+
+element("connect").addEventListener("submit", event => {
+    event.preventDefault();
+
+    connect(() => send({ Register: { name: element("name").value, symbol: element("symbol").value } }));
+});
+
+element("watch").addEventListener("click", () => {
+    const hex = element("game-id").value.trim();
+    const gameId = hex.match(/../g)?.map(byte => parseInt(byte, 16)) ?? [];
+
+    connect(() => send({ Watch: gameId }));
+});
+
+// Only the games proven by the same guest build as this client can be verified by it.
+element("open").addEventListener("click", () => {
+    send({ Open: { ruleset: "Standard", image_id: pin.make_move_id, first_player: "A" } });
+    element("lobby").hidden = true;
+    status("Waiting for another player to join...");
+});
+
+element("refresh").addEventListener("click", () => send("ListGames"));
+
+// Sent instead of a move, so only when it's our turn.
+element("take-back").addEventListener("click", () => {
+    if (myTurn) {
+        myTurn = false;
+        send("RequestTakeBack");
+        status("Waiting for the opponent to agree to the take-back...");
+    }
+});
+
+element("offer-draw").addEventListener("click", () => {
+    if (myTurn) {
+        myTurn = false;
+        send("OfferDraw");
+        status("Waiting for the opponent to answer the draw offer...");
+    }
+});
+
+element("resign").addEventListener("click", () => {
+    if (myTurn && confirm("Give the game to your opponent?")) {
+        myTurn = false;
+        send("Resign");
+    }
+});
+
+for (let y = 0; y < 3; y++) {
+    for (let x = 0; x < 3; x++) {
+        const cell = document.createElement("button");
+
+        cell.addEventListener("click", () => {
+            if (myTurn) {
+                myTurn = false;
+                send({ Move: { x, y } });
+                status("Waiting for the receipt of the move...");
+            }
+        });
+
+        element("board").append(cell);
+    }
+}
+
+function connect(onOpen) {
+    socket?.close();
+    socket = new WebSocket(`ws://${element("address").value}`);
+
+    greeted = false;
+
+    // Greeting the server comes before anything else, see `protocol::greet`.
+    socket.addEventListener("open", () => {
+        send(hello);
+        onOpen();
+    });
+    socket.addEventListener("message", event => receive(JSON.parse(event.data)));
+    socket.addEventListener("close", () => myTurn = false);
+}
+
+function send(message) {
+    socket.send(JSON.stringify(message));
+}
+
+// Handles a `ServerMessage`, whose unit variants arrive as strings,
+// after the `Hello` of the server.
+function receive(message) {
+    if (!greeted) {
+        return greet(message);
+    }
+
+    if (message === "YourTurn") {
+        myTurn = true;
+        status("Your turn.");
+
+        return;
+    }
+
+    if (message === "TakeBackRequested") {
+        send({ AcceptTakeBack: confirm("Your opponent asks to take back their last move and your reply. Agree?") });
+
+        return;
+    }
+
+    if (message === "DrawOffered") {
+        send({ AcceptDraw: confirm("Your opponent offers a draw. Agree?") });
+
+        return;
+    }
+
+    if (message === "RematchOffered") {
+        rematch = confirm("Play a rematch, with the other player moving first?");
+        send({ AcceptRematch: rematch });
+
+        return;
+    }
+
+    const [kind, body] = Object.entries(message)[0];
+
+    try {
+        switch (kind) {
+            case "Lobby": return showLobby(body);
+            case "Refused": return alert(body);
+            case "Welcome": return welcome(body);
+            case "Spectating": return start(body, "Watching the game.");
+            case "Move": return played(JSON.parse(client.verifyMove(JSON.stringify(body))));
+            case "Finished": return finished(JSON.parse(client.verifyResult(JSON.stringify(body))));
+            case "Forfeit": return forfeited(JSON.parse(client.verifyForfeit(JSON.stringify(body))));
+            case "TakenBack": return takenBack(JSON.parse(client.takeBack(body.move_index)));
+            case "Chat": return chatted(JSON.parse(client.verifyChat(JSON.stringify(body))));
+            case "Resigned": return endedEarly(`${client.verifyEnding(JSON.stringify(body))} resigned.`);
+            case "Drawn":
+                client.verifyEnding(JSON.stringify(body));
+                return endedEarly("The players agreed to a draw.");
+        }
+    } catch (error) {
+        // The server sent a receipt that does not verify, so it cannot be trusted with the game.
+        log(`Verification failed: ${error}`, "failed");
+        socket.close();
+    }
+}
+
+// The server refuses clients of builds whose receipts it cannot verify, and
+// this client refuses servers the same way, telling the player why.
+function greet(theirs) {
+    const refusal = incompatibility(theirs);
+
+    if (refusal) {
+        alert(`Cannot play on this server: ${refusal}`);
+        socket.close();
+    } else {
+        greeted = true;
+    }
+}
+
+function incompatibility({ protocol_version, journal_version, image_id }) {
+    if (protocol_version !== hello.protocol_version) {
+        return `The server speaks version ${protocol_version} of the protocol and this page version ${hello.protocol_version}.`;
+    }
+
+    if (journal_version !== hello.journal_version) {
+        return `The server's guests commit journals of version ${journal_version} and this page's of version ${hello.journal_version}.`;
+    }
+
+    if (JSON.stringify(image_id) !== JSON.stringify(hello.image_id)) {
+        return "The server proves moves with another guest than this page. Both have to be built from the same source.";
+    }
+
+    return null;
+}
+
+function showLobby(games) {
+    const list = element("games");
+    list.replaceChildren();
+
+    for (const game of games) {
+        if (JSON.stringify(game.terms.image_id) !== JSON.stringify(pin.make_move_id)) {
+            continue;
+        }
+
+        const item = document.createElement("li");
+        const join = document.createElement("button");
+
+        join.textContent = `Join ${game.host.name} (${game.host.symbol})`;
+        join.addEventListener("click", () => send({ Join: game.id }));
+
+        item.append(join);
+        list.append(item);
+    }
+
+    element("lobby").hidden = false;
+}
+
+function start({ metadata, pin, receipt, referee }, greeting) {
+    client?.free();
+    client = new BrowserClient(
+        JSON.stringify(metadata),
+        JSON.stringify(pin),
+        JSON.stringify(receipt),
+        JSON.stringify(referee)
+    );
+
+    element("lobby").hidden = true;
+    element("game").hidden = false;
+    element("log").replaceChildren();
+
+    for (const cell of element("board").children) {
+        cell.textContent = "";
+    }
+
+    status(`${metadata.player_a.name} (${metadata.player_a.symbol}) against ${metadata.player_b.name} (${metadata.player_b.symbol}). ${greeting}`);
+    log(`✓ Verified the initial state of game ${client.gameId()}.`, "verified");
+    showStateHash();
+}
+
+function welcome(body) {
+    if (rematch && !client.isRematch(JSON.stringify(body.metadata))) {
+        throw new Error("Rematch terms mismatch!");
+    }
+
+    rematch = false;
+    start(body, `Joined as ${seated(body).name}.`);
+}
+
+function seated({ player, metadata }) {
+    return player === "A" ? metadata.player_a : metadata.player_b;
+}
+
+function played({ player, x, y, error, cells }) {
+    cells.forEach((symbol, index) => element("board").children[index].textContent = symbol);
+
+    if (error) {
+        log(`✓ Verified that ${player} attempted ${x} ${y}, which was rejected: ${error}`, "verified");
+    } else {
+        log(`✓ Verified that ${player} played ${x} ${y}.`, "verified");
+    }
+
+    showStateHash();
+}
+
+// Chat messages can only be sent by players with a key, which browsers do not register with yet.
+function chatted({ player, text }) {
+    log(`✓ ${player}: ${text}`, "verified");
+}
+
+function takenBack(cells) {
+    cells.forEach((symbol, index) => element("board").children[index].textContent = symbol);
+
+    log("The players agreed to take back the last two moves, returning to a verified state.", "verified");
+    showStateHash();
+}
+
+function finished({ winner, move_count }) {
+    const result = winner ? `${winner} won in ${move_count} moves` : "the game ended in a stalemate";
+
+    status(`Verified that ${result}.`);
+    log(`✓ Verified the receipt of the whole game.`, "verified");
+}
+
+function forfeited({ player, winner, move_limit_secs }) {
+    myTurn = false;
+    status(`${player} ran out of time, so ${winner} wins.`);
+    log(`✓ Verified the forfeit of ${player}, who took longer than ${move_limit_secs} seconds, as signed by the server.`, "verified");
+}
+
+// Nothing proves a resignation or a draw, only the moves before it.
+function endedEarly(text) {
+    myTurn = false;
+    status(text);
+    log(text, "ended");
+}
+
+function showStateHash() {
+    element("state-hash").textContent = client.stateHash();
+}
+
+function status(text) {
+    element("status").textContent = text;
+}
+
+function log(text, className) {
+    const item = document.createElement("li");
+
+    item.textContent = text;
+    item.className = className;
+
+    element("log").append(item);
+    item.scrollIntoView({ block: "nearest" });
+}
@@ -0,0 +1,330 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+use risc0_zkvm::{SessionReceipt, sha::Digest};
+use game::{
+    Player, Point,
+    metadata::{MatchMetadata, PlayerInfo, Ruleset, GameId}, clock::{Clock, Forfeit}, chat::ChatMessage,
+    auth::SignedMove
+};
+use verifier::receipt::GuestPin;
+
+// The version of the messages below, which servers and clients of remote games
+// exchange as bincode over TCP or as JSON over WebSockets, so that native
+// clients and the browser client share them. Must be incremented whenever a
+// message is added, removed or changed, as neither encoding can tell.
+pub const PROTOCOL_VERSION: u32 = 5;
+
+// Sent by both the client and the server before anything else on every
+// connection, so that they refuse to play across builds that cannot verify each
+// other's receipts, rather than failing on a hash mismatch once the game has
+// started. Unlike the messages, it must never change, so that any two versions
+// can read each other's.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Hello {
+    pub protocol_version: u32,
+    // Of the journals that the guests commit, see `game::JOURNAL_VERSION`.
+    pub journal_version: u32,
+    // Of the guest that proves moves, see `GuestPin`.
+    pub image_id: Digest
+}
+
+// Why a client and a server cannot play together, see `Hello::check`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Incompatible {
+    Protocol { ours: u32, theirs: u32 },
+    Journal { ours: u32, theirs: u32 },
+    Guest { ours: Digest, theirs: Digest }
+}
+
+// Given to each seated client, which resumes its seat with it after reconnecting.
+pub type SessionToken = [u8; 32];
+
+// Signed by players that register with a public key, to prove that they hold the secret key.
+pub type Challenge = [u8; 32];
+
+// The parameters of a match that the paired players agree on. They end up in
+// its metadata and so are committed to by the receipt of its initial state.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Terms {
+    pub ruleset: Ruleset,
+    // Of the guest that proves moves, see `GuestPin`.
+    pub image_id: Digest,
+    // Whether the player that opened the game or the one that joined it moves first.
+    pub first_player: Player
+}
+
+// A game waiting for a second player, who joins it as player B.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct OpenGame {
+    pub id: u32,
+    pub host: PlayerInfo,
+    pub terms: Terms
+}
+
+// Sent by the server to the remote clients.
+#[derive(Serialize, Deserialize, Clone)]
+pub enum ServerMessage {
+    // Asks a client that registered with a public key to prove that it holds
+    // the secret key, see `ClientMessage::Authenticate`.
+    Challenge(Challenge),
+    // The games in the lobby that can be joined.
+    Lobby(Vec<OpenGame>),
+    // Why the last request of the client could not be fulfilled, which is the
+    // error of the protocol. The connection stays open.
+    Refused(String),
+    // Seats the client as `player` in the match, with the
+    // receipt proving the initial state of the game. Forfeits
+    // on time are signed with the `referee` key of the server.
+    Welcome {
+        player: Player,
+        metadata: Box<MatchMetadata>,
+        pin: GuestPin,
+        receipt: SessionReceipt,
+        token: SessionToken,
+        clock: Option<Clock>,
+        referee: [u8; 32]
+    },
+    // Starts sending the receipts of the match to a spectator, beginning with the
+    // moves made so far, which it verifies just like the players do.
+    Spectating {
+        metadata: Box<MatchMetadata>,
+        pin: GuestPin,
+        receipt: SessionReceipt,
+        clock: Option<Clock>,
+        referee: [u8; 32]
+    },
+    // Asks the client for the next move.
+    YourTurn,
+    // The receipt of a move by either player.
+    Move(SessionReceipt),
+    // The receipt proving the whole game, once it has ended.
+    Finished(SessionReceipt),
+    // Ends the game instead once the player on turn has run out of time.
+    Forfeit(Forfeit),
+    // Ends the game instead once the given player resigned, see `ClientMessage::SignedResign`.
+    // The signature is the one of the player, if they were seated with a key.
+    Resigned {
+        player: Player,
+        signature: Option<Vec<u8>>
+    },
+    // Asks the client whether to agree to a draw that its opponent offered,
+    // see `ClientMessage::OfferDraw`.
+    DrawOffered,
+    // Ends the game instead once the players agreed to a draw, with the
+    // signatures of players A and B, of those that were seated with a key.
+    Drawn {
+        signatures: [Option<Vec<u8>>; 2]
+    },
+    // Asks the client whether to agree to take back the last move of its
+    // opponent along with its own reply, see `ClientMessage::RequestTakeBack`.
+    TakeBackRequested,
+    // The players agreed to go back to the state after the first `move_index`
+    // moves, which the clients have verified the receipts of already. The
+    // receipts of the moves since are dropped, leaving `receipts` of them.
+    TakenBack {
+        move_index: u32,
+        receipts: u32
+    },
+    // Offers both players a rematch once their game has ended. If they both
+    // accept it, they are seated again with the same session tokens in a match
+    // with the terms of `MatchMetadata::rematch`, and its own initial state.
+    RematchOffered,
+    // A chat message of either player, relayed to the opponent and the spectators.
+    Chat(ChatMessage),
+    // Sent instead of the missed messages to a client resuming a seat that
+    // does not exist, or from a point of the game that the server did not
+    // have it at, see `ClientMessage::Resume`. The client has to give up on
+    // the game, as resuming it again would be refused again.
+    ResumeRefused(String)
+}
+
+// Sent by a remote client to the server. Clients first register in the
+// lobby and then either open a game or join one, see `lobby::Lobby`.
+#[derive(Serialize, Deserialize)]
+pub enum ClientMessage {
+    // Players that register with a public key are challenged to sign with it
+    // before they enter the lobby. Their key is then part of the terms of their
+    // matches, so their chat messages can be attributed to them.
+    Register(PlayerInfo),
+    // The signature over the challenge, see `identity::verify_login`.
+    Authenticate(Vec<u8>),
+    ListGames,
+    // Waits for another player to join, after which the client is seated.
+    Open(Terms),
+    Join(u32),
+    // Takes the seat of `token` over from a lost connection, instead of registering.
+    // The server then sends the `Move` and `TakenBack` messages of the game after
+    // the first `received` ones, so that clients learn of both whichever they missed.
+    // The seat is only given back to clients that verified the hash chain up to
+    // `chain_hash` with those messages, as the server has them, see `Client::chain_hash`.
+    Resume {
+        token: SessionToken,
+        received: u32,
+        chain_hash: Digest
+    },
+    // Watches the game being played with the given ID, instead of registering.
+    Watch(GameId),
+    // Only accepted from players that registered without a key, see `SignedMove`.
+    Move(Point),
+    // A move signed with the key that the player registered with, over the game
+    // ID, the move index and the point, see `auth::move_message`. The server
+    // refuses moves that are not signed by the player on turn before proving
+    // them, so that a compromised connection cannot make moves for a player.
+    SignedMove(SignedMove),
+    // Sent instead of a move to take back the last move of the player, along
+    // with the reply of the opponent, if the opponent agrees to it.
+    RequestTakeBack,
+    // The answer of the opponent to `ServerMessage::TakeBackRequested`.
+    AcceptTakeBack(bool),
+    // Only accepted from players that registered without a key, see `SignedResign`.
+    Resign,
+    // Sent instead of a move to give the game to the opponent, signed with the
+    // key that the player registered with over `auth::concession_message` at the
+    // current move index. Nothing is proven for it, the receipts so far prove
+    // the game up to there, so the signature is what the opponent and the
+    // spectators check instead of taking the word of the server for it.
+    SignedResign(Vec<u8>),
+    // Only accepted from players that registered without a key, see `SignedOfferDraw`.
+    OfferDraw,
+    // Sent instead of a move to offer the opponent a draw, which ends the game
+    // like a resignation if they agree to it, or else asks for a move again.
+    // Signed like `SignedResign`, and relayed in `ServerMessage::Drawn`.
+    SignedOfferDraw(Vec<u8>),
+    // The answer of the opponent to `ServerMessage::DrawOffered`, which only
+    // declines it for players that registered with a key.
+    AcceptDraw(bool),
+    // Agrees to the draw, signed like the offer.
+    SignedAcceptDraw(Vec<u8>),
+    AcceptRematch(bool),
+    // Sent by either player at any time during the game, signed with the key
+    // that they registered with, if any. The server only relays and logs the
+    // messages that verify, so that each one can be attributed to the player
+    // it names, and those of players without a key unsigned.
+    Chat(ChatMessage)
+}
+
+impl Hello {
+    pub fn new(pin: GuestPin) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            journal_version: pin.journal_version,
+            image_id: pin.make_move_id
+        }
+    }
+
+    // Of this build.
+    #[cfg(feature = "guests")]
+    pub fn local() -> Self {
+        Self::new(GuestPin::local())
+    }
+
+    // Whether `theirs`, of the other side, speaks the same protocol and proves
+    // moves with the same guest, whose journals this side can read.
+    pub fn check(&self, theirs: &Hello) -> Result<(), Incompatible> {
+        if theirs.protocol_version != self.protocol_version {
+            return Err(Incompatible::Protocol { ours: self.protocol_version, theirs: theirs.protocol_version });
+        }
+
+        if theirs.journal_version != self.journal_version {
+            return Err(Incompatible::Journal { ours: self.journal_version, theirs: theirs.journal_version });
+        }
+
+        if theirs.image_id != self.image_id {
+            return Err(Incompatible::Guest { ours: self.image_id, theirs: theirs.image_id });
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Incompatible {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Protocol { ours, theirs } => write!(
+                f,
+                "The other side speaks version {theirs} of the protocol and this one version {ours}."
+            ),
+            Self::Journal { ours, theirs } => write!(
+                f,
+                "The other side's guests commit journals of version {theirs} and this build's of version {ours}."
+            ),
+            Self::Guest { ours, theirs } => write!(
+                f,
+                "The other side proves moves with the guest {} and this build with {}. Both have to be built \
+                    from the same source.",
+                hex::encode(theirs.as_bytes()),
+                hex::encode(ours.as_bytes())
+            )
+        }
+    }
+}
+
+impl std::error::Error for Incompatible { }
+
+#[cfg(feature = "guests")]
+impl Terms {
+    // A standard game proven by the guest build of this binary,
+    // where the player that opened it moves first.
+    pub fn local() -> Self {
+        Self {
+            ruleset: Ruleset::Standard,
+            image_id: GuestPin::local().make_move_id,
+            first_player: Player::A
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_are_the_same_in_bincode_and_json() {
+        let messages = [
+            ClientMessage::Move(Point::new(1, 2)),
+            ClientMessage::Open(Terms { ruleset: Ruleset::Standard, image_id: Digest::from([7; 8]), first_player: Player::B }),
+            ClientMessage::Resign,
+            ClientMessage::SignedResign(vec![1, 2]),
+            ClientMessage::AcceptDraw(true)
+        ];
+
+        for message in messages {
+            let bincode = bincode::serialize(&message).unwrap();
+            let json = serde_json::to_string(&message).unwrap();
+
+            let from_bincode: ClientMessage = bincode::deserialize(&bincode).unwrap();
+            let from_json: ClientMessage = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(serde_json::to_string(&from_bincode).unwrap(), json);
+            assert_eq!(serde_json::to_string(&from_json).unwrap(), json);
+        }
+
+        // As the browser client sends them.
+        assert!(matches!(serde_json::from_str(r#"{"Move":{"x":1,"y":2}}"#), Ok(ClientMessage::Move(_))));
+        assert!(matches!(serde_json::from_str(r#""OfferDraw""#), Ok(ClientMessage::OfferDraw)));
+    }
+
+    #[test]
+    fn hellos_of_other_builds_are_refused() {
+        let ours = Hello::new(GuestPin { make_move_id: Digest::from([1; 8]), journal_version: 2 });
+
+        assert_eq!(ours.check(&ours), Ok(()));
+
+        assert_eq!(
+            ours.check(&Hello { protocol_version: PROTOCOL_VERSION + 1, ..ours }),
+            Err(Incompatible::Protocol { ours: PROTOCOL_VERSION, theirs: PROTOCOL_VERSION + 1 })
+        );
+        assert_eq!(
+            ours.check(&Hello { journal_version: 1, ..ours }),
+            Err(Incompatible::Journal { ours: 2, theirs: 1 })
+        );
+        assert_eq!(
+            ours.check(&Hello { image_id: Digest::from([3; 8]), ..ours }),
+            Err(Incompatible::Guest { ours: ours.image_id, theirs: Digest::from([3; 8]) })
+        );
+
+        // As read by servers and clients of every version, see `Hello`.
+        assert_eq!(bincode::serialize(&ours).unwrap().len(), 4 + 4 + 32);
+    }
+}